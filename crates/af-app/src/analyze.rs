@@ -0,0 +1,272 @@
+//! `classcii analyze` : analyse offline d'un fichier audio, export de la timeline
+//! de features (CSV / JSON / NPY) et résumé dans le terminal.
+
+use std::fmt::Write as _;
+
+use af_audio::batch_analyzer::BatchAnalyzer;
+use af_core::config::AnalysisConfig;
+use af_core::feature_timeline::{FeatureTimeline, StemFeatureTimeline};
+use af_core::frame::{AudioFeatures, LOUDNESS_FLOOR_LUFS};
+use af_export::timeline::{FeatureTable, TimelineFormat, write_timeline};
+use anyhow::{Context, Result};
+
+use crate::cli::AnalyzeArgs;
+
+/// Largeur par défaut des courbes du résumé (caractères).
+pub const DEFAULT_PLOT_WIDTH: usize = 64;
+
+/// Colonnes tracées dans le résumé.
+const PLOT_COLUMNS: [&str; 8] = [
+    "rms",
+    "sub_bass",
+    "bass",
+    "mid",
+    "brilliance",
+    "onset_envelope",
+    "spectral_centroid",
+    "loudness_lufs",
+];
+
+/// Préfixes des colonnes de stems, dans l'ordre de `StemFeatureTimeline::timelines`.
+const STEM_PREFIXES: [&str; 4] = ["drums", "bass", "other", "vocals"];
+
+const NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Table de la timeline : colonnes du mix, puis de chaque stem si `stems`
+/// est fourni. Les bandes utilisateur portent les noms de `analysis.bands`.
+#[must_use]
+pub fn build_table(
+    timeline: &FeatureTimeline,
+    stems: Option<&StemFeatureTimeline>,
+    analysis: &AnalysisConfig,
+) -> FeatureTable {
+    let band_names: Vec<String> = analysis.custom_bands().map(|b| b.name.clone()).collect();
+    let mut table = FeatureTable::from_timeline(timeline, &band_names);
+    if let Some(stems) = stems {
+        for (prefix, stem) in STEM_PREFIXES.iter().zip(&stems.timelines) {
+            table.add_stem(prefix, stem, &band_names);
+        }
+    }
+    table
+}
+
+/// `timeline` avec d'autres `frames` (brutes, d'un stem), mêmes niveaux
+/// d'énergie et sections : les colonnes de structure restent celles du mix.
+#[must_use]
+pub fn with_frames(timeline: &FeatureTimeline, frames: Vec<AudioFeatures>) -> FeatureTimeline {
+    FeatureTimeline {
+        frames,
+        frame_duration: timeline.frame_duration,
+        sample_rate: timeline.sample_rate,
+        energy_levels: timeline.energy_levels.clone(),
+        sections: timeline.sections.clone(),
+    }
+}
+
+/// Résumé texte : durée, tempo, tonalité, sonie, sections et une courbe de
+/// `width` caractères par feature principale (rms, bandes, onsets, sonie).
+#[must_use]
+pub fn summary(timeline: &FeatureTimeline, table: &FeatureTable, width: usize) -> String {
+    let mut out = String::new();
+    let n = timeline.frames.len();
+    let duration = n as f32 * timeline.frame_duration;
+    let _ = writeln!(
+        out,
+        "{} · {n} frames × {} colonnes ({:.0} fps)",
+        clock(duration),
+        table.columns.len(),
+        1.0 / timeline.frame_duration.max(f32::EPSILON)
+    );
+    let Some(last) = timeline.frames.last() else {
+        return out;
+    };
+
+    // Tempo : médiane des frames où la grille est calée.
+    let mut bpms: Vec<f32> = timeline
+        .frames
+        .iter()
+        .filter(|f| f.bpm > 0.0)
+        .map(|f| f.bpm)
+        .collect();
+    bpms.sort_by(f32::total_cmp);
+    if let Some(&bpm) = bpms.get(bpms.len() / 2) {
+        let _ = write!(out, "Tempo {bpm:.1} BPM");
+    } else {
+        out.push_str("Tempo —");
+    }
+    // La tonalité courante de la dernière frame porte sur tout le morceau.
+    let _ = write!(
+        out,
+        " · Tonalité {} {} ({:.2})",
+        NOTE_NAMES[usize::from(last.key % 12)],
+        if last.key_minor { "mineur" } else { "majeur" },
+        last.key_confidence
+    );
+    let true_peak = timeline
+        .frames
+        .iter()
+        .map(|f| f.true_peak)
+        .fold(LOUDNESS_FLOOR_LUFS, f32::max);
+    let _ = writeln!(
+        out,
+        " · Sonie {:.1} LUFS, true peak {true_peak:.1} dBTP",
+        integrated_loudness(timeline)
+    );
+
+    if !timeline.sections.is_empty() {
+        out.push_str("Sections :\n");
+        for section in &timeline.sections {
+            let _ = writeln!(
+                out,
+                "  {:>6}  {:<10} {}  énergie {:.2}",
+                clock(section.start as f32 * timeline.frame_duration),
+                format!("{:?}", section.kind),
+                char::from(b'A' + section.label.min(25)),
+                section.energy
+            );
+        }
+    }
+
+    for name in PLOT_COLUMNS {
+        let Some(col) = table.column(name) else {
+            continue;
+        };
+        let values: Vec<f32> = table.rows.iter().map(|r| r[col]).collect();
+        let (min, max) = values
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+        let _ = writeln!(
+            out,
+            "  {name:<18} {}  {min:.2} … {max:.2}",
+            sparkline(&values, width)
+        );
+    }
+    out
+}
+
+/// Courbe d'une série : moyenne par case sur `width` cases, échelle min–max.
+///
+/// # Example
+/// ```
+/// use af_app::analyze::sparkline;
+/// assert_eq!(sparkline(&[0.0, 0.0, 0.5, 0.5, 1.0, 1.0], 3), "▁▅█");
+/// ```
+#[must_use]
+pub fn sparkline(values: &[f32], width: usize) -> String {
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+    let width = width.min(values.len());
+    let (min, max) = values
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let range = (max - min).max(f32::EPSILON);
+    (0..width)
+        .map(|i| {
+            let bucket = &values[i * values.len() / width..(i + 1) * values.len() / width];
+            let v = bucket.iter().sum::<f32>() / bucket.len() as f32;
+            let level = ((v - min) / range * (SPARK.len() - 1) as f32).round() as usize;
+            SPARK[level.min(SPARK.len() - 1)]
+        })
+        .collect()
+}
+
+/// Sonie intégrée approchée (EBU R128) depuis la sonie momentanée des frames :
+/// porte absolue à −70 LUFS puis relative à −10 LU.
+fn integrated_loudness(timeline: &FeatureTimeline) -> f32 {
+    let gated_mean = |gate: f32| {
+        let (sum, count) = timeline
+            .frames
+            .iter()
+            .filter(|f| f.loudness_lufs > gate)
+            .fold((0.0f64, 0u32), |(s, c), f| {
+                (s + 10f64.powf(f64::from(f.loudness_lufs) / 10.0), c + 1)
+            });
+        if count == 0 {
+            LOUDNESS_FLOOR_LUFS
+        } else {
+            (10.0 * (sum / f64::from(count)).log10()) as f32
+        }
+    };
+    let absolute = gated_mean(LOUDNESS_FLOOR_LUFS);
+    if absolute <= LOUDNESS_FLOOR_LUFS {
+        return LOUDNESS_FLOOR_LUFS;
+    }
+    gated_mean(absolute - 10.0)
+}
+
+fn clock(secs: f32) -> String {
+    let secs = secs.max(0.0);
+    format!("{}:{:04.1}", (secs / 60.0) as u32, secs % 60.0)
+}
+
+/// `classcii analyze` : analyse `args.audio` (et ses stems avec `--stems`),
+/// écrit la table dans `args.out` (features brutes, normalisées avec
+/// `--normalized`) et affiche le résumé sur stdout.
+///
+/// # Errors
+/// Retourne une erreur si l'extension de sortie est inconnue, si le décodage,
+/// la séparation ou l'écriture échoue.
+pub fn run_analyze(
+    args: &AnalyzeArgs,
+    analysis: &AnalysisConfig,
+    paths: &af_core::paths::AppPaths,
+) -> Result<()> {
+    // Valider la sortie avant une analyse potentiellement longue.
+    if let Some(ref out) = args.out {
+        TimelineFormat::from_path(out).with_context(|| {
+            format!(
+                "Extension de timeline inconnue : {} (attendu .csv, .json ou .npy)",
+                out.display()
+            )
+        })?;
+    }
+
+    let mut analyzer = BatchAnalyzer::with_analysis(args.fps.max(1), 44100, analysis);
+    let frames = analyzer
+        .analyze_file_frames(&args.audio)
+        .with_context(|| format!("Analyse de {} échouée", args.audio.display()))?;
+    // Sections et niveaux d'énergie viennent toujours de la timeline normalisée
+    let raw = (!args.normalized).then(|| frames.clone());
+    let timeline = analyzer.timeline_from_frames(frames);
+
+    let stems = if args.stems {
+        let stem_set = crate::batch::separate_stems(&args.audio, &args.stem_model, paths)?;
+        let stem_samples: [Vec<f32>; 4] =
+            std::array::from_fn(|i| (*stem_set.stems[i].samples).clone());
+        Some(if args.normalized {
+            analyzer.analyze_stems(&stem_samples)?
+        } else {
+            StemFeatureTimeline {
+                timelines: analyzer
+                    .analyze_stem_frames(&stem_samples)
+                    .map(|f| with_frames(&timeline, f)),
+            }
+        })
+    } else {
+        None
+    };
+
+    let table = match raw {
+        Some(raw) => build_table(&with_frames(&timeline, raw), stems.as_ref(), analysis),
+        None => build_table(&timeline, stems.as_ref(), analysis),
+    };
+    if let Some(ref out) = args.out {
+        let format = write_timeline(&table, out)?;
+        log::info!(
+            "Timeline {:?} {}×{} → {}",
+            format,
+            table.rows.len(),
+            table.columns.len(),
+            out.display()
+        );
+    }
+    if !args.quiet {
+        print!("{}", summary(&timeline, &table, DEFAULT_PLOT_WIDTH));
+    }
+    Ok(())
+}
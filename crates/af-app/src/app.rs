@@ -1,20 +1,31 @@
+use std::io::Write;
 use std::path::Path;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use crate::creation::CreationEngine;
 use af_ascii::compositor::Compositor;
-use af_audio::state::AudioCommand;
+use af_audio::capture::CaptureOptions;
+use af_audio::network::FeatureSender;
+use af_audio::state::{AudioCommand, AudioThread};
+use af_core::automation::Automation;
 use af_core::charset;
 use af_core::clock::MediaClock;
-use af_core::config::{BgStyle, ColorMode, DitherMode, RenderConfig, RenderMode};
-use af_core::frame::{AsciiGrid, AudioFeatures, FrameBuffer};
+use af_core::config::{
+    AUDIO_TARGETS, BgStyle, ColorMode, DisplayBackend, DitherMode, RenderConfig, RenderMode,
+};
+use af_core::frame::{AsciiGrid, AudioFeatures, ChannelFeatures, FrameBuffer};
 
+use af_core::traits::Source;
+use af_export::asciicast::{CastAudio, CastRecorder};
+use af_export::graphics::GraphicsRenderer;
 use af_render::fps::FpsCounter;
 use af_render::ui::{
-    DrawContext, RenderState, SIDEBAR_WIDTH, SPECTRUM_HEIGHT, StemDisplayInfo, StemOverlayData,
-    WorkflowBrowseData, WorkflowBrowseEntry, WorkflowSaveData,
+    DrawContext, MIN_TERM_HEIGHT, MIN_TERM_WIDTH, MidiLearnData, RenderState, SIDEBAR_WIDTH,
+    SPECTRUM_HEIGHT, StemDisplayInfo, StemOverlayData, WorkflowBrowseData, WorkflowBrowseEntry,
+    WorkflowSaveData,
 };
+use af_source::procedural::{GeneratorKind, GeneratorParams, ProceduralSource};
 use af_source::resize::Resizer;
 #[cfg(feature = "video")]
 use af_source::video::VideoCommand;
@@ -28,6 +39,9 @@ use crossterm::event::{
 };
 use ratatui::DefaultTerminal;
 
+use crate::http::{HttpCommand, HttpServer, PresetList};
+use crate::midi::{MidiInput, MidiState};
+use crate::osc::{OscCommand, OscServer};
 use crate::pipeline;
 
 /// Category of a loaded media file, determined by extension.
@@ -51,6 +65,38 @@ fn classify_media(path: &Path) -> Option<MediaType> {
     }
 }
 
+/// Identifiant de l'image Kitty du canvas (remplacée à chaque frame).
+const GRAPHICS_IMAGE_ID: u32 = 1;
+
+/// Font size for the Sixel/Kitty rasterizer, matched to the terminal cell height.
+///
+/// Falls back to 16px when the terminal does not report its pixel size.
+fn graphics_scale_px() -> f32 {
+    match crossterm::terminal::window_size() {
+        Ok(ws) if ws.height > 0 && ws.rows > 0 => {
+            (f32::from(ws.height) / f32::from(ws.rows)).floor().max(4.0)
+        }
+        _ => 16.0,
+    }
+}
+
+/// Canvas size in cells for a terminal of `terminal` cells: the full terminal
+/// in fullscreen, otherwise without the sidebar and the spectrum bar.
+fn canvas_size(config: &RenderConfig, terminal: (u16, u16)) -> (u16, u16) {
+    if config.fullscreen {
+        return terminal;
+    }
+    let spectrum_h = if config.show_spectrum {
+        SPECTRUM_HEIGHT
+    } else {
+        0
+    };
+    (
+        terminal.0.saturating_sub(SIDEBAR_WIDTH),
+        terminal.1.saturating_sub(spectrum_h),
+    )
+}
+
 /// Accumulated mouse camera deltas, flushed once per frame to avoid per-event config cloning.
 #[derive(Default)]
 struct MouseCameraDelta {
@@ -101,6 +147,8 @@ pub enum AppState {
     WorkflowSave,
     /// Workflow browse/load overlay (list of saved workflows).
     WorkflowBrowse,
+    /// MIDI learn overlay: the next CC/note binds to the selected target.
+    MidiLearn,
     /// Fermeture de l'application. doit se terminer au prochain tour de boucle.
     Quitting,
 }
@@ -114,6 +162,8 @@ pub struct App {
     pub config: Arc<ArcSwap<RenderConfig>>,
     /// Features audio courantes (lecture via triple buffer).
     pub audio_output: Option<triple_buffer::Output<AudioFeatures>>,
+    /// Per-channel features of a split capture (`--audio-channels split`).
+    pub channel_features_output: Option<triple_buffer::Output<ChannelFeatures>>,
     /// Frame source courante.
     pub current_frame: Option<Arc<FrameBuffer>>,
     /// Grille ASCII pré-allouée, réutilisée chaque frame.
@@ -128,6 +178,14 @@ pub struct App {
     pub fps_counter: FpsCounter,
     /// Récepteur de frames depuis le source thread.
     pub frame_rx: Option<flume::Receiver<Arc<FrameBuffer>>>,
+    /// Générateur procédural (rendu sur le thread principal, params audio-mappés).
+    pub procedural_source: Option<ProceduralSource>,
+    /// Renderer Sixel/Kitty, créé à la demande quand `display_backend` est graphique.
+    graphics: Option<GraphicsRenderer>,
+    /// Une image Kitty est affichée (à supprimer sous les overlays et en sortie).
+    kitty_image_visible: bool,
+    /// Enregistrement asciicast en cours (Ctrl+R / --record).
+    recorder: Option<CastRecorder>,
     /// Dernier terminal size connu (pour détecter les resize).
     pub terminal_size: (u16, u16),
     /// Compositor pour la conversion pixel→ASCII.
@@ -147,6 +205,8 @@ pub struct App {
     pub video_cmd_tx: Option<flume::Sender<VideoCommand>>,
     /// Channel pour les commandes audio (Play, Pause, Seek).
     pub audio_cmd_tx: Option<flume::Sender<AudioCommand>>,
+    /// Thread de la source audio live (capture, PCM, réseau), joint par `shutdown_audio`.
+    pub audio_thread: Option<AudioThread>,
     /// Nom du fichier visuel chargé (image/vidéo).
     pub loaded_visual_name: Option<String>,
     /// Nom du fichier audio chargé.
@@ -177,6 +237,27 @@ pub struct App {
     pub wave_phase: f32,
     /// Per-mapping EMA smooth state for audio mappings.
     pub mapping_smooth_state: Vec<f32>,
+    /// Keyframe automation, evaluated before audio mappings.
+    pub automation: Option<Automation>,
+    /// Automation time when no media clock is running: seconds spent
+    /// outside `Paused`, advanced once per frame.
+    pub automation_secs: f64,
+    /// Frame at which `automation_secs` was last advanced.
+    pub automation_tick: Instant,
+    /// OSC remote control server (`--osc`).
+    pub osc: Option<OscServer>,
+    /// Local HTTP/WebSocket control API (`--http`).
+    pub http: Option<HttpServer>,
+    /// UDP feature publisher for secondary instances (`--audio-send`).
+    pub audio_send: Option<FeatureSender>,
+    /// MIDI controller input (`--midi`).
+    pub midi: Option<MidiInput>,
+    /// Last CC/note values received, read by `[[midi.mappings]]`.
+    pub midi_state: MidiState,
+    /// Selected target in the MIDI learn overlay (index into `AUDIO_TARGETS`).
+    midi_learn_idx: usize,
+    /// Last MIDI message label, shown in the learn overlay.
+    midi_last_event: Option<String>,
     /// Creation mode engine for automated audio-reactive effects.
     pub creation_engine: CreationEngine,
     /// Whether creation mode modulation is active (independent of overlay visibility).
@@ -257,6 +338,7 @@ impl App {
     ///
     /// # Errors
     /// Returns an error if terminal size cannot be queried.
+    #[allow(clippy::too_many_lines)]
     pub fn new(
         config: Arc<ArcSwap<RenderConfig>>,
         audio_output: Option<triple_buffer::Output<AudioFeatures>>,
@@ -296,6 +378,7 @@ impl App {
             state: AppState::Running,
             config,
             audio_output,
+            channel_features_output: None,
             current_frame: None,
             grid: AsciiGrid::new(canvas_width, canvas_height),
             resized_frame: FrameBuffer::new(u32::from(canvas_width), u32::from(canvas_height)),
@@ -303,6 +386,10 @@ impl App {
             sidebar_dirty: true,
             fps_counter: FpsCounter::new(60),
             frame_rx,
+            procedural_source: None,
+            graphics: None,
+            kitty_image_visible: false,
+            recorder: None,
             terminal_size: (0, 0), // Force initial resize trigger
             compositor: Compositor::new(&initial_charset),
             resizer: Resizer::new(),
@@ -315,6 +402,7 @@ impl App {
             #[cfg(feature = "video")]
             video_cmd_tx,
             audio_cmd_tx,
+            audio_thread: None,
             loaded_visual_name: None,
             loaded_audio_name: None,
             loaded_audio_path: None,
@@ -331,6 +419,16 @@ impl App {
             color_pulse_phase: 0.0,
             wave_phase: 0.0,
             mapping_smooth_state: Vec::new(),
+            automation: None,
+            automation_secs: 0.0,
+            automation_tick: Instant::now(),
+            osc: None,
+            http: None,
+            audio_send: None,
+            midi: None,
+            midi_state: MidiState::default(),
+            midi_learn_idx: 0,
+            midi_last_event: None,
             creation_engine: CreationEngine::default(),
             creation_mode_active: false,
             render_config_scratch: RenderConfig::default(),
@@ -420,6 +518,11 @@ impl App {
             // === Flush accumulated mouse camera deltas (one config write per frame) ===
             self.flush_mouse_camera();
 
+            // === Commandes OSC distantes ===
+            self.poll_osc();
+            self.poll_http();
+            self.poll_midi();
+
             // === File dialogs si demandés ===
             if self.open_visual_requested {
                 self.open_visual_requested = false;
@@ -463,12 +566,13 @@ impl App {
                 } else {
                     (self.audio_output.as_mut().map(|out| *out.read()), None)
                 };
+            let live_channel_feats = self.channel_features_output.as_mut().map(|out| *out.read());
 
             // === Lire frame source ===
-            if let Some(ref rx) = self.frame_rx
-                && let Ok(frame) = rx.try_recv()
-            {
-                self.current_frame = Some(frame);
+            match self.frame_rx.as_ref().map(flume::Receiver::try_recv) {
+                Some(Ok(frame)) => self.current_frame = Some(frame),
+                Some(Err(flume::TryRecvError::Disconnected)) => self.visual_source_ended(),
+                _ => {}
             }
 
             // === Appliquer audio mappings à la config ===
@@ -477,6 +581,21 @@ impl App {
             let mut render_config = std::mem::take(&mut self.render_config_scratch);
             render_config.clone_from(&config);
 
+            // Automation first: audio mappings add their deltas on top.
+            // Sans media clock, le temps ne court pas pendant la pause.
+            let tick = Instant::now();
+            if self.state != AppState::Paused {
+                self.automation_secs += (tick - self.automation_tick).as_secs_f64();
+            }
+            self.automation_tick = tick;
+            if let Some(ref automation) = self.automation {
+                let t = self
+                    .media_clock
+                    .as_ref()
+                    .map_or(self.automation_secs, |c| c.pos_secs());
+                automation.apply(&mut render_config, t);
+            }
+
             // Update onset envelope BEFORE mapping — ensures onset_envelope is synchronous
             // (mappings using onset_envelope as source see the current frame's value)
             if let Some(ref features) = audio_features {
@@ -486,6 +605,18 @@ impl App {
                     self.onset_envelope *= render_config.strobe_decay;
                 }
             }
+            if let (Some(osc), Some(features)) = (self.osc.as_mut(), audio_features.as_ref()) {
+                osc.send_features(features, self.onset_envelope);
+            }
+            if let (Some(http), Some(features)) = (self.http.as_ref(), audio_features.as_ref()) {
+                http.publish_features(features, self.onset_envelope);
+            }
+            // Raw features: each receiver derives its own envelope from `onset`
+            if let (Some(sender), Some(features)) =
+                (self.audio_send.as_mut(), audio_features.as_ref())
+            {
+                sender.send(features);
+            }
 
             if let Some(ref features) = audio_features {
                 let fps = render_config.target_fps;
@@ -493,11 +624,21 @@ impl App {
                     &mut render_config,
                     features,
                     live_stem_feats.as_ref(),
+                    live_channel_feats.as_ref(),
                     self.onset_envelope,
                     &mut self.mapping_smooth_state,
                     fps,
                 );
             }
+            pipeline::apply_midi_mappings(&mut render_config, &self.midi_state);
+
+            // === Source procédurale (après mappings : params audio-réactifs) ===
+            if let Some(ref mut generator) = self.procedural_source
+                && self.state != AppState::Paused
+            {
+                generator.set_params(GeneratorParams::from_config(&render_config));
+                self.current_frame = generator.next_frame();
+            }
 
             // === Process source frame into ASCII grid ===
             let render_start = Instant::now();
@@ -773,6 +914,29 @@ impl App {
                 None
             };
 
+            let layout_midi_learn = if state == RenderState::MidiLearn {
+                let base = self.config.load();
+                let rows = AUDIO_TARGETS
+                    .iter()
+                    .map(|&target| {
+                        let binding = base
+                            .midi_mappings
+                            .iter()
+                            .find(|m| m.target == target)
+                            .map(crate::midi::mapping_label);
+                        (target, binding)
+                    })
+                    .collect();
+                Some(MidiLearnData {
+                    rows,
+                    selected_idx: self.midi_learn_idx,
+                    port: self.midi.as_ref().map(|m| m.port_name().to_string()),
+                    last_event: self.midi_last_event.clone(),
+                })
+            } else {
+                None
+            };
+
             terminal.draw(|frame| {
                 let ctx = DrawContext {
                     grid,
@@ -795,12 +959,24 @@ impl App {
                     stem: stem_overlay.as_ref(),
                     workflow_save: layout_workflow_save.as_ref(),
                     workflow_browse: layout_workflow_browse.as_ref(),
+                    midi_learn: layout_midi_learn.as_ref(),
                     flash_msg: self.workflow_flash_msg.as_deref(),
                 };
                 af_render::ui::draw(frame, &ctx);
             })?;
             self.sidebar_dirty = false;
 
+            self.draw_graphics(&render_config, &state)?;
+
+            if let Some(ref mut recorder) = self.recorder
+                && let Err(e) = recorder.record_frame(&self.grid)
+            {
+                log::error!("Recording failed: {e}");
+                self.recorder = None;
+                self.workflow_flash_msg = Some(format!("Recording failed: {e}"));
+                self.workflow_flash_frames = 120;
+            }
+
             // Decrement flash counters
             self.param_flash_frames = self.param_flash_frames.saturating_sub(1);
             if self.workflow_flash_frames > 0 {
@@ -814,9 +990,177 @@ impl App {
             self.render_config_scratch = render_config;
         }
 
+        if self.kitty_image_visible {
+            let mut seq = Vec::new();
+            af_export::graphics::kitty_delete(GRAPHICS_IMAGE_ID, &mut seq);
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&seq)?;
+            stdout.flush()?;
+            self.kitty_image_visible = false;
+        }
+
+        self.stop_recording();
+
+        Ok(())
+    }
+
+    /// Emit the canvas as a Sixel/Kitty image over the blank cells drawn by ratatui.
+    ///
+    /// Skipped under modal overlays (the image would hide them) and when the
+    /// terminal is below the minimum size.
+    fn draw_graphics(&mut self, config: &RenderConfig, state: &RenderState) -> Result<()> {
+        let backend = config.display_backend;
+        let too_small =
+            self.terminal_size.0 < MIN_TERM_WIDTH || self.terminal_size.1 < MIN_TERM_HEIGHT;
+        let overlay = !matches!(state, RenderState::Running | RenderState::Paused);
+
+        let mut stdout = std::io::stdout().lock();
+        if !backend.is_graphics() || overlay || too_small {
+            // Kitty : l'image reste au-dessus du texte tant qu'elle n'est pas supprimée
+            if self.kitty_image_visible {
+                let mut seq = Vec::new();
+                af_export::graphics::kitty_delete(GRAPHICS_IMAGE_ID, &mut seq);
+                stdout.write_all(&seq)?;
+                stdout.flush()?;
+                self.kitty_image_visible = false;
+            }
+            return Ok(());
+        }
+
+        if self.graphics.is_none() {
+            match GraphicsRenderer::new(graphics_scale_px()) {
+                Ok(renderer) => self.graphics = Some(renderer),
+                Err(e) => {
+                    log::warn!("Backend graphique indisponible, retour au texte : {e}");
+                    return Ok(());
+                }
+            }
+        }
+        let Some(renderer) = self.graphics.as_mut() else {
+            return Ok(());
+        };
+
+        // Budget du canvas tel que dessiné (la sidebar a pu réapparaître sans
+        // resize) ; Sixel : ne jamais toucher la dernière ligne du terminal (scroll)
+        let (cols, rows) = canvas_size(config, self.terminal_size);
+        let max_rows = if rows >= self.terminal_size.1 {
+            rows.saturating_sub(1)
+        } else {
+            rows
+        };
+        let bytes = renderer.encode(
+            &self.grid,
+            backend,
+            config.zalgo_intensity,
+            (cols, max_rows),
+            GRAPHICS_IMAGE_ID,
+        );
+        crossterm::queue!(stdout, crossterm::cursor::MoveTo(0, 0))?;
+        stdout.write_all(bytes)?;
+        stdout.flush()?;
+        self.kitty_image_visible = backend == DisplayBackend::Kitty;
         Ok(())
     }
 
+    /// Show `msg` in the status bar for a few seconds (errors from startup or
+    /// background operations the user would otherwise only find in the log).
+    pub fn show_flash(&mut self, msg: String) {
+        self.workflow_flash_msg = Some(msg);
+        self.workflow_flash_frames = 180;
+    }
+
+    /// Start an asciicast recording of the canvas.
+    ///
+    /// `path` defaults to `recordings/rec_<ms>.cast`. When an audio file is
+    /// playing, its path and current position go into the cast header.
+    pub fn start_recording(&mut self, path: Option<&Path>) {
+        let path = if let Some(p) = path {
+            p.to_path_buf()
+        } else {
+            let millis = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis());
+            let dir = &self.paths.recordings_dir;
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                log::error!("Cannot create {}: {e}", dir.display());
+            }
+            dir.join(format!("rec_{millis}.cast"))
+        };
+
+        let audio = self.loaded_audio_path.as_ref().map(|file| CastAudio {
+            file: file.to_string_lossy().into_owned(),
+            offset_secs: self.media_clock.as_ref().map_or(0.0, |c| c.pos_secs()),
+        });
+
+        match CastRecorder::create(&path, self.grid.width, self.grid.height, audio.as_ref()) {
+            Ok(recorder) => {
+                log::info!("Recording started: {}", path.display());
+                self.recorder = Some(recorder);
+                self.workflow_flash_msg = Some("Recording\u{2026}".to_string());
+                self.workflow_flash_frames = 90;
+            }
+            Err(e) => {
+                log::error!("Recording failed: {e}");
+                self.workflow_flash_msg = Some(format!("Recording failed: {e}"));
+                self.workflow_flash_frames = 120;
+            }
+        }
+    }
+
+    /// Stop the current recording (no-op if none) and flush the `.cast` file.
+    fn stop_recording(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        match recorder.finish() {
+            Ok(path) => {
+                log::info!("Recording saved: {}", path.display());
+                let name = path
+                    .file_name()
+                    .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+                self.workflow_flash_msg = Some(format!("Recorded: {name}"));
+                self.workflow_flash_frames = 90;
+            }
+            Err(e) => {
+                log::error!("Recording flush failed: {e}");
+                self.workflow_flash_msg = Some(format!("Recording failed: {e}"));
+                self.workflow_flash_frames = 120;
+            }
+        }
+    }
+
+    /// Write the current grid as `.txt`, `.ans` and `.html` into `snapshots/`.
+    fn save_snapshot(&mut self) {
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis());
+        let name = format!("snapshot_{millis}");
+        let dir = &self.paths.snapshots_dir;
+
+        let result = std::fs::create_dir_all(dir)
+            .map_err(anyhow::Error::from)
+            .and_then(|()| {
+                for format in af_export::text::TextFormat::ALL {
+                    let path = dir.join(format!("{name}.{}", format.extension()));
+                    af_export::text::write_snapshot(&self.grid, &path)?;
+                }
+                Ok(())
+            });
+
+        match result {
+            Ok(()) => {
+                log::info!("Snapshot saved: {}", dir.join(&name).display());
+                self.workflow_flash_msg = Some(format!("Snapshot: {name}"));
+                self.workflow_flash_frames = 90;
+            }
+            Err(e) => {
+                log::error!("Snapshot failed: {e}");
+                self.workflow_flash_msg = Some(format!("Snapshot failed: {e}"));
+                self.workflow_flash_frames = 120;
+            }
+        }
+    }
+
     /// Convert `AppState` to `RenderState` for the UI.
     fn render_state(&self) -> RenderState {
         match self.state {
@@ -829,6 +1173,7 @@ impl App {
             AppState::StemMode => RenderState::StemMode,
             AppState::WorkflowSave => RenderState::WorkflowSave,
             AppState::WorkflowBrowse => RenderState::WorkflowBrowse,
+            AppState::MidiLearn => RenderState::MidiLearn,
             AppState::Quitting => RenderState::Quitting,
         }
     }
@@ -867,6 +1212,22 @@ impl App {
                         self.enter_workflow_browse();
                         return;
                     }
+                    KeyCode::Char('l') => {
+                        self.state = if self.state == AppState::MidiLearn {
+                            AppState::Running
+                        } else {
+                            AppState::MidiLearn
+                        };
+                        self.sidebar_dirty = true;
+                        return;
+                    }
+                    KeyCode::Char('r') => {
+                        if self.recorder.is_some() {
+                            self.stop_recording();
+                        } else {
+                            self.start_recording(None);
+                        }
+                    }
                     _ => {}
                 }
                 return;
@@ -892,6 +1253,10 @@ impl App {
                 self.handle_workflow_browse_key(code);
                 return;
             }
+            if self.state == AppState::MidiLearn {
+                self.handle_midi_learn_key(code);
+                return;
+            }
             if self.state == AppState::Help {
                 match code {
                     KeyCode::Up => {
@@ -1058,25 +1423,21 @@ impl App {
                     self.mouse_last_pos = (mouse.column, mouse.row);
                 }
             }
-            MouseEventKind::Drag(MouseButton::Left) => {
-                if self.mouse_drag_origin.is_some() {
-                    let (lx, ly) = self.mouse_last_pos;
-                    let dx = f32::from(mouse.column) - f32::from(lx);
-                    let dy = f32::from(mouse.row) - f32::from(ly);
-                    self.mouse_last_pos = (mouse.column, mouse.row);
-                    self.mouse_cam_delta.pan_x += dx * 0.005;
-                    self.mouse_cam_delta.pan_y += dy * 0.005;
-                }
+            MouseEventKind::Drag(MouseButton::Left) if self.mouse_drag_origin.is_some() => {
+                let (lx, ly) = self.mouse_last_pos;
+                let dx = f32::from(mouse.column) - f32::from(lx);
+                let dy = f32::from(mouse.row) - f32::from(ly);
+                self.mouse_last_pos = (mouse.column, mouse.row);
+                self.mouse_cam_delta.pan_x += dx * 0.005;
+                self.mouse_cam_delta.pan_y += dy * 0.005;
             }
-            MouseEventKind::Drag(MouseButton::Right) => {
-                if self.mouse_drag_origin.is_some() {
-                    let (lx, ly) = self.mouse_last_pos;
-                    let dx = f32::from(mouse.column) - f32::from(lx);
-                    let dy = f32::from(mouse.row) - f32::from(ly);
-                    self.mouse_last_pos = (mouse.column, mouse.row);
-                    self.mouse_cam_delta.rotation += dx * 0.01;
-                    self.mouse_cam_delta.tilt += dy * 0.005;
-                }
+            MouseEventKind::Drag(MouseButton::Right) if self.mouse_drag_origin.is_some() => {
+                let (lx, ly) = self.mouse_last_pos;
+                let dx = f32::from(mouse.column) - f32::from(lx);
+                let dy = f32::from(mouse.row) - f32::from(ly);
+                self.mouse_last_pos = (mouse.column, mouse.row);
+                self.mouse_cam_delta.rotation += dx * 0.01;
+                self.mouse_cam_delta.tilt += dy * 0.005;
             }
             MouseEventKind::Up(_) => {
                 self.mouse_drag_origin = None;
@@ -1118,25 +1479,21 @@ impl App {
                 self.state = AppState::Running;
                 self.sidebar_dirty = true;
             }
-            KeyCode::Enter => {
-                if self.charset_edit_buf.chars().count() >= 2 {
-                    let new_charset = self.charset_edit_buf.clone();
-                    self.toggle_config(|c| {
-                        c.charset = new_charset;
-                        c.charset_index = 10;
-                    });
-                    self.state = AppState::Running;
-                    self.sidebar_dirty = true;
-                }
+            KeyCode::Enter if self.charset_edit_buf.chars().count() >= 2 => {
+                let new_charset = self.charset_edit_buf.clone();
+                self.toggle_config(|c| {
+                    c.charset = new_charset;
+                    c.charset_index = 10;
+                });
+                self.state = AppState::Running;
+                self.sidebar_dirty = true;
             }
-            KeyCode::Backspace => {
-                if self.charset_edit_cursor > 0 {
-                    let mut chars: Vec<char> = self.charset_edit_buf.chars().collect();
-                    chars.remove(self.charset_edit_cursor - 1);
-                    self.charset_edit_buf = chars.into_iter().collect();
-                    self.charset_edit_cursor -= 1;
-                    self.apply_charset_preview();
-                }
+            KeyCode::Backspace if self.charset_edit_cursor > 0 => {
+                let mut chars: Vec<char> = self.charset_edit_buf.chars().collect();
+                chars.remove(self.charset_edit_cursor - 1);
+                self.charset_edit_buf = chars.into_iter().collect();
+                self.charset_edit_cursor -= 1;
+                self.apply_charset_preview();
             }
             KeyCode::Delete => {
                 let chars: Vec<char> = self.charset_edit_buf.chars().collect();
@@ -1147,15 +1504,11 @@ impl App {
                     self.apply_charset_preview();
                 }
             }
-            KeyCode::Left => {
-                if self.charset_edit_cursor > 0 {
-                    self.charset_edit_cursor -= 1;
-                }
+            KeyCode::Left if self.charset_edit_cursor > 0 => {
+                self.charset_edit_cursor -= 1;
             }
-            KeyCode::Right => {
-                if self.charset_edit_cursor < self.charset_edit_buf.chars().count() {
-                    self.charset_edit_cursor += 1;
-                }
+            KeyCode::Right if self.charset_edit_cursor < self.charset_edit_buf.chars().count() => {
+                self.charset_edit_cursor += 1;
             }
             KeyCode::Home => {
                 self.charset_edit_cursor = 0;
@@ -1239,15 +1592,13 @@ impl App {
                 self.state = AppState::Running;
                 self.sidebar_dirty = true;
             }
-            KeyCode::Up => {
-                if self.creation_engine.selected_effect > 0 {
-                    self.creation_engine.selected_effect -= 1;
-                }
+            KeyCode::Up if self.creation_engine.selected_effect > 0 => {
+                self.creation_engine.selected_effect -= 1;
             }
-            KeyCode::Down => {
-                if self.creation_engine.selected_effect < crate::creation::NUM_EFFECTS - 1 {
-                    self.creation_engine.selected_effect += 1;
-                }
+            KeyCode::Down
+                if self.creation_engine.selected_effect < crate::creation::NUM_EFFECTS - 1 =>
+            {
+                self.creation_engine.selected_effect += 1;
             }
             KeyCode::Right => {
                 self.adjust_creation_effect(0.1);
@@ -1315,15 +1666,11 @@ impl App {
                     self.start_stem_separation(&p);
                 }
             }
-            KeyCode::Up => {
-                if self.stem_selected_idx > 0 {
-                    self.stem_selected_idx -= 1;
-                }
+            KeyCode::Up if self.stem_selected_idx > 0 => {
+                self.stem_selected_idx -= 1;
             }
-            KeyCode::Down => {
-                if self.stem_selected_idx < STEM_COUNT - 1 {
-                    self.stem_selected_idx += 1;
-                }
+            KeyCode::Down if self.stem_selected_idx < STEM_COUNT - 1 => {
+                self.stem_selected_idx += 1;
             }
             KeyCode::Right => {
                 let st = &mut self.stem_states[self.stem_selected_idx];
@@ -1463,7 +1810,8 @@ impl App {
                     ColorMode::Direct => ColorMode::HsvBright,
                     ColorMode::HsvBright => ColorMode::Oklab,
                     ColorMode::Oklab => ColorMode::Quantized,
-                    ColorMode::Quantized => ColorMode::Direct,
+                    ColorMode::Quantized => ColorMode::KeyHue,
+                    ColorMode::KeyHue => ColorMode::Direct,
                 };
             }),
             KeyCode::Char('b') => self.toggle_config(|c| {
@@ -1609,6 +1957,7 @@ impl App {
                     c.zalgo_intensity = (c.zalgo_intensity + 0.5).min(5.0);
                 });
             }
+            KeyCode::Char('X') => self.save_snapshot(),
             KeyCode::Char('y') => {
                 self.toggle_config(|c| {
                     c.temporal_stability = (c.temporal_stability - 0.1).max(0.0);
@@ -1874,6 +2223,7 @@ impl App {
             config.target_fps,
             config.audio_smoothing,
             config.input_gain,
+            &config.analysis,
         ) {
             Ok((output, handle)) => {
                 self.stem_features_output = Some(output);
@@ -1943,7 +2293,7 @@ impl App {
                 if self.workflow_save_name.is_empty() {
                     return;
                 }
-                self.execute_workflow_save();
+                let _ = self.execute_workflow_save();
             }
             KeyCode::Backspace => {
                 let field = if self.workflow_save_field == 0 {
@@ -1971,10 +2321,8 @@ impl App {
                     *field = chars.into_iter().collect();
                 }
             }
-            KeyCode::Left => {
-                if self.workflow_save_cursor > 0 {
-                    self.workflow_save_cursor -= 1;
-                }
+            KeyCode::Left if self.workflow_save_cursor > 0 => {
+                self.workflow_save_cursor -= 1;
             }
             KeyCode::Right => {
                 let field = if self.workflow_save_field == 0 {
@@ -2002,15 +2350,23 @@ impl App {
     }
 
     /// Execute workflow save with current name/description.
-    fn execute_workflow_save(&mut self) {
+    fn execute_workflow_save(&mut self) -> Result<(), String> {
         let config = self.config.load();
 
-        let source_path = self
-            .loaded_visual_name
-            .as_deref()
-            .map(std::path::PathBuf::from)
-            .unwrap_or_default();
-        let media_type = af_core::workflow::MediaType::None;
+        let (source_path, media_type) = if let Some(ref generator) = self.procedural_source {
+            (
+                std::path::PathBuf::from(generator.kind().name()),
+                af_core::workflow::MediaType::Procedural,
+            )
+        } else {
+            (
+                self.loaded_visual_name
+                    .as_deref()
+                    .map(std::path::PathBuf::from)
+                    .unwrap_or_default(),
+                af_core::workflow::MediaType::None,
+            )
+        };
         let audio_path = self.loaded_audio_path.clone();
 
         let source = af_core::workflow::SourceInfo {
@@ -2036,7 +2392,7 @@ impl App {
                 }),
             });
 
-        match af_core::workflow_io::save_workflow(
+        let result = match af_core::workflow_io::save_workflow(
             &self.workflow_save_name,
             &config,
             &source,
@@ -2046,6 +2402,11 @@ impl App {
             &self.paths.workflows_dir,
         ) {
             Ok(wf_dir) => {
+                if let Some(ref automation) = self.automation
+                    && let Err(e) = af_core::workflow_io::save_automation(&wf_dir, automation)
+                {
+                    log::warn!("Could not save automation: {e}");
+                }
                 // Update manifest description if provided
                 if !self.workflow_save_desc.is_empty() {
                     af_core::workflow_io::update_workflow_description(
@@ -2056,16 +2417,19 @@ impl App {
                 self.workflow_flash_msg = Some(format!("Saved: {}", self.workflow_save_name));
                 self.workflow_flash_frames = 90; // ~1.5s at 60fps
                 log::info!("Workflow saved: {}", self.workflow_save_name);
+                Ok(())
             }
             Err(e) => {
                 self.workflow_flash_msg = Some(format!("Save failed: {e}"));
                 self.workflow_flash_frames = 120;
                 log::error!("Workflow save failed: {e}");
+                Err(format!("{e:#}"))
             }
-        }
+        };
 
         self.state = AppState::Running;
         self.sidebar_dirty = true;
+        result
     }
 
     /// Handle keys in WorkflowBrowse overlay.
@@ -2075,22 +2439,19 @@ impl App {
                 self.state = AppState::Running;
                 self.sidebar_dirty = true;
             }
-            KeyCode::Up => {
-                if self.workflow_browse_idx > 0 {
-                    self.workflow_browse_idx -= 1;
-                }
+            KeyCode::Up if self.workflow_browse_idx > 0 => {
+                self.workflow_browse_idx -= 1;
             }
-            KeyCode::Down => {
+            KeyCode::Down
                 if !self.workflow_browse_list.is_empty()
-                    && self.workflow_browse_idx < self.workflow_browse_list.len() - 1
-                {
-                    self.workflow_browse_idx += 1;
-                }
+                    && self.workflow_browse_idx < self.workflow_browse_list.len() - 1 =>
+            {
+                self.workflow_browse_idx += 1;
             }
             KeyCode::Enter => {
                 if let Some(entry) = self.workflow_browse_list.get(self.workflow_browse_idx) {
                     let name = entry.name.clone();
-                    self.execute_workflow_load(&name);
+                    let _ = self.execute_workflow_load(&name);
                 }
             }
             KeyCode::Delete => {
@@ -2117,40 +2478,67 @@ impl App {
         }
     }
 
-    /// Load a workflow by name and apply its config.
-    fn execute_workflow_load(&mut self, name: &str) {
-        match af_core::workflow_io::load_workflow_by_name(name, &self.paths.workflows_dir) {
-            Ok(wf) => {
-                // Apply config
-                self.config.store(Arc::new(wf.config));
+    /// Handle keys in the MIDI learn overlay.
+    fn handle_midi_learn_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.state = AppState::Running;
                 self.sidebar_dirty = true;
-                self.terminal_size = (0, 0); // Force resize recalc
-
-                // Restore stem states if available
-                if let Some(ref states) = wf.stem_states {
-                    for (i, entry) in states.states.iter().enumerate() {
-                        if i < STEM_COUNT {
-                            self.stem_states[i].muted = entry.muted;
-                            self.stem_states[i].solo = entry.solo;
-                            self.stem_states[i].volume = entry.volume;
-                            self.stem_states[i].visible = entry.visible;
-                        }
-                    }
-                }
-
-                self.workflow_flash_msg = Some(format!("Loaded: {name}"));
-                self.workflow_flash_frames = 90;
-                log::info!("Workflow loaded: {name}");
             }
-            Err(e) => {
-                self.workflow_flash_msg = Some(format!("Load failed: {e}"));
-                self.workflow_flash_frames = 120;
-                log::error!("Workflow load failed: {e}");
+            KeyCode::Up if self.midi_learn_idx > 0 => {
+                self.midi_learn_idx -= 1;
+            }
+            KeyCode::Down if self.midi_learn_idx < AUDIO_TARGETS.len() - 1 => {
+                self.midi_learn_idx += 1;
             }
+            KeyCode::Delete | KeyCode::Backspace => {
+                let target = AUDIO_TARGETS[self.midi_learn_idx];
+                self.toggle_config(|c| c.midi_mappings.retain(|m| m.target != target));
+            }
+            _ => {}
         }
+    }
+
+    /// Load a workflow by name and apply its config.
+    fn execute_workflow_load(&mut self, name: &str) -> Result<(), String> {
+        let result =
+            match af_core::workflow_io::load_workflow_by_name(name, &self.paths.workflows_dir) {
+                Ok(wf) => {
+                    // Apply config
+                    self.config.store(Arc::new(wf.config));
+                    self.automation = wf.automation;
+                    self.automation_secs = 0.0;
+                    self.sidebar_dirty = true;
+                    self.terminal_size = (0, 0); // Force resize recalc
+
+                    // Restore stem states if available
+                    if let Some(ref states) = wf.stem_states {
+                        for (i, entry) in states.states.iter().enumerate() {
+                            if i < STEM_COUNT {
+                                self.stem_states[i].muted = entry.muted;
+                                self.stem_states[i].solo = entry.solo;
+                                self.stem_states[i].volume = entry.volume;
+                                self.stem_states[i].visible = entry.visible;
+                            }
+                        }
+                    }
+
+                    self.workflow_flash_msg = Some(format!("Loaded: {name}"));
+                    self.workflow_flash_frames = 90;
+                    log::info!("Workflow loaded: {name}");
+                    Ok(())
+                }
+                Err(e) => {
+                    self.workflow_flash_msg = Some(format!("Load failed: {e}"));
+                    self.workflow_flash_frames = 120;
+                    log::error!("Workflow load failed: {e}");
+                    Err(format!("{e:#}"))
+                }
+            };
 
         self.state = AppState::Running;
         self.sidebar_dirty = true;
+        result
     }
 
     /// Set charset by index.
@@ -2203,55 +2591,24 @@ impl App {
 
             let config = self.config.load();
 
-            let (canvas_width, canvas_height) = if config.fullscreen {
-                (new_size.0, new_size.1)
-            } else {
-                let spectrum_h = if config.show_spectrum {
-                    SPECTRUM_HEIGHT
-                } else {
-                    0u16
-                };
-                (
-                    new_size.0.saturating_sub(SIDEBAR_WIDTH),
-                    new_size.1.saturating_sub(spectrum_h),
-                )
-            };
+            let (canvas_width, canvas_height) = canvas_size(&config, new_size);
 
             // Réallouer la grille ASCII (rare, OK d'allouer ici)
             self.grid = AsciiGrid::new(canvas_width, canvas_height);
             self.prev_grid = AsciiGrid::new(canvas_width, canvas_height);
 
-            let density = config.density_scale.clamp(0.25, 4.0);
-            let (pixel_w, pixel_h) = match config.render_mode {
-                RenderMode::Ascii => (
-                    (f32::from(canvas_width) * density) as u32,
-                    (f32::from(canvas_height) * density) as u32,
-                ),
-                RenderMode::HalfBlock => (
-                    (f32::from(canvas_width) * density) as u32,
-                    (f32::from(canvas_height) * density * 2.0) as u32,
-                ),
-                RenderMode::Braille | RenderMode::Octant => (
-                    (f32::from(canvas_width) * density * 2.0) as u32,
-                    (f32::from(canvas_height) * density * 4.0) as u32,
-                ),
-                RenderMode::Quadrant => (
-                    (f32::from(canvas_width) * density * 2.0) as u32,
-                    (f32::from(canvas_height) * density * 2.0) as u32,
-                ),
-                RenderMode::Sextant => (
-                    (f32::from(canvas_width) * density * 2.0) as u32,
-                    (f32::from(canvas_height) * density * 3.0) as u32,
-                ),
-            };
-
-            // Appliquer la correction aspect ratio
-            let pixel_h_corrected = (pixel_h as f32 / config.aspect_ratio) as u32;
-            let final_w = pixel_w.max(1);
-            let final_h = pixel_h_corrected.max(1);
+            let (final_w, final_h) =
+                pipeline::source_pixel_size(&config, canvas_width, canvas_height);
 
             self.resized_frame = FrameBuffer::new(final_w, final_h);
 
+            if let Some(ref mut generator) = self.procedural_source {
+                generator.resize(final_w, final_h);
+            }
+
+            // La taille des cellules en pixels a pu changer (zoom police)
+            self.graphics = None;
+
             #[cfg(feature = "video")]
             if let Some(ref tx) = self.video_cmd_tx {
                 let _ = tx.send(VideoCommand::Resize(final_w, final_h));
@@ -2381,9 +2738,28 @@ impl App {
             #[cfg(feature = "video")]
             let paths_ref = &self.paths;
             #[cfg(feature = "video")]
+            let profile = config.export_profile;
+            #[cfg(feature = "video")]
             if let Err(e) = crate::batch::run_batch_export(
-                &folder, None, None, config, 30, None, false, None, 15.0, None, 1.0, false,
-                "standard", None, paths_ref,
+                Some(&folder),
+                None,
+                None,
+                None,
+                config,
+                30,
+                None,
+                false,
+                None,
+                15.0,
+                None,
+                1.0,
+                false,
+                "standard",
+                None,
+                crate::batch::FrameLimits::default(),
+                profile,
+                self.automation.clone(),
+                paths_ref,
             ) {
                 println!("\n[ERROR] Batch export failed: {e}");
             } else {
@@ -2416,16 +2792,45 @@ impl App {
             self.video_cmd_tx = None;
         }
         self.frame_rx = None;
+        self.procedural_source = None;
         self.current_frame = None;
     }
 
+    /// The source thread has exited (video EOF, camera unplugged or failed):
+    /// keep the last frame, and tell the user when a live camera is gone.
+    fn visual_source_ended(&mut self) {
+        self.frame_rx = None;
+        if let Some(name) = self
+            .loaded_visual_name
+            .as_deref()
+            .and_then(|n| n.strip_prefix("cam:"))
+        {
+            self.show_flash(format!("Camera {name} disconnected (see log)"));
+        }
+    }
+
+    /// Replace the visual source with a procedural generator.
+    pub fn set_generator(&mut self, kind: GeneratorKind) {
+        self.shutdown_visual();
+        self.procedural_source = Some(ProceduralSource::new(
+            kind,
+            self.resized_frame.width,
+            self.resized_frame.height,
+        ));
+        self.loaded_visual_name = Some(format!("gen:{}", kind.name()));
+        self.sidebar_dirty = true;
+    }
+
     /// Stop only the audio source, leave visual untouched.
     fn shutdown_audio(&mut self) {
         if let Some(ref tx) = self.audio_cmd_tx {
             let _ = tx.send(AudioCommand::Quit);
         }
         self.audio_cmd_tx = None;
+        // Drop = arrêt + join : périphérique, lecteur PCM ou port UDP libérés avant la source suivante
+        self.audio_thread = None;
         self.audio_output = None;
+        self.channel_features_output = None;
         self.media_clock = None;
 
         // Informer le thread vidéo que le clock est supprimé → mode wall-clock
@@ -2562,10 +2967,17 @@ impl App {
     /// (if running) via `VideoCommand::UpdateClock` to maintain A/V sync.
     fn start_audio_from_path(&mut self, path_str: &str) {
         let clock = Arc::new(MediaClock::new(0));
-        match pipeline::start_audio(path_str, &self.config, Arc::clone(&clock)) {
-            Ok((output, tx)) => {
+        match pipeline::start_audio(
+            path_str,
+            &self.config,
+            Arc::clone(&clock),
+            &CaptureOptions::default(),
+        ) {
+            Ok((output, channels, tx, thread)) => {
                 self.audio_output = Some(output);
+                self.channel_features_output = channels;
                 self.audio_cmd_tx = tx;
+                self.audio_thread = thread;
                 self.media_clock = Some(Arc::clone(&clock));
                 self.loaded_audio_path = Some(std::path::PathBuf::from(path_str));
                 // Propager le nouveau clock au thread vidéo (fix: video statique après chargement audio)
@@ -2575,7 +2987,143 @@ impl App {
                 }
                 log::info!("Audio démarré: {path_str}");
             }
-            Err(e) => log::warn!("Audio non disponible: {e}"),
+            Err(e) => {
+                log::warn!("Audio non disponible: {e}");
+                self.show_flash(format!("Audio : {e}"));
+            }
+        }
+    }
+
+    /// Apply commands received by the OSC server, through the same paths as
+    /// the keyboard handlers.
+    fn poll_osc(&mut self) {
+        let Some(ref osc) = self.osc else {
+            return;
+        };
+        let commands: Vec<OscCommand> = osc.drain().collect();
+        for cmd in commands {
+            match cmd {
+                OscCommand::SetParam { target, value } => {
+                    self.toggle_config(|c| {
+                        c.set_target(&target, value);
+                    });
+                }
+                OscCommand::PresetNext => self.cycle_preset(true),
+                OscCommand::PresetPrev => self.cycle_preset(false),
+                OscCommand::PresetIndex(idx) => self.select_preset(idx),
+                OscCommand::StemMute(id, muted) => {
+                    let st = &mut self.stem_states[id.index()];
+                    st.muted = muted.unwrap_or(!st.muted);
+                    if let Some(tx) = &self.stem_cmd_tx {
+                        let _ = tx.send(StemCommand::SetMuted(id, st.muted));
+                    }
+                    self.sidebar_dirty = true;
+                }
+                OscCommand::StemSolo(id, solo) => {
+                    let st = &mut self.stem_states[id.index()];
+                    st.solo = solo.unwrap_or(!st.solo);
+                    if let Some(tx) = &self.stem_cmd_tx {
+                        let _ = tx.send(StemCommand::SetSolo(id, st.solo));
+                    }
+                    self.sidebar_dirty = true;
+                }
+                OscCommand::StemVolume(id, volume) => {
+                    self.stem_states[id.index()].volume = volume;
+                    if let Some(tx) = &self.stem_cmd_tx {
+                        let _ = tx.send(StemCommand::SetVolume(id, volume));
+                    }
+                    self.sidebar_dirty = true;
+                }
+            }
+        }
+    }
+
+    /// Apply requests of the HTTP API that need the app state.
+    fn poll_http(&mut self) {
+        let Some(ref http) = self.http else {
+            return;
+        };
+        let commands: Vec<HttpCommand> = http.drain().collect();
+        for cmd in commands {
+            match cmd {
+                HttpCommand::ConfigReplaced => {
+                    self.sidebar_dirty = true;
+                    self.terminal_size = (0, 0); // render_mode/density may have changed
+                }
+                HttpCommand::ListPresets(reply) => {
+                    let _ = reply.send(PresetList {
+                        presets: self.presets.clone(),
+                        current: self.presets.get(self.current_preset_idx).cloned(),
+                    });
+                }
+                HttpCommand::LoadPreset(name, reply) => {
+                    let result = match self.presets.iter().position(|p| *p == name) {
+                        Some(idx) if self.paths.preset_path(&name).is_some() => {
+                            self.select_preset(idx);
+                            Ok(())
+                        }
+                        _ => Err(format!("Preset introuvable : {name}")),
+                    };
+                    let _ = reply.send(result);
+                }
+                HttpCommand::LoadWorkflow(name, reply) => {
+                    let _ = reply.send(self.execute_workflow_load(&name));
+                }
+                HttpCommand::SaveWorkflow {
+                    name,
+                    description,
+                    reply,
+                } => {
+                    self.workflow_save_name = af_core::workflow::sanitize_workflow_name(&name);
+                    self.workflow_save_desc = description;
+                    let _ = reply.send(self.execute_workflow_save());
+                }
+            }
+        }
+    }
+
+    /// Fold received MIDI messages into `midi_state`. In the learn overlay,
+    /// the first CC or note-on binds to the selected target, replacing every
+    /// mapping of that target (hand-written `[[midi.mappings]]` included) and
+    /// any mapping already using that control.
+    fn poll_midi(&mut self) {
+        let Some(ref midi) = self.midi else {
+            return;
+        };
+        for event in midi.drain() {
+            self.midi_state.update(&event);
+            self.midi_last_event = Some(event.label());
+            if self.state != AppState::MidiLearn {
+                continue;
+            }
+            let target = AUDIO_TARGETS[self.midi_learn_idx];
+            let Some(mapping) = event.to_mapping(target) else {
+                continue;
+            };
+            let label = crate::midi::mapping_label(&mapping);
+            let replaced = self
+                .config
+                .load()
+                .midi_mappings
+                .iter()
+                .filter(|m| m.target == target)
+                .count();
+            self.toggle_config(|c| {
+                c.midi_mappings.retain(|m| {
+                    m.target != target
+                        && !(m.cc == mapping.cc
+                            && m.note == mapping.note
+                            && m.channel == mapping.channel)
+                });
+                c.midi_mappings.push(mapping);
+            });
+            self.workflow_flash_msg = Some(if replaced == 0 {
+                format!("MIDI: {label} \u{2192} {target}")
+            } else {
+                format!("MIDI: {label} \u{2192} {target} (replaced {replaced} mapping(s))")
+            });
+            self.workflow_flash_frames = 60;
+            self.state = AppState::Running;
         }
     }
 
@@ -2586,13 +3134,23 @@ impl App {
             return;
         }
 
-        if forward {
-            self.current_preset_idx = (self.current_preset_idx + 1) % self.presets.len();
+        let idx = if forward {
+            (self.current_preset_idx + 1) % self.presets.len()
         } else if self.current_preset_idx == 0 {
-            self.current_preset_idx = self.presets.len() - 1;
+            self.presets.len() - 1
         } else {
-            self.current_preset_idx -= 1;
+            self.current_preset_idx - 1
+        };
+        self.select_preset(idx);
+    }
+
+    /// Load preset `idx` of the preset list live.
+    fn select_preset(&mut self, idx: usize) {
+        if idx >= self.presets.len() {
+            log::warn!("Preset #{idx} inexistant ({} presets)", self.presets.len());
+            return;
         }
+        self.current_preset_idx = idx;
 
         let name = &self.presets[self.current_preset_idx];
 
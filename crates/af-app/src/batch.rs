@@ -7,18 +7,27 @@ use std::path::Path;
 use af_ascii::compositor::Compositor;
 #[cfg(feature = "video")]
 use af_audio::batch_analyzer::BatchAnalyzer;
-use af_core::config::RenderConfig;
+use af_core::automation::Automation;
 #[cfg(feature = "video")]
-use af_core::frame::{AsciiCell, AsciiGrid, FrameBuffer};
+use af_core::clock::FrameRate;
+#[cfg(feature = "video")]
+use af_core::config::BgStyle;
+use af_core::config::{ExportProfile, RenderConfig};
+#[cfg(feature = "video")]
+use af_core::frame::{AsciiCell, AsciiGrid, AudioFeatures, FrameBuffer};
 #[cfg(feature = "video")]
 use af_core::traits::Source;
 #[cfg(feature = "video")]
-use af_export::muxer::{Mp4Muxer, mux_audio_video};
+use af_export::animation::{AnimEncoder, AnimFormat};
+#[cfg(feature = "video")]
+use af_export::muxer::{Mp4Muxer, ProfileSpec, mux_audio_video, validate_output};
 #[cfg(feature = "video")]
 use af_export::rasterizer::Rasterizer;
 
 #[cfg(feature = "video")]
 use af_source::folder_batch::FolderBatchSource;
+#[cfg(feature = "video")]
+use af_source::procedural::{GeneratorKind, GeneratorParams, ProceduralSource};
 
 #[cfg(feature = "video")]
 use crate::generative::AutoGenerativeMapper;
@@ -72,6 +81,231 @@ const PROB_INVERT_FLASH: f64 = 0.10;
 #[cfg(feature = "video")]
 const PROB_CAMERA_BURST: f64 = 0.04;
 
+// ─── Frame Limits ──────────────────────────────────────────────────
+
+/// Fenêtre de frames exportée (`--frame-range`, `--max-duration`).
+///
+/// Ne s'applique qu'aux sorties GIF/APNG : l'export MP4 rend toujours la
+/// piste audio complète. Toute la timeline reste simulée jusqu'à `end`
+/// (mutations, séquenceur de presets) ; seules les frames de la fenêtre sont
+/// rasterisées et encodées.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameLimits {
+    /// Première frame exportée.
+    pub start: usize,
+    /// Frame de fin (exclue). `None` = fin de la timeline.
+    pub end: Option<usize>,
+    /// Durée maximale exportée (secondes). `None` = défaut GIF/APNG.
+    pub max_duration_secs: Option<f32>,
+}
+
+impl FrameLimits {
+    /// Construit les limites depuis `--frame-range START:END` et `--max-duration`.
+    ///
+    /// `START` et `END` sont optionnels (`120:`, `:300`) ; `END` est exclu.
+    ///
+    /// # Errors
+    /// Retourne une erreur si la plage est mal formée ou vide, ou si la durée
+    /// n'est pas strictement positive.
+    pub fn parse(range: Option<&str>, max_duration_secs: Option<f32>) -> Result<Self> {
+        let (start, end) = match range {
+            None => (0, None),
+            Some(r) => {
+                let (a, b) = r.split_once(':').ok_or_else(|| {
+                    anyhow::anyhow!("--frame-range attend START:END (ex. 0:150), reçu '{r}'")
+                })?;
+                let parse = |s: &str| -> Result<Option<usize>> {
+                    let s = s.trim();
+                    if s.is_empty() {
+                        return Ok(None);
+                    }
+                    s.parse()
+                        .map(Some)
+                        .map_err(|_| anyhow::anyhow!("--frame-range : '{s}' n'est pas un entier"))
+                };
+                let start = parse(a)?.unwrap_or(0);
+                let end = parse(b)?;
+                if end.is_some_and(|e| e <= start) {
+                    anyhow::bail!("--frame-range vide : {r}");
+                }
+                (start, end)
+            }
+        };
+        if let Some(d) = max_duration_secs
+            && !(d > 0.0 && d.is_finite())
+        {
+            anyhow::bail!("--max-duration doit être > 0 (reçu {d})");
+        }
+        Ok(Self {
+            start,
+            end,
+            max_duration_secs,
+        })
+    }
+
+    /// `true` si aucune limite explicite n'a été demandée.
+    #[must_use]
+    pub fn is_unset(&self) -> bool {
+        self.start == 0 && self.end.is_none() && self.max_duration_secs.is_none()
+    }
+
+    /// Fenêtre effective pour une animation de `total_frames` à `fps`.
+    ///
+    /// La durée est plafonnée à `--max-duration` (défaut
+    /// [`DEFAULT_MAX_DURATION_SECS`](af_export::animation::DEFAULT_MAX_DURATION_SECS))
+    /// et à [`MAX_ANIM_FRAMES`](af_export::animation::MAX_ANIM_FRAMES).
+    #[must_use]
+    pub fn resolve(&self, total_frames: usize, fps: u32) -> std::ops::Range<usize> {
+        use af_export::animation::{DEFAULT_MAX_DURATION_SECS, MAX_ANIM_FRAMES};
+
+        let start = self.start.min(total_frames);
+        let secs = self.max_duration_secs.unwrap_or(DEFAULT_MAX_DURATION_SECS);
+        let max_len =
+            ((secs * fps.max(1) as f32).round() as usize).clamp(1, MAX_ANIM_FRAMES as usize);
+        let end = self
+            .end
+            .unwrap_or(total_frames)
+            .min(total_frames)
+            .min(start.saturating_add(max_len));
+        start..end
+    }
+}
+
+// ─── Batch Output ──────────────────────────────────────────────────
+
+/// Destination des frames rasterisées, choisie selon l'extension de sortie.
+#[cfg(feature = "video")]
+enum BatchOutput {
+    /// MP4 via ffmpeg (vidéo temporaire, muxée avec l'audio à la fin).
+    Video {
+        muxer: Mp4Muxer,
+        temp_video: std::path::PathBuf,
+    },
+    /// GIF/APNG pur Rust, sans audio.
+    Anim(AnimEncoder),
+}
+
+#[cfg(feature = "video")]
+impl BatchOutput {
+    fn write_frame(&mut self, fb: &FrameBuffer) -> Result<()> {
+        match self {
+            Self::Video { muxer, .. } => muxer.write_frame(fb),
+            Self::Anim(encoder) => encoder.write_frame(fb),
+        }
+    }
+}
+
+// ─── Post Effects ──────────────────────────────────────────────────
+
+/// Chaîne de post-effets offline, dans l'ordre du TUI.
+///
+/// Possède la grille précédente (stabilité temporelle, trainées) et les
+/// buffers de travail pré-alloués (R1), réutilisés d'une frame à l'autre.
+#[cfg(feature = "video")]
+pub(crate) struct PostEffects {
+    prev_grid: AsciiGrid,
+    glow_brightness_buf: Vec<u8>,
+    fg_buf: Vec<(u8, u8, u8)>,
+    row_buf: Vec<AsciiCell>,
+    color_pulse_phase: f32,
+    wave_phase: f32,
+}
+
+#[cfg(feature = "video")]
+impl PostEffects {
+    pub(crate) fn new(grid_w: u16, grid_h: u16) -> Self {
+        Self {
+            prev_grid: AsciiGrid::new(grid_w, grid_h),
+            glow_brightness_buf: Vec::with_capacity(usize::from(grid_w) * usize::from(grid_h)),
+            fg_buf: Vec::new(),
+            row_buf: Vec::new(),
+            color_pulse_phase: 0.0,
+            wave_phase: 0.0,
+        }
+    }
+
+    /// `true` si une grille précédente existe (effets applicables sans source).
+    pub(crate) fn has_previous(&self) -> bool {
+        self.prev_grid.width > 0
+    }
+
+    /// Applique les effets sur `grid` puis la mémorise pour la frame suivante.
+    ///
+    /// L'`onset_envelope` est tenu à jour par l'appelant (avant le mapping audio).
+    pub(crate) fn apply(
+        &mut self,
+        grid: &mut AsciiGrid,
+        config: &RenderConfig,
+        features: &AudioFeatures,
+        onset_envelope: f32,
+        fps: u32,
+    ) {
+        // 0. Temporal stability (anti-flicker)
+        if config.temporal_stability > 0.0 {
+            af_render::effects::apply_temporal_stability(
+                grid,
+                &self.prev_grid,
+                config.temporal_stability,
+            );
+        }
+
+        // Color pulse phase
+        if config.color_pulse_speed > 0.0 {
+            self.color_pulse_phase =
+                (self.color_pulse_phase + config.color_pulse_speed / fps as f32) % 1.0;
+        } else {
+            self.color_pulse_phase = 0.0;
+        }
+
+        // 1. Wave distortion
+        if config.wave_amplitude > 0.001 {
+            self.wave_phase =
+                (self.wave_phase + config.wave_speed / fps as f32) % std::f32::consts::TAU;
+        }
+        let wave_phase_total = self.wave_phase + features.beat_phase * std::f32::consts::TAU * 0.5;
+        af_render::effects::apply_wave_distortion(
+            grid,
+            config.wave_amplitude,
+            config.wave_speed,
+            wave_phase_total,
+            &mut self.row_buf,
+        );
+
+        // 2. Chromatic aberration
+        af_render::effects::apply_chromatic_aberration(
+            grid,
+            config.chromatic_offset,
+            &mut self.fg_buf,
+        );
+
+        // 3. Color pulse
+        af_render::effects::apply_color_pulse(grid, self.color_pulse_phase);
+
+        // 4. Fade trails
+        if config.fade_decay > 0.0 {
+            af_render::effects::apply_fade_trails(grid, &self.prev_grid, config.fade_decay);
+        }
+
+        // 5. Strobe
+        af_render::effects::apply_strobe(grid, onset_envelope, config.beat_flash_intensity);
+
+        // 6. Scan lines
+        af_render::effects::apply_scan_lines(grid, config.scanline_gap, config.scanline_darken);
+
+        // 7. Glow
+        if config.glow_intensity > 0.0 {
+            af_render::effects::apply_glow(
+                grid,
+                config.glow_intensity,
+                &mut self.glow_brightness_buf,
+            );
+        }
+
+        // Save grid for next frame
+        self.prev_grid.copy_from(grid);
+    }
+}
+
 // ─── Smooth Override ───────────────────────────────────────────────
 
 /// Smoothed mutation override with ramp-up/hold/ramp-down easing.
@@ -264,6 +498,8 @@ struct PresetSequencer {
     transition: Option<PresetTransition>,
     frames_at_current: u32,
     prev_energy: u8,
+    /// Section boundary reached during a transition, applied when it ends.
+    pending_section: bool,
 }
 
 #[cfg(feature = "video")]
@@ -275,6 +511,7 @@ impl PresetSequencer {
             transition: None,
             frames_at_current: 0,
             prev_energy: 1,
+            pending_section: false,
         }
     }
 
@@ -322,20 +559,39 @@ impl PresetSequencer {
     }
 
     /// Check if a preset change should be triggered.
-    fn should_change(&mut self, energy: u8, preset_duration_frames: u32, fps: u32) -> bool {
-        if self.transition.is_some() || self.presets.len() < 2 {
+    ///
+    /// `section_start` is `Some` when the timeline has structural sections:
+    /// section boundaries then replace the energy-change trigger. A boundary
+    /// reached during a transition is kept and fires once the transition ends.
+    fn should_change(
+        &mut self,
+        energy: u8,
+        section_start: Option<bool>,
+        preset_duration_frames: u32,
+        fps: u32,
+    ) -> bool {
+        if self.presets.len() < 2 {
+            return false;
+        }
+        if section_start == Some(true) {
+            self.pending_section = true;
+        }
+        if self.transition.is_some() {
             return false;
         }
 
-        // Energy transition trigger (with minimum duration)
+        // Structural trigger: section boundary, or energy transition (with minimum duration)
         let min_frames = (MIN_PRESET_DURATION_SECS * fps as f32) as u32;
-        let energy_changed = energy != self.prev_energy && self.frames_at_current >= min_frames;
+        let structural = match section_start {
+            Some(_) => std::mem::take(&mut self.pending_section),
+            None => energy != self.prev_energy && self.frames_at_current >= min_frames,
+        };
         self.prev_energy = energy;
 
         // Time-based trigger
         let time_expired = self.frames_at_current >= preset_duration_frames;
 
-        energy_changed || time_expired
+        structural || time_expired
     }
 }
 
@@ -370,6 +626,9 @@ fn interpolate_configs(from: &RenderConfig, to: &RenderConfig, t: f32, out: &mut
     out.camera_rotation = lerp(from.camera_rotation, to.camera_rotation);
     out.camera_pan_x = lerp(from.camera_pan_x, to.camera_pan_x);
     out.camera_pan_y = lerp(from.camera_pan_y, to.camera_pan_y);
+    out.generator_speed = lerp(from.generator_speed, to.generator_speed);
+    out.generator_scale = lerp(from.generator_scale, to.generator_scale);
+    out.generator_complexity = lerp(from.generator_complexity, to.generator_complexity);
     out.audio_sensitivity = lerp(from.audio_sensitivity, to.audio_sensitivity);
     out.audio_smoothing = lerp(from.audio_smoothing, to.audio_smoothing);
 
@@ -392,6 +651,132 @@ fn interpolate_configs(from: &RenderConfig, to: &RenderConfig, t: f32, out: &mut
     }
 }
 
+// ─── Batch Source ──────────────────────────────────────────────────
+
+/// Native resolution of procedural generators in batch (upscaled by the resizer).
+#[cfg(feature = "video")]
+const PROCEDURAL_BATCH_SIZE: (u32, u32) = (640, 360);
+
+/// Visual input of the batch pipeline: media folder or procedural generator.
+#[cfg(feature = "video")]
+enum BatchSource {
+    Folder(FolderBatchSource),
+    Procedural {
+        generator: Box<ProceduralSource>,
+        /// `--generator all`: cycle through every generator on clip advance.
+        cycle: bool,
+        clip_frame_count: u32,
+        max_clip_frames: u32,
+    },
+}
+
+#[cfg(feature = "video")]
+impl BatchSource {
+    /// Build a procedural source from a `--generator` name (`all` = cycle).
+    fn procedural(name: &str, total_frames: u32, seed: Option<u64>) -> Result<Self> {
+        let cycle = name.eq_ignore_ascii_case("all");
+        let kind = if cycle {
+            GeneratorKind::Plasma
+        } else {
+            GeneratorKind::from_name(name).with_context(|| {
+                format!(
+                    "Générateur inconnu : {name}. Disponibles : all, {}",
+                    GeneratorKind::ALL.map(GeneratorKind::name).join(", ")
+                )
+            })?
+        };
+        let (w, h) = PROCEDURAL_BATCH_SIZE;
+        let mut generator = ProceduralSource::new(kind, w, h);
+        if let Some(s) = seed {
+            generator = generator.with_seed(s);
+        }
+        let max_clip_frames = if cycle {
+            (total_frames / GeneratorKind::ALL.len() as u32).max(1)
+        } else {
+            u32::MAX
+        };
+        Ok(Self::Procedural {
+            generator: Box::new(generator),
+            cycle,
+            clip_frame_count: 0,
+            max_clip_frames,
+        })
+    }
+
+    fn native_size(&self) -> (u32, u32) {
+        match self {
+            Self::Folder(s) => s.native_size(),
+            Self::Procedural { generator, .. } => generator.native_size(),
+        }
+    }
+
+    fn clip_frame_count(&self) -> u32 {
+        match self {
+            Self::Folder(s) => s.clip_frame_count(),
+            Self::Procedural {
+                clip_frame_count, ..
+            } => *clip_frame_count,
+        }
+    }
+
+    fn max_clip_frames(&self) -> u32 {
+        match self {
+            Self::Folder(s) => s.max_clip_frames(),
+            Self::Procedural {
+                max_clip_frames, ..
+            } => *max_clip_frames,
+        }
+    }
+
+    fn set_crossfade_duration(&mut self, frames: u32) {
+        if let Self::Folder(s) = self {
+            s.set_crossfade_duration(frames);
+        }
+    }
+
+    fn next_media(&mut self) {
+        match self {
+            Self::Folder(s) => s.next_media(),
+            Self::Procedural {
+                generator,
+                cycle,
+                clip_frame_count,
+                ..
+            } => {
+                if *cycle {
+                    generator.set_kind(generator.kind().next());
+                    log::info!("Générateur : {}", generator.kind().name());
+                }
+                *clip_frame_count = 0;
+            }
+        }
+    }
+
+    /// Push the audio-modulated generator parameters (no-op for folders).
+    fn set_params(&mut self, config: &RenderConfig, fps: u32) {
+        if let Self::Procedural { generator, .. } = self {
+            generator.set_params(GeneratorParams {
+                fps,
+                ..GeneratorParams::from_config(config)
+            });
+        }
+    }
+
+    fn next_frame(&mut self) -> Option<std::sync::Arc<FrameBuffer>> {
+        match self {
+            Self::Folder(s) => s.next_frame(),
+            Self::Procedural {
+                generator,
+                clip_frame_count,
+                ..
+            } => {
+                *clip_frame_count = clip_frame_count.saturating_add(1);
+                generator.next_frame()
+            }
+        }
+    }
+}
+
 /// Load all presets: disk (via AppPaths) + embedded, deduped, sorted by name.
 #[cfg(feature = "video")]
 fn load_all_presets(paths: &af_core::paths::AppPaths) -> Vec<(String, RenderConfig)> {
@@ -427,10 +812,68 @@ fn load_all_presets(paths: &af_core::paths::AppPaths) -> Vec<(String, RenderConf
     map.into_iter().collect()
 }
 
+/// Sépare `audio_path` en 4 stems avec SCNet (`model` : "standard" ou "large"),
+/// progression sur stderr. Bloquant.
+///
+/// # Errors
+/// Retourne une erreur si l'environnement Python/SCNet est absent ou si la séparation échoue.
+pub fn separate_stems(
+    audio_path: &Path,
+    model: &str,
+    paths: &af_core::paths::AppPaths,
+) -> Result<af_stems::stem::StemSet> {
+    let model = match model {
+        "large" => af_stems::separator::ModelVariant::Large,
+        _ => af_stems::separator::ModelVariant::Standard,
+    };
+    let sep_config = af_stems::separator::SeparationConfig {
+        model,
+        python_bin: paths.python_bin(),
+        scnet_dir: paths.scnet_dir(),
+    };
+    af_stems::separator::preflight_check(&sep_config)?;
+
+    // Synchronous separation with progress on stderr
+    let (progress_tx, progress_rx) = flume::unbounded();
+    let audio_p = audio_path.to_path_buf();
+    let sep_handle = std::thread::spawn(move || {
+        af_stems::separator::separate_file(&audio_p, &sep_config, &progress_tx)
+    });
+
+    // Print progress to stderr (batch / analyze)
+    while let Ok(prog) = progress_rx.recv() {
+        match prog {
+            af_stems::separator::SeparationProgress::Progress(p) => {
+                eprint!("\r  Separation: {:.0}%", p * 100.0);
+            }
+            af_stems::separator::SeparationProgress::Complete => {
+                eprintln!("\r  Separation: 100% — complete");
+                break;
+            }
+            af_stems::separator::SeparationProgress::Error(e) => {
+                eprintln!("\r  Separation error: {e}");
+                break;
+            }
+            af_stems::separator::SeparationProgress::Starting => {
+                eprintln!("  Separation: starting...");
+            }
+        }
+    }
+
+    sep_handle
+        .join()
+        .map_err(|_| anyhow::anyhow!("Stem separation thread panicked"))?
+}
+
 // ─── Main Export Function ──────────────────────────────────────────
 
 /// Point d'entrée pour l'export génératif par lots.
 ///
+/// La source visuelle est soit un dossier de médias (`folder`), soit un
+/// générateur procédural (`generator`, `"all"` pour les enchaîner). Sans
+/// dossier, `audio_path_str` est obligatoire. `automation` est évaluée au
+/// temps de chaque frame, avant les audio mappings.
+///
 /// # Errors
 /// Retourne une erreur si l'analyse audio, le scan du dossier, ou l'encodage échoue.
 #[allow(
@@ -439,7 +882,8 @@ fn load_all_presets(paths: &af_core::paths::AppPaths) -> Vec<(String, RenderConf
     clippy::too_many_arguments
 )]
 pub fn run_batch_export(
-    folder: &Path,
+    folder: Option<&Path>,
+    generator: Option<&str>,
     audio_path_str: Option<&String>,
     final_output: Option<&Path>,
     config: RenderConfig,
@@ -453,12 +897,16 @@ pub fn run_batch_export(
     stems_enabled: bool,
     stem_model: &str,
     save_workflow_name: Option<&str>,
+    limits: FrameLimits,
+    profile: ExportProfile,
+    automation: Option<Automation>,
     paths: &af_core::paths::AppPaths,
 ) -> Result<()> {
     #[cfg(not(feature = "video"))]
     {
         let _ = (
             folder,
+            generator,
             audio_path_str,
             final_output,
             config,
@@ -472,6 +920,9 @@ pub fn run_batch_export(
             stems_enabled,
             stem_model,
             save_workflow_name,
+            limits,
+            profile,
+            automation,
             paths,
         );
         anyhow::bail!("L'export par lots requiert la feature 'video' (ffmpeg support).");
@@ -488,7 +939,7 @@ pub fn run_batch_export(
         // === Auto-discovery Audio ===
         let resolved_audio_path = if let Some(path_str) = audio_path_str {
             std::path::PathBuf::from(path_str)
-        } else {
+        } else if let Some(folder) = folder {
             log::info!("Recherche d'un fichier audio dans {}...", folder.display());
             let found = std::fs::read_dir(folder)?
                 .filter_map(std::result::Result::ok)
@@ -508,6 +959,8 @@ pub fn run_batch_export(
                 }
                 None => anyhow::bail!("Aucun fichier audio trouvé dans le dossier."),
             }
+        } else {
+            anyhow::bail!("--audio est requis pour l'export d'un générateur procédural.");
         };
 
         // === Auto-naming Output ===
@@ -515,8 +968,9 @@ pub fn run_batch_export(
             out_path.to_path_buf()
         } else {
             let folder_name = folder
-                .file_name()
+                .and_then(|f| f.file_name())
                 .and_then(|n| n.to_str())
+                .or(generator)
                 .unwrap_or("batch");
             let timestamp = {
                 use std::time::SystemTime;
@@ -568,7 +1022,11 @@ pub fn run_batch_export(
                 }
                 format!("{y}{:02}{:02}_{h:02}{m:02}{s:02}", mo + 1, remaining + 1)
             };
-            let default_name = format!("{folder_name}_{timestamp}.mp4");
+            // Séquence PNG : extension vide → dossier
+            let default_name = match ProfileSpec::of(profile).default_extension() {
+                "" => format!("{folder_name}_{timestamp}"),
+                ext => format!("{folder_name}_{timestamp}.{ext}"),
+            };
             let mut p = paths.base_dir.clone();
             p.push(default_name);
             p
@@ -577,6 +1035,32 @@ pub fn run_batch_export(
         let audio_path = resolved_audio_path.as_path();
         let final_output = resolved_output_path.as_path();
 
+        // GIF/APNG : encodeur pur Rust ; sinon le profil ffmpeg doit accepter le conteneur
+        let anim_format = if profile == ExportProfile::PngSequence {
+            None
+        } else {
+            AnimFormat::from_path(final_output)
+        };
+        if anim_format.is_some() {
+            if profile != ExportProfile::default() {
+                log::warn!(
+                    "--export-profile {} ignoré : sortie GIF/APNG",
+                    profile.name()
+                );
+            }
+        } else {
+            validate_output(profile, final_output)?;
+        }
+
+        // Fond transparent : alpha réel seulement si la sortie le conserve
+        let keep_alpha = anim_format.is_some() || ProfileSpec::of(profile).alpha;
+        if matches!(config.bg_style, BgStyle::Transparent) && !keep_alpha {
+            log::warn!(
+                "Fond transparent ignoré : le profil {} n'a pas de canal alpha (utiliser prores, vp9 ou png-sequence)",
+                profile.name()
+            );
+        }
+
         // === Preset sequencer (--preset all) ===
         let mut preset_seq = if preset_all {
             let all_presets = load_all_presets(paths);
@@ -602,8 +1086,19 @@ pub fn run_batch_export(
 
         // === Étape 1 : Pré-analyse audio complète (offline) ===
         log::info!("Étape 1/4 : Analyse Audio de {}", audio_path.display());
-        let mut analyzer = BatchAnalyzer::new(target_fps, 44100, 2048);
+        let mut analyzer =
+            BatchAnalyzer::with_analysis(target_fps, 44100, &initial_config.analysis);
         let timeline = analyzer.analyze_file(audio_path)?;
+        log::info!("Structure : {} sections", timeline.sections.len());
+        for section in &timeline.sections {
+            log::debug!(
+                "  {:>7.1}s {:?} (label {}, énergie {:.2})",
+                section.start as f32 * timeline.frame_duration,
+                section.kind,
+                section.label,
+                section.energy
+            );
+        }
 
         // === Optional stem separation + per-stem analysis ===
         // Store stem samples + metadata for workflow save (if requested)
@@ -611,50 +1106,7 @@ pub fn run_batch_export(
 
         let stem_timeline = if stems_enabled {
             log::info!("Étape 1b/4 : Séparation stems (modèle: {stem_model})...");
-            let model = match stem_model {
-                "large" => af_stems::separator::ModelVariant::Large,
-                _ => af_stems::separator::ModelVariant::Standard,
-            };
-            let mut sep_config = af_stems::separator::SeparationConfig {
-                model: af_stems::separator::ModelVariant::Standard,
-                python_bin: paths.python_bin(),
-                scnet_dir: paths.scnet_dir(),
-            };
-            sep_config.model = model;
-            af_stems::separator::preflight_check(&sep_config)?;
-
-            // Synchronous separation with progress on stderr
-            let (progress_tx, progress_rx) = flume::unbounded();
-            let audio_p = audio_path.to_path_buf();
-            let sep_start = std::time::Instant::now();
-            let sep_handle = std::thread::spawn(move || {
-                af_stems::separator::separate_file(&audio_p, &sep_config, &progress_tx)
-            });
-
-            // Print progress to stderr for batch mode
-            while let Ok(prog) = progress_rx.recv() {
-                match prog {
-                    af_stems::separator::SeparationProgress::Progress(p) => {
-                        eprint!("\r  Separation: {:.0}%", p * 100.0);
-                    }
-                    af_stems::separator::SeparationProgress::Complete => {
-                        eprintln!("\r  Separation: 100% — complete");
-                        break;
-                    }
-                    af_stems::separator::SeparationProgress::Error(e) => {
-                        eprintln!("\r  Separation error: {e}");
-                        break;
-                    }
-                    af_stems::separator::SeparationProgress::Starting => {
-                        eprintln!("  Separation: starting...");
-                    }
-                }
-            }
-
-            let stem_set = sep_handle
-                .join()
-                .map_err(|_| anyhow::anyhow!("Stem separation thread panicked"))??;
-            let _sep_elapsed = sep_start.elapsed().as_secs_f64();
+            let stem_set = separate_stems(audio_path, stem_model, paths)?;
 
             log::info!("Étape 1c/4 : Analyse per-stem features...");
             let stem_samples: [Vec<f32>; 4] =
@@ -681,6 +1133,9 @@ pub fn run_batch_export(
         };
 
         let mut mapper = AutoGenerativeMapper::new(initial_config, timeline);
+        if let Some(automation) = automation {
+            mapper.set_automation(automation);
+        }
 
         // Inject stem timeline into mapper for per-stem audio mappings
         if let Some(stl) = stem_timeline {
@@ -692,13 +1147,23 @@ pub fn run_batch_export(
             mapper.set_stem_timeline(stl);
         }
 
-        // === Étape 2 : Initialisation de la source dossier ===
-        log::info!(
-            "Étape 2/4 : Initialisation Media Folder {}",
-            folder.display()
-        );
+        // === Étape 2 : Initialisation de la source (dossier ou générateur) ===
         let total_frames_u32 = mapper.get_timeline().total_frames() as u32;
-        let mut source = FolderBatchSource::new(folder, target_fps, total_frames_u32)?;
+        let mut source = if let Some(folder) = folder {
+            log::info!(
+                "Étape 2/4 : Initialisation Media Folder {}",
+                folder.display()
+            );
+            BatchSource::Folder(FolderBatchSource::new(
+                folder,
+                target_fps,
+                total_frames_u32,
+            )?)
+        } else {
+            let name = generator.unwrap_or("plasma");
+            log::info!("Étape 2/4 : Initialisation du générateur procédural '{name}'");
+            BatchSource::procedural(name, total_frames_u32, seed)?
+        };
 
         let (native_w, native_h) = source.native_size();
         let target_w = native_w.max(1280);
@@ -714,19 +1179,66 @@ pub fn run_batch_export(
 
         let font_data = include_bytes!("../../af-export/assets/CascadiaMono.ttf");
         let scale_val = export_scale.unwrap_or(16.0);
-        let rasterizer = Rasterizer::new(font_data, scale_val)?;
+        let mut rasterizer = Rasterizer::new(font_data, scale_val)?;
 
         let (raster_w, raster_h) = rasterizer.target_dimensions(grid_w, grid_h);
 
-        let temp_video = final_output.with_extension("temp.mp4");
-        let mut muxer = Mp4Muxer::new(&temp_video, raster_w, raster_h, target_fps)?;
+        let total_frames = mapper.get_timeline().total_frames();
+
+        // GIF/APNG : fenêtre de frames limitée
+        let export_window = if anim_format.is_some() {
+            limits.resolve(total_frames, target_fps)
+        } else {
+            if !limits.is_unset() {
+                log::warn!("--frame-range/--max-duration ignorés : réservés aux sorties GIF/APNG");
+            }
+            0..total_frames
+        };
+        let mut output = if let Some(format) = anim_format {
+            log::info!(
+                "Sortie {format:?} : frames {}..{} ({:.1}s), sans audio",
+                export_window.start,
+                export_window.end,
+                export_window.len() as f64 / f64::from(target_fps.max(1))
+            );
+            BatchOutput::Anim(AnimEncoder::new(
+                final_output,
+                format,
+                raster_w,
+                raster_h,
+                target_fps,
+                export_window.len() as u32,
+            )?)
+        } else {
+            log::info!("Profil d'export : {}", profile.name());
+            // Séquence PNG : écrite directement, pas de muxage audio
+            let temp_video = if profile == ExportProfile::PngSequence {
+                final_output.to_path_buf()
+            } else {
+                let ext = final_output
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .unwrap_or("mp4");
+                final_output.with_extension(format!("temp.{ext}"))
+            };
+            BatchOutput::Video {
+                muxer: Mp4Muxer::with_profile(
+                    &temp_video,
+                    raster_w,
+                    raster_h,
+                    FrameRate::integer(target_fps),
+                    profile,
+                )?,
+                temp_video,
+            }
+        };
+        let total_frames = export_window.end;
 
         let mut frame_config = RenderConfig::default();
         mapper.apply_at(0.0, 0.0, &mut frame_config);
         let mut compositor = Compositor::new(&frame_config.charset);
         let mut raster_fb = FrameBuffer::new(raster_w, raster_h);
 
-        let total_frames = mapper.get_timeline().total_frames();
         let frame_duration = 1.0 / f64::from(target_fps.max(1));
         let preset_duration_frames = (preset_duration_secs * target_fps as f32) as u32;
 
@@ -735,14 +1247,8 @@ pub fn run_batch_export(
         let mut transformed_source = FrameBuffer::new(1, 1);
 
         // === Pre-allocated effect buffers (R1 compliance) ===
-        let mut prev_grid = AsciiGrid::new(grid_w, grid_h);
-        let mut glow_brightness_buf: Vec<u8> =
-            Vec::with_capacity(usize::from(grid_w) * usize::from(grid_h));
-        let mut effect_fg_buf: Vec<(u8, u8, u8)> = Vec::new();
-        let mut effect_row_buf: Vec<AsciiCell> = Vec::new();
+        let mut effects = PostEffects::new(grid_w, grid_h);
         let mut onset_envelope: f32 = 0.0;
-        let mut color_pulse_phase: f32 = 0.0;
-        let mut wave_phase: f32 = 0.0;
 
         // Pre-allocated charset pool — aligned with TUI key mapping (1-0) + export extras.
         // Indices 0-9 match TUI keys; 10-13 are batch/TOML-only charsets.
@@ -776,10 +1282,16 @@ pub fn run_batch_export(
             let current_features = mapper.get_timeline().get_at_time(timestamp_secs);
 
             // === 0. PRESET SEQUENCING (--preset all) ===
-            let energy = mapper.get_timeline().energy_at(frame_idx);
+            // A section boundary sets the pace of the section it opens
+            let timeline = mapper.get_timeline();
+            let section_start = timeline.section_starting_at(frame_idx).copied();
+            let energy = section_start
+                .map_or_else(|| timeline.energy_at(frame_idx), |s| s.kind.energy_level());
+            let has_sections = !timeline.sections.is_empty();
 
             if let Some(ref mut seq) = preset_seq {
-                if seq.should_change(energy, preset_duration_frames, target_fps) {
+                let trigger = has_sections.then_some(section_start.is_some());
+                if seq.should_change(energy, trigger, preset_duration_frames, target_fps) {
                     let transition_dur = match energy {
                         2 => target_fps,     // ~1s fast
                         0 => target_fps * 3, // ~3s slow
@@ -804,11 +1316,12 @@ pub fn run_batch_export(
             // === 1. CLIP SEQUENCING (decoupled from mutations) ===
             let clip_budget = match energy {
                 2 => source.max_clip_frames() / 2,
-                0 => source.max_clip_frames() * 3 / 2,
+                0 => source.max_clip_frames().saturating_mul(3) / 2,
                 _ => source.max_clip_frames(),
             };
 
             let should_advance = source.clip_frame_count() >= clip_budget
+                || section_start.is_some()
                 || (energy == 2
                     && current_features.onset
                     && current_features.beat_intensity > CLIP_ADVANCE_BEAT_THRESHOLD);
@@ -981,6 +1494,7 @@ pub fn run_batch_export(
             macros.apply(&mut frame_config);
 
             // === 4. RENDER PIPELINE ===
+            source.set_params(&frame_config, target_fps);
             let have_source = if let Some(src_frame) = source.next_frame() {
                 if transformed_source.width != src_frame.width
                     || transformed_source.height != src_frame.height
@@ -1008,93 +1522,26 @@ pub fn run_batch_export(
                 false
             };
 
-            if have_source || prev_grid.width > 0 {
-                // 0. Temporal stability (anti-flicker)
-                if frame_config.temporal_stability > 0.0 {
-                    af_render::effects::apply_temporal_stability(
-                        &mut grid,
-                        &prev_grid,
-                        frame_config.temporal_stability,
-                    );
-                }
-
-                // Onset envelope tracking — already updated before mapper.apply_at() above.
-
-                // Color pulse phase
-                if frame_config.color_pulse_speed > 0.0 {
-                    color_pulse_phase = (color_pulse_phase
-                        + frame_config.color_pulse_speed / target_fps as f32)
-                        % 1.0;
-                } else {
-                    color_pulse_phase = 0.0;
-                }
-
-                // 1. Wave distortion
-                if frame_config.wave_amplitude > 0.001 {
-                    wave_phase = (wave_phase + frame_config.wave_speed / target_fps as f32)
-                        % std::f32::consts::TAU;
-                }
-                let wave_phase_total =
-                    wave_phase + current_features.beat_phase * std::f32::consts::TAU * 0.5;
-                af_render::effects::apply_wave_distortion(
-                    &mut grid,
-                    frame_config.wave_amplitude,
-                    frame_config.wave_speed,
-                    wave_phase_total,
-                    &mut effect_row_buf,
-                );
-
-                // 2. Chromatic aberration
-                af_render::effects::apply_chromatic_aberration(
-                    &mut grid,
-                    frame_config.chromatic_offset,
-                    &mut effect_fg_buf,
-                );
-
-                // 3. Color pulse
-                af_render::effects::apply_color_pulse(&mut grid, color_pulse_phase);
-
-                // 4. Fade trails
-                if frame_config.fade_decay > 0.0 {
-                    af_render::effects::apply_fade_trails(
-                        &mut grid,
-                        &prev_grid,
-                        frame_config.fade_decay,
-                    );
-                }
-
-                // 5. Strobe
-                af_render::effects::apply_strobe(
+            if have_source || effects.has_previous() {
+                effects.apply(
                     &mut grid,
+                    &frame_config,
+                    &current_features,
                     onset_envelope,
-                    frame_config.beat_flash_intensity,
-                );
-
-                // 6. Scan lines
-                af_render::effects::apply_scan_lines(
-                    &mut grid,
-                    frame_config.scanline_gap,
-                    frame_config.scanline_darken,
+                    target_fps,
                 );
 
-                // 7. Glow
-                if frame_config.glow_intensity > 0.0 {
-                    af_render::effects::apply_glow(
-                        &mut grid,
-                        frame_config.glow_intensity,
-                        &mut glow_brightness_buf,
+                if frame_idx >= export_window.start {
+                    raster_fb.data.fill(0);
+                    rasterizer.set_transparent_bg(
+                        keep_alpha && matches!(frame_config.bg_style, BgStyle::Transparent),
                     );
-                }
-
-                // Save grid for next frame
-                prev_grid.copy_from(&grid);
+                    rasterizer.render(&grid, &mut raster_fb, frame_config.zalgo_intensity);
 
-                raster_fb.data.fill(0);
-                rasterizer.render(&grid, &mut raster_fb, frame_config.zalgo_intensity);
-
-                muxer.write_frame(&raster_fb).with_context(|| {
-                    format!("Pipe write failed at frame {frame_idx}/{total_frames}")
-                })?;
+                    output.write_frame(&raster_fb).with_context(|| {
+                        format!("Frame write failed at frame {frame_idx}/{total_frames}")
+                    })?;
+                }
             }
 
             // Progress with ETA
@@ -1112,13 +1559,27 @@ pub fn run_batch_export(
         }
 
         log::info!("Clôture du flux vidéo...");
-        muxer.finish()?;
-
-        // === Étape 4 : Muxage Audio + Vidéo ===
-        log::info!("Étape 4/4 : Muxing Audio/Video Final");
-        let mux_result = mux_audio_video(&temp_video, audio_path, final_output);
-        let _ = std::fs::remove_file(&temp_video); // always clean up temp
-        mux_result?;
+        match output {
+            BatchOutput::Video { muxer, temp_video } => {
+                muxer.finish()?;
+
+                // === Étape 4 : Muxage Audio + Vidéo ===
+                if ProfileSpec::of(profile).audio_args.is_some() {
+                    log::info!("Étape 4/4 : Muxing Audio/Video Final");
+                    let mux_result =
+                        mux_audio_video(&temp_video, audio_path, final_output, profile);
+                    let _ = std::fs::remove_file(&temp_video); // always clean up temp
+                    mux_result?;
+                } else {
+                    log::info!("Étape 4/4 : séquence d'images, pas de piste audio");
+                }
+            }
+            BatchOutput::Anim(encoder) => {
+                // Pas d'audio en GIF/APNG : l'étape de muxage est sautée
+                log::info!("Étape 4/4 : {} frames encodées", encoder.frames_written());
+                encoder.finish()?;
+            }
+        }
 
         log::info!("Export réussi vers {}", final_output.display());
 
@@ -1127,10 +1588,18 @@ pub fn run_batch_export(
             use af_core::workflow;
             use af_core::workflow_io;
 
-            let source = workflow::SourceInfo {
-                path: folder.to_path_buf(),
-                media_type: workflow::MediaType::Video,
-                audio_path: Some(audio_path.to_path_buf()),
+            let source = if let Some(folder) = folder {
+                workflow::SourceInfo {
+                    path: folder.to_path_buf(),
+                    media_type: workflow::MediaType::Video,
+                    audio_path: Some(audio_path.to_path_buf()),
+                }
+            } else {
+                workflow::SourceInfo {
+                    path: std::path::PathBuf::from(generator.unwrap_or("plasma")),
+                    media_type: workflow::MediaType::Procedural,
+                    audio_path: Some(audio_path.to_path_buf()),
+                }
             };
 
             // Build stem WAV paths + metadata if stems were separated
@@ -1168,6 +1637,10 @@ pub fn run_batch_export(
                 log::info!("Stem WAVs written to workflow");
             }
 
+            if let Some(automation) = mapper.automation() {
+                workflow_io::save_automation(&wf_dir, automation)?;
+            }
+
             // Save feature timeline as binary for deterministic replay
             let timeline = mapper.get_timeline();
             if let Err(e) = workflow_io::save_feature_timeline(&wf_dir, timeline) {
@@ -1221,6 +1694,40 @@ mod tests {
         assert!(o3.value() < 1.5, "ramp down should reduce value");
     }
 
+    #[test]
+    fn post_effects_neutral_config_is_identity() {
+        let config = RenderConfig {
+            temporal_stability: 0.0,
+            wave_amplitude: 0.0,
+            chromatic_offset: 0.0,
+            color_pulse_speed: 0.0,
+            fade_decay: 0.0,
+            beat_flash_intensity: 0.0,
+            scanline_gap: 0,
+            glow_intensity: 0.0,
+            ..RenderConfig::default()
+        };
+        let mut grid = AsciiGrid::new(4, 3);
+        for (i, cell) in grid.cells.iter_mut().enumerate() {
+            cell.ch = if i % 2 == 0 { '#' } else { '.' };
+            cell.fg = (i as u8 * 20, 100, 200);
+        }
+        let before: Vec<_> = grid.cells.iter().map(|c| (c.ch, c.fg)).collect();
+
+        let mut effects = PostEffects::new(4, 3);
+        effects.apply(&mut grid, &config, &AudioFeatures::default(), 0.0, 30);
+
+        let after: Vec<_> = grid.cells.iter().map(|c| (c.ch, c.fg)).collect();
+        assert_eq!(before, after);
+        let saved: Vec<_> = effects
+            .prev_grid
+            .cells
+            .iter()
+            .map(|c| (c.ch, c.fg))
+            .collect();
+        assert_eq!(saved, after, "grid kept for the next frame");
+    }
+
     #[test]
     fn interpolate_configs_endpoints() {
         let a = RenderConfig {
@@ -1269,6 +1776,29 @@ mod tests {
         assert_eq!(seq.presets[seq.current_idx].0, "a"); // Cycle
     }
 
+    #[test]
+    fn section_boundary_during_transition_is_latched() {
+        let presets = vec![
+            ("a".into(), RenderConfig::default()),
+            ("b".into(), RenderConfig::default()),
+        ];
+        let mut seq = PresetSequencer::new(presets);
+        let mut out = RenderConfig::default();
+        seq.advance(3);
+        seq.write_config(&mut out);
+        // Boundary lands mid-transition: no change yet
+        assert!(!seq.should_change(1, Some(true), u32::MAX, 30));
+        seq.write_config(&mut out);
+        assert!(!seq.should_change(1, Some(false), u32::MAX, 30));
+        seq.write_config(&mut out);
+        assert!(seq.transition.is_none());
+        // Transition over: the latched boundary fires once
+        assert!(seq.should_change(1, Some(false), u32::MAX, 30));
+        seq.advance(1);
+        seq.write_config(&mut out);
+        assert!(!seq.should_change(1, Some(false), u32::MAX, 30));
+    }
+
     #[test]
     fn load_all_presets_finds_toml_files() -> anyhow::Result<()> {
         // Ensure we're in the workspace root (tests may run from crate dir)
@@ -1295,4 +1825,47 @@ mod tests {
         assert_eq!(names, sorted);
         Ok(())
     }
+
+    #[test]
+    fn frame_limits_parse() -> anyhow::Result<()> {
+        assert_eq!(FrameLimits::parse(None, None)?, FrameLimits::default());
+        let l = FrameLimits::parse(Some("30:90"), Some(2.5))?;
+        assert_eq!(
+            (l.start, l.end, l.max_duration_secs),
+            (30, Some(90), Some(2.5))
+        );
+        assert_eq!(FrameLimits::parse(Some("120:"), None)?.end, None);
+        assert_eq!(FrameLimits::parse(Some(":60"), None)?.start, 0);
+        assert!(FrameLimits::parse(Some("90:30"), None).is_err());
+        assert!(FrameLimits::parse(Some("12"), None).is_err());
+        assert!(FrameLimits::parse(Some("a:b"), None).is_err());
+        assert!(FrameLimits::parse(None, Some(0.0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn frame_limits_resolve_caps_duration() {
+        // Défaut : 10 s max
+        assert_eq!(FrameLimits::default().resolve(1000, 30), 0..300);
+        assert_eq!(FrameLimits::default().resolve(100, 30), 0..100);
+        let l = FrameLimits {
+            start: 50,
+            end: Some(80),
+            max_duration_secs: None,
+        };
+        assert_eq!(l.resolve(1000, 30), 50..80);
+        assert_eq!(l.resolve(60, 30), 50..60);
+        let l = FrameLimits {
+            start: 10,
+            end: None,
+            max_duration_secs: Some(1.0),
+        };
+        assert_eq!(l.resolve(1000, 24), 10..34);
+        // Plafond absolu en frames
+        let l = FrameLimits {
+            max_duration_secs: Some(1.0e6),
+            ..FrameLimits::default()
+        };
+        assert_eq!(l.resolve(usize::MAX, 60).len(), 3000);
+    }
 }
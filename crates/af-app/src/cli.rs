@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 
 /// clasSCII — Audio-reactive ASCII art engine.
 #[derive(Parser, Debug)]
@@ -15,7 +15,20 @@ pub struct Cli {
     #[arg(long)]
     pub video: Option<PathBuf>,
 
-    /// Source audio : "mic" pour microphone, ou chemin vers fichier audio.
+    /// Source visuelle live : caméra ("default", index, /dev/videoN, ou FORMAT:DEVICE
+    /// comme "lavfi:testsrc"). Requiert --features video.
+    #[arg(long)]
+    pub camera: Option<String>,
+
+    /// Source visuelle procédurale : plasma, noise, reaction-diffusion, life, metaballs, tunnel.
+    /// En batch (avec --batch-out), "all" enchaîne tous les générateurs.
+    #[arg(long)]
+    pub generator: Option<String>,
+
+    /// Source audio : "mic" pour microphone, "loopback" pour le son joué par
+    /// la machine, "-" ou "fifo:CHEMIN" pour du PCM brut (--pcm-format),
+    /// "net:PORT" pour les features d'une autre instance (--audio-send),
+    /// ou chemin vers fichier audio.
     #[arg(long)]
     pub audio: Option<String>,
 
@@ -23,10 +36,17 @@ pub struct Cli {
     #[arg(long)]
     pub batch_folder: Option<PathBuf>,
 
-    /// Fichier de destination final MP4. Requis si --batch-folder est utilisé.
+    /// Fichier de destination final : .mp4 (ffmpeg), ou .gif / .apng (pur Rust, sans audio).
+    /// Requis si --batch-folder est utilisé.
+    /// Combiné à --generator, lance l'export par lots du générateur.
     #[arg(long)]
     pub batch_out: Option<PathBuf>,
 
+    /// Transcoder --video image par image vers ce fichier (sans TUI) : cadence
+    /// source, audio de la vidéo, sans mutations ni séquence de presets.
+    #[arg(long)]
+    pub export: Option<PathBuf>,
+
     /// Fichier de configuration TOML. Si omis, résolution automatique.
     #[arg(short, long)]
     pub config: Option<PathBuf>,
@@ -55,10 +75,29 @@ pub struct Cli {
     #[arg(long, default_value_t = false)]
     pub no_color: bool,
 
+    /// Backend d'affichage du canvas : text, sixel, kitty.
+    /// Sixel/Kitty rasterisent la grille en pixels (terminal compatible requis).
+    #[arg(long)]
+    pub display: Option<String>,
+
     /// Niveau de log : error, warn, info, debug, trace.
     #[arg(long, default_value = "warn")]
     pub log_level: String,
 
+    /// Rendre une frame de --image sans TUI vers un fichier texte et quitter.
+    /// Format selon l'extension : .txt (brut), .ans (ANSI truecolor), .html.
+    #[arg(long)]
+    pub snapshot: Option<PathBuf>,
+
+    /// Largeur du snapshot en colonnes (hauteur déduite du ratio de l'image).
+    #[arg(long)]
+    pub snapshot_width: Option<u16>,
+
+    /// Enregistrer la session TUI en asciicast v2 (.cast) dès le démarrage.
+    /// Ctrl+R démarre/arrête un enregistrement en cours de session.
+    #[arg(long)]
+    pub record: Option<PathBuf>,
+
     /// Lister tous les presets disponibles et quitter.
     #[arg(long, default_value_t = false)]
     pub preset_list: bool,
@@ -75,6 +114,24 @@ pub struct Cli {
     #[arg(long)]
     pub crossfade_ms: Option<u32>,
 
+    /// Profil d'encodage batch : lossless, h264, hevc, vp9, prores, png-sequence.
+    /// Défaut : section [export] de la config, sinon lossless.
+    #[arg(long)]
+    pub export_profile: Option<String>,
+
+    /// Fichier d'automation (TOML ou .json) : keyframes `[[automation]]` par paramètre,
+    /// évaluées sur l'horloge média (TUI) ou le temps de frame (batch, transcodage).
+    #[arg(long)]
+    pub automation: Option<PathBuf>,
+
+    /// Plage de frames exportée en GIF/APNG : START:END (END exclu, bornes optionnelles).
+    #[arg(long)]
+    pub frame_range: Option<String>,
+
+    /// Durée maximale d'un export GIF/APNG (secondes, défaut 10).
+    #[arg(long)]
+    pub max_duration: Option<f32>,
+
     /// Multiplicateur d'intensité des mutations batch (0=aucune, 1=défaut, 2=aggressif).
     #[arg(long)]
     pub mutation_intensity: Option<f32>,
@@ -87,6 +144,49 @@ pub struct Cli {
     #[arg(long, default_value = "standard")]
     pub stem_model: String,
 
+    /// Serveur OSC (UDP) de contrôle à distance : PORT ou ADRESSE:PORT (ex. 9000).
+    #[arg(long)]
+    pub osc: Option<String>,
+
+    /// Cible du feedback OSC des audio features (HOTE:PORT). Requiert --osc.
+    #[arg(long, requires = "osc")]
+    pub osc_feedback: Option<String>,
+
+    /// API HTTP/WebSocket locale : PORT ou ADRESSE:PORT, loopback uniquement (ex. 8080).
+    #[arg(long)]
+    pub http: Option<String>,
+
+    /// Périphérique d'entrée audio (avec --audio mic) : index ou partie du nom.
+    #[arg(long)]
+    pub audio_device: Option<String>,
+
+    /// Canaux analysés : "mix" (downmix mono), numéro de canal (1 = gauche),
+    /// ou "split" (une analyse par canal, lue par channel_source).
+    #[arg(long, default_value = "mix")]
+    pub audio_channels: af_audio::capture::ChannelRouting,
+
+    /// Fréquence d'échantillonnage demandée au périphérique d'entrée (Hz).
+    #[arg(long)]
+    pub audio_rate: Option<u32>,
+
+    /// Format du PCM brut lu avec --audio - ou fifo:CHEMIN : ENCODAGE:FRÉQUENCE:CANAUX
+    /// (encodage f32 ou s16, little-endian ; ex. f32:48000:1).
+    #[arg(long, default_value = "s16:44100:2")]
+    pub pcm_format: af_audio::pcm::PcmFormat,
+
+    /// Lister les périphériques d'entrée audio et quitter.
+    #[arg(long, default_value_t = false)]
+    pub audio_device_list: bool,
+
+    /// Diffuser les audio features en UDP vers HOTE:PORT (broadcast accepté),
+    /// pour des instances secondaires lancées avec --audio net:PORT.
+    #[arg(long)]
+    pub audio_send: Option<String>,
+
+    /// Entrée MIDI : index ou partie du nom du port (ex. 0, "nanoKONTROL").
+    #[arg(long)]
+    pub midi: Option<String>,
+
     /// Charger un workflow sauvegardé (remplace --config/--preset/--audio).
     #[arg(long)]
     pub load_workflow: Option<PathBuf>,
@@ -98,6 +198,52 @@ pub struct Cli {
     /// Lister tous les workflows sauvegardés et quitter.
     #[arg(long, default_value_t = false)]
     pub workflow_list: bool,
+
+    /// Sous-commande (sans : TUI, batch, transcodage ou snapshot selon les options).
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Sous-commandes de `classcii`.
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Analyser un fichier audio hors-ligne : timeline de features brutes (ou
+    /// normalisées avec --normalized) en CSV / JSON / NPY et résumé dans le
+    /// terminal. Section [audio.analysis] lue depuis --config / --preset.
+    Analyze(AnalyzeArgs),
+}
+
+/// Options de `classcii analyze`.
+#[derive(Args, Debug)]
+pub struct AnalyzeArgs {
+    /// Fichier audio à analyser.
+    pub audio: PathBuf,
+
+    /// Fichier de sortie : .csv, .json ou .npy (colonnes nommées par feature).
+    /// Sans --out, seul le résumé est affiché.
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// Frames de la timeline par seconde.
+    #[arg(long, default_value_t = 30)]
+    pub fps: u32,
+
+    /// Séparer et analyser les stems : colonnes drums.*, bass.*, other.*, vocals.*.
+    #[arg(long, default_value_t = false)]
+    pub stems: bool,
+
+    /// Modèle SCNet pour la séparation : "standard" (41MB) ou "large" (162MB).
+    #[arg(long, default_value = "standard")]
+    pub stem_model: String,
+
+    /// Exporter les features normalisées sur le morceau ([0, 1], comme le
+    /// rendu batch) au lieu des valeurs brutes.
+    #[arg(long, default_value_t = false)]
+    pub normalized: bool,
+
+    /// Ne pas afficher le résumé.
+    #[arg(short, long, default_value_t = false)]
+    pub quiet: bool,
 }
 
 impl Cli {
@@ -108,15 +254,29 @@ impl Cli {
     pub fn validate_source(&self) -> anyhow::Result<()> {
         let count = usize::from(self.image.is_some())
             + usize::from(self.video.is_some())
-            + usize::from(self.batch_folder.is_some());
+            + usize::from(self.camera.is_some())
+            + usize::from(self.batch_folder.is_some())
+            + usize::from(self.generator.is_some());
 
         // count == 0 is valid: interactive TUI launches with no visual source.
         if count > 1 {
             anyhow::bail!(
-                "Une seule source visuelle à la fois. Spécifiez --image, --video, OU --batch-folder."
+                "Une seule source visuelle à la fois. Spécifiez --image, --video, --camera, --batch-folder OU --generator."
             );
         }
 
         Ok(())
     }
+
+    /// Batch export requested: `--batch-folder`, or `--generator` with `--batch-out`.
+    #[must_use]
+    pub fn is_batch(&self) -> bool {
+        self.batch_folder.is_some() || (self.generator.is_some() && self.batch_out.is_some())
+    }
+
+    /// Offline transcode requested: `--video` with `--export`.
+    #[must_use]
+    pub fn is_transcode(&self) -> bool {
+        self.video.is_some() && self.export.is_some()
+    }
 }
@@ -1,6 +1,7 @@
-use af_core::config::{MappingCurve, RenderConfig};
+use af_core::automation::Automation;
+use af_core::config::{AnalysisConfig, MappingCurve, RenderConfig};
 use af_core::feature_timeline::{FeatureTimeline, StemFeatureTimeline};
-use af_core::frame::AudioFeatures;
+use af_core::frame::{AudioFeatures, BEATS_PER_BAR};
 
 /// Moteur génératif offline adaptant config + audio feature timeline.
 ///
@@ -9,10 +10,16 @@ use af_core::frame::AudioFeatures;
 ///
 /// When `stem_timeline` is set, mappings with `stem_source` resolve features
 /// from the corresponding stem instead of the combined mix.
+///
+/// When `automation` is set, its keyframes are written before the mappings,
+/// exactly like the interactive loop does against the media clock.
 pub struct AutoGenerativeMapper {
     base_config: RenderConfig,
+    /// Bands the timeline was analysed with (user band sources).
+    analysis: AnalysisConfig,
     timeline: FeatureTimeline,
     stem_timeline: Option<StemFeatureTimeline>,
+    automation: Option<Automation>,
     smooth_state: Vec<f32>,
 }
 
@@ -21,9 +28,11 @@ impl AutoGenerativeMapper {
     pub fn new(base_config: RenderConfig, timeline: FeatureTimeline) -> Self {
         let n = base_config.audio_mappings.len();
         Self {
+            analysis: base_config.analysis.clone(),
             base_config,
             timeline,
             stem_timeline: None,
+            automation: None,
             smooth_state: vec![0.0; n],
         }
     }
@@ -33,6 +42,17 @@ impl AutoGenerativeMapper {
         self.stem_timeline = Some(stem_tl);
     }
 
+    /// Attach keyframe automation, evaluated at the frame timestamp.
+    pub fn set_automation(&mut self, automation: Automation) {
+        self.automation = Some(automation);
+    }
+
+    /// Automation lanes attached to this mapper, if any.
+    #[must_use]
+    pub fn automation(&self) -> Option<&Automation> {
+        self.automation.as_ref()
+    }
+
     /// Applique les mappings audio sur `out`, en le réinitialisant depuis `base_config`.
     ///
     /// L'`onset_envelope` est calculé par l'appelant (batch loop) et passé ici.
@@ -41,6 +61,10 @@ impl AutoGenerativeMapper {
     pub fn apply_at(&mut self, timestamp_secs: f64, onset_envelope: f32, out: &mut RenderConfig) {
         let features = self.timeline.get_at_time(timestamp_secs);
         out.clone_from(&self.base_config);
+        out.key_hue = features.key_hue();
+        if let Some(ref automation) = self.automation {
+            automation.apply(out, timestamp_secs);
+        }
 
         let sensitivity = self.base_config.audio_sensitivity;
         let fps = self.base_config.target_fps.max(1) as f32;
@@ -72,12 +96,27 @@ impl AutoGenerativeMapper {
                         _ => usize::MAX, // fallback to combined
                     };
                     if stem_idx < 4 {
-                        resolve_source(&sf[stem_idx], mapping.source.as_str(), onset_envelope)
+                        resolve_source(
+                            &sf[stem_idx],
+                            mapping.source.as_str(),
+                            onset_envelope,
+                            &self.analysis,
+                        )
                     } else {
-                        resolve_source(&features, mapping.source.as_str(), onset_envelope)
+                        resolve_source(
+                            &features,
+                            mapping.source.as_str(),
+                            onset_envelope,
+                            &self.analysis,
+                        )
                     }
                 }
-                _ => resolve_source(&features, mapping.source.as_str(), onset_envelope),
+                _ => resolve_source(
+                    &features,
+                    mapping.source.as_str(),
+                    onset_envelope,
+                    &self.analysis,
+                ),
             };
 
             // Apply response curve (parité avec pipeline.rs)
@@ -95,7 +134,7 @@ impl AutoGenerativeMapper {
                 raw_delta
             };
 
-            apply_target(out, mapping.target.as_str(), delta);
+            out.apply_target_delta(&mapping.target, delta);
         }
     }
 
@@ -135,7 +174,12 @@ impl AutoGenerativeMapper {
     }
 }
 
-fn resolve_source(features: &AudioFeatures, source: &str, onset_envelope: f32) -> f32 {
+fn resolve_source(
+    features: &AudioFeatures,
+    source: &str,
+    onset_envelope: f32,
+    analysis: &AnalysisConfig,
+) -> f32 {
     match source {
         "rms" => features.rms,
         "peak" => features.peak,
@@ -150,21 +194,38 @@ fn resolve_source(features: &AudioFeatures, source: &str, onset_envelope: f32) -
         "spectral_flux" => features.spectral_flux,
         "spectral_flatness" => features.spectral_flatness,
         "beat_intensity" => features.beat_intensity,
-        "onset" => {
-            if features.onset {
-                1.0
-            } else {
-                0.0
-            }
-        }
+        "onset" if features.onset => 1.0,
         "beat_phase" => features.beat_phase,
+        "bar_phase" => features.bar_phase,
+        "beat_in_bar" => f32::from(features.beat_in_bar) / f32::from(BEATS_PER_BAR - 1),
+        "tempo_confidence" => features.tempo_confidence,
         "bpm" => features.bpm / 300.0,
         "timbral_brightness" => features.timbral_brightness,
         "timbral_roughness" => features.timbral_roughness,
         "onset_envelope" => onset_envelope,
         "spectral_rolloff" => features.spectral_rolloff,
         "zero_crossing_rate" => features.zero_crossing_rate,
-        _ => 0.0,
+        "kick_onset" if features.kick_onset => 1.0,
+        "kick_intensity" => features.kick_intensity,
+        "kick_envelope" => features.kick_envelope,
+        "snare_onset" if features.snare_onset => 1.0,
+        "snare_intensity" => features.snare_intensity,
+        "snare_envelope" => features.snare_envelope,
+        "hat_onset" if features.hat_onset => 1.0,
+        "hat_intensity" => features.hat_intensity,
+        "hat_envelope" => features.hat_envelope,
+        "pitch" => features.pitch_normalized(),
+        "pitch_confidence" => features.pitch_confidence,
+        "loudness_lufs" => features.loudness_normalized(),
+        "dynamic_range" => features.dynamic_range,
+        other => features
+            .chroma_source(other)
+            .or_else(|| {
+                analysis
+                    .custom_band_index(other)
+                    .map(|i| features.custom_bands[i])
+            })
+            .unwrap_or(0.0),
     }
 }
 
@@ -183,71 +244,6 @@ fn apply_curve(curve: &MappingCurve, value: f32) -> f32 {
     }
 }
 
-fn apply_target(config: &mut RenderConfig, target: &str, delta: f32) {
-    match target {
-        "edge_threshold" => {
-            config.edge_threshold = (config.edge_threshold + delta).clamp(0.0, 1.0);
-        }
-        "edge_mix" => {
-            config.edge_mix = (config.edge_mix + delta).clamp(0.0, 1.0);
-        }
-        "contrast" => {
-            config.contrast = (config.contrast + delta).clamp(0.1, 3.0);
-        }
-        "brightness" => {
-            config.brightness = (config.brightness + delta).clamp(-1.0, 1.0);
-        }
-        "saturation" => {
-            config.saturation = (config.saturation + delta).clamp(0.0, 3.0);
-        }
-        "density_scale" => {
-            config.density_scale = (config.density_scale + delta).clamp(0.25, 4.0);
-        }
-        "invert" => {
-            config.invert = delta > 0.5;
-        }
-        "beat_flash_intensity" => {
-            config.beat_flash_intensity = (config.beat_flash_intensity + delta).clamp(0.0, 2.0);
-        }
-        "chromatic_offset" => {
-            config.chromatic_offset = (config.chromatic_offset + delta).clamp(0.0, 5.0);
-        }
-        "wave_amplitude" => {
-            config.wave_amplitude = (config.wave_amplitude + delta).clamp(0.0, 1.0);
-        }
-        "color_pulse_speed" => {
-            config.color_pulse_speed = (config.color_pulse_speed + delta).clamp(0.0, 5.0);
-        }
-        "fade_decay" => {
-            config.fade_decay = (config.fade_decay + delta).clamp(0.0, 1.0);
-        }
-        "glow_intensity" => {
-            config.glow_intensity = (config.glow_intensity + delta).clamp(0.0, 2.0);
-        }
-        "zalgo_intensity" => {
-            config.zalgo_intensity = (config.zalgo_intensity + delta).clamp(0.0, 5.0);
-        }
-        "camera_zoom_amplitude" => {
-            config.camera_zoom_amplitude =
-                (config.camera_zoom_amplitude + delta * 2.0).clamp(0.1, 10.0);
-        }
-        "camera_rotation" => {
-            config.camera_rotation += delta * 0.1;
-            config.camera_rotation = config.camera_rotation.rem_euclid(std::f32::consts::TAU);
-        }
-        "camera_pan_x" => {
-            config.camera_pan_x = (config.camera_pan_x + delta * 0.5).clamp(-2.0, 2.0);
-        }
-        "camera_pan_y" => {
-            config.camera_pan_y = (config.camera_pan_y + delta * 0.5).clamp(-2.0, 2.0);
-        }
-        "camera_tilt_x" => {
-            config.camera_tilt_x = (config.camera_tilt_x + delta * 0.3).clamp(-1.0, 1.0);
-        }
-        _ => {}
-    }
-}
-
 #[cfg(test)]
 #[allow(clippy::field_reassign_with_default)]
 mod tests {
@@ -259,11 +255,11 @@ mod tests {
 
         // delta > 0.5 → invert = true
         config.invert = false;
-        apply_target(&mut config, "invert", 0.8);
+        config.apply_target_delta("invert", 0.8);
         assert!(config.invert, "delta 0.8 should set invert=true");
 
         // delta < 0.5 → invert = false (NOT toggle)
-        apply_target(&mut config, "invert", 0.2);
+        config.apply_target_delta("invert", 0.2);
         assert!(
             !config.invert,
             "delta 0.2 should set invert=false, not toggle"
@@ -274,11 +270,65 @@ mod tests {
     fn camera_rotation_wraps() {
         let mut config = RenderConfig::default();
         config.camera_rotation = 6.0; // near TAU (6.28)
-        apply_target(&mut config, "camera_rotation", 5.0); // adds 0.5 radians
+        config.apply_target_delta("camera_rotation", 5.0); // adds 0.5 radians
         assert!(
             config.camera_rotation < std::f32::consts::TAU,
             "rotation should wrap at TAU, got {}",
             config.camera_rotation
         );
     }
+
+    #[test]
+    fn automation_sets_base_before_mappings() {
+        use af_core::automation::{AutomationLane, Easing, Keyframe};
+        use af_core::config::AudioMapping;
+
+        let mut base = RenderConfig::default();
+        base.audio_sensitivity = 1.0;
+        base.audio_mappings = vec![AudioMapping {
+            enabled: true,
+            source: "rms".into(),
+            target: "brightness".into(),
+            amount: 1.0,
+            offset: 0.0,
+            curve: MappingCurve::Linear,
+            smoothing: None,
+            stem_source: None,
+            channel_source: None,
+        }];
+        let timeline = FeatureTimeline {
+            frames: vec![AudioFeatures {
+                rms: 0.25,
+                ..AudioFeatures::default()
+            }],
+            frame_duration: 1.0 / 30.0,
+            sample_rate: 44100,
+            energy_levels: vec![1],
+            sections: vec![],
+        };
+        let mut mapper = AutoGenerativeMapper::new(base, timeline);
+        mapper.set_automation(Automation {
+            automation: vec![AutomationLane {
+                enabled: true,
+                target: "brightness".into(),
+                keyframes: vec![
+                    Keyframe {
+                        time: 0.0,
+                        value: -0.5,
+                        easing: Easing::Linear,
+                    },
+                    Keyframe {
+                        time: 2.0,
+                        value: 0.5,
+                        easing: Easing::Linear,
+                    },
+                ],
+            }],
+        });
+
+        let mut out = RenderConfig::default();
+        mapper.apply_at(1.0, 0.0, &mut out);
+        // Automated base 0.0 at t=1s, plus the rms mapping delta
+        assert!((out.brightness - 0.25).abs() < 1e-5, "{}", out.brightness);
+    }
 }
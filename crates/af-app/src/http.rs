@@ -0,0 +1,528 @@
+//! Local HTTP/WebSocket control API (`--http`).
+//!
+//! A listener thread (`tiny_http`, loopback addresses only) serves JSON
+//! endpoints under `/api`:
+//!
+//! | Method | Path | Effect |
+//! |--------|------|--------|
+//! | `GET` | `/api/config` | Current `RenderConfig` |
+//! | `PUT` | `/api/config` | Replace the config (missing fields take their defaults) |
+//! | `GET` | `/api/presets` | Preset names and the active one |
+//! | `POST` | `/api/presets/<name>` | Load a preset |
+//! | `GET` | `/api/workflows` | Saved workflows |
+//! | `PUT` | `/api/workflows/<name>` | Save the current state as a workflow |
+//! | `POST` | `/api/workflows/<name>/load` | Load a workflow |
+//! | `GET` | `/api/audio` | WebSocket: one JSON `AudioFeatures` text frame per rendered frame |
+//!
+//! The config lives in the same `ArcSwap` as the rest of the app: `GET`/`PUT
+//! /api/config` read and store it directly, like the hot-reload watcher.
+//! Preset and workflow operations need the app state, so they are sent as
+//! [`HttpCommand`]s to the render loop, which answers on a reply channel.
+
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use af_core::config::RenderConfig;
+use af_core::frame::AudioFeatures;
+use anyhow::{Context, Result};
+use arc_swap::ArcSwap;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, StatusCode};
+
+/// How long a request waits for the render loop to answer a command.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+/// Largest accepted request body.
+const MAX_BODY: u64 = 1 << 20;
+
+/// Answer of the render loop to a command.
+pub type Reply = flume::Sender<Result<(), String>>;
+
+/// Requests that need the app state, applied by the render loop.
+#[derive(Debug)]
+pub enum HttpCommand {
+    /// `PUT /api/config` stored a new config (UI refresh, canvas resize).
+    ConfigReplaced,
+    /// List preset names and the active one.
+    ListPresets(flume::Sender<PresetList>),
+    /// Load a preset by name.
+    LoadPreset(String, Reply),
+    /// Load a saved workflow by name.
+    LoadWorkflow(String, Reply),
+    /// Save the current state as a workflow.
+    SaveWorkflow {
+        name: String,
+        description: String,
+        reply: Reply,
+    },
+}
+
+/// Body of `GET /api/presets`.
+#[derive(Clone, Debug, Serialize)]
+pub struct PresetList {
+    pub presets: Vec<String>,
+    pub current: Option<String>,
+}
+
+/// Body of `PUT /api/workflows/<name>`.
+#[derive(serde::Deserialize, Default)]
+struct SaveBody {
+    #[serde(default)]
+    description: String,
+}
+
+/// Running HTTP server. Dropping it stops the listener thread.
+pub struct HttpServer {
+    local_addr: SocketAddr,
+    rx: flume::Receiver<HttpCommand>,
+    subscribers: Arc<Mutex<Vec<flume::Sender<Arc<str>>>>>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+/// State shared with the listener thread.
+struct Ctx {
+    config: Arc<ArcSwap<RenderConfig>>,
+    workflows_dir: PathBuf,
+    tx: flume::Sender<HttpCommand>,
+    subscribers: Arc<Mutex<Vec<flume::Sender<Arc<str>>>>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl HttpServer {
+    /// Bind `listen` (`PORT` or `HOST:PORT`, loopback only) and start the
+    /// listener thread.
+    ///
+    /// # Errors
+    /// Returns an error if the address is invalid or not a loopback address,
+    /// or if the socket cannot be bound.
+    pub fn spawn(
+        listen: &str,
+        config: Arc<ArcSwap<RenderConfig>>,
+        workflows_dir: &Path,
+    ) -> Result<Self> {
+        let addr = resolve_loopback(listen)?;
+        let server =
+            tiny_http::Server::http(addr).map_err(|e| anyhow::anyhow!("Bind HTTP {addr} : {e}"))?;
+        let local_addr = server
+            .server_addr()
+            .to_ip()
+            .context("Adresse HTTP locale indisponible")?;
+
+        let (tx, rx) = flume::unbounded();
+        let subscribers = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+        let ctx = Ctx {
+            config,
+            workflows_dir: workflows_dir.to_path_buf(),
+            tx,
+            subscribers: Arc::clone(&subscribers),
+            stop: Arc::clone(&stop),
+        };
+
+        let handle = std::thread::Builder::new()
+            .name("af-http".into())
+            .spawn(move || {
+                while !ctx.stop.load(Ordering::Relaxed) {
+                    match server.recv_timeout(Duration::from_millis(100)) {
+                        Ok(Some(request)) => handle_request(&ctx, request),
+                        Ok(None) => {}
+                        Err(e) => log::debug!("HTTP recv: {e}"),
+                    }
+                }
+            })
+            .context("Spawn HTTP thread")?;
+
+        log::info!("HTTP API: http://{local_addr}/api");
+        Ok(Self {
+            local_addr,
+            rx,
+            subscribers,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// Bound address (useful with port 0).
+    #[must_use]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Commands received since the last call, oldest first.
+    #[must_use]
+    pub fn drain(&self) -> flume::TryIter<'_, HttpCommand> {
+        self.rx.try_iter()
+    }
+
+    /// Send one frame of features to every `/api/audio` client, with the
+    /// app's `onset_envelope`. Serialized once; slow clients drop frames,
+    /// closed ones are removed.
+    pub fn publish_features(&self, features: &AudioFeatures, onset_envelope: f32) {
+        let Ok(mut subs) = self.subscribers.lock() else {
+            return;
+        };
+        if subs.is_empty() {
+            return;
+        }
+        let frame = AudioFeatures {
+            onset_envelope,
+            ..*features
+        };
+        let Ok(json) = serde_json::to_string(&frame) else {
+            return;
+        };
+        let msg: Arc<str> = json.into();
+        subs.retain(|tx| {
+            !matches!(
+                tx.try_send(Arc::clone(&msg)),
+                Err(flume::TrySendError::Disconnected(_))
+            )
+        });
+    }
+}
+
+impl Drop for HttpServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// `PORT` → `127.0.0.1:PORT`; anything else must resolve to a loopback address.
+fn resolve_loopback(listen: &str) -> Result<SocketAddr> {
+    let spec = if listen.parse::<u16>().is_ok() {
+        format!("127.0.0.1:{listen}")
+    } else {
+        listen.to_string()
+    };
+    let addr = spec
+        .to_socket_addrs()
+        .with_context(|| format!("Adresse HTTP invalide : {listen}"))?
+        .next()
+        .with_context(|| format!("Adresse HTTP invalide : {listen}"))?;
+    anyhow::ensure!(
+        addr.ip().is_loopback(),
+        "--http n'accepte que des adresses locales (127.0.0.1, ::1, localhost), pas {}",
+        addr.ip()
+    );
+    Ok(addr)
+}
+
+fn header(name: &str, value: &str) -> Option<Header> {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).ok()
+}
+
+fn header_value<'a>(request: &'a Request, name: &str) -> Option<&'a str> {
+    request
+        .headers()
+        .iter()
+        .find(|h| h.field.as_str().as_str().eq_ignore_ascii_case(name))
+        .map(|h| h.value.as_str())
+}
+
+/// Host part of an authority (`host[:port]`, `[v6][:port]`).
+fn authority_host(authority: &str) -> &str {
+    if authority.starts_with('[') {
+        authority
+            .find(']')
+            .map_or(authority, |end| &authority[..=end])
+    } else {
+        authority.split_once(':').map_or(authority, |(h, _)| h)
+    }
+}
+
+fn is_local_host(host: &str) -> bool {
+    matches!(
+        authority_host(host).to_ascii_lowercase().as_str(),
+        "localhost" | "127.0.0.1" | "[::1]"
+    )
+}
+
+/// `Origin` header, if it names a local page (`http(s)://localhost…`).
+fn local_origin(request: &Request) -> Option<String> {
+    let origin = header_value(request, "Origin")?;
+    let authority = origin
+        .strip_prefix("http://")
+        .or_else(|| origin.strip_prefix("https://"))?;
+    is_local_host(authority).then(|| origin.to_string())
+}
+
+/// Binding to loopback is not enough: a web page can still reach the port
+/// through the browser (CSRF) or through a DNS name rebound to 127.0.0.1.
+/// Every request, WebSocket upgrades included, must therefore carry a local
+/// `Host`, and a local `Origin` when the browser sends one.
+fn is_local_request(request: &Request) -> bool {
+    let host_ok = header_value(request, "Host").is_some_and(is_local_host);
+    let origin_ok = header_value(request, "Origin").is_none() || local_origin(request).is_some();
+    host_ok && origin_ok
+}
+
+/// Decode `%XX` escapes of a path segment; `None` if malformed or not UTF-8.
+fn percent_decode(segment: &str) -> Option<String> {
+    let bytes = segment.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+fn respond_json(request: Request, status: u16, body: &str) {
+    let mut response = Response::from_string(body).with_status_code(StatusCode(status));
+    if let Some(h) = header("Content-Type", "application/json") {
+        response.add_header(h);
+    }
+    if let Some(origin) = local_origin(&request) {
+        for h in [
+            header("Access-Control-Allow-Origin", &origin),
+            header("Access-Control-Allow-Methods", "GET, PUT, POST, OPTIONS"),
+            header("Access-Control-Allow-Headers", "Content-Type"),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            response.add_header(h);
+        }
+    }
+    if let Err(e) = request.respond(response) {
+        log::debug!("HTTP respond: {e}");
+    }
+}
+
+fn respond_value<T: Serialize>(request: Request, value: &T) {
+    match serde_json::to_string(value) {
+        Ok(body) => respond_json(request, 200, &body),
+        Err(e) => respond_error(request, 500, &e.to_string()),
+    }
+}
+
+fn respond_error(request: Request, status: u16, message: &str) {
+    let body = serde_json::json!({ "error": message }).to_string();
+    respond_json(request, status, &body);
+}
+
+fn read_body(request: &mut Request) -> Result<String> {
+    use std::io::Read;
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY)
+        .read_to_string(&mut body)
+        .context("Lecture du corps de la requête")?;
+    Ok(body)
+}
+
+/// Send a command and wait for the render loop's answer.
+fn ask(ctx: &Ctx, request: Request, make: impl FnOnce(Reply) -> HttpCommand) {
+    let (reply_tx, reply_rx) = flume::bounded(1);
+    if ctx.tx.send(make(reply_tx)).is_err() {
+        respond_error(request, 503, "application stopped");
+        return;
+    }
+    match reply_rx.recv_timeout(REPLY_TIMEOUT) {
+        Ok(Ok(())) => respond_json(request, 200, r#"{"ok":true}"#),
+        Ok(Err(e)) => respond_error(request, 404, &e),
+        Err(_) => respond_error(request, 503, "application busy"),
+    }
+}
+
+fn handle_request(ctx: &Ctx, mut request: Request) {
+    let url = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .to_string();
+    if !is_local_request(&request) {
+        respond_error(request, 403, "non-local Host or Origin");
+        return;
+    }
+    let Some(decoded) = url
+        .trim_start_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(percent_decode)
+        .collect::<Option<Vec<String>>>()
+    else {
+        respond_error(request, 400, "invalid percent-encoding in path");
+        return;
+    };
+    let segments: Vec<&str> = decoded.iter().map(String::as_str).collect();
+    let method = request.method().clone();
+
+    match (&method, segments.as_slice()) {
+        (Method::Options, _) => respond_json(request, 204, ""),
+        (Method::Get, ["api", "config"]) => {
+            let config = ctx.config.load();
+            respond_value(request, &**config);
+        }
+        (Method::Put, ["api", "config"]) => {
+            let parsed = read_body(&mut request).and_then(|body| {
+                serde_json::from_str::<RenderConfig>(&body).context("JSON RenderConfig invalide")
+            });
+            match parsed {
+                Ok(mut new_config) => {
+                    new_config.clamp_all();
+                    ctx.config.store(Arc::new(new_config));
+                    let _ = ctx.tx.send(HttpCommand::ConfigReplaced);
+                    let config = ctx.config.load();
+                    respond_value(request, &**config);
+                }
+                Err(e) => respond_error(request, 400, &format!("{e:#}")),
+            }
+        }
+        (Method::Get, ["api", "presets"]) => {
+            let (reply_tx, reply_rx) = flume::bounded(1);
+            if ctx.tx.send(HttpCommand::ListPresets(reply_tx)).is_err() {
+                respond_error(request, 503, "application stopped");
+                return;
+            }
+            match reply_rx.recv_timeout(REPLY_TIMEOUT) {
+                Ok(list) => respond_value(request, &list),
+                Err(_) => respond_error(request, 503, "application busy"),
+            }
+        }
+        (Method::Post, ["api", "presets", name]) => {
+            let name = (*name).to_string();
+            ask(ctx, request, |reply| HttpCommand::LoadPreset(name, reply));
+        }
+        (Method::Get, ["api", "workflows"]) => {
+            match af_core::workflow_io::list_workflows_detailed_in(&ctx.workflows_dir) {
+                Ok(entries) => respond_value(request, &entries),
+                Err(e) => respond_error(request, 500, &format!("{e:#}")),
+            }
+        }
+        (Method::Put, ["api", "workflows", name]) => {
+            let name = (*name).to_string();
+            let parsed = read_body(&mut request).and_then(|body| {
+                if body.trim().is_empty() {
+                    Ok(SaveBody::default())
+                } else {
+                    serde_json::from_str::<SaveBody>(&body).context("JSON invalide")
+                }
+            });
+            match parsed {
+                Ok(SaveBody { description }) => {
+                    ask(ctx, request, |reply| HttpCommand::SaveWorkflow {
+                        name,
+                        description,
+                        reply,
+                    });
+                }
+                Err(e) => respond_error(request, 400, &format!("{e:#}")),
+            }
+        }
+        (Method::Post, ["api", "workflows", name, "load"]) => {
+            let name = (*name).to_string();
+            ask(ctx, request, |reply| HttpCommand::LoadWorkflow(name, reply));
+        }
+        (Method::Get, ["api", "audio"]) => upgrade_audio(ctx, request),
+        (_, ["api", ..]) => respond_error(request, 404, "unknown endpoint"),
+        _ => respond_error(request, 404, "not found"),
+    }
+}
+
+/// Upgrade `/api/audio` to a WebSocket and start its writer thread.
+fn upgrade_audio(ctx: &Ctx, request: Request) {
+    let is_upgrade =
+        header_value(&request, "Upgrade").is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    let Some(key) = header_value(&request, "Sec-WebSocket-Key").map(str::to_string) else {
+        respond_error(request, 400, "WebSocket upgrade required");
+        return;
+    };
+    if !is_upgrade {
+        respond_error(request, 400, "WebSocket upgrade required");
+        return;
+    }
+
+    let accept = tungstenite::handshake::derive_accept_key(key.as_bytes());
+    let mut response = Response::empty(StatusCode(101));
+    for h in [
+        header("Upgrade", "websocket"),
+        header("Sec-WebSocket-Accept", &accept),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        response.add_header(h);
+    }
+    let stream = request.upgrade("websocket", response);
+
+    let (tx, rx) = flume::bounded::<Arc<str>>(8);
+    if let Ok(mut subs) = ctx.subscribers.lock() {
+        subs.push(tx);
+    }
+    let stop = Arc::clone(&ctx.stop);
+    let spawned = std::thread::Builder::new()
+        .name("af-http-ws".into())
+        .spawn(move || {
+            let mut ws = tungstenite::WebSocket::from_raw_socket(
+                stream,
+                tungstenite::protocol::Role::Server,
+                None,
+            );
+            while !stop.load(Ordering::Relaxed) {
+                match rx.recv_timeout(Duration::from_millis(200)) {
+                    Ok(msg) => {
+                        if ws
+                            .send(tungstenite::Message::Text(msg.to_string()))
+                            .is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Err(flume::RecvTimeoutError::Timeout) => {}
+                    Err(flume::RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            let _ = ws.close(None);
+            let _ = ws.flush();
+        });
+    if let Err(e) = spawned {
+        log::warn!("WebSocket thread: {e}");
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_loopback_addresses_accepted() {
+        assert_eq!(
+            resolve_loopback("9100").unwrap(),
+            "127.0.0.1:9100".parse().unwrap()
+        );
+        assert!(resolve_loopback("[::1]:9100").is_ok());
+        assert!(resolve_loopback("0.0.0.0:9100").is_err());
+        assert!(resolve_loopback("192.168.1.10:9100").is_err());
+    }
+
+    #[test]
+    fn local_hosts_and_percent_decoding() {
+        assert!(is_local_host("localhost:8080"));
+        assert!(is_local_host("127.0.0.1"));
+        assert!(is_local_host("[::1]:8080"));
+        assert!(!is_local_host("evil.example:8080"));
+        assert!(!is_local_host("127.0.0.1.evil.example"));
+        assert_eq!(percent_decode("my%20flow").unwrap(), "my flow");
+        assert_eq!(percent_decode("caf%C3%A9").unwrap(), "café");
+        assert!(percent_decode("bad%2").is_none());
+        assert!(percent_decode("%FF").is_none());
+    }
+}
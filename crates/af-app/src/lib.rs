@@ -1,7 +1,13 @@
 // Library entry point for af-app — exposes modules for integration testing.
 // The binary entry point remains in main.rs.
 
+pub mod analyze;
+pub mod batch;
 pub mod cli;
 pub mod creation;
 pub mod generative;
+pub mod http;
+pub mod midi;
+pub mod osc;
 pub mod pipeline;
+pub mod snapshot;
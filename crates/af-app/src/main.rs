@@ -2,17 +2,23 @@ use std::sync::Arc;
 
 use af_core::clock::MediaClock;
 use af_core::paths::AppPaths;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use arc_swap::ArcSwap;
 use clap::Parser;
 
+pub mod analyze;
 pub mod app;
 pub mod batch;
 pub mod cli;
 pub mod creation;
 pub mod generative;
 pub mod hotreload;
+pub mod http;
+pub mod midi;
+pub mod osc;
 pub mod pipeline;
+pub mod snapshot;
+pub mod transcode;
 
 #[allow(clippy::too_many_lines)]
 fn main() -> Result<()> {
@@ -26,8 +32,14 @@ fn main() -> Result<()> {
     // 2. Initialiser le logging
     // TUI mode: redirect logs to file to prevent stderr from corrupting ratatui display.
     // Batch/CLI modes: keep stderr for direct terminal output.
-    let is_tui_mode =
-        cli.batch_folder.is_none() && !cli.init && !cli.preset_list && !cli.workflow_list;
+    let is_tui_mode = !cli.is_batch()
+        && !cli.is_transcode()
+        && cli.snapshot.is_none()
+        && !cli.init
+        && !cli.preset_list
+        && !cli.workflow_list
+        && !cli.audio_device_list
+        && cli.command.is_none();
     let log_level = cli.log_level.parse().unwrap_or(log::LevelFilter::Warn);
     let mut log_builder = env_logger::Builder::new();
     log_builder.filter_level(log_level);
@@ -59,6 +71,18 @@ fn main() -> Result<()> {
         return list_workflows_cli(&paths);
     }
 
+    // 2e. --audio-device-list : énumérer les entrées audio, then exit
+    if cli.audio_device_list {
+        return list_audio_devices();
+    }
+
+    // 2f. classcii analyze : timeline de features d'un fichier audio, then exit
+    if let Some(cli::Command::Analyze(ref args)) = cli.command {
+        let mut config = resolve_config(&cli, &paths)?;
+        config.clamp_all();
+        return analyze::run_analyze(args, &config.analysis, &paths);
+    }
+
     // 3. Valider la source
     cli.validate_source()?;
 
@@ -78,8 +102,14 @@ fn main() -> Result<()> {
         None
     };
 
-    // Export Par lots
-    if let Some(folder) = cli.batch_folder.as_deref() {
+    // 3c. --automation : surcharge l'automation du workflow
+    let automation = match cli.automation {
+        Some(ref path) => Some(af_core::automation::Automation::load(path)?),
+        None => loaded_wf.as_ref().and_then(|wf| wf.automation.clone()),
+    };
+
+    // Export Par lots (dossier média ou générateur procédural)
+    if cli.is_batch() {
         log::info!("Lancement du traitement par lots offline...");
         let preset_all = cli.preset.as_deref() == Some("all");
         let mut config = if let Some(ref wf) = loaded_wf {
@@ -102,8 +132,12 @@ fn main() -> Result<()> {
                 .map(|p| p.to_string_lossy().into_owned())
         });
 
+        let limits = batch::FrameLimits::parse(cli.frame_range.as_deref(), cli.max_duration)?;
+        let profile = resolve_export_profile(&cli, &config)?;
+
         let result = batch::run_batch_export(
-            folder,
+            cli.batch_folder.as_deref(),
+            cli.generator.as_deref(),
             audio_arg.as_ref(),
             cli.batch_out.as_deref(),
             config.clone(),
@@ -117,12 +151,54 @@ fn main() -> Result<()> {
             cli.stems,
             &cli.stem_model,
             cli.save_workflow.as_deref(),
+            limits,
+            profile,
+            automation,
             &paths,
         );
 
         return result;
     }
 
+    // Transcodage offline : --video → --export, image par image
+    if let (Some(input), Some(out)) = (&cli.video, &cli.export) {
+        let mut config = if let Some(ref wf) = loaded_wf {
+            wf.config.clone()
+        } else {
+            resolve_config(&cli, &paths)?
+        };
+        apply_cli_overrides(&cli, &mut config);
+        let profile = resolve_export_profile(&cli, &config)?;
+        return transcode::run_transcode(
+            input,
+            out,
+            config,
+            cli.export_scale,
+            profile,
+            automation.as_ref(),
+        );
+    }
+
+    // Snapshot headless : une frame de --image vers .txt / .ans / .html
+    if let Some(ref out) = cli.snapshot {
+        let Some(ref image) = cli.image else {
+            anyhow::bail!("--snapshot requiert --image.");
+        };
+        let mut config = if let Some(ref wf) = loaded_wf {
+            wf.config.clone()
+        } else {
+            resolve_config(&cli, &paths)?
+        };
+        apply_cli_overrides(&cli, &mut config);
+        return snapshot::run_snapshot(
+            image,
+            out,
+            &config,
+            cli.snapshot_width
+                .unwrap_or(snapshot::DEFAULT_SNAPSHOT_WIDTH),
+        );
+    }
+
     // 4. Charger la config
     let (mut config, config_file_path) = if let Some(ref wf) = loaded_wf {
         (wf.config.clone(), None)
@@ -145,7 +221,11 @@ fn main() -> Result<()> {
 
     // 6. Démarrer le thread audio (si --audio fourni)
     let media_clock = Arc::new(MediaClock::new(0));
-    let (audio_output, audio_cmd_tx) = init_audio(&cli, &config, &media_clock);
+    let (audio, audio_error) = init_audio(&cli, &config, &media_clock);
+    let (audio_output, channel_output, audio_cmd_tx, audio_thread) = match audio {
+        Some((output, channels, tx, thread)) => (Some(output), channels, tx, thread),
+        None => (None, None, None, None),
+    };
 
     // 7. Démarrer le source thread (si vidéo/procédural)
     let has_audio = audio_output.is_some();
@@ -160,6 +240,48 @@ fn main() -> Result<()> {
     #[cfg(not(feature = "video"))]
     let (initial_frame, frame_rx) = pipeline::start_source(&cli, video_clock, Arc::clone(&config))?;
 
+    // 7b. Source procédurale : --generator, ou workflow sauvegardé depuis un générateur
+    let generator_name = cli.generator.clone().or_else(|| {
+        loaded_wf
+            .as_ref()
+            .filter(|wf| {
+                matches!(
+                    wf.source.media_type,
+                    af_core::workflow::MediaType::Procedural
+                )
+            })
+            .map(|wf| wf.source.path.to_string_lossy().into_owned())
+    });
+    let generator_kind = match generator_name {
+        Some(name) => Some(
+            af_source::procedural::GeneratorKind::from_name(&name)
+                .ok_or_else(|| anyhow::anyhow!("Générateur inconnu : {name}"))?,
+        ),
+        None => None,
+    };
+
+    // 7c. Serveurs OSC/HTTP, envoi audio réseau et port MIDI (avant ratatui : une erreur reste lisible)
+    let osc_server = match cli.osc {
+        Some(ref listen) => Some(osc::OscServer::spawn(listen, cli.osc_feedback.as_deref())?),
+        None => None,
+    };
+    let http_server = match cli.http {
+        Some(ref listen) => Some(http::HttpServer::spawn(
+            listen,
+            Arc::clone(&config),
+            &paths.workflows_dir,
+        )?),
+        None => None,
+    };
+    let audio_sender = match cli.audio_send {
+        Some(ref target) => Some(af_audio::network::FeatureSender::new(target)?),
+        None => None,
+    };
+    let midi_input = match cli.midi {
+        Some(ref port) => Some(midi::MidiInput::open(port)?),
+        None => None,
+    };
+
     // 8. Initialiser le terminal ratatui
     let terminal = ratatui::init();
     // Purge scrollback so the terminal scrollbar disappears (Windows Terminal)
@@ -191,9 +313,22 @@ fn main() -> Result<()> {
     if let Some(frame) = initial_frame {
         app_instance.current_frame = Some(frame);
     }
+    if let Some(kind) = generator_kind {
+        app_instance.set_generator(kind);
+    }
     if has_audio {
         app_instance.media_clock = Some(media_clock);
     }
+    app_instance.automation = automation;
+    app_instance.osc = osc_server;
+    app_instance.http = http_server;
+    app_instance.audio_send = audio_sender;
+    app_instance.channel_features_output = channel_output;
+    app_instance.audio_thread = audio_thread;
+    if let Some(e) = audio_error {
+        app_instance.show_flash(format!("Audio : {e}"));
+    }
+    app_instance.midi = midi_input;
 
     // 9b. Set initial loaded file names from CLI args
     if let Some(ref path) = cli.image {
@@ -202,17 +337,29 @@ fn main() -> Result<()> {
     } else if let Some(ref path) = cli.video {
         app_instance.loaded_visual_name =
             path.file_name().and_then(|n| n.to_str()).map(String::from);
+    } else if let Some(ref spec) = cli.camera {
+        app_instance.loaded_visual_name = Some(format!("cam:{spec}"));
     }
     if let Some(ref audio_arg) = cli.audio {
-        let p = std::path::Path::new(audio_arg.as_str());
-        app_instance.loaded_audio_name = p.file_name().and_then(|n| n.to_str()).map(String::from);
-        app_instance.loaded_audio_path = Some(p.to_path_buf());
+        if audio_arg == "-" || audio_arg.starts_with("net:") || audio_arg.starts_with("fifo:") {
+            // Flux sans fichier local : rien à séparer en stems
+            app_instance.loaded_audio_name = Some(audio_arg.clone());
+        } else {
+            let p = std::path::Path::new(audio_arg.as_str());
+            app_instance.loaded_audio_name =
+                p.file_name().and_then(|n| n.to_str()).map(String::from);
+            app_instance.loaded_audio_path = Some(p.to_path_buf());
+        }
     } else if let Some(ref video_arg) = cli.video {
         // Video also provides audio for stem separation
         app_instance.loaded_audio_path = Some(video_arg.clone());
     }
 
     // 10. Boucle principale
+    if let Some(ref path) = cli.record {
+        app_instance.start_recording(Some(path));
+    }
+
     let result = app_instance.run(terminal);
 
     // 11. Restaurer le terminal (TOUJOURS, même en cas d'erreur)
@@ -223,27 +370,39 @@ fn main() -> Result<()> {
 }
 
 /// Initialize audio pipeline from CLI args (--audio or video fallback).
+///
+/// Never fails: an `--audio` error is returned as a message for the status
+/// bar, the TUI starts without audio.
 fn init_audio(
     cli: &cli::Cli,
     config: &Arc<ArcSwap<af_core::config::RenderConfig>>,
     clock: &Arc<MediaClock>,
-) -> (
-    Option<triple_buffer::Output<af_core::frame::AudioFeatures>>,
-    Option<flume::Sender<af_audio::state::AudioCommand>>,
-) {
+) -> (Option<pipeline::AudioResult>, Option<String>) {
+    let capture = af_audio::capture::CaptureOptions {
+        device: cli.audio_device.clone(),
+        routing: cli.audio_channels,
+        sample_rate: cli.audio_rate,
+        loopback: false,
+        pcm_format: cli.pcm_format,
+    };
     if let Some(ref audio_arg) = cli.audio {
-        match pipeline::start_audio(audio_arg, config, Arc::clone(clock)) {
-            Ok((output, tx)) => (Some(output), tx),
+        match pipeline::start_audio(audio_arg, config, Arc::clone(clock), &capture) {
+            Ok(audio) => (Some(audio), None),
             Err(e) => {
-                log::warn!("Audio non disponible : {e}");
-                (None, None)
+                log::warn!("Audio non disponible : {e:#}");
+                (None, Some(format!("{e:#}")))
             }
         }
     } else if let Some(ref video_arg) = cli.video {
-        match pipeline::start_audio(&video_arg.to_string_lossy(), config, Arc::clone(clock)) {
-            Ok((output, tx)) => {
+        match pipeline::start_audio(
+            &video_arg.to_string_lossy(),
+            config,
+            Arc::clone(clock),
+            &capture,
+        ) {
+            Ok(audio) => {
                 log::info!("Piste audio de la vidéo chargée avec succès.");
-                (Some(output), tx)
+                (Some(audio), None)
             }
             Err(e) => {
                 log::info!("Pas de piste audio gérée dans la vidéo : {e}");
@@ -255,6 +414,38 @@ fn init_audio(
     }
 }
 
+/// Print the audio input devices (`--audio-device-list`).
+fn list_audio_devices() -> Result<()> {
+    let devices = af_audio::capture::list_input_devices()?;
+    if devices.is_empty() {
+        println!("No audio input devices found.");
+        return Ok(());
+    }
+
+    println!("Audio input devices ({}):", devices.len());
+    for d in &devices {
+        let default_tag = if d.is_default { " [default]" } else { "" };
+        let rates = d
+            .rate_ranges
+            .iter()
+            .map(|&(min, max)| {
+                if min == max {
+                    format!("{min}")
+                } else {
+                    format!("{min}-{max}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "  {}: {}{} ({} ch, {} Hz; supported: {} Hz)",
+            d.index, d.name, default_tag, d.channels, d.sample_rate, rates
+        );
+    }
+
+    Ok(())
+}
+
 /// List available presets: external (disk) + embedded (built-in).
 fn list_presets(paths: &AppPaths) {
     use std::collections::BTreeSet;
@@ -315,7 +506,7 @@ fn list_workflows_cli(paths: &AppPaths) -> Result<()> {
     Ok(())
 }
 
-/// Apply CLI overrides (--mode, --fps, --no-color) onto a mutable config.
+/// Apply CLI overrides (--mode, --fps, --no-color, --display) onto a mutable config.
 fn apply_cli_overrides(cli: &cli::Cli, config: &mut af_core::config::RenderConfig) {
     if let Some(ref mode) = cli.mode {
         match mode.as_str() {
@@ -334,6 +525,30 @@ fn apply_cli_overrides(cli: &cli::Cli, config: &mut af_core::config::RenderConfi
     if cli.no_color {
         config.color_enabled = false;
     }
+    if let Some(ref name) = cli.display {
+        match af_core::config::DisplayBackend::from_name(name) {
+            Some(backend) => config.display_backend = backend,
+            None => log::warn!("Backend d'affichage inconnu '{name}', utilisation du texte."),
+        }
+    }
+}
+
+/// Export profile: `--export-profile` if given, else the config's `[export]` section.
+fn resolve_export_profile(
+    cli: &cli::Cli,
+    config: &af_core::config::RenderConfig,
+) -> Result<af_core::config::ExportProfile> {
+    use af_core::config::ExportProfile;
+
+    match cli.export_profile.as_deref() {
+        None => Ok(config.export_profile),
+        Some(name) => ExportProfile::from_name(name).with_context(|| {
+            format!(
+                "Profil d'export inconnu : {name}. Disponibles : {}",
+                ExportProfile::ALL.map(ExportProfile::name).join(", ")
+            )
+        }),
+    }
 }
 
 /// Resolve config with embedded fallback. Returns the config only.
@@ -0,0 +1,297 @@
+//! MIDI controller input: CC and note messages as a mapping source.
+//!
+//! [`MidiInput`] opens one port through `midir` and forwards parsed
+//! [`MidiEvent`]s over a channel. The render loop folds them into a
+//! [`MidiState`], which `[[midi.mappings]]` read through
+//! [`crate::pipeline::apply_midi_mappings`].
+
+use af_core::config::MidiMapping;
+use anyhow::Result;
+
+/// Channel voice message kinds that drive mappings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MidiEventKind {
+    ControlChange { cc: u8, value: u8 },
+    NoteOn { note: u8, velocity: u8 },
+    NoteOff { note: u8 },
+}
+
+/// A parsed MIDI message. `channel` is 0-based (0-15).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MidiEvent {
+    pub channel: u8,
+    pub kind: MidiEventKind,
+}
+
+impl MidiEvent {
+    /// Parse a raw MIDI message. Returns `None` for anything other than
+    /// Note On/Off and Control Change. Note On with velocity 0 is a Note Off.
+    #[must_use]
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let (&status, data) = bytes.split_first()?;
+        let channel = status & 0x0F;
+        let a = *data.first()? & 0x7F;
+        let b = data.get(1).map_or(0, |v| v & 0x7F);
+        let kind = match status & 0xF0 {
+            0x80 => MidiEventKind::NoteOff { note: a },
+            0x90 if b == 0 => MidiEventKind::NoteOff { note: a },
+            0x90 => MidiEventKind::NoteOn {
+                note: a,
+                velocity: b,
+            },
+            0xB0 if data.len() >= 2 => MidiEventKind::ControlChange { cc: a, value: b },
+            _ => return None,
+        };
+        Some(Self { channel, kind })
+    }
+
+    /// Mapping matching this event on its channel (`cc`/`note` and `channel`
+    /// set, amount 1.0). Note Off yields `None`: learn binds on press only.
+    #[must_use]
+    pub fn to_mapping(&self, target: &str) -> Option<MidiMapping> {
+        let (cc, note) = match self.kind {
+            MidiEventKind::ControlChange { cc, .. } => (Some(cc), None),
+            MidiEventKind::NoteOn { note, .. } => (None, Some(note)),
+            MidiEventKind::NoteOff { .. } => return None,
+        };
+        Some(MidiMapping {
+            enabled: true,
+            cc,
+            note,
+            channel: Some(self.channel + 1),
+            target: target.to_string(),
+            amount: 1.0,
+            offset: 0.0,
+            curve: af_core::config::MappingCurve::Linear,
+        })
+    }
+
+    /// Short label for the UI: `CC21 ch1`, `N60 ch10`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self.kind {
+            MidiEventKind::ControlChange { cc, value } => {
+                format!("CC{cc} ch{} = {value}", self.channel + 1)
+            }
+            MidiEventKind::NoteOn { note, velocity } => {
+                format!("N{note} ch{} = {velocity}", self.channel + 1)
+            }
+            MidiEventKind::NoteOff { note } => format!("N{note} ch{} off", self.channel + 1),
+        }
+    }
+}
+
+/// Label of a mapping's source, same format as [`MidiEvent::label`] without value.
+#[must_use]
+pub fn mapping_label(mapping: &MidiMapping) -> String {
+    let ch = mapping
+        .channel
+        .map_or_else(|| "omni".to_string(), |c| format!("ch{c}"));
+    match (mapping.note, mapping.cc) {
+        (Some(n), _) => format!("N{n} {ch}"),
+        (None, Some(cc)) => format!("CC{cc} {ch}"),
+        (None, None) => "—".to_string(),
+    }
+}
+
+/// Last value of every CC and note, normalized to [0.0, 1.0].
+///
+/// Values are `None` until a first message arrives, so an unmapped knob does
+/// not push its `offset` into the config. Notes go back to 0.0 on Note Off.
+#[derive(Clone, Debug)]
+pub struct MidiState {
+    cc: Box<[[Option<f32>; 128]; 16]>,
+    notes: Box<[[Option<f32>; 128]; 16]>,
+    cc_any: [Option<f32>; 128],
+    notes_any: [Option<f32>; 128],
+}
+
+impl Default for MidiState {
+    fn default() -> Self {
+        Self {
+            cc: Box::new([[None; 128]; 16]),
+            notes: Box::new([[None; 128]; 16]),
+            cc_any: [None; 128],
+            notes_any: [None; 128],
+        }
+    }
+}
+
+impl MidiState {
+    /// Fold one event into the state.
+    pub fn update(&mut self, event: &MidiEvent) {
+        let ch = usize::from(event.channel & 0x0F);
+        let (table, any, idx, value) = match event.kind {
+            MidiEventKind::ControlChange { cc, value } => {
+                (&mut self.cc, &mut self.cc_any, cc, value)
+            }
+            MidiEventKind::NoteOn { note, velocity } => {
+                (&mut self.notes, &mut self.notes_any, note, velocity)
+            }
+            MidiEventKind::NoteOff { note } => (&mut self.notes, &mut self.notes_any, note, 0),
+        };
+        let idx = usize::from(idx & 0x7F);
+        let v = f32::from(value) / 127.0;
+        table[ch][idx] = Some(v);
+        any[idx] = Some(v);
+    }
+
+    /// Current value for `mapping`'s source, `None` if nothing received yet
+    /// (or the mapping has neither `cc` nor `note`).
+    #[must_use]
+    pub fn value_for(&self, mapping: &MidiMapping) -> Option<f32> {
+        let channel = mapping.channel.map(|c| usize::from(c.clamp(1, 16) - 1));
+        let (table, any, idx) = match (mapping.note, mapping.cc) {
+            (Some(n), _) => (&self.notes, &self.notes_any, n),
+            (None, Some(cc)) => (&self.cc, &self.cc_any, cc),
+            (None, None) => return None,
+        };
+        let idx = usize::from(idx.min(127));
+        match channel {
+            Some(ch) => table[ch][idx],
+            None => any[idx],
+        }
+    }
+}
+
+/// An open MIDI input port. Dropping it closes the connection.
+pub struct MidiInput {
+    port_name: String,
+    rx: flume::Receiver<MidiEvent>,
+    _conn: midir::MidiInputConnection<()>,
+}
+
+impl MidiInput {
+    /// Open the port matching `spec`: a port index (`0`), or a case-insensitive
+    /// substring of its name (`"nanoKONTROL"`).
+    ///
+    /// # Errors
+    /// Returns an error if the MIDI backend is unavailable, no port matches
+    /// (the message lists the available ports), or the connection fails.
+    pub fn open(spec: &str) -> Result<Self> {
+        let midi_in = midir::MidiInput::new("classcii")
+            .map_err(|e| anyhow::anyhow!("MIDI backend indisponible : {e}"))?;
+        let ports = midi_in.ports();
+        let names: Vec<String> = ports
+            .iter()
+            .map(|p| midi_in.port_name(p).unwrap_or_else(|_| "?".to_string()))
+            .collect();
+
+        let needle = spec.to_lowercase();
+        let idx = spec
+            .parse::<usize>()
+            .ok()
+            .filter(|&i| i < ports.len())
+            .or_else(|| {
+                names
+                    .iter()
+                    .position(|n| n.to_lowercase().contains(&needle))
+            });
+        let Some(idx) = idx else {
+            let list = if names.is_empty() {
+                "aucun".to_string()
+            } else {
+                names
+                    .iter()
+                    .enumerate()
+                    .map(|(i, n)| format!("{i}: {n}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            anyhow::bail!("Port MIDI '{spec}' introuvable (ports disponibles : {list})");
+        };
+
+        let (tx, rx) = flume::bounded(256);
+        let conn = midi_in
+            .connect(
+                &ports[idx],
+                "classcii-in",
+                move |_stamp, bytes, ()| {
+                    if let Some(event) = MidiEvent::parse(bytes) {
+                        // Full channel = render loop stalled; dropping is fine for controllers.
+                        let _ = tx.try_send(event);
+                    }
+                },
+                (),
+            )
+            .map_err(|e| anyhow::anyhow!("Connexion au port MIDI '{}' : {e}", names[idx]))?;
+
+        log::info!("MIDI input: {}", names[idx]);
+        Ok(Self {
+            port_name: names[idx].clone(),
+            rx,
+            _conn: conn,
+        })
+    }
+
+    /// Name of the connected port.
+    #[must_use]
+    pub fn port_name(&self) -> &str {
+        &self.port_name
+    }
+
+    /// Events received since the last call, oldest first.
+    #[must_use]
+    pub fn drain(&self) -> Vec<MidiEvent> {
+        self.rx.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn cc_mapping(cc: u8, channel: Option<u8>) -> MidiMapping {
+        MidiMapping {
+            enabled: true,
+            cc: Some(cc),
+            note: None,
+            channel,
+            target: "glow_intensity".to_string(),
+            amount: 1.0,
+            offset: 0.0,
+            curve: af_core::config::MappingCurve::Linear,
+        }
+    }
+
+    #[test]
+    fn parses_channel_voice_messages() {
+        assert_eq!(
+            MidiEvent::parse(&[0xB3, 21, 127]),
+            Some(MidiEvent {
+                channel: 3,
+                kind: MidiEventKind::ControlChange { cc: 21, value: 127 }
+            })
+        );
+        assert_eq!(
+            MidiEvent::parse(&[0x90, 60, 0]).map(|e| e.kind),
+            Some(MidiEventKind::NoteOff { note: 60 })
+        );
+        assert_eq!(MidiEvent::parse(&[0xF8]), None); // clock
+        assert_eq!(MidiEvent::parse(&[0xE0, 0, 64]), None); // pitch bend
+    }
+
+    #[test]
+    fn state_respects_channel_filter() {
+        let mut state = MidiState::default();
+        assert_eq!(state.value_for(&cc_mapping(21, None)), None);
+
+        state.update(&MidiEvent::parse(&[0xB1, 21, 127]).unwrap());
+        assert_eq!(state.value_for(&cc_mapping(21, None)), Some(1.0));
+        assert_eq!(state.value_for(&cc_mapping(21, Some(2))), Some(1.0));
+        assert_eq!(state.value_for(&cc_mapping(21, Some(1))), None);
+    }
+
+    #[test]
+    fn note_off_resets_value() {
+        let mut state = MidiState::default();
+        let event = MidiEvent::parse(&[0x90, 60, 127]).unwrap();
+        let mapping = event.to_mapping("brightness").unwrap();
+        state.update(&event);
+        assert_eq!(state.value_for(&mapping), Some(1.0));
+        state.update(&MidiEvent::parse(&[0x80, 60, 0]).unwrap());
+        assert_eq!(state.value_for(&mapping), Some(0.0));
+        assert_eq!(mapping_label(&mapping), "N60 ch1");
+    }
+}
@@ -0,0 +1,552 @@
+//! OSC (UDP) remote control: lighting desks, Ableton, TouchOSC.
+//!
+//! Incoming messages are decoded on a dedicated thread and forwarded as
+//! [`OscCommand`] over a flume channel; the main loop applies them through
+//! the same paths as the keyboard handlers. Optional feedback sends the
+//! current [`AudioFeatures`] as one OSC bundle per frame.
+//!
+//! Address space (prefix `/classcii`):
+//!
+//! | Address | Arguments | Effect |
+//! |---------|-----------|--------|
+//! | `/render/<target>` | float or int | Set a mapping target (`contrast`, `camera_zoom_amplitude`, …) |
+//! | `/preset/next`, `/preset/prev` | — | Cycle presets |
+//! | `/preset/index` | int | Select preset by index |
+//! | `/stem/<name>/mute`, `/stem/<name>/solo` | optional 0/1 | Set, or toggle without argument |
+//! | `/stem/<name>/volume` | float 0–2 | Stem volume |
+//!
+//! Only the OSC 1.0 types `i`, `f`, `s` (plus `T`/`F`) are decoded; other
+//! type tags reject the message.
+
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use af_core::config::AUDIO_TARGETS;
+use af_core::frame::AudioFeatures;
+use af_stems::stem::StemId;
+use anyhow::{Context, Result};
+
+/// Address prefix shared by all classcii messages.
+pub const OSC_PREFIX: &str = "/classcii";
+
+/// Maximum UDP datagram accepted by the listener.
+const MAX_PACKET: usize = 8192;
+
+/// A decoded OSC argument.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    Str(String),
+    Bool(bool),
+}
+
+impl OscArg {
+    /// Numeric view (`i`, `f`, `T`/`F`); `None` for strings.
+    #[must_use]
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Self::Int(v) => Some(*v as f32),
+            Self::Float(v) => Some(*v),
+            Self::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            Self::Str(_) => None,
+        }
+    }
+}
+
+/// A single OSC message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OscMessage {
+    pub addr: String,
+    pub args: Vec<OscArg>,
+}
+
+// ─── Codec ─────────────────────────────────────────────────────────
+
+/// Decode a datagram into its messages (bundles are flattened, time tags ignored).
+///
+/// # Errors
+/// Returns an error on truncated or malformed packets.
+pub fn decode_packet(buf: &[u8]) -> Result<Vec<OscMessage>> {
+    let mut out = Vec::new();
+    decode_into(buf, &mut out)?;
+    Ok(out)
+}
+
+fn decode_into(buf: &[u8], out: &mut Vec<OscMessage>) -> Result<()> {
+    if buf.starts_with(b"#bundle\0") {
+        // 8 bytes "#bundle\0" + 8 bytes time tag, then size-prefixed elements
+        let mut pos = 16;
+        anyhow::ensure!(buf.len() >= pos, "OSC bundle truncated");
+        while pos < buf.len() {
+            let size = read_u32(buf, pos)? as usize;
+            pos += 4;
+            let end = pos.checked_add(size).filter(|&e| e <= buf.len());
+            let end = end.context("OSC bundle element overruns packet")?;
+            decode_into(&buf[pos..end], out)?;
+            pos = end;
+        }
+        return Ok(());
+    }
+
+    let (addr, mut pos) = read_str(buf, 0)?;
+    anyhow::ensure!(addr.starts_with('/'), "OSC address must start with '/'");
+    let mut args = Vec::new();
+    if pos < buf.len() {
+        let (tags, next) = read_str(buf, pos)?;
+        pos = next;
+        let tags = tags
+            .strip_prefix(',')
+            .context("OSC type tag string missing ','")?;
+        for tag in tags.chars() {
+            match tag {
+                'i' => {
+                    args.push(OscArg::Int(read_u32(buf, pos)?.cast_signed()));
+                    pos += 4;
+                }
+                'f' => {
+                    args.push(OscArg::Float(f32::from_bits(read_u32(buf, pos)?)));
+                    pos += 4;
+                }
+                's' => {
+                    let (s, next) = read_str(buf, pos)?;
+                    args.push(OscArg::Str(s));
+                    pos = next;
+                }
+                'T' => args.push(OscArg::Bool(true)),
+                'F' => args.push(OscArg::Bool(false)),
+                other => anyhow::bail!("Unsupported OSC type tag '{other}'"),
+            }
+        }
+    }
+    out.push(OscMessage { addr, args });
+    Ok(())
+}
+
+fn read_u32(buf: &[u8], pos: usize) -> Result<u32> {
+    let bytes = buf.get(pos..pos + 4).context("OSC packet truncated")?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Read a NUL-terminated, 4-byte padded string; returns it and the next offset.
+fn read_str(buf: &[u8], pos: usize) -> Result<(String, usize)> {
+    let rest = buf.get(pos..).context("OSC packet truncated")?;
+    let len = rest
+        .iter()
+        .position(|&b| b == 0)
+        .context("OSC string not terminated")?;
+    let s = std::str::from_utf8(&rest[..len]).context("OSC string not UTF-8")?;
+    Ok((s.to_string(), pos + padded_len(len + 1)))
+}
+
+fn padded_len(len: usize) -> usize {
+    len.div_ceil(4) * 4
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(s.as_bytes());
+    let pad = padded_len(s.len() + 1) - s.len();
+    out.extend(std::iter::repeat_n(0u8, pad));
+}
+
+/// Encode one message.
+#[must_use]
+pub fn encode_message(msg: &OscMessage) -> Vec<u8> {
+    let mut out = Vec::with_capacity(64);
+    write_str(&mut out, &msg.addr);
+    let mut tags = String::with_capacity(msg.args.len() + 1);
+    tags.push(',');
+    for arg in &msg.args {
+        tags.push(match arg {
+            OscArg::Int(_) => 'i',
+            OscArg::Float(_) => 'f',
+            OscArg::Str(_) => 's',
+            OscArg::Bool(true) => 'T',
+            OscArg::Bool(false) => 'F',
+        });
+    }
+    write_str(&mut out, &tags);
+    for arg in &msg.args {
+        match arg {
+            OscArg::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+            OscArg::Float(v) => out.extend_from_slice(&v.to_bits().to_be_bytes()),
+            OscArg::Str(s) => write_str(&mut out, s),
+            OscArg::Bool(_) => {}
+        }
+    }
+    out
+}
+
+/// Encode messages as a bundle with the "immediately" time tag.
+#[must_use]
+pub fn encode_bundle(msgs: &[OscMessage]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16 + msgs.len() * 48);
+    out.extend_from_slice(b"#bundle\0");
+    out.extend_from_slice(&1u64.to_be_bytes());
+    for msg in msgs {
+        let encoded = encode_message(msg);
+        out.extend_from_slice(&(encoded.len() as u32).to_be_bytes());
+        out.extend_from_slice(&encoded);
+    }
+    out
+}
+
+// ─── Commands ──────────────────────────────────────────────────────
+
+/// Remote action decoded from an OSC message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OscCommand {
+    /// Absolute value for one of `AUDIO_TARGETS`.
+    SetParam {
+        target: String,
+        value: f32,
+    },
+    PresetNext,
+    PresetPrev,
+    PresetIndex(usize),
+    /// `None` toggles.
+    StemMute(StemId, Option<bool>),
+    /// `None` toggles.
+    StemSolo(StemId, Option<bool>),
+    StemVolume(StemId, f32),
+}
+
+impl OscCommand {
+    /// Map a message onto a command. `None` for unknown addresses or
+    /// missing/invalid arguments.
+    #[must_use]
+    pub fn from_message(msg: &OscMessage) -> Option<Self> {
+        let path = msg.addr.strip_prefix(OSC_PREFIX)?;
+        let first = msg.args.first().and_then(OscArg::as_f32);
+        let mut parts = path.trim_start_matches('/').split('/');
+
+        match (parts.next()?, parts.next(), parts.next()) {
+            ("render", Some(target), None) if AUDIO_TARGETS.contains(&target) => {
+                let value = first.filter(|v| v.is_finite())?;
+                Some(Self::SetParam {
+                    target: target.to_string(),
+                    value,
+                })
+            }
+            ("preset", Some("next"), None) => Some(Self::PresetNext),
+            ("preset", Some("prev"), None) => Some(Self::PresetPrev),
+            ("preset", Some("index"), None) => {
+                let idx = first.filter(|v| *v >= 0.0)?;
+                Some(Self::PresetIndex(idx as usize))
+            }
+            ("stem", Some(name), Some(action)) => {
+                let id = StemId::ALL.into_iter().find(|s| s.scnet_name() == name)?;
+                let flag = first.map(|v| v > 0.5);
+                match action {
+                    "mute" => Some(Self::StemMute(id, flag)),
+                    "solo" => Some(Self::StemSolo(id, flag)),
+                    "volume" => Some(Self::StemVolume(
+                        id,
+                        first.filter(|v| v.is_finite())?.clamp(0.0, 2.0),
+                    )),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+// ─── Server ────────────────────────────────────────────────────────
+
+/// Listener thread plus optional feedback socket.
+///
+/// Dropping the server stops the thread.
+pub struct OscServer {
+    rx: flume::Receiver<OscCommand>,
+    local_addr: SocketAddr,
+    feedback: Option<(UdpSocket, SocketAddr)>,
+    feedback_msgs: Vec<OscMessage>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl OscServer {
+    /// Bind `listen` (`"9000"` is shorthand for `0.0.0.0:9000`) and start the
+    /// listener. With `feedback`, [`OscServer::send_features`] targets it.
+    ///
+    /// # Errors
+    /// Returns an error if an address does not resolve or the socket cannot be bound.
+    pub fn spawn(listen: &str, feedback: Option<&str>) -> Result<Self> {
+        let listen_addr = resolve_addr(listen)?;
+        let socket = UdpSocket::bind(listen_addr)
+            .with_context(|| format!("OSC : impossible d'écouter sur {listen_addr}"))?;
+        // Short timeout: the thread re-checks the stop flag between packets
+        socket.set_read_timeout(Some(Duration::from_millis(100)))?;
+        let local_addr = socket.local_addr()?;
+
+        let feedback = match feedback {
+            Some(target) => {
+                let target = resolve_addr(target)?;
+                let bind: SocketAddr = if target.is_ipv4() {
+                    ([0, 0, 0, 0], 0).into()
+                } else {
+                    ([0u16; 8], 0).into()
+                };
+                let out = UdpSocket::bind(bind).context("OSC : socket de feedback")?;
+                out.set_nonblocking(true)?;
+                Some((out, target))
+            }
+            None => None,
+        };
+
+        let (tx, rx) = flume::unbounded();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+        let handle = std::thread::Builder::new()
+            .name("af-osc".into())
+            .spawn(move || listen_loop(&socket, &tx, &stop_flag))
+            .context("OSC : impossible de lancer le thread")?;
+
+        log::info!("OSC : écoute sur {local_addr}");
+        Ok(Self {
+            rx,
+            local_addr,
+            feedback,
+            feedback_msgs: Vec::new(),
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// Address actually bound (resolves port 0).
+    #[must_use]
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Commands received since the last call (non-blocking).
+    #[must_use]
+    pub fn drain(&self) -> flume::Drain<'_, OscCommand> {
+        self.rx.drain()
+    }
+
+    /// Send `features` to the feedback target as one bundle of
+    /// `/classcii/audio/<source>` messages. No-op without feedback target.
+    pub fn send_features(&mut self, features: &AudioFeatures, onset_envelope: f32) {
+        let Some((ref socket, target)) = self.feedback else {
+            return;
+        };
+        let values = feature_values(features, onset_envelope);
+        if self.feedback_msgs.len() != values.len() {
+            self.feedback_msgs = values
+                .iter()
+                .map(|(name, _)| OscMessage {
+                    addr: format!("{OSC_PREFIX}/audio/{name}"),
+                    args: vec![OscArg::Float(0.0)],
+                })
+                .collect();
+        }
+        for (msg, (_, v)) in self.feedback_msgs.iter_mut().zip(values) {
+            msg.args[0] = OscArg::Float(v);
+        }
+        // Non-blocking: a slow or absent receiver never stalls the render loop
+        let _ = socket.send_to(&encode_bundle(&self.feedback_msgs), target);
+    }
+}
+
+impl Drop for OscServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn listen_loop(socket: &UdpSocket, tx: &flume::Sender<OscCommand>, stop: &AtomicBool) {
+    let mut buf = [0u8; MAX_PACKET];
+    while !stop.load(Ordering::Relaxed) {
+        let len = match socket.recv_from(&mut buf) {
+            Ok((len, _)) => len,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue;
+            }
+            Err(e) => {
+                log::warn!("OSC : erreur de réception : {e}");
+                continue;
+            }
+        };
+        match decode_packet(&buf[..len]) {
+            Ok(msgs) => {
+                for msg in &msgs {
+                    match OscCommand::from_message(msg) {
+                        Some(cmd) => {
+                            if tx.send(cmd).is_err() {
+                                return;
+                            }
+                        }
+                        None => log::debug!("OSC : message ignoré {}", msg.addr),
+                    }
+                }
+            }
+            Err(e) => log::debug!("OSC : paquet invalide : {e}"),
+        }
+    }
+}
+
+fn resolve_addr(spec: &str) -> Result<SocketAddr> {
+    if let Ok(port) = spec.parse::<u16>() {
+        return Ok(([0, 0, 0, 0], port).into());
+    }
+    spec.to_socket_addrs()
+        .with_context(|| format!("Adresse OSC invalide : {spec}"))?
+        .next()
+        .with_context(|| format!("Adresse OSC introuvable : {spec}"))
+}
+
+/// Feedback values, named like the mapping sources.
+fn feature_values(f: &AudioFeatures, onset_envelope: f32) -> [(&'static str, f32); 49] {
+    [
+        ("rms", f.rms),
+        ("peak", f.peak),
+        ("sub_bass", f.sub_bass),
+        ("bass", f.bass),
+        ("low_mid", f.low_mid),
+        ("mid", f.mid),
+        ("high_mid", f.high_mid),
+        ("presence", f.presence),
+        ("brilliance", f.brilliance),
+        ("spectral_centroid", f.spectral_centroid),
+        ("spectral_flux", f.spectral_flux),
+        ("spectral_flatness", f.spectral_flatness),
+        ("beat_intensity", f.beat_intensity),
+        ("onset", if f.onset { 1.0 } else { 0.0 }),
+        ("beat_phase", f.beat_phase),
+        ("bar_phase", f.bar_phase),
+        ("beat_in_bar", f32::from(f.beat_in_bar)),
+        ("tempo_confidence", f.tempo_confidence),
+        ("bpm", f.bpm),
+        ("timbral_brightness", f.timbral_brightness),
+        ("timbral_roughness", f.timbral_roughness),
+        ("onset_envelope", onset_envelope),
+        ("spectral_rolloff", f.spectral_rolloff),
+        ("zero_crossing_rate", f.zero_crossing_rate),
+        ("kick_onset", if f.kick_onset { 1.0 } else { 0.0 }),
+        ("kick_intensity", f.kick_intensity),
+        ("kick_envelope", f.kick_envelope),
+        ("snare_onset", if f.snare_onset { 1.0 } else { 0.0 }),
+        ("snare_intensity", f.snare_intensity),
+        ("snare_envelope", f.snare_envelope),
+        ("hat_onset", if f.hat_onset { 1.0 } else { 0.0 }),
+        ("hat_intensity", f.hat_intensity),
+        ("hat_envelope", f.hat_envelope),
+        ("pitch", f.pitch),
+        ("pitch_confidence", f.pitch_confidence),
+        ("chroma_c", f.chroma[0]),
+        ("chroma_cs", f.chroma[1]),
+        ("chroma_d", f.chroma[2]),
+        ("chroma_ds", f.chroma[3]),
+        ("chroma_e", f.chroma[4]),
+        ("chroma_f", f.chroma[5]),
+        ("chroma_fs", f.chroma[6]),
+        ("chroma_g", f.chroma[7]),
+        ("chroma_gs", f.chroma[8]),
+        ("chroma_a", f.chroma[9]),
+        ("chroma_as", f.chroma[10]),
+        ("chroma_b", f.chroma[11]),
+        ("loudness_lufs", f.loudness_lufs),
+        ("dynamic_range", f.dynamic_range),
+    ]
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn msg(addr: &str, args: Vec<OscArg>) -> OscMessage {
+        OscMessage {
+            addr: addr.to_string(),
+            args,
+        }
+    }
+
+    #[test]
+    fn message_roundtrip_is_padded() {
+        let m = msg(
+            "/classcii/render/contrast",
+            vec![
+                OscArg::Float(1.5),
+                OscArg::Int(-3),
+                OscArg::Str("abc".into()),
+                OscArg::Bool(true),
+            ],
+        );
+        let bytes = encode_message(&m);
+        assert_eq!(bytes.len() % 4, 0);
+        assert_eq!(decode_packet(&bytes).unwrap(), vec![m]);
+    }
+
+    #[test]
+    fn bundle_is_flattened() {
+        let a = msg("/classcii/preset/next", vec![]);
+        let b = msg("/classcii/stem/drums/mute", vec![OscArg::Int(1)]);
+        let bytes = encode_bundle(&[a.clone(), b.clone()]);
+        assert_eq!(decode_packet(&bytes).unwrap(), vec![a, b]);
+    }
+
+    #[test]
+    fn truncated_packet_rejected() {
+        let bytes = encode_message(&msg("/classcii/render/contrast", vec![OscArg::Float(1.0)]));
+        assert!(decode_packet(&bytes[..bytes.len() - 2]).is_err());
+    }
+
+    #[test]
+    fn addresses_map_to_commands() {
+        assert_eq!(
+            OscCommand::from_message(&msg("/classcii/render/contrast", vec![OscArg::Int(2)])),
+            Some(OscCommand::SetParam {
+                target: "contrast".into(),
+                value: 2.0
+            })
+        );
+        assert_eq!(
+            OscCommand::from_message(&msg("/classcii/stem/vocals/solo", vec![])),
+            Some(OscCommand::StemSolo(StemId::Vocals, None))
+        );
+        assert_eq!(
+            OscCommand::from_message(&msg("/classcii/preset/index", vec![OscArg::Int(3)])),
+            Some(OscCommand::PresetIndex(3))
+        );
+        assert!(OscCommand::from_message(&msg("/classcii/render/charset", vec![])).is_none());
+        assert!(
+            OscCommand::from_message(&msg("/other/render/contrast", vec![OscArg::Int(1)]))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn non_finite_values_rejected() {
+        for address in ["/classcii/render/contrast", "/classcii/stem/drums/volume"] {
+            assert!(
+                OscCommand::from_message(&msg(address, vec![OscArg::Float(f32::NAN)])).is_none(),
+                "{address}"
+            );
+        }
+        assert_eq!(
+            OscCommand::from_message(&msg(
+                "/classcii/stem/drums/volume",
+                vec![OscArg::Float(f32::INFINITY)]
+            )),
+            None
+        );
+        assert_eq!(
+            OscCommand::from_message(&msg("/classcii/stem/drums/volume", vec![OscArg::Int(5)])),
+            Some(OscCommand::StemVolume(StemId::Drums, 2.0))
+        );
+    }
+}
@@ -1,11 +1,14 @@
 use std::sync::Arc;
 
+use af_audio::capture::CaptureOptions;
+use af_audio::state::{AudioCommand, AudioThread};
 use af_core::clock::MediaClock;
-use af_core::config::RenderConfig;
-use af_core::frame::{AudioFeatures, FrameBuffer};
+use af_core::config::{MappingCurve, RenderConfig, RenderMode};
+use af_core::frame::{AudioFeatures, BEATS_PER_BAR, ChannelFeatures, FrameBuffer};
 use arc_swap::ArcSwap;
 
 use crate::cli::Cli;
+use crate::midi::MidiState;
 
 #[cfg(feature = "video")]
 pub type SourceResult = (
@@ -19,10 +22,25 @@ pub type SourceResult = (
     Option<Arc<FrameBuffer>>,
     Option<flume::Receiver<Arc<FrameBuffer>>>,
 );
+
+/// Audio pipeline outputs: main features, per-channel features (split
+/// capture only), the playback command channel (audio files only), and the
+/// live source thread (stopped and joined when dropped).
+pub type AudioResult = (
+    triple_buffer::Output<AudioFeatures>,
+    Option<triple_buffer::Output<ChannelFeatures>>,
+    Option<flume::Sender<AudioCommand>>,
+    Option<AudioThread>,
+);
+
 /// Start the audio pipeline.
 ///
-/// `audio_arg` can be `"default"` or `"mic"` for microphone capture,
-/// or a file path for audio file analysis.
+/// `audio_arg` can be `"default"` or `"mic"` for microphone capture
+/// (device, channels and sample rate from `capture`), `"loopback"` for what
+/// the machine plays (monitor source, JACK or WASAPI), `-` or `fifo:<PATH>`
+/// for raw PCM in `capture.pcm_format`, `net:<PORT>` for
+/// features received from another instance, or a file path for audio file analysis.
+/// The local sources analyse with `config.analysis`, read once at start.
 ///
 /// # Errors
 /// Returns an error if the audio device or file is unavailable.
@@ -30,19 +48,48 @@ pub fn start_audio(
     audio_arg: &str,
     config: &Arc<ArcSwap<RenderConfig>>,
     clock: Arc<MediaClock>,
-) -> anyhow::Result<(
-    triple_buffer::Output<AudioFeatures>,
-    Option<flume::Sender<af_audio::state::AudioCommand>>,
-)> {
+    capture: &CaptureOptions,
+) -> anyhow::Result<AudioResult> {
     let fps = config.load().target_fps;
     let smoothing = config.load().audio_smoothing;
     let input_gain = config.load().input_gain;
+    let analysis = config.load().analysis.clone();
+
+    if let Some(source) = af_audio::pcm::PcmSource::from_arg(audio_arg) {
+        log::info!(
+            "Starting raw PCM input from {} ({:?})",
+            source.label(),
+            capture.pcm_format
+        );
+        let (out, channels, thread) = af_audio::state::spawn_pcm_thread(
+            source, fps, smoothing, input_gain, capture, &analysis,
+        )?;
+        return Ok((out, channels, None, Some(thread)));
+    }
+    if let Some(listen) = audio_arg.strip_prefix("net:") {
+        log::info!("Starting network audio source on {listen}");
+        let (out, _, thread) = af_audio::network::spawn_network_thread(listen)?;
+        return Ok((out, None, None, Some(thread)));
+    }
 
     match audio_arg {
+        "loopback" | "system" => {
+            log::info!("Starting system audio loopback capture (gain={input_gain:.1})");
+            let loopback = CaptureOptions {
+                loopback: true,
+                ..capture.clone()
+            };
+            let (out, channels, thread) = af_audio::state::spawn_audio_thread(
+                fps, smoothing, input_gain, &loopback, &analysis,
+            )?;
+            Ok((out, channels, None, Some(thread)))
+        }
         "default" | "mic" | "microphone" => {
             log::info!("Starting microphone capture (gain={input_gain:.1})");
-            let out = af_audio::state::spawn_audio_thread(fps, smoothing, input_gain)?;
-            Ok((out, None))
+            let (out, channels, thread) = af_audio::state::spawn_audio_thread(
+                fps, smoothing, input_gain, capture, &analysis,
+            )?;
+            Ok((out, channels, None, Some(thread)))
         }
         path => {
             let audio_path = std::path::Path::new(path);
@@ -50,9 +97,9 @@ pub fn start_audio(
                 log::info!("Starting audio file analysis: {path} (gain={input_gain:.1})");
                 let (cmd_tx, cmd_rx) = flume::bounded(10);
                 let out = af_audio::state::spawn_audio_file_thread(
-                    audio_path, fps, smoothing, input_gain, cmd_rx, clock,
+                    audio_path, fps, smoothing, input_gain, cmd_rx, clock, &analysis,
                 )?;
-                Ok((out, Some(cmd_tx)))
+                Ok((out, None, Some(cmd_tx), None))
             } else {
                 anyhow::bail!("Audio source not found: {path}")
             }
@@ -63,7 +110,7 @@ pub fn start_audio(
 /// Start the visual source pipeline.
 ///
 /// For static images, returns the image as an Arc-wrapped frame.
-/// For dynamic sources (video), returns a receiver channel.
+/// For dynamic sources (video, camera), returns a receiver channel.
 ///
 /// # Errors
 /// Returns an error if source initialization fails.
@@ -116,10 +163,50 @@ pub fn start_source(
         return Ok((None, Some(frame_rx), Some(cmd_tx)));
     }
 
+    #[cfg(feature = "video")]
+    if let Some(ref spec) = cli.camera {
+        let spec = af_source::camera::CameraSpec::parse(spec)?;
+        log::info!("Starting camera source: {} {}", spec.format, spec.device);
+        let (frame_tx, frame_rx) = flume::bounded(3);
+        let (cmd_tx, cmd_rx) = flume::bounded(10);
+        // Cameras rarely exceed 30 fps; higher requests are rejected by some drivers.
+        let fps = config.load().target_fps.min(30);
+        af_source::camera::spawn_camera_thread(spec, frame_tx, cmd_rx, fps)?;
+        return Ok((None, Some(frame_rx), Some(cmd_tx)));
+    }
+
     #[cfg(feature = "video")]
     return Ok((None, None, None));
     #[cfg(not(feature = "video"))]
-    return Ok((None, None));
+    {
+        if cli.camera.is_some() {
+            anyhow::bail!("--camera requiert la feature 'video' (ffmpeg support).");
+        }
+        Ok((None, None))
+    }
+}
+
+/// Source frame size (pixels) for a canvas of `cols × rows` cells.
+///
+/// Sub-pixels per cell depend on the render mode, scaled by `density_scale`,
+/// then the height is corrected by `aspect_ratio`. Shared by the TUI resize
+/// path and headless `--snapshot`.
+#[must_use]
+pub fn source_pixel_size(config: &RenderConfig, cols: u16, rows: u16) -> (u32, u32) {
+    let density = config.density_scale.clamp(0.25, 4.0);
+    let (sub_x, sub_y) = match config.render_mode {
+        RenderMode::Ascii => (1.0, 1.0),
+        RenderMode::HalfBlock => (1.0, 2.0),
+        RenderMode::Braille | RenderMode::Octant => (2.0, 4.0),
+        RenderMode::Quadrant => (2.0, 2.0),
+        RenderMode::Sextant => (2.0, 3.0),
+    };
+    let pixel_w = (f32::from(cols) * density * sub_x) as u32;
+    let pixel_h = (f32::from(rows) * density * sub_y) as u32;
+
+    // Appliquer la correction aspect ratio
+    let pixel_h_corrected = (pixel_h as f32 / config.aspect_ratio) as u32;
+    (pixel_w.max(1), pixel_h_corrected.max(1))
 }
 
 /// Applique les mappings audio à une copie de la config avant le rendu.
@@ -141,27 +228,29 @@ pub fn start_source(
 /// let mut config = RenderConfig::default();
 /// let features = AudioFeatures::default();
 /// let mut smooth = vec![];
-/// apply_audio_mappings(&mut config, &features, None, 0.0, &mut smooth, 60);
+/// apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
 /// ```
 #[allow(clippy::too_many_lines)]
 pub fn apply_audio_mappings(
     config: &mut RenderConfig,
     features: &AudioFeatures,
     stem_features: Option<&af_stems::stem::StemFeatures>,
+    channel_features: Option<&ChannelFeatures>,
     onset_envelope: f32,
     smooth_state: &mut Vec<f32>,
     target_fps: u32,
 ) {
-    use af_core::config::MappingCurve;
-
     let sensitivity = config.audio_sensitivity;
+    config.key_hue = features.key_hue();
 
     // Resize smooth_state si le nombre de mappings a changé
     if smooth_state.len() != config.audio_mappings.len() {
         smooth_state.resize(config.audio_mappings.len(), 0.0);
     }
 
-    for (i, mapping) in config.audio_mappings.iter().enumerate() {
+    // Sortis de la config le temps de la boucle : apply_target_delta l'emprunte en &mut.
+    let mappings = std::mem::take(&mut config.audio_mappings);
+    for (i, mapping) in mappings.iter().enumerate() {
         if !mapping.enabled {
             continue;
         }
@@ -182,7 +271,13 @@ pub fn apply_audio_mappings(
                     features
                 }
             }
-            _ => features,
+            // Then per-channel data if mapping has channel_source (1-based)
+            _ => match (mapping.channel_source, channel_features) {
+                (Some(ch), Some(cf)) if (1..=cf.count).contains(&usize::from(ch)) => {
+                    &cf.features[usize::from(ch) - 1]
+                }
+                _ => features,
+            },
         };
 
         let source_value = match mapping.source.as_str() {
@@ -199,37 +294,45 @@ pub fn apply_audio_mappings(
             "spectral_flux" => effective_features.spectral_flux,
             "spectral_flatness" => effective_features.spectral_flatness,
             "beat_intensity" => effective_features.beat_intensity,
-            "onset" => {
-                if effective_features.onset {
-                    1.0
-                } else {
-                    0.0
-                }
-            }
+            "onset" if effective_features.onset => 1.0,
             "beat_phase" => effective_features.beat_phase,
+            "bar_phase" => effective_features.bar_phase,
+            "beat_in_bar" => {
+                f32::from(effective_features.beat_in_bar) / f32::from(BEATS_PER_BAR - 1)
+            }
+            "tempo_confidence" => effective_features.tempo_confidence,
             "bpm" => effective_features.bpm / 300.0,
             "timbral_brightness" => effective_features.timbral_brightness,
             "timbral_roughness" => effective_features.timbral_roughness,
             "onset_envelope" => onset_envelope,
             "spectral_rolloff" => effective_features.spectral_rolloff,
             "zero_crossing_rate" => effective_features.zero_crossing_rate,
-            _ => 0.0, // validated at config load time
-        };
-
-        // Apply response curve
-        let shaped = match &mapping.curve {
-            MappingCurve::Linear => source_value,
-            MappingCurve::Exponential => source_value * source_value,
-            MappingCurve::Threshold => {
-                if source_value > 0.3 {
-                    (source_value - 0.3) / 0.7
-                } else {
-                    0.0
-                }
-            }
-            MappingCurve::Smooth => source_value * source_value * (3.0 - 2.0 * source_value),
+            "kick_onset" if effective_features.kick_onset => 1.0,
+            "kick_intensity" => effective_features.kick_intensity,
+            "kick_envelope" => effective_features.kick_envelope,
+            "snare_onset" if effective_features.snare_onset => 1.0,
+            "snare_intensity" => effective_features.snare_intensity,
+            "snare_envelope" => effective_features.snare_envelope,
+            "hat_onset" if effective_features.hat_onset => 1.0,
+            "hat_intensity" => effective_features.hat_intensity,
+            "hat_envelope" => effective_features.hat_envelope,
+            "pitch" => effective_features.pitch_normalized(),
+            "pitch_confidence" => effective_features.pitch_confidence,
+            "loudness_lufs" => effective_features.loudness_normalized(),
+            "dynamic_range" => effective_features.dynamic_range,
+            // chroma_* and [audio.analysis] bands — other names are rejected at config load time
+            other => effective_features
+                .chroma_source(other)
+                .or_else(|| {
+                    config
+                        .analysis
+                        .custom_band_index(other)
+                        .map(|i| effective_features.custom_bands[i])
+                })
+                .unwrap_or(0.0),
         };
 
+        let shaped = shape_curve(&mapping.curve, source_value);
         let raw_delta = shaped * mapping.amount * sensitivity + mapping.offset;
 
         // Per-mapping EMA smoothing — opt-in only.
@@ -247,73 +350,53 @@ pub fn apply_audio_mappings(
             raw_delta
         };
 
-        match mapping.target.as_str() {
-            "edge_threshold" => {
-                config.edge_threshold = (config.edge_threshold + delta).clamp(0.0, 1.0);
-            }
-            "edge_mix" => {
-                config.edge_mix = (config.edge_mix + delta).clamp(0.0, 1.0);
-            }
-            "contrast" => {
-                config.contrast = (config.contrast + delta).clamp(0.1, 3.0);
-            }
-            "brightness" => {
-                config.brightness = (config.brightness + delta).clamp(-1.0, 1.0);
-            }
-            "saturation" => {
-                config.saturation = (config.saturation + delta).clamp(0.0, 3.0);
-            }
-            "density_scale" => {
-                config.density_scale = (config.density_scale + delta).clamp(0.25, 4.0);
-            }
-            "invert" => {
-                config.invert = delta > 0.5;
-            }
-            "beat_flash_intensity" => {
-                config.beat_flash_intensity = (config.beat_flash_intensity + delta).clamp(0.0, 2.0);
-            }
-            "chromatic_offset" => {
-                config.chromatic_offset = (config.chromatic_offset + delta).clamp(0.0, 5.0);
-            }
-            "wave_amplitude" => {
-                config.wave_amplitude = (config.wave_amplitude + delta).clamp(0.0, 1.0);
-            }
-            "color_pulse_speed" => {
-                config.color_pulse_speed = (config.color_pulse_speed + delta).clamp(0.0, 5.0);
-            }
-            "fade_decay" => {
-                config.fade_decay = (config.fade_decay + delta).clamp(0.0, 1.0);
-            }
-            "glow_intensity" => {
-                config.glow_intensity = (config.glow_intensity + delta).clamp(0.0, 2.0);
-            }
-            "zalgo_intensity" => {
-                config.zalgo_intensity = (config.zalgo_intensity + delta).clamp(0.0, 5.0);
-            }
-            "camera_zoom_amplitude" => {
-                // Zoom varies around 1.0. Delta from audio typically modulates positively.
-                // An arbitrary practical range like 0.1 (strong unzoom) to 10.0 (high zoom).
-                config.camera_zoom_amplitude =
-                    (config.camera_zoom_amplitude + delta * 2.0).clamp(0.1, 10.0);
-            }
-            "camera_rotation" => {
-                config.camera_rotation += delta * 0.1;
-                // Wrap at TAU to prevent float precision degradation
-                config.camera_rotation = config.camera_rotation.rem_euclid(std::f32::consts::TAU);
-            }
-            "camera_pan_x" => {
-                // Audio delta for panning (wiggling) on X axis
-                config.camera_pan_x = (config.camera_pan_x + delta * 0.5).clamp(-2.0, 2.0);
-            }
-            "camera_pan_y" => {
-                // Audio delta for panning (wiggling) on Y axis
-                config.camera_pan_y = (config.camera_pan_y + delta * 0.5).clamp(-2.0, 2.0);
-            }
-            "camera_tilt_x" => {
-                config.camera_tilt_x = (config.camera_tilt_x + delta * 0.3).clamp(-1.0, 1.0);
+        config.apply_target_delta(&mapping.target, delta);
+    }
+    config.audio_mappings = mappings;
+}
+
+/// Applique les mappings MIDI (`[[midi.mappings]]`) à la config du frame.
+///
+/// Même chaîne que les mappings audio (courbe → `amount` → `offset`), sans
+/// `audio_sensitivity` ni lissage : un contrôleur est déjà un signal propre.
+///
+/// # Example
+/// ```
+/// use af_core::config::RenderConfig;
+/// use af_app::midi::MidiState;
+/// use af_app::pipeline::apply_midi_mappings;
+///
+/// let mut config = RenderConfig::default();
+/// apply_midi_mappings(&mut config, &MidiState::default());
+/// ```
+pub fn apply_midi_mappings(config: &mut RenderConfig, midi: &MidiState) {
+    let mappings = std::mem::take(&mut config.midi_mappings);
+    for mapping in &mappings {
+        if !mapping.enabled {
+            continue;
+        }
+        let Some(value) = midi.value_for(mapping) else {
+            continue;
+        };
+        let delta = shape_curve(&mapping.curve, value) * mapping.amount + mapping.offset;
+        config.apply_target_delta(&mapping.target, delta);
+    }
+    config.midi_mappings = mappings;
+}
+
+/// Courbe de réponse d'un mapping, `x` dans [0, 1].
+fn shape_curve(curve: &MappingCurve, x: f32) -> f32 {
+    match curve {
+        MappingCurve::Linear => x,
+        MappingCurve::Exponential => x * x,
+        MappingCurve::Threshold => {
+            if x > 0.3 {
+                (x - 0.3) / 0.7
+            } else {
+                0.0
             }
-            _ => {}
         }
+        MappingCurve::Smooth => x * x * (3.0 - 2.0 * x),
     }
 }
 
@@ -331,7 +414,7 @@ mod tests {
         features.bass = 0.5;
         let mut smooth = vec![];
 
-        apply_audio_mappings(&mut config, &features, None, 0.0, &mut smooth, 60);
+        apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
 
         // With Smooth curve on bass=0.5: shaped = 3*(0.25) - 2*(0.125) = 0.5
         // delta = 0.5 * 0.7 * 2.0 = 0.7 — direct passthrough (no per-mapping EMA)
@@ -354,13 +437,14 @@ mod tests {
             curve: MappingCurve::Linear,
             smoothing: Some(0.3), // Explicit per-mapping smoothing
             stem_source: None,
+            channel_source: None,
         }];
         let mut features = AudioFeatures::default();
         features.rms = 1.0;
         let mut smooth = vec![];
 
         // First frame: EMA with alpha=0.3 → 0.3 * raw_delta + 0.7 * 0
-        apply_audio_mappings(&mut config, &features, None, 0.0, &mut smooth, 60);
+        apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
         let first = config.brightness;
 
         // With smoothing, first frame should be substantially less than raw delta
@@ -383,11 +467,12 @@ mod tests {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: None,
+            channel_source: None,
         }];
         let features = AudioFeatures::default();
         let mut smooth = vec![];
 
-        apply_audio_mappings(&mut config, &features, None, 0.75, &mut smooth, 60);
+        apply_audio_mappings(&mut config, &features, None, None, 0.75, &mut smooth, 60);
         // delta = 0.75 * 1.0 * 2.0 = 1.5, clamped brightness to 1.0
         assert!(
             config.brightness > 0.5,
@@ -410,6 +495,7 @@ mod tests {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: Some("drums".into()), // stem index 0
+            channel_source: None,
         }];
 
         // Combined features have bass=0.0 (should NOT be used)
@@ -424,6 +510,7 @@ mod tests {
             &mut config,
             &combined,
             Some(&stem_feats),
+            None,
             0.0,
             &mut smooth,
             60,
@@ -449,6 +536,7 @@ mod tests {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: Some("drums".into()),
+            channel_source: None,
         }];
 
         let mut combined = AudioFeatures::default();
@@ -456,7 +544,7 @@ mod tests {
 
         let mut smooth = vec![];
         // Pass None for stem_features → should fall back to combined
-        apply_audio_mappings(&mut config, &combined, None, 0.0, &mut smooth, 60);
+        apply_audio_mappings(&mut config, &combined, None, None, 0.0, &mut smooth, 60);
 
         assert!(
             config.brightness > 0.2,
@@ -464,4 +552,163 @@ mod tests {
             config.brightness
         );
     }
+    #[test]
+    fn channel_source_resolves_per_channel_features() {
+        let mut config = RenderConfig::default();
+        let base = config.brightness;
+        config.audio_mappings = vec![AudioMapping {
+            enabled: true,
+            source: "bass".into(),
+            target: "brightness".into(),
+            amount: 1.0,
+            offset: 0.0,
+            curve: MappingCurve::Linear,
+            smoothing: None,
+            stem_source: None,
+            channel_source: Some(2),
+        }];
+
+        // Main (downmix) bass=0.0, right channel bass=0.8
+        let main = AudioFeatures::default();
+        let mut channels = ChannelFeatures {
+            count: 2,
+            ..ChannelFeatures::default()
+        };
+        channels.features[1].bass = 0.8;
+
+        let mut smooth = vec![];
+        apply_audio_mappings(
+            &mut config,
+            &main,
+            None,
+            Some(&channels),
+            0.0,
+            &mut smooth,
+            60,
+        );
+        assert!(
+            config.brightness - base > 0.3,
+            "channel_source=2 should read the right channel, got brightness={}",
+            config.brightness
+        );
+
+        // Channel not analysed → main features
+        channels.count = 1;
+        config.brightness = base;
+        apply_audio_mappings(
+            &mut config,
+            &main,
+            None,
+            Some(&channels),
+            0.0,
+            &mut smooth,
+            60,
+        );
+        assert!((config.brightness - base).abs() < 1e-6);
+    }
+
+    #[test]
+    fn drum_sources_drive_separate_targets() {
+        let mapping = |source: &str, target: &str| AudioMapping {
+            enabled: true,
+            source: source.into(),
+            target: target.into(),
+            amount: 1.0,
+            offset: 0.0,
+            curve: MappingCurve::Linear,
+            smoothing: None,
+            stem_source: None,
+            channel_source: None,
+        };
+        let mut config = RenderConfig::default();
+        let (brightness, contrast) = (config.brightness, config.contrast);
+        config.audio_mappings = vec![
+            mapping("kick_onset", "brightness"),
+            mapping("hat_envelope", "contrast"),
+        ];
+
+        // Snare only: neither the kick nor the hi-hat mapping moves
+        let snare = AudioFeatures {
+            snare_onset: true,
+            snare_intensity: 1.0,
+            snare_envelope: 1.0,
+            ..AudioFeatures::default()
+        };
+        let mut smooth = vec![];
+        apply_audio_mappings(&mut config, &snare, None, None, 0.0, &mut smooth, 60);
+        assert!((config.brightness - brightness).abs() < 1e-6);
+        assert!((config.contrast - contrast).abs() < 1e-6);
+
+        let kick = AudioFeatures {
+            kick_onset: true,
+            hat_envelope: 0.5,
+            ..AudioFeatures::default()
+        };
+        apply_audio_mappings(&mut config, &kick, None, None, 0.0, &mut smooth, 60);
+        assert!(config.brightness > brightness);
+        assert!(config.contrast > contrast);
+    }
+
+    #[test]
+    fn chroma_sources_and_key_hue() {
+        let mut config = RenderConfig::default();
+        let brightness = config.brightness;
+        config.audio_mappings = vec![AudioMapping {
+            enabled: true,
+            source: "chroma_a".into(),
+            target: "brightness".into(),
+            amount: 1.0,
+            offset: 0.0,
+            curve: MappingCurve::Linear,
+            smoothing: None,
+            stem_source: None,
+            channel_source: None,
+        }];
+
+        // G major: chroma on G, not A
+        let mut g_major = AudioFeatures {
+            key: 7,
+            ..AudioFeatures::default()
+        };
+        g_major.chroma[7] = 1.0;
+        let mut smooth = vec![];
+        apply_audio_mappings(&mut config, &g_major, None, None, 0.0, &mut smooth, 60);
+        assert!((config.brightness - brightness).abs() < 1e-6);
+        assert!((config.key_hue - g_major.key_hue()).abs() < f32::EPSILON);
+
+        g_major.chroma[9] = 1.0;
+        apply_audio_mappings(&mut config, &g_major, None, None, 0.0, &mut smooth, 60);
+        assert!(config.brightness > brightness);
+    }
+
+    #[test]
+    fn custom_band_sources() {
+        let mut config = RenderConfig::default();
+        config.analysis.bands = vec![af_core::config::AnalysisBand {
+            name: "kick_body".into(),
+            low_hz: 40.0,
+            high_hz: 90.0,
+        }];
+        let brightness = config.brightness;
+        config.audio_mappings = vec![AudioMapping {
+            enabled: true,
+            source: "kick_body".into(),
+            target: "brightness".into(),
+            amount: 1.0,
+            offset: 0.0,
+            curve: MappingCurve::Linear,
+            smoothing: None,
+            stem_source: None,
+            channel_source: None,
+        }];
+
+        let mut features = AudioFeatures::default();
+        let mut smooth = vec![];
+        apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
+        assert!((config.brightness - brightness).abs() < 1e-6);
+
+        features.custom_bands[0] = 0.8;
+        apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
+        assert!(config.brightness > brightness);
+    }
 }
@@ -0,0 +1,71 @@
+//! Headless snapshot: render one frame of an image to `.txt`, `.ans` or `.html`.
+
+use std::path::Path;
+
+use af_ascii::compositor::Compositor;
+use af_core::config::RenderConfig;
+use af_core::frame::{AsciiGrid, FrameBuffer};
+use anyhow::{Context, Result};
+
+use crate::pipeline;
+
+/// Nombre de colonnes par défaut de `--snapshot`.
+pub const DEFAULT_SNAPSHOT_WIDTH: u16 = 120;
+
+/// Convert a frame into a grid `cols` wide, height following the image aspect.
+///
+/// Same chain as the TUI (camera → resize → compositor), plus the static
+/// post-effects (chromatic aberration, scan lines, glow). Temporal effects
+/// need several frames and are skipped.
+///
+/// # Errors
+/// Returns an error if the frame cannot be resized.
+pub fn render_frame_grid(
+    frame: &FrameBuffer,
+    config: &RenderConfig,
+    cols: u16,
+) -> Result<AsciiGrid> {
+    let cols = cols.max(1);
+    let aspect = config.aspect_ratio.max(0.1);
+    let rows = (f32::from(cols) * frame.height as f32 / frame.width.max(1) as f32 / aspect)
+        .round()
+        .clamp(1.0, f32::from(u16::MAX)) as u16;
+
+    let mut transformed = FrameBuffer::new(frame.width, frame.height);
+    af_render::camera::VirtualCamera::apply_transform(config, frame, &mut transformed);
+
+    let (pixel_w, pixel_h) = pipeline::source_pixel_size(config, cols, rows);
+    let mut resized = FrameBuffer::new(pixel_w, pixel_h);
+    af_source::resize::Resizer::new().resize_into(&transformed, &mut resized)?;
+
+    let mut grid = AsciiGrid::new(cols, rows);
+    Compositor::new(&config.charset).process(&resized, None, config, &mut grid);
+
+    let mut fg_buf = Vec::new();
+    af_render::effects::apply_chromatic_aberration(&mut grid, config.chromatic_offset, &mut fg_buf);
+    af_render::effects::apply_scan_lines(&mut grid, config.scanline_gap, config.scanline_darken);
+    let mut brightness_buf = Vec::new();
+    af_render::effects::apply_glow(&mut grid, config.glow_intensity, &mut brightness_buf);
+
+    Ok(grid)
+}
+
+/// `--snapshot` : charge `image`, rend une frame et l'écrit dans `out`.
+///
+/// # Errors
+/// Retourne une erreur si l'image est illisible, l'extension de `out` inconnue
+/// ou l'écriture impossible.
+pub fn run_snapshot(image: &Path, out: &Path, config: &RenderConfig, cols: u16) -> Result<()> {
+    let frame = af_source::image::load_image(&image.to_string_lossy())?;
+    let grid = render_frame_grid(&frame, config, cols)?;
+    let format = af_export::text::write_snapshot(&grid, out)
+        .with_context(|| format!("Snapshot {} échoué", out.display()))?;
+    log::info!(
+        "Snapshot {:?} {}x{} → {}",
+        format,
+        grid.width,
+        grid.height,
+        out.display()
+    );
+    Ok(())
+}
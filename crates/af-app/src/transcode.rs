@@ -0,0 +1,238 @@
+//! Offline transcode: one video file → ASCII video, frame for frame.
+//!
+//! Unlike [`crate::batch::run_batch_export`] there is no clip sequencing, no
+//! preset cycling and no random mutation: the same input and config always
+//! produce the same output. The source's own audio track drives the audio
+//! mappings (via `BatchAnalyzer`) and is muxed back into the result.
+
+use std::path::Path;
+
+use af_core::automation::Automation;
+use af_core::config::{ExportProfile, RenderConfig};
+use anyhow::Result;
+
+#[cfg(feature = "video")]
+use af_ascii::compositor::Compositor;
+#[cfg(feature = "video")]
+use af_audio::batch_analyzer::BatchAnalyzer;
+#[cfg(feature = "video")]
+use af_core::clock::FrameRate;
+#[cfg(feature = "video")]
+use af_core::config::BgStyle;
+#[cfg(feature = "video")]
+use af_core::frame::{AsciiGrid, AudioFeatures, FrameBuffer};
+#[cfg(feature = "video")]
+use af_export::animation::AnimFormat;
+#[cfg(feature = "video")]
+use af_export::muxer::{Mp4Muxer, ProfileSpec, mux_audio_video, validate_output};
+#[cfg(feature = "video")]
+use af_export::rasterizer::Rasterizer;
+#[cfg(feature = "video")]
+use af_source::video::{probe_video, read_exact_or_eof, spawn_ffmpeg_pipe};
+#[cfg(feature = "video")]
+use anyhow::Context;
+
+#[cfg(feature = "video")]
+use crate::batch::PostEffects;
+#[cfg(feature = "video")]
+use crate::generative::AutoGenerativeMapper;
+
+/// Rend `input` image par image vers `output` (`--video IN --export OUT`).
+///
+/// La cadence est exactement celle de la source (`24000/1001` reste 23.976 :
+/// ni frame dupliquée, ni dérive de durée face à la piste audio). Sans piste
+/// audio décodable, le rendu se fait sans réactivité et sans muxage.
+/// `automation` est évaluée au temps de chaque frame.
+///
+/// # Errors
+/// Retourne une erreur si la sortie est incompatible avec `profile` (GIF/APNG
+/// non supportés), si la vidéo est illisible, ou si l'encodage échoue.
+#[allow(clippy::too_many_lines)]
+pub fn run_transcode(
+    input: &Path,
+    output: &Path,
+    config: RenderConfig,
+    export_scale: Option<f32>,
+    profile: ExportProfile,
+    automation: Option<&Automation>,
+) -> Result<()> {
+    #[cfg(not(feature = "video"))]
+    {
+        let _ = (input, output, config, export_scale, profile, automation);
+        anyhow::bail!("Le transcodage vidéo requiert la feature 'video' (ffmpeg support).");
+    }
+
+    #[cfg(feature = "video")]
+    {
+        if profile != ExportProfile::PngSequence && AnimFormat::from_path(output).is_some() {
+            anyhow::bail!(
+                "--export : GIF/APNG non supportés en transcodage, utiliser un profil vidéo ou png-sequence"
+            );
+        }
+        validate_output(profile, output)?;
+
+        // === Étape 1 : Métadonnées source ===
+        let info = probe_video(input)?;
+        let rate = if (1.0..=240.0).contains(&info.fps) {
+            info.frame_rate
+        } else {
+            FrameRate::integer(info.fps.round().clamp(1.0, 240.0) as u32)
+        };
+        // Cadence entière pour l'analyse audio et les décroissances d'effets
+        let fps = rate.rounded();
+        log::info!(
+            "Étape 1/4 : Source {}x{} @ {:.3}fps → rendu à {rate}fps",
+            info.width,
+            info.height,
+            info.fps
+        );
+        let mut config = config;
+        config.target_fps = fps;
+
+        // === Étape 2 : Analyse de la piste audio de la vidéo ===
+        log::info!("Étape 2/4 : Analyse audio de {}", input.display());
+        let mut mapper = match BatchAnalyzer::with_analysis(fps, 44100, &config.analysis)
+            .analyze_file(input)
+        {
+            Ok(timeline) => {
+                let mut m = AutoGenerativeMapper::new(config.clone(), timeline);
+                if let Some(a) = automation {
+                    m.set_automation(a.clone());
+                }
+                Some(m)
+            }
+            Err(e) => {
+                log::warn!("Pas de piste audio exploitable ({e}) : rendu sans réactivité audio");
+                None
+            }
+        };
+        let expected_frames = mapper.as_ref().map(|m| m.get_timeline().total_frames());
+
+        // === Étape 3 : Pipeline de rendu ===
+        log::info!("Étape 3/4 : Rendu et encodage ({})", profile.name());
+        let target_w = info.width.max(1280);
+        let target_h = info.height.max(720);
+        let grid_w = (target_w / 8) as u16;
+        let grid_h = (target_h / 16) as u16;
+        let mut grid = AsciiGrid::new(grid_w, grid_h);
+
+        let font_data = include_bytes!("../../af-export/assets/CascadiaMono.ttf");
+        let mut rasterizer = Rasterizer::new(font_data, export_scale.unwrap_or(16.0))?;
+        let (raster_w, raster_h) = rasterizer.target_dimensions(grid_w, grid_h);
+        let mut raster_fb = FrameBuffer::new(raster_w, raster_h);
+
+        let spec = ProfileSpec::of(profile);
+        if matches!(config.bg_style, BgStyle::Transparent) && !spec.alpha {
+            log::warn!(
+                "Fond transparent ignoré : le profil {} n'a pas de canal alpha (utiliser prores, vp9 ou png-sequence)",
+                profile.name()
+            );
+        }
+
+        // Séquence PNG : écrite directement, pas de muxage audio
+        let temp_video = if profile == ExportProfile::PngSequence {
+            output.to_path_buf()
+        } else {
+            let ext = output.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+            output.with_extension(format!("temp.{ext}"))
+        };
+        let mut muxer = Mp4Muxer::with_profile(&temp_video, raster_w, raster_h, rate, profile)?;
+
+        let mut pipe = spawn_ffmpeg_pipe(input, info.width, info.height, 0.0, rate)
+            .with_context(|| format!("Impossible de décoder {}", input.display()))?;
+        let mut stdout = pipe.stdout.take().context("ffmpeg stdout non disponible")?;
+        let mut src_frame = FrameBuffer::new(info.width, info.height);
+
+        let mut frame_config = config.clone();
+        let mut compositor = Compositor::new(&frame_config.charset);
+        let mut resizer = af_source::resize::Resizer::new();
+        let mut transformed_source = FrameBuffer::new(info.width, info.height);
+        let mut resized_source = FrameBuffer::new(target_w, target_h);
+        let mut effects = PostEffects::new(grid_w, grid_h);
+        let mut onset_envelope: f32 = 0.0;
+
+        let render_start = std::time::Instant::now();
+        let mut frame_idx = 0usize;
+
+        while read_exact_or_eof(&mut stdout, &mut src_frame.data)? {
+            let timestamp_secs = rate.timestamp(frame_idx as u64);
+            let features = mapper.as_ref().map_or_else(AudioFeatures::default, |m| {
+                m.get_timeline().get_at_time(timestamp_secs)
+            });
+
+            // Onset envelope BEFORE mapping — synchronous with current frame
+            if features.onset {
+                onset_envelope = 1.0;
+            } else {
+                onset_envelope *= frame_config.strobe_decay;
+            }
+            if let Some(ref mut m) = mapper {
+                m.apply_at(timestamp_secs, onset_envelope, &mut frame_config);
+            } else if let Some(a) = automation {
+                frame_config.clone_from(&config);
+                a.apply(&mut frame_config, timestamp_secs);
+            }
+
+            af_render::camera::VirtualCamera::apply_transform(
+                &frame_config,
+                &src_frame,
+                &mut transformed_source,
+            );
+            resizer.resize_into(&transformed_source, &mut resized_source)?;
+
+            compositor.update_if_needed(&frame_config.charset);
+            compositor.process(&resized_source, Some(&features), &frame_config, &mut grid);
+            effects.apply(&mut grid, &frame_config, &features, onset_envelope, fps);
+
+            raster_fb.data.fill(0);
+            rasterizer.set_transparent_bg(
+                spec.alpha && matches!(frame_config.bg_style, BgStyle::Transparent),
+            );
+            rasterizer.render(&grid, &mut raster_fb, frame_config.zalgo_intensity);
+            muxer
+                .write_frame(&raster_fb)
+                .with_context(|| format!("Frame write failed at frame {frame_idx}"))?;
+
+            frame_idx += 1;
+            if frame_idx.is_multiple_of(100) {
+                let fps_actual = frame_idx as f64 / render_start.elapsed().as_secs_f64();
+                match expected_frames {
+                    Some(total) if total > frame_idx => log::info!(
+                        "Progress: {frame_idx}/{total} ({:.1}%) — {fps_actual:.1} fps — ETA {:.0}s",
+                        frame_idx as f64 / total as f64 * 100.0,
+                        (total - frame_idx) as f64 / fps_actual,
+                    ),
+                    _ => log::info!("Progress: {frame_idx} frames — {fps_actual:.1} fps"),
+                }
+            }
+        }
+        let _ = pipe.wait();
+        anyhow::ensure!(
+            frame_idx > 0,
+            "Aucune frame décodée depuis {}",
+            input.display()
+        );
+
+        muxer.finish()?;
+
+        // === Étape 4 : Muxage de la piste audio source ===
+        if profile == ExportProfile::PngSequence {
+            log::info!("Étape 4/4 : séquence d'images, pas de piste audio");
+        } else if mapper.is_some() {
+            log::info!("Étape 4/4 : Muxing de l'audio source");
+            let mux_result = mux_audio_video(&temp_video, input, output, profile);
+            let _ = std::fs::remove_file(&temp_video); // always clean up temp
+            mux_result?;
+        } else {
+            log::info!("Étape 4/4 : vidéo sans audio");
+            std::fs::rename(&temp_video, output)
+                .with_context(|| format!("Impossible d'écrire {}", output.display()))?;
+        }
+
+        log::info!(
+            "Transcodage réussi : {frame_idx} frames vers {}",
+            output.display()
+        );
+        Ok(())
+    }
+}
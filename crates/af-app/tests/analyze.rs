@@ -0,0 +1,106 @@
+//! Integration test: `classcii analyze`.
+//! Verifies: samples → BatchAnalyzer → build_table → CSV / JSON / NPY on disk + terminal summary.
+#![allow(clippy::expect_used)]
+
+use af_app::analyze::{DEFAULT_PLOT_WIDTH, build_table, summary, with_frames};
+use af_audio::batch_analyzer::BatchAnalyzer;
+use af_core::config::{AnalysisBand, AnalysisConfig};
+use af_export::timeline::{TimelineFormat, write_timeline};
+
+const SR: u32 = 44100;
+
+/// 110 Hz tone, plus a 60 Hz burst every half second (120 BPM), 8 s.
+fn pulse_track() -> Vec<f32> {
+    let beat = SR as usize / 2;
+    (0..SR as usize * 8)
+        .map(|i| {
+            let t = i as f32 / SR as f32;
+            let tone = 0.2 * (std::f32::consts::TAU * 110.0 * t).sin();
+            let decay = (-((i % beat) as f32) / 2000.0).exp();
+            tone + 0.7 * decay * (std::f32::consts::TAU * 60.0 * t).sin()
+        })
+        .collect()
+}
+
+fn analysis() -> AnalysisConfig {
+    AnalysisConfig {
+        bands: vec![AnalysisBand {
+            name: "kick_body".into(),
+            low_hz: 40.0,
+            high_hz: 90.0,
+        }],
+        ..AnalysisConfig::default()
+    }
+}
+
+#[test]
+fn table_exports_named_columns_per_format() {
+    let analysis = analysis();
+    let timeline = BatchAnalyzer::with_analysis(30, SR, &analysis).analyze_all(&pulse_track());
+    let table = build_table(&timeline, None, &analysis);
+
+    assert_eq!(table.rows.len(), timeline.frames.len());
+    let kick = table.column("kick_body").expect("custom band column");
+    assert!(table.rows.iter().any(|r| r[kick] > 0.5));
+
+    let dir = std::env::temp_dir().join(format!("classcii_analyze_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("tmp dir");
+    for format in TimelineFormat::ALL {
+        let path = dir.join(format!("timeline.{}", format.extension()));
+        assert_eq!(write_timeline(&table, &path).expect("write"), format);
+        assert_eq!(
+            std::fs::read(&path).expect("read back"),
+            table.render(format)
+        );
+    }
+    let csv = std::fs::read_to_string(dir.join("timeline.csv")).expect("read back");
+    let header = csv.lines().next().expect("header");
+    assert!(header.starts_with("time,frame,energy_level,section,section_label,rms,"));
+    assert!(write_timeline(&table, &dir.join("timeline.bin")).is_err());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn summary_reports_tempo_and_plots() {
+    let analysis = AnalysisConfig::default();
+    let timeline = BatchAnalyzer::with_analysis(30, SR, &analysis).analyze_all(&pulse_track());
+    let table = build_table(&timeline, None, &analysis);
+    let text = summary(&timeline, &table, DEFAULT_PLOT_WIDTH);
+
+    assert!(text.starts_with("0:08.0 · 240 frames"), "{text}");
+    assert!(text.contains("BPM"), "{text}");
+    assert!(text.contains("LUFS"), "{text}");
+    let rms = text
+        .lines()
+        .find(|l| l.trim_start().starts_with("rms "))
+        .expect("rms plot");
+    assert!(rms.chars().filter(|c| ('▁'..='█').contains(c)).count() == DEFAULT_PLOT_WIDTH);
+}
+
+#[test]
+fn raw_table_keeps_unnormalized_levels() {
+    let analysis = AnalysisConfig::default();
+    let mut analyzer = BatchAnalyzer::with_analysis(30, SR, &analysis);
+    let raw = analyzer.analyze_frames(&pulse_track());
+    let timeline = analyzer.timeline_from_frames(raw.clone());
+    let normalized = build_table(&timeline, None, &analysis);
+    let table = build_table(&with_frames(&timeline, raw), None, &analysis);
+
+    let max = |table: &af_export::timeline::FeatureTable, name: &str| {
+        let col = table.column(name).expect("column");
+        table.rows.iter().map(|r| r[col]).fold(f32::MIN, f32::max)
+    };
+    // Normalization stretches rms to [0, 1]; the raw peak of a 0.9 signal stays below
+    assert!((max(&normalized, "rms") - 1.0).abs() < 1e-3);
+    assert!(max(&table, "rms") < 0.9, "{}", max(&table, "rms"));
+    // Structure columns come from the normalized timeline in both tables
+    let section = table.column("section").expect("section column");
+    assert!(
+        table
+            .rows
+            .iter()
+            .zip(&normalized.rows)
+            .all(|(a, b)| a[section].to_bits() == b[section].to_bits())
+    );
+}
@@ -0,0 +1,215 @@
+//! Integration test: HTTP/WebSocket control API on a loopback port.
+//! Verifies: config GET/PUT through the shared `ArcSwap`, preset commands
+//! answered by the render loop, and the `/api/audio` feature stream.
+#![allow(clippy::expect_used)]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use af_app::http::{HttpCommand, HttpServer};
+use af_core::config::RenderConfig;
+use af_core::frame::AudioFeatures;
+use arc_swap::ArcSwap;
+
+/// Minimal HTTP/1.1 client: returns (status, body).
+fn request(addr: SocketAddr, method: &str, path: &str, body: &str) -> (u16, String) {
+    request_with(addr, method, path, body, "Host: localhost\r\n")
+}
+
+/// Same as [`request`] with explicit `Host`/`Origin` header lines.
+fn request_with(
+    addr: SocketAddr,
+    method: &str,
+    path: &str,
+    body: &str,
+    headers: &str,
+) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).expect("connect");
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .expect("timeout");
+    write!(
+        stream,
+        "{method} {path} HTTP/1.1\r\n{headers}Connection: close\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )
+    .expect("write");
+    let mut response = String::new();
+    stream.read_to_string(&mut response).expect("read");
+    let status = response
+        .split_whitespace()
+        .nth(1)
+        .and_then(|s| s.parse().ok())
+        .expect("status");
+    let body = response
+        .split_once("\r\n\r\n")
+        .map(|(_, b)| b.to_string())
+        .unwrap_or_default();
+    (status, body)
+}
+
+fn spawn() -> (HttpServer, Arc<ArcSwap<RenderConfig>>) {
+    let config = Arc::new(ArcSwap::from_pointee(RenderConfig::default()));
+    let server = HttpServer::spawn(
+        "127.0.0.1:0",
+        Arc::clone(&config),
+        Path::new("/nonexistent/classcii-workflows"),
+    )
+    .expect("bind");
+    (server, config)
+}
+
+#[test]
+fn config_roundtrip_through_arc_swap() {
+    let (server, config) = spawn();
+    let addr = server.local_addr();
+
+    let (status, body) = request(addr, "GET", "/api/config", "");
+    assert_eq!(status, 200);
+    let mut json: serde_json::Value = serde_json::from_str(&body).expect("json");
+    assert!(json["contrast"].is_number());
+
+    json["contrast"] = serde_json::json!(2.0);
+    json["glow_intensity"] = serde_json::json!(99.0); // clamped to 2.0
+    let (status, _) = request(addr, "PUT", "/api/config", &json.to_string());
+    assert_eq!(status, 200);
+    let stored = config.load();
+    assert!((stored.contrast - 2.0).abs() < f32::EPSILON);
+    assert!((stored.glow_intensity - 2.0).abs() < f32::EPSILON);
+    assert!(matches!(
+        server.drain().next(),
+        Some(HttpCommand::ConfigReplaced)
+    ));
+
+    let (status, _) = request(addr, "PUT", "/api/config", "{ not json");
+    assert_eq!(status, 400);
+    let (status, body) = request(addr, "GET", "/api/workflows", "");
+    assert_eq!((status, body.as_str()), (200, "[]"));
+}
+
+#[test]
+fn foreign_origin_is_forbidden() {
+    let (server, _config) = spawn();
+    let addr = server.local_addr();
+    let headers = "Host: localhost\r\nOrigin: http://evil.example\r\n";
+    let (status, _) = request_with(addr, "PUT", "/api/config", "{}", headers);
+    assert_eq!(status, 403);
+    assert!(server.drain().next().is_none());
+
+    let headers = "Host: localhost\r\nOrigin: http://localhost:3000\r\n";
+    let (status, _) = request_with(addr, "GET", "/api/config", "", headers);
+    assert_eq!(status, 200);
+}
+
+#[test]
+fn foreign_host_is_forbidden() {
+    let (server, _config) = spawn();
+    let addr = server.local_addr();
+    // DNS rebinding: the browser sends the attacker's name as Host.
+    let (status, _) = request_with(
+        addr,
+        "GET",
+        "/api/config",
+        "",
+        "Host: rebind.evil.example\r\n",
+    );
+    assert_eq!(status, 403);
+
+    let stream = TcpStream::connect(addr).expect("connect");
+    let request = tungstenite::http::Request::builder()
+        .uri(format!("ws://{addr}/api/audio"))
+        .header("Host", "rebind.evil.example")
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header(
+            "Sec-WebSocket-Key",
+            tungstenite::handshake::client::generate_key(),
+        )
+        .body(())
+        .expect("request");
+    assert!(tungstenite::client(request, stream).is_err());
+}
+
+#[test]
+fn workflow_names_are_percent_decoded() {
+    let (server, _config) = spawn();
+    let addr = server.local_addr();
+    let client =
+        std::thread::spawn(move || request(addr, "POST", "/api/workflows/my%20flow/load", ""));
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut received = None;
+    while received.is_none() && Instant::now() < deadline {
+        for cmd in server.drain() {
+            if let HttpCommand::LoadWorkflow(name, reply) = cmd {
+                reply.send(Ok(())).expect("reply");
+                received = Some(name);
+            }
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(received.as_deref(), Some("my flow"));
+    assert_eq!(client.join().expect("client").0, 200);
+    assert_eq!(
+        request(addr, "POST", "/api/workflows/bad%2/load", "").0,
+        400
+    );
+}
+
+#[test]
+fn preset_load_waits_for_render_loop() {
+    let (server, _config) = spawn();
+    let addr = server.local_addr();
+    let client = std::thread::spawn(move || request(addr, "POST", "/api/presets/neon", ""));
+
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let mut answered = false;
+    while !answered && Instant::now() < deadline {
+        for cmd in server.drain() {
+            if let HttpCommand::LoadPreset(name, reply) = cmd {
+                assert_eq!(name, "neon");
+                reply
+                    .send(Err("Preset introuvable : neon".into()))
+                    .expect("reply");
+                answered = true;
+            }
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert!(answered, "no LoadPreset command received");
+    let (status, body) = client.join().expect("client");
+    assert_eq!(status, 404);
+    assert!(body.contains("introuvable"));
+}
+
+#[test]
+fn websocket_streams_features() {
+    let (server, _config) = spawn();
+    let stream = TcpStream::connect(server.local_addr()).expect("connect");
+    stream
+        .set_read_timeout(Some(Duration::from_millis(200)))
+        .expect("timeout");
+    let url = format!("ws://{}/api/audio", server.local_addr());
+    let (mut ws, _) = tungstenite::client(url.as_str(), stream).expect("handshake");
+
+    let features = AudioFeatures {
+        bass: 0.5,
+        ..AudioFeatures::default()
+    };
+    // The subscriber is registered by the listener thread after the handshake.
+    let deadline = Instant::now() + Duration::from_secs(2);
+    let msg = loop {
+        server.publish_features(&features, 0.25);
+        if let Ok(tungstenite::Message::Text(text)) = ws.read() {
+            break text;
+        }
+        assert!(Instant::now() < deadline, "no feature frame received");
+    };
+    let json: serde_json::Value = serde_json::from_str(&msg).expect("json");
+    assert_eq!(json["bass"], 0.5);
+    assert_eq!(json["onset_envelope"], 0.25);
+}
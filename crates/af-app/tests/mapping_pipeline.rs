@@ -22,7 +22,7 @@ fn default_mappings_produce_visible_deltas() {
     features.spectral_centroid = 0.4;
 
     let mut smooth = vec![];
-    apply_audio_mappings(&mut config, &features, None, 0.7, &mut smooth, 60);
+    apply_audio_mappings(&mut config, &features, None, None, 0.7, &mut smooth, 60);
 
     // bass → edge_threshold (Smooth curve, amount=0.7, sensitivity=2.0)
     assert!(
@@ -69,7 +69,7 @@ fn zero_features_produce_zero_deltas() {
     let original_contrast = config.contrast;
     let original_brightness = config.brightness;
 
-    apply_audio_mappings(&mut config, &features, None, 0.0, &mut smooth, 60);
+    apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
 
     assert!(
         (config.contrast - original_contrast).abs() < 0.01,
@@ -81,6 +81,36 @@ fn zero_features_produce_zero_deltas() {
     );
 }
 
+#[test]
+fn paused_file_maps_loudness_to_zero() {
+    use af_core::config::{AudioMapping, MappingCurve};
+
+    let mut config = RenderConfig::default();
+    config.audio_mappings = vec![AudioMapping {
+        enabled: true,
+        source: "loudness_lufs".into(),
+        target: "contrast".into(),
+        amount: 1.0,
+        offset: 0.0,
+        curve: MappingCurve::Linear,
+        smoothing: None,
+        stem_source: None,
+        channel_source: None,
+    }];
+    let original_contrast = config.contrast;
+    // Paused file playback publishes `AudioFeatures::default()` every frame
+    let features = AudioFeatures::default();
+    let mut smooth = vec![];
+
+    apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
+
+    assert!(
+        (config.contrast - original_contrast).abs() < 0.01,
+        "silence should not drive loudness mappings, got contrast {}",
+        config.contrast
+    );
+}
+
 #[test]
 fn disabled_mapping_has_no_effect() {
     let mut config = RenderConfig::default();
@@ -94,10 +124,81 @@ fn disabled_mapping_has_no_effect() {
     features.rms = 1.0;
     let mut smooth = vec![];
 
-    apply_audio_mappings(&mut config, &features, None, 1.0, &mut smooth, 60);
+    apply_audio_mappings(&mut config, &features, None, None, 1.0, &mut smooth, 60);
 
     assert!(
         config.edge_threshold < 0.01,
         "disabled mappings should not affect edge_threshold"
     );
 }
+
+#[test]
+fn generator_targets_feed_procedural_params() {
+    use af_core::config::{AudioMapping, MappingCurve};
+    use af_source::procedural::GeneratorParams;
+
+    let mut config = RenderConfig::default();
+    config.audio_mappings = ["generator_speed", "generator_scale", "generator_complexity"]
+        .iter()
+        .map(|target| AudioMapping {
+            enabled: true,
+            source: "bass".into(),
+            target: (*target).into(),
+            amount: 0.2,
+            offset: 0.0,
+            curve: MappingCurve::Linear,
+            smoothing: None,
+            stem_source: None,
+            channel_source: None,
+        })
+        .collect();
+    let mut features = AudioFeatures::default();
+    features.bass = 1.0;
+
+    let mut smooth = vec![];
+    apply_audio_mappings(&mut config, &features, None, None, 0.0, &mut smooth, 60);
+
+    let params = GeneratorParams::from_config(&config);
+    assert!(
+        params.speed > 1.0,
+        "speed should rise, got {}",
+        params.speed
+    );
+    assert!(
+        params.scale > 1.0,
+        "scale should rise, got {}",
+        params.scale
+    );
+    assert!(
+        params.complexity > 0.5,
+        "complexity should rise, got {}",
+        params.complexity
+    );
+}
+
+#[test]
+fn midi_mappings_apply_after_first_message() {
+    use af_app::midi::{MidiEvent, MidiState};
+    use af_app::pipeline::apply_midi_mappings;
+    use af_core::config::load_config_from_str;
+
+    let base = load_config_from_str(
+        "[render]\n[[midi.mappings]]\ncc = 21\nchannel = 1\ntarget = \"glow_intensity\"\n",
+    )
+    .expect("midi section");
+    assert_eq!(base.midi_mappings.len(), 1);
+
+    let mut state = MidiState::default();
+    let mut config = base.clone();
+    apply_midi_mappings(&mut config, &state);
+    assert_eq!(config.glow_intensity, base.glow_intensity);
+
+    state.update(&MidiEvent::parse(&[0xB0, 21, 127]).expect("cc"));
+    let mut config = base.clone();
+    apply_midi_mappings(&mut config, &state);
+    assert!(
+        config.glow_intensity > base.glow_intensity + 0.5,
+        "CC21 at max should raise glow, got {}",
+        config.glow_intensity
+    );
+}
@@ -0,0 +1,80 @@
+//! Integration test: OSC server over a local UDP socket.
+//! Verifies: client datagram → decoded `OscCommand`, and audio feature feedback.
+#![allow(clippy::expect_used)]
+
+use std::net::UdpSocket;
+use std::time::{Duration, Instant};
+
+use af_app::osc::{OscArg, OscCommand, OscMessage, OscServer, decode_packet, encode_message};
+use af_core::frame::AudioFeatures;
+
+fn recv_command(server: &OscServer) -> Option<OscCommand> {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while Instant::now() < deadline {
+        if let Some(cmd) = server.drain().next() {
+            return Some(cmd);
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    None
+}
+
+#[test]
+fn udp_client_drives_commands() {
+    let server = OscServer::spawn("127.0.0.1:0", None).expect("bind");
+    let client = UdpSocket::bind("127.0.0.1:0").expect("client");
+
+    let msg = OscMessage {
+        addr: "/classcii/render/contrast".into(),
+        args: vec![OscArg::Float(2.5)],
+    };
+    client
+        .send_to(&encode_message(&msg), server.local_addr())
+        .expect("send");
+    assert_eq!(
+        recv_command(&server),
+        Some(OscCommand::SetParam {
+            target: "contrast".into(),
+            value: 2.5
+        })
+    );
+
+    let next = OscMessage {
+        addr: "/classcii/preset/next".into(),
+        args: vec![],
+    };
+    client
+        .send_to(&encode_message(&next), server.local_addr())
+        .expect("send");
+    assert_eq!(recv_command(&server), Some(OscCommand::PresetNext));
+}
+
+#[test]
+fn feedback_sends_feature_bundle() {
+    let listener = UdpSocket::bind("127.0.0.1:0").expect("listener");
+    listener
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .expect("timeout");
+    let target = listener.local_addr().expect("addr").to_string();
+    let mut server = OscServer::spawn("127.0.0.1:0", Some(&target)).expect("bind");
+
+    let features = AudioFeatures {
+        rms: 0.5,
+        onset: true,
+        ..AudioFeatures::default()
+    };
+    server.send_features(&features, 0.75);
+
+    let mut buf = [0u8; 4096];
+    let (len, _) = listener.recv_from(&mut buf).expect("feedback");
+    let msgs = decode_packet(&buf[..len]).expect("decode");
+    let value = |name: &str| {
+        msgs.iter()
+            .find(|m| m.addr == format!("/classcii/audio/{name}"))
+            .and_then(|m| m.args.first())
+            .and_then(OscArg::as_f32)
+    };
+    assert_eq!(value("rms"), Some(0.5));
+    assert_eq!(value("onset"), Some(1.0));
+    assert_eq!(value("onset_envelope"), Some(0.75));
+}
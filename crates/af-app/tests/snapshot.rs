@@ -0,0 +1,50 @@
+//! Integration test: headless snapshot (`--snapshot`).
+//! Verifies: FrameBuffer + RenderConfig → render_frame_grid → text export on disk.
+#![allow(clippy::expect_used)]
+
+use af_app::snapshot::render_frame_grid;
+use af_core::config::{RenderConfig, RenderMode};
+use af_core::frame::FrameBuffer;
+use af_export::text::{TextFormat, write_snapshot};
+
+/// Horizontal gradient, 64×32.
+fn gradient() -> FrameBuffer {
+    let mut fb = FrameBuffer::new(64, 32);
+    for (i, px) in fb.data.chunks_exact_mut(4).enumerate() {
+        let x = (i % 64) as u8;
+        px.copy_from_slice(&[x * 4, x * 4, x * 4, 255]);
+    }
+    fb
+}
+
+#[test]
+fn grid_follows_requested_width_and_image_aspect() {
+    let config = RenderConfig {
+        render_mode: RenderMode::Ascii,
+        ..RenderConfig::default()
+    };
+    let grid = render_frame_grid(&gradient(), &config, 40).expect("render");
+    assert_eq!(grid.width, 40);
+    // 40 cols × (32/64) / aspect 2.0 = 10 rows
+    assert_eq!(grid.height, 10);
+    // Gradient: left edge dark, right edge dense
+    assert_ne!(grid.get(0, 5).ch, grid.get(39, 5).ch);
+}
+
+#[test]
+fn snapshot_files_written_per_extension() {
+    let grid = render_frame_grid(&gradient(), &RenderConfig::default(), 20).expect("render");
+    let dir = std::env::temp_dir().join(format!("classcii_snapshot_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("tmp dir");
+
+    for format in TextFormat::ALL {
+        let path = dir.join(format!("frame.{}", format.extension()));
+        assert_eq!(write_snapshot(&grid, &path).expect("write"), format);
+        let text = std::fs::read_to_string(&path).expect("read back");
+        assert_eq!(text, format.render(&grid));
+    }
+    let plain = std::fs::read_to_string(dir.join("frame.txt")).expect("read back");
+    assert_eq!(plain.lines().count(), usize::from(grid.height));
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
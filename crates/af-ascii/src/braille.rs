@@ -110,11 +110,7 @@ pub fn process_braille(frame: &FrameBuffer, config: &RenderConfig, grid: &mut As
             }
 
             // Passe 2 : seuil adaptatif (moyenne locale)
-            let local_threshold = if count > 0 {
-                (lum_sum / count) as u8
-            } else {
-                128
-            };
+            let local_threshold = lum_sum.checked_div(count).map_or(128, |v| v as u8);
             let mut dots = [false; 8];
             for i in 0..sub_idx {
                 let on = if config.invert {
@@ -126,15 +122,8 @@ pub fn process_braille(frame: &FrameBuffer, config: &RenderConfig, grid: &mut As
             }
 
             let ch = encode_braille(dots);
-            let fg = if count > 0 {
-                (
-                    (avg_r / count) as u8,
-                    (avg_g / count) as u8,
-                    (avg_b / count) as u8,
-                )
-            } else {
-                (255, 255, 255)
-            };
+            let avg = |sum: u32| sum.checked_div(count).map_or(255, |v| v as u8);
+            let fg = (avg(avg_r), avg(avg_g), avg(avg_b));
 
             *cell = AsciiCell {
                 ch,
@@ -1,22 +1,33 @@
-use af_core::color::{apply_hsv_bright, apply_oklab_bright};
+use af_core::color::{apply_hsv_bright, apply_key_hue, apply_oklab_bright};
 use af_core::config::ColorMode;
 
 /// Map a pixel color according to the selected color mode.
 ///
+/// `key_hue` is the musical key hue used by [`ColorMode::KeyHue`]
+/// (`RenderConfig::key_hue`); other modes ignore it.
+///
 /// # Example
 /// ```
 /// use af_ascii::color_map::map_color;
 /// use af_core::config::ColorMode;
-/// let (r, g, b) = map_color(200, 50, 50, &ColorMode::Direct, 1.0);
+/// let (r, g, b) = map_color(200, 50, 50, &ColorMode::Direct, 1.0, 0.0);
 /// assert_eq!((r, g, b), (200, 50, 50));
 /// ```
 #[must_use]
-pub fn map_color(r: u8, g: u8, b: u8, mode: &ColorMode, saturation: f32) -> (u8, u8, u8) {
+pub fn map_color(
+    r: u8,
+    g: u8,
+    b: u8,
+    mode: &ColorMode,
+    saturation: f32,
+    key_hue: f32,
+) -> (u8, u8, u8) {
     match mode {
         ColorMode::Direct => (r, g, b),
         ColorMode::HsvBright => apply_hsv_bright(r, g, b, saturation),
         ColorMode::Quantized => quantize(r, g, b),
         ColorMode::Oklab => apply_oklab_bright(r, g, b, saturation),
+        ColorMode::KeyHue => apply_key_hue(r, g, b, key_hue, saturation),
     }
 }
 
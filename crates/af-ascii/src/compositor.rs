@@ -86,6 +86,7 @@ impl Compositor {
                     cell.fg.2,
                     &config.color_mode,
                     config.saturation,
+                    config.key_hue,
                 );
                 cell.fg = (mr, mg, mb);
             }
@@ -163,8 +164,14 @@ impl Compositor {
                     };
 
                     if config.color_enabled {
-                        let (mr, mg, mb) =
-                            color_map::map_color(r, g, b, &config.color_mode, config.saturation);
+                        let (mr, mg, mb) = color_map::map_color(
+                            r,
+                            g,
+                            b,
+                            &config.color_mode,
+                            config.saturation,
+                            config.key_hue,
+                        );
                         cell.fg = (mr, mg, mb);
                     } else {
                         cell.fg = (r, g, b);
@@ -32,7 +32,11 @@ fn bench_audio(c: &mut Criterion) {
         let spectrum = fft.process(&samples);
         group.bench_function("extract_features", |b| {
             b.iter(|| {
-                extract_features(black_box(&samples), black_box(&spectrum), 44100);
+                black_box(extract_features(
+                    black_box(&samples),
+                    black_box(&spectrum),
+                    44100,
+                ));
             });
         });
     }
@@ -1,16 +1,17 @@
-use std::collections::VecDeque;
-
-use crate::features::extract_features;
-use crate::fft::FftPipeline;
-use crate::mfcc::MelFilterbank;
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::loudness::LoudnessMeter;
+use crate::segmentation::segment;
+use af_core::config::AnalysisConfig;
 use af_core::feature_timeline::FeatureTimeline;
+use af_core::frame::AudioFeatures;
 
 /// Analyseur audio pour le traitement offline en lot (Batch Export).
 ///
 /// Divise un vecteur d'échantillons en frames correspondant au framerate cible,
 /// et extrait les `AudioFeatures` pour générer une `FeatureTimeline`.
+/// L'extraction par fenêtre est celle de l'analyse live ([`FrameAnalyzer`]).
 pub struct BatchAnalyzer {
-    fft: FftPipeline,
+    analysis: AnalysisConfig,
     target_fps: u32,
     sample_rate: u32,
 }
@@ -19,7 +20,7 @@ impl BatchAnalyzer {
     /// Crée un nouvel analyseur batch.
     ///
     /// # Panics
-    /// Panics if `fft_size` is 0.
+    /// `analyze_all` panics if `fft_size` is 0.
     ///
     /// # Example
     /// ```
@@ -29,7 +30,32 @@ impl BatchAnalyzer {
     #[must_use]
     pub fn new(target_fps: u32, sample_rate: u32, fft_size: usize) -> Self {
         Self {
-            fft: FftPipeline::new(fft_size),
+            analysis: AnalysisConfig {
+                fft_size,
+                ..AnalysisConfig::default()
+            },
+            target_fps,
+            sample_rate,
+        }
+    }
+
+    /// Crée un analyseur batch avec la taille de FFT, la fenêtre et les bandes
+    /// de `[audio.analysis]`, comme le thread audio live.
+    ///
+    /// Une fenêtre est analysée par frame rendue : le pas est celui du
+    /// framerate cible, `overlap` ne concerne que la capture live.
+    ///
+    /// # Example
+    /// ```
+    /// use af_audio::batch_analyzer::BatchAnalyzer;
+    /// use af_core::config::AnalysisConfig;
+    /// let mut analyzer = BatchAnalyzer::with_analysis(60, 44100, &AnalysisConfig::default());
+    /// assert_eq!(analyzer.analyze_all(&vec![0.0; 44100]).frames.len(), 60);
+    /// ```
+    #[must_use]
+    pub fn with_analysis(target_fps: u32, sample_rate: u32, analysis: &AnalysisConfig) -> Self {
+        Self {
+            analysis: analysis.clone(),
             target_fps,
             sample_rate,
         }
@@ -47,152 +73,81 @@ impl BatchAnalyzer {
     /// ```
     #[must_use]
     pub fn analyze_all(&mut self, samples: &[f32]) -> FeatureTimeline {
-        let frame_duration = 1.0 / self.target_fps.max(1) as f32;
-        let samples_per_frame = (self.sample_rate as f32 * frame_duration) as usize;
-
-        // Zero division protection
-        if samples_per_frame == 0 {
-            return FeatureTimeline {
-                frames: Vec::new(),
-                frame_duration,
-                sample_rate: self.sample_rate,
-                energy_levels: Vec::new(),
-            };
-        }
-
-        let num_frames = samples.len().div_ceil(samples_per_frame);
-        let mut frames = Vec::with_capacity(num_frames);
-
-        let mut prev_magnitudes: Vec<f32> = Vec::new();
-        let mut filterbank = MelFilterbank::new(self.fft.fft_size(), self.sample_rate);
-
-        for i in 0..num_frames {
-            let start = i * samples_per_frame;
-            let end = (start + self.fft.fft_size()).min(samples.len());
-
-            let frame_samples = if start < samples.len() {
-                &samples[start..end]
-            } else {
-                &[]
-            };
-
-            let magnitudes = self.fft.process(frame_samples);
-            let mut features = extract_features(frame_samples, magnitudes, self.sample_rate);
-
-            // Bass-weighted spectral flux (parity with BeatDetector in beat.rs)
-            // Normalized by bin count for volume-independent beat detection.
-            let mut flux = 0.0f32;
-            if prev_magnitudes.len() == magnitudes.len() {
-                let bass_cutoff = magnitudes.len() / 4;
-                for (j, (curr, prev)) in magnitudes.iter().zip(prev_magnitudes.iter()).enumerate() {
-                    let diff = (curr - prev).max(0.0);
-                    flux += if j < bass_cutoff { diff * 2.0 } else { diff };
-                }
-                flux /= magnitudes.len().max(1) as f32;
-            } else {
-                prev_magnitudes.resize(magnitudes.len(), 0.0);
-            }
-            features.spectral_flux = (flux * 5.0).sqrt().min(1.0);
-            prev_magnitudes.copy_from_slice(magnitudes);
-
-            // MFCC timbral features (parity with live mode)
-            let mfcc = filterbank.compute(magnitudes);
-            features.mfcc = mfcc;
-            features.timbral_brightness = (mfcc[1] / 50.0 + 0.5).clamp(0.0, 1.0);
-            features.timbral_roughness = (mfcc[2].abs() / 30.0).clamp(0.0, 1.0);
-
-            frames.push(features);
-        }
-
-        // Post-processing: onset detection with BPM/beat_phase (parity with BeatDetector)
-        Self::detect_onsets(&mut frames, self.target_fps as f32);
+        let frames = self.analyze_frames(samples);
+        self.timeline_from_frames(frames)
+    }
 
+    /// Timeline du rendu batch à partir des features brutes de
+    /// [`Self::analyze_frames`] : normalisation, niveaux d'énergie, sections.
+    #[must_use]
+    pub fn timeline_from_frames(&self, frames: Vec<AudioFeatures>) -> FeatureTimeline {
         let mut timeline = FeatureTimeline {
             frames,
-            frame_duration,
+            frame_duration: 1.0 / self.target_fps.max(1) as f32,
             sample_rate: self.sample_rate,
             energy_levels: Vec::new(),
+            sections: Vec::new(),
         };
+        if timeline.frames.is_empty() {
+            return timeline;
+        }
 
         // Normalize features to [0, 1] across the entire track
         timeline.normalize();
         // Compute energy classification for clip pacing
         timeline.compute_energy_levels();
+        // Structural sections for preset / clip changes
+        timeline.sections = segment(&timeline);
 
         timeline
     }
 
-    /// Offline onset detection replicating the interactive BeatDetector logic.
+    /// Features brutes de chaque frame, avant normalisation : la frame `i`
+    /// analyse la fenêtre FFT commençant à `i / fps` secondes, comme une
+    /// capture live dont le pas vaudrait une frame (sans lissage).
     ///
-    /// Features: warmup skip, FPS-adaptive cooldown, BPM estimation from
-    /// inter-onset intervals, beat_phase accumulator.
-    fn detect_onsets(frames: &mut [af_core::frame::AudioFeatures], fps: f32) {
-        let cooldown = (fps * 0.13).max(2.0) as usize;
-        let mut ema_flux = 0.0f32;
-        let mut last_onset: usize = 0;
-        let mut intervals: VecDeque<usize> = VecDeque::with_capacity(16);
-        let mut bpm: f32 = 0.0;
-        let mut phase: f32 = 0.0;
-        let mut onset_env: f32 = 0.0;
-        let strobe_decay: f32 = 0.85;
-
-        for (i, frame) in frames.iter_mut().enumerate() {
-            let flux = frame.spectral_flux;
-
-            // Adaptive threshold (parity with beat.rs EMA alpha=0.07)
-            ema_flux = ema_flux * 0.93 + flux * 0.07;
-            let threshold = ema_flux * 1.5 + 0.01;
-
-            // Warmup: skip first ~10 frames to avoid false positives
-            // Silence guard: spectral energy threshold (parity with live BeatDetector)
-            let spectral_energy = frame.sub_bass * frame.sub_bass
-                + frame.bass * frame.bass
-                + frame.mid * frame.mid
-                + frame.brilliance * frame.brilliance;
-            let warmup_complete = i > 10;
-            let since = i.saturating_sub(last_onset);
-            let onset =
-                warmup_complete && spectral_energy > 1e-6 && flux > threshold && since > cooldown;
-
-            if onset {
-                frame.onset = true;
-                frame.beat_intensity = ((flux - threshold) / (threshold + 0.001)).clamp(0.0, 1.0);
-                last_onset = i;
-
-                // BPM estimation from onset intervals
-                if since > 5 && since < 300 {
-                    intervals.push_back(since);
-                    if intervals.len() > 16 {
-                        intervals.pop_front();
-                    }
-
-                    if intervals.len() >= 4 {
-                        let avg: f64 = intervals.iter().map(|&v| v as f64).sum::<f64>()
-                            / intervals.len() as f64;
-                        if avg > 0.0 {
-                            bpm = (60.0 * f64::from(fps) / avg) as f32;
-                            bpm = bpm.clamp(30.0, 300.0);
-                        }
-                    }
-                }
-
-                phase = 0.0;
-                onset_env = 1.0;
-            } else {
-                frame.onset = false;
-                frame.beat_intensity = 0.0;
-                onset_env *= strobe_decay;
-
-                // Phase accumulation between beats
-                if bpm > 0.0 {
-                    phase = (phase + bpm / (60.0 * fps)) % 1.0;
-                }
-            }
-
-            frame.onset_envelope = onset_env;
-            frame.bpm = bpm;
-            frame.beat_phase = phase;
+    /// # Example
+    /// ```
+    /// use af_audio::batch_analyzer::BatchAnalyzer;
+    /// let mut analyzer = BatchAnalyzer::new(30, 44100, 2048);
+    /// let frames = analyzer.analyze_frames(&vec![0.0; 44100]);
+    /// assert_eq!(frames.len(), 30);
+    /// ```
+    #[must_use]
+    pub fn analyze_frames(&mut self, samples: &[f32]) -> Vec<AudioFeatures> {
+        let fps = self.target_fps.max(1) as f32;
+        let samples_per_frame = (self.sample_rate as f32 / fps) as usize;
+
+        // Zero division protection
+        if samples_per_frame == 0 {
+            return Vec::new();
+        }
+
+        let num_frames = samples.len().div_ceil(samples_per_frame);
+        let mut frames = Vec::with_capacity(num_frames);
+
+        let mut analyzer = FrameAnalyzer::new(self.sample_rate, &self.analysis);
+        let fft_size = analyzer.fft_size();
+        let mut meter = LoudnessMeter::new(self.sample_rate);
+        let mut metered = 0;
+
+        for i in 0..num_frames {
+            let start = (i * samples_per_frame).min(samples.len());
+            let end = (start + fft_size).min(samples.len());
+            let mut features = analyzer.analyze(&samples[start..end], fps);
+
+            // Loudness up to the end of the window, as a capture meters each
+            // sample once when it arrives (without AGC: `FeatureTimeline::normalize`
+            // already evens out the levels)
+            meter
+                .process(&samples[metered.min(end)..end])
+                .write_to(&mut features);
+            metered = metered.max(end);
+
+            frames.push(features);
         }
+
+        frames
     }
 
     /// Décode un fichier audio et analyse l'intégralité de ses échantillons.
@@ -205,6 +160,38 @@ impl BatchAnalyzer {
         Ok(self.analyze_all(&samples))
     }
 
+    /// Comme [`Self::analyze_file`], sans normalisation : features brutes
+    /// de [`Self::analyze_frames`].
+    ///
+    /// # Errors
+    /// Retourne une erreur si le fichier ne peut être décodé.
+    pub fn analyze_file_frames(
+        &mut self,
+        path: &std::path::Path,
+    ) -> anyhow::Result<Vec<AudioFeatures>> {
+        let (samples, actual_sr) = crate::decode::decode_file(path)?;
+        self.sample_rate = actual_sr;
+        Ok(self.analyze_frames(&samples))
+    }
+
+    /// Features brutes des 4 stems ([drums, bass, other, vocals]), en
+    /// parallèle via rayon, sans normalisation ni segmentation.
+    #[must_use]
+    pub fn analyze_stem_frames(&self, stem_samples: &[Vec<f32>; 4]) -> [Vec<AudioFeatures>; 4] {
+        use rayon::prelude::*;
+
+        let mut frames: [Vec<AudioFeatures>; 4] = Default::default();
+        frames
+            .par_iter_mut()
+            .zip(stem_samples.par_iter())
+            .for_each(|(out, samples)| {
+                *out =
+                    BatchAnalyzer::with_analysis(self.target_fps, self.sample_rate, &self.analysis)
+                        .analyze_frames(samples);
+            });
+        frames
+    }
+
     /// Analyze 4 stems and produce a `StemFeatureTimeline`.
     ///
     /// Each stem's samples are analyzed independently, producing 4 parallel timelines.
@@ -232,15 +219,11 @@ impl BatchAnalyzer {
     ) -> anyhow::Result<af_core::feature_timeline::StemFeatureTimeline> {
         use rayon::prelude::*;
 
-        let fps = self.target_fps;
-        let sr = self.sample_rate;
-        let fft_size = self.fft.fft_size();
-
         let results: Vec<FeatureTimeline> = stem_samples
             .par_iter()
             .map(|samples| {
-                let mut analyzer = BatchAnalyzer::new(fps, sr, fft_size);
-                analyzer.analyze_all(samples)
+                BatchAnalyzer::with_analysis(self.target_fps, self.sample_rate, &self.analysis)
+                    .analyze_all(samples)
             })
             .collect();
 
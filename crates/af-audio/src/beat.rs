@@ -1,12 +1,304 @@
 use std::collections::VecDeque;
 
+use af_core::frame::{AudioFeatures, BEATS_PER_BAR};
+
 /// Gain applied to spectral flux before sqrt compression for [0,1] normalization.
 /// Compensates for per-bin normalization that yields small raw values.
 const FLUX_GAIN: f32 = 5.0;
 
-/// Simple onset / beat detection.
+/// Tempo search range, in BPM.
+const MIN_BPM: f32 = 60.0;
+const MAX_BPM: f32 = 200.0;
+/// Onset-strength history used by the tempo estimator, in seconds.
+const HISTORY_SECS: f32 = 6.0;
+/// Interval between tempo / phase re-estimations, in seconds.
+const ESTIMATE_SECS: f32 = 0.25;
+/// Tempo prior: log-Gaussian centred on `PRIOR_BPM`, `PRIOR_OCTAVES` wide.
+/// Resolves octave ambiguity (64 vs 128 BPM) towards dance tempi.
+const PRIOR_BPM: f32 = 120.0;
+const PRIOR_OCTAVES: f32 = 1.0;
+/// Fraction of the phase error corrected at each re-estimation.
+const PHASE_GAIN: f32 = 0.5;
+/// Half-life of the per-beat accent memory used to find the downbeat, in seconds.
+const ACCENT_HALF_LIFE_SECS: f32 = 8.0;
+
+/// Beat grid at the current frame, as published in [`AudioFeatures`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TempoState {
+    /// Estimated tempo (0.0 until a pulse is found).
+    pub bpm: f32,
+    /// Position within the beat [0.0, 1.0), 0.0 = on the beat.
+    pub beat_phase: f32,
+    /// Position within the bar [0.0, 1.0), 0.0 = on the downbeat.
+    pub bar_phase: f32,
+    /// Beat index within the bar, 0 = downbeat.
+    pub beat_in_bar: u8,
+    /// Periodicity of the onset signal at the chosen tempo [0.0, 1.0].
+    pub confidence: f32,
+}
+
+impl TempoState {
+    /// Copy the beat grid into `features`.
+    pub fn write_to(&self, features: &mut AudioFeatures) {
+        features.bpm = self.bpm;
+        features.beat_phase = self.beat_phase;
+        features.bar_phase = self.bar_phase;
+        features.beat_in_bar = self.beat_in_bar;
+        features.tempo_confidence = self.confidence;
+    }
+}
+
+/// Autocorrelation tempo estimator with a phase-locked beat grid.
 ///
-/// Uses spectral flux with an adaptive threshold and onset cooldown.
+/// Fed one onset-strength value per analysis frame. Every `ESTIMATE_SECS`,
+/// the autocorrelation of the last `HISTORY_SECS` is scored through a comb
+/// filter (lags k·T, k = 1..4) weighted by a tempo prior; the best period is
+/// refined by parabolic interpolation. The phase runs freely at that period
+/// and is pulled towards the offset that best aligns a pulse train with the
+/// history, so syncopated onsets between beats do not reset it.
+///
+/// Bars assume [`BEATS_PER_BAR`] beats; the downbeat is the beat slot with
+/// the strongest accumulated accents.
+///
+/// Driven by [`BeatDetector`], which live, stem and batch analysis all run
+/// through [`crate::frame_analyzer::FrameAnalyzer`].
+///
+/// # Example
+/// ```
+/// use af_audio::beat::TempoTracker;
+/// let mut tracker = TempoTracker::new();
+/// let state = tracker.process(0.0, 60.0);
+/// assert!(state.bpm.abs() < f32::EPSILON);
+/// ```
+pub struct TempoTracker {
+    fps: f32,
+    /// Onset strength per frame, oldest first.
+    history: VecDeque<f32>,
+    frames_since_estimate: usize,
+    /// Beat period in frames (0.0 = unknown).
+    period: f32,
+    /// Period found by the last estimation, adopted once confirmed.
+    candidate: f32,
+    confidence: f32,
+    phase: f32,
+    /// Beats elapsed since the grid started.
+    beat_count: u64,
+    /// Decaying accent energy per beat slot of the bar.
+    accents: [f32; BEATS_PER_BAR as usize],
+}
+
+impl TempoTracker {
+    /// Create a tracker with no tempo.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            fps: 0.0,
+            history: VecDeque::new(),
+            frames_since_estimate: 0,
+            period: 0.0,
+            candidate: 0.0,
+            confidence: 0.0,
+            phase: 0.0,
+            beat_count: 0,
+            accents: [0.0; BEATS_PER_BAR as usize],
+        }
+    }
+
+    /// Push one frame of onset strength (e.g. normalized spectral flux)
+    /// analysed at `fps` frames per second and return the beat grid.
+    ///
+    /// A change of `fps` by more than 10% restarts the tracker.
+    pub fn process(&mut self, strength: f32, fps: f32) -> TempoState {
+        if fps <= 0.0 {
+            return self.state();
+        }
+        if (fps - self.fps).abs() > self.fps * 0.1 {
+            *self = Self::new();
+            self.fps = fps;
+        }
+
+        let capacity = (HISTORY_SECS * fps) as usize;
+        self.history.push_back(if strength.is_finite() {
+            strength.max(0.0)
+        } else {
+            0.0
+        });
+        while self.history.len() > capacity {
+            self.history.pop_front();
+        }
+
+        if self.period > 0.0 {
+            self.phase += 1.0 / self.period;
+            if self.phase >= 1.0 {
+                self.phase -= 1.0;
+                self.beat_count += 1;
+            }
+            self.accumulate_accent(strength);
+        }
+
+        self.frames_since_estimate += 1;
+        let min_history = (60.0 * fps / MIN_BPM) as usize * 2;
+        if self.frames_since_estimate as f32 >= ESTIMATE_SECS * fps
+            && self.history.len() >= min_history
+        {
+            self.frames_since_estimate = 0;
+            self.estimate();
+        }
+
+        self.state()
+    }
+
+    /// Current beat grid without advancing it.
+    #[must_use]
+    pub fn state(&self) -> TempoState {
+        if self.period <= 0.0 {
+            return TempoState::default();
+        }
+        let beat_in_bar = self.beat_in_bar();
+        TempoState {
+            bpm: (60.0 * self.fps / self.period).clamp(30.0, 300.0),
+            beat_phase: self.phase,
+            bar_phase: (f32::from(beat_in_bar) + self.phase) / f32::from(BEATS_PER_BAR),
+            beat_in_bar,
+            confidence: self.confidence,
+        }
+    }
+
+    fn beat_in_bar(&self) -> u8 {
+        let bar = u64::from(BEATS_PER_BAR);
+        let downbeat = self
+            .accents
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(slot, _)| slot as u64);
+        ((self.beat_count + bar - downbeat) % bar) as u8
+    }
+
+    /// Credit `strength` to the beat slot nearest to the current phase.
+    fn accumulate_accent(&mut self, strength: f32) {
+        let decay = 0.5f32.powf(1.0 / (ACCENT_HALF_LIFE_SECS * self.fps));
+        for a in &mut self.accents {
+            *a *= decay;
+        }
+        let distance = self.phase.min(1.0 - self.phase);
+        if distance < 0.15 {
+            let nearest = if self.phase < 0.5 {
+                self.beat_count
+            } else {
+                self.beat_count + 1
+            };
+            let slot = (nearest % u64::from(BEATS_PER_BAR)) as usize;
+            self.accents[slot] += strength * (1.0 - distance / 0.15);
+        }
+    }
+
+    /// Re-estimate the period (autocorrelation + comb + prior) and pull the
+    /// phase towards the best-aligned pulse train.
+    fn estimate(&mut self) {
+        let hist = self.history.make_contiguous();
+        let n = hist.len();
+        let mean = hist.iter().sum::<f32>() / n as f32;
+        let centred: Vec<f32> = hist.iter().map(|&v| v - mean).collect();
+
+        let acf = |lag: usize| -> f32 {
+            if lag >= n {
+                return 0.0;
+            }
+            centred[lag..]
+                .iter()
+                .zip(&centred[..n - lag])
+                .map(|(a, b)| a * b)
+                .sum::<f32>()
+                / (n - lag) as f32
+        };
+        let energy = acf(0);
+        if energy <= 1e-9 {
+            self.confidence = 0.0;
+            return;
+        }
+
+        let min_lag = ((60.0 * self.fps / MAX_BPM).floor() as usize).max(2);
+        let max_lag = ((60.0 * self.fps / MIN_BPM).ceil() as usize).min(n / 2);
+        if max_lag <= min_lag + 1 {
+            return;
+        }
+        let score = |lag: usize| -> f32 {
+            let comb: f32 = (1..=4)
+                .map(|k| k * lag)
+                .take_while(|&l| l < n / 2)
+                .map(|l| acf(l) / l as f32 * lag as f32)
+                .sum();
+            let bpm = 60.0 * self.fps / lag as f32;
+            let octaves = (bpm / PRIOR_BPM).log2() / PRIOR_OCTAVES;
+            comb * (-0.5 * octaves * octaves).exp()
+        };
+        let scores: Vec<f32> = (min_lag - 1..=max_lag + 1).map(score).collect();
+        let Some(best) = (1..scores.len() - 1).max_by(|&a, &b| scores[a].total_cmp(&scores[b]))
+        else {
+            return;
+        };
+        if scores[best] <= 0.0 {
+            self.confidence = 0.0;
+            return;
+        }
+
+        // Parabolic interpolation around the integer peak
+        let (l, c, r) = (scores[best - 1], scores[best], scores[best + 1]);
+        let denom = l - 2.0 * c + r;
+        let offset = if denom.abs() > 1e-9 {
+            (0.5 * (l - r) / denom).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        let lag = best + min_lag - 1;
+        let period = lag as f32 + offset;
+        self.confidence = (acf(lag) / energy).clamp(0.0, 1.0);
+
+        // Tempo hysteresis: a new period is adopted once two estimations
+        // agree; a matching one only nudges the current period.
+        let close = |a: f32, b: f32| a > 0.0 && (a - b).abs() < a * 0.04;
+        if close(self.period, period) {
+            self.period = self.period * 0.8 + period * 0.2;
+        } else if self.period <= 0.0 || close(self.candidate, period) {
+            self.period = period;
+        }
+        self.candidate = period;
+
+        // Phase: offset (frames since the last beat) maximising the energy
+        // of a pulse train at the current period over the history.
+        let hist = self.history.make_contiguous();
+        let period = self.period;
+        let steps = (n as f32 / period) as usize;
+        let best_offset = (0..period.round().max(1.0) as usize)
+            .map(|o| {
+                let e: f32 = (0..steps)
+                    .filter_map(|k| {
+                        let back = o + (k as f32 * period).round() as usize;
+                        (back < n).then(|| hist[n - 1 - back])
+                    })
+                    .sum();
+                (o, e)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0, |(o, _)| o);
+        let target = best_offset as f32 / period;
+        let error = (target - self.phase + 0.5).rem_euclid(1.0) - 0.5;
+        self.phase = (self.phase + PHASE_GAIN * error).rem_euclid(1.0);
+    }
+}
+
+impl Default for TempoTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Onset / beat detection with tempo tracking.
+///
+/// Onsets use spectral flux with an adaptive threshold and onset cooldown;
+/// BPM, beat phase and bar position come from a [`TempoTracker`] fed with
+/// the normalized flux.
 ///
 /// # Example
 /// ```
@@ -18,16 +310,14 @@ pub struct BeatDetector {
     prev_spectrum: Vec<f32>,
     /// Running average of flux for adaptive threshold.
     flux_avg: f32,
-    /// Last known BPM.
-    bpm: f32,
-    /// Phase accumulator [0.0, 1.0].
-    phase: f32,
     /// Timestamp of last onset (in frames).
     last_onset_frame: u64,
     /// Current frame counter.
     frame_count: u64,
-    /// Onset interval accumulator for BPM estimation (VecDeque for O(1) pop_front).
-    intervals: VecDeque<u64>,
+    /// Tempo estimation and beat grid.
+    tempo: TempoTracker,
+    /// Beat grid of the last processed frame.
+    state: TempoState,
 }
 
 impl BeatDetector {
@@ -37,17 +327,17 @@ impl BeatDetector {
         Self {
             prev_spectrum: Vec::new(),
             flux_avg: 0.0,
-            bpm: 0.0,
-            phase: 0.0,
             last_onset_frame: 0,
             frame_count: 0,
-            intervals: VecDeque::with_capacity(16),
+            tempo: TempoTracker::new(),
+            state: TempoState::default(),
         }
     }
 
     /// Process a spectrum frame.
     ///
-    /// Returns (onset, beat_intensity, bpm, phase, spectral_flux).
+    /// Returns (onset, beat_intensity, bpm, phase, spectral_flux). The full
+    /// beat grid of the frame is available from [`Self::tempo`].
     pub fn process(&mut self, spectrum: &[f32], fps: f32) -> (bool, f32, f32, f32, f32) {
         self.frame_count += 1;
 
@@ -95,32 +385,8 @@ impl BeatDetector {
             && spectral_energy > 1e-6
             && flux > threshold
             && frames_since > cooldown_frames;
-
-        // BPM estimation from onset intervals
         if onset {
-            let interval = frames_since;
             self.last_onset_frame = self.frame_count;
-
-            if interval > 5 && interval < 300 {
-                self.intervals.push_back(interval);
-                if self.intervals.len() > 16 {
-                    self.intervals.pop_front();
-                }
-
-                if self.intervals.len() >= 4 {
-                    let avg_interval: f64 = self.intervals.iter().map(|&i| i as f64).sum::<f64>()
-                        / self.intervals.len() as f64;
-                    if avg_interval > 0.0 {
-                        self.bpm = (60.0 * f64::from(fps) / avg_interval) as f32;
-                        self.bpm = self.bpm.clamp(30.0, 300.0);
-                    }
-                }
-            }
-
-            self.phase = 0.0;
-        } else if self.bpm > 0.0 {
-            let beats_per_frame = self.bpm / (60.0 * fps);
-            self.phase = (self.phase + beats_per_frame) % 1.0;
         }
 
         // Zero-alloc update: resize only on first call or format change, then copy_from_slice
@@ -132,7 +398,22 @@ impl BeatDetector {
         // Normalize flux to [0, 1] with gain + sqrt compression for mapping use.
         let normalized_flux = (flux * FLUX_GAIN).sqrt().min(1.0);
 
-        (onset, beat_intensity, self.bpm, self.phase, normalized_flux)
+        // Tempo from the same signal as the batch analyzer (normalized flux)
+        self.state = self.tempo.process(normalized_flux, fps);
+
+        (
+            onset,
+            beat_intensity,
+            self.state.bpm,
+            self.state.beat_phase,
+            normalized_flux,
+        )
+    }
+
+    /// Beat grid of the last processed frame.
+    #[must_use]
+    pub fn tempo(&self) -> TempoState {
+        self.state
     }
 }
 
@@ -141,3 +422,89 @@ impl Default for BeatDetector {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Onset strength of a kick every `period` frames, accented every 4th beat.
+    fn pulse_train(frames: usize, period: f32, accent_offset: usize) -> Vec<f32> {
+        let mut out = vec![0.05; frames];
+        let mut beat = 0usize;
+        let mut t = 0.0f32;
+        while (t as usize) < frames {
+            out[t as usize] = if beat % 4 == accent_offset { 1.0 } else { 0.6 };
+            beat += 1;
+            t += period;
+        }
+        out
+    }
+
+    #[test]
+    fn tracks_steady_tempo() {
+        let fps = 60.0;
+        // 128 BPM at 60 fps = 28.125 frames per beat
+        let signal = pulse_train(60 * 20, 60.0 * fps / 128.0, 0);
+        let mut tracker = TempoTracker::new();
+        let mut state = TempoState::default();
+        for &s in &signal {
+            state = tracker.process(s, fps);
+        }
+        assert!((state.bpm - 128.0).abs() < 2.0, "bpm = {}", state.bpm);
+        assert!(state.confidence > 0.3, "confidence = {}", state.confidence);
+    }
+
+    #[test]
+    fn phase_ignores_syncopation() {
+        let fps = 60.0;
+        let period = 30.0; // 120 BPM
+        let mut signal = pulse_train(60 * 20, period, 0);
+        // Off-beat hits between beats must not reset the phase
+        for k in 0..(signal.len() / 60) {
+            let i = k * 60 + 45;
+            if i < signal.len() {
+                signal[i] = 0.8;
+            }
+        }
+        let mut tracker = TempoTracker::new();
+        let mut on_beat_phases = Vec::new();
+        for (i, &s) in signal.iter().enumerate() {
+            let state = tracker.process(s, fps);
+            if i > 60 * 10 && i % 30 == 0 {
+                on_beat_phases.push(state.beat_phase);
+            }
+        }
+        for p in on_beat_phases {
+            let distance = p.min(1.0 - p);
+            assert!(distance < 0.15, "phase on beat = {p}");
+        }
+    }
+
+    #[test]
+    fn finds_accented_downbeat() {
+        let fps = 60.0;
+        let period = 30.0;
+        // Beat 2 of each group of 4 (0-based) carries the accent
+        let signal = pulse_train(60 * 30, period, 2);
+        let mut tracker = TempoTracker::new();
+        let mut state = TempoState::default();
+        // Stop mid-beat: frame 59 * 30 + 15
+        let last = 59 * 30 + 15;
+        for &s in &signal[..=last] {
+            state = tracker.process(s, fps);
+        }
+        let expected = ((59 + 4 - 2) % 4) as u8;
+        assert_eq!(state.beat_in_bar, expected, "state = {state:?}");
+        assert!(state.bar_phase >= 0.0 && state.bar_phase < 1.0);
+    }
+
+    #[test]
+    fn silence_has_no_tempo() {
+        let mut tracker = TempoTracker::new();
+        let mut state = TempoState::default();
+        for _ in 0..600 {
+            state = tracker.process(0.0, 60.0);
+        }
+        assert_eq!(state, TempoState::default());
+    }
+}
@@ -1,10 +1,444 @@
-use anyhow::Result;
+use std::str::FromStr;
+use std::thread;
+
+use af_core::frame::MAX_INPUT_CHANNELS;
+use anyhow::{Context, Result};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use rtrb::{Consumer, RingBuffer};
+use rtrb::{Consumer, Producer, RingBuffer};
+
+use crate::pcm::{self, PcmFormat, PcmSource};
+
+/// Which input channels feed the analysis.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChannelRouting {
+    /// Downmix every channel to mono.
+    #[default]
+    Mix,
+    /// A single channel (0-based).
+    Channel(u16),
+    /// Mono downmix for the main features, plus one feature set per channel
+    /// (the first [`MAX_INPUT_CHANNELS`]).
+    Split,
+}
+
+impl FromStr for ChannelRouting {
+    type Err = anyhow::Error;
+
+    /// `mix`, `split`, or a 1-based channel number (`2` = right).
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "mix" | "mono" => Ok(Self::Mix),
+            "split" => Ok(Self::Split),
+            n => match n.parse::<u16>() {
+                Ok(ch) if ch >= 1 => Ok(Self::Channel(ch - 1)),
+                _ => anyhow::bail!(
+                    "Canaux audio invalides : '{s}' (attendu : mix, split ou numéro de canal ≥ 1)"
+                ),
+            },
+        }
+    }
+}
 
-/// Audio capture via cpal.
+/// Capture settings: input device, channel routing, sample rate.
+#[derive(Clone, Debug, Default)]
+pub struct CaptureOptions {
+    /// Device index or case-insensitive part of its name. `None` = default input.
+    pub device: Option<String>,
+    /// Channel routing of the analysis.
+    pub routing: ChannelRouting,
+    /// Requested sample rate in Hz. `None` = device default.
+    pub sample_rate: Option<u32>,
+    /// Capture what the machine plays instead of an input (`--audio loopback`).
+    /// `device` then narrows the search among monitor sources.
+    pub loopback: bool,
+    /// Layout of raw PCM input (`--audio -`, `--audio fifo:PATH`).
+    pub pcm_format: PcmFormat,
+}
+
+/// An input device, as listed by [`list_input_devices`].
+#[derive(Clone, Debug)]
+pub struct InputDeviceInfo {
+    /// Index accepted by [`CaptureOptions::device`].
+    pub index: usize,
+    pub name: String,
+    /// Default input device of the host.
+    pub is_default: bool,
+    /// Default channel count (0 if the device could not be queried).
+    pub channels: u16,
+    /// Default sample rate in Hz (0 if the device could not be queried).
+    pub sample_rate: u32,
+    /// Supported sample-rate ranges `(min, max)` in Hz, deduplicated.
+    pub rate_ranges: Vec<(u32, u32)>,
+}
+
+/// Enumerate the input devices of the default host.
 ///
-/// Writes mono f32 samples into a lock-free ring buffer.
+/// # Errors
+/// Returns an error if the host cannot enumerate its devices.
+pub fn list_input_devices() -> Result<Vec<InputDeviceInfo>> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+    let devices = host
+        .input_devices()
+        .context("Impossible d'énumérer les périphériques audio")?;
+
+    Ok(devices
+        .enumerate()
+        .map(|(index, device)| {
+            let name = device.name().unwrap_or_else(|_| "?".to_string());
+            let (channels, sample_rate) = device
+                .default_input_config()
+                .map_or((0, 0), |c| (c.channels(), c.sample_rate().0));
+            InputDeviceInfo {
+                index,
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+                channels,
+                sample_rate,
+                rate_ranges: rate_ranges(&device),
+            }
+        })
+        .collect())
+}
+
+/// Supported input sample-rate ranges of `device`, deduplicated and sorted.
+fn rate_ranges(device: &cpal::Device) -> Vec<(u32, u32)> {
+    device
+        .supported_input_configs()
+        .map(|configs| sorted_ranges(&configs.collect::<Vec<_>>()))
+        .unwrap_or_default()
+}
+
+fn sorted_ranges(configs: &[cpal::SupportedStreamConfigRange]) -> Vec<(u32, u32)> {
+    let mut ranges: Vec<(u32, u32)> = configs
+        .iter()
+        .map(|c| (c.min_sample_rate().0, c.max_sample_rate().0))
+        .collect();
+    ranges.sort_unstable();
+    ranges.dedup();
+    ranges
+}
+
+/// `44100 Hz, 48000–96000 Hz`
+fn format_ranges(ranges: &[(u32, u32)]) -> String {
+    if ranges.is_empty() {
+        return "inconnues".to_string();
+    }
+    ranges
+        .iter()
+        .map(|&(min, max)| {
+            if min == max {
+                format!("{min} Hz")
+            } else {
+                format!("{min}–{max} Hz")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Resolve `spec` (index or name substring) to an input device and its name.
+fn select_device(host: &cpal::Host, spec: Option<&str>) -> Result<(cpal::Device, String)> {
+    let Some(spec) = spec else {
+        let device = host
+            .default_input_device()
+            .ok_or_else(|| anyhow::anyhow!("Pas de périphérique audio trouvé"))?;
+        let name = device.name().unwrap_or_else(|_| "?".to_string());
+        return Ok((device, name));
+    };
+
+    let devices: Vec<cpal::Device> = host
+        .input_devices()
+        .context("Impossible d'énumérer les périphériques audio")?
+        .collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_else(|_| "?".to_string()))
+        .collect();
+
+    let needle = spec.to_lowercase();
+    let idx = spec
+        .parse::<usize>()
+        .ok()
+        .filter(|&i| i < devices.len())
+        .or_else(|| {
+            names
+                .iter()
+                .position(|n| n.to_lowercase().contains(&needle))
+        });
+    let Some(idx) = idx else {
+        let list = if names.is_empty() {
+            "aucun".to_string()
+        } else {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| format!("{i}: {n}"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        anyhow::bail!("Périphérique audio '{spec}' introuvable (entrées disponibles : {list})");
+    };
+    let name = names[idx].clone();
+    let device = devices
+        .into_iter()
+        .nth(idx)
+        .context("Périphérique audio disparu")?;
+    Ok((device, name))
+}
+
+/// Name fragments of monitor / loopback inputs: PulseAudio and PipeWire
+/// monitors, Windows "Stereo Mix", macOS virtual devices.
+const LOOPBACK_PATTERNS: [&str; 6] = [
+    "monitor",
+    "loopback",
+    "stereo mix",
+    "blackhole",
+    "soundflower",
+    "what u hear",
+];
+
+/// Find a device capturing system playback. Tried in order, the first
+/// available wins:
+///
+/// 1. `PULSE_SOURCE` set (e.g. `@DEFAULT_MONITOR@`) and an ALSA `pulse` device;
+/// 2. the JACK host, when built with the `jack` feature and a server runs;
+/// 3. WASAPI loopback on the default output device (Windows);
+/// 4. an input whose name looks like a monitor (see `LOOPBACK_PATTERNS`).
+///
+/// With `spec`, only step 4 runs, among inputs whose name contains `spec`.
+/// Returns the device, its name, and whether it must be opened with its
+/// output config (WASAPI loopback).
+fn select_loopback_device(
+    host: &cpal::Host,
+    spec: Option<&str>,
+) -> Result<(cpal::Device, String, bool)> {
+    let devices: Vec<cpal::Device> = host
+        .input_devices()
+        .context("Impossible d'énumérer les périphériques audio")?
+        .collect();
+    let names: Vec<String> = devices
+        .iter()
+        .map(|d| d.name().unwrap_or_else(|_| "?".to_string()))
+        .collect();
+    let pick = |idx: usize| (devices[idx].clone(), names[idx].clone(), false);
+
+    if spec.is_none() {
+        if std::env::var_os("PULSE_SOURCE").is_some()
+            && let Some(idx) = names.iter().position(|n| n == "pulse")
+        {
+            return Ok(pick(idx));
+        }
+        if let Some(found) = jack_loopback() {
+            return Ok(found);
+        }
+        #[cfg(windows)]
+        if let Some(device) = host.default_output_device() {
+            let name = device.name().unwrap_or_else(|_| "?".to_string());
+            return Ok((device, format!("{name} (loopback)"), true));
+        }
+    }
+
+    let needle = spec.map(str::to_lowercase);
+    names
+        .iter()
+        .position(|n| {
+            looks_like_loopback(n)
+                && needle
+                    .as_ref()
+                    .is_none_or(|s| n.to_lowercase().contains(s.as_str()))
+        })
+        .map(pick)
+        .ok_or_else(|| anyhow::anyhow!("Aucune source loopback trouvée. {}", loopback_hint()))
+}
+
+/// Input name matching one of [`LOOPBACK_PATTERNS`], case-insensitively.
+fn looks_like_loopback(name: &str) -> bool {
+    let name = name.to_lowercase();
+    LOOPBACK_PATTERNS.iter().any(|p| name.contains(p))
+}
+
+/// Device name of the JACK loopback, recognized by [`AudioCapture::start`]
+/// to rewire its ports once the stream runs.
+const JACK_LOOPBACK: &str = "JACK (system:monitor_*)";
+
+/// JACK input (`jack` feature), `None` if no server runs or it exposes no
+/// `system:monitor_*` ports (copies of what goes to `system:playback_*`).
+/// cpal connects its inputs to `system:capture_*`, the microphone:
+/// [`route_jack_monitors`] swaps them for the monitors after the stream starts.
+#[cfg(all(
+    feature = "jack",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    )
+))]
+fn jack_loopback() -> Option<(cpal::Device, String, bool)> {
+    let monitors = jack::Client::new("classcii-probe", jack::ClientOptions::NO_START_SERVER)
+        .map(|(client, _)| client.ports(Some("^system:monitor_"), None, jack::PortFlags::IS_OUTPUT))
+        .map_err(|e| log::info!("JACK indisponible : {e}"))
+        .ok()?;
+    if monitors.is_empty() {
+        log::info!("JACK : aucun port system:monitor_*, loopback JACK ignoré");
+        return None;
+    }
+    let host = cpal::host_from_id(cpal::HostId::Jack)
+        .map_err(|e| log::info!("JACK indisponible : {e}"))
+        .ok()?;
+    let device = host.default_input_device()?;
+    Some((device, JACK_LOOPBACK.to_string(), false))
+}
+
+/// Disconnect the cpal input ports from `system:capture_*` and connect them
+/// to `system:monitor_*` instead (round-robin if the counts differ).
+#[cfg(all(
+    feature = "jack",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    )
+))]
+fn route_jack_monitors() -> Result<()> {
+    let (client, _) = jack::Client::new("classcii-route", jack::ClientOptions::NO_START_SERVER)
+        .context("Client JACK indisponible")?;
+    let monitors = client.ports(Some("^system:monitor_"), None, jack::PortFlags::IS_OUTPUT);
+    let captures = client.ports(Some("^system:capture_"), None, jack::PortFlags::IS_OUTPUT);
+    let inputs = client.ports(Some("^cpal_client_in"), None, jack::PortFlags::IS_INPUT);
+    anyhow::ensure!(
+        !monitors.is_empty() && !inputs.is_empty(),
+        "Ports JACK introuvables pour le loopback. {}",
+        loopback_hint()
+    );
+    for input in &inputs {
+        for capture in &captures {
+            let _ = client.disconnect_ports_by_name(capture, input);
+        }
+    }
+    for (monitor, input) in monitors.iter().cycle().zip(&inputs) {
+        client
+            .connect_ports_by_name(monitor, input)
+            .with_context(|| format!("Connexion JACK {monitor} → {input}"))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(all(
+    feature = "jack",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    )
+)))]
+fn jack_loopback() -> Option<(cpal::Device, String, bool)> {
+    None
+}
+
+#[cfg(not(all(
+    feature = "jack",
+    any(
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd"
+    )
+)))]
+#[allow(clippy::unnecessary_wraps)]
+fn route_jack_monitors() -> Result<()> {
+    Ok(())
+}
+
+/// Platform-specific advice when no loopback source is found.
+fn loopback_hint() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Installez un périphérique virtuel (BlackHole, Loopback) et dirigez-y la sortie système."
+    } else if cfg!(windows) {
+        "Aucune sortie audio active pour le loopback WASAPI."
+    } else if cfg!(feature = "jack") {
+        "PipeWire/PulseAudio : PULSE_SOURCE=@DEFAULT_MONITOR@ classcii --audio loopback ; \
+         JACK : démarrez le serveur avec des ports moniteur (jackd -d alsa --monitor)."
+    } else {
+        "PipeWire/PulseAudio : PULSE_SOURCE=@DEFAULT_MONITOR@ classcii --audio loopback ; \
+         JACK : recompilez avec --features jack."
+    }
+}
+
+/// `d'entrée` / `de sortie`, for config errors.
+fn direction(output: bool) -> &'static str {
+    if output { "de sortie" } else { "d'entrée" }
+}
+
+/// Default config of `device`, or the one supporting `rate` (same sample
+/// format and channel count preferred). `output` reads the output configs
+/// (WASAPI loopback captures an output device).
+fn negotiate_config(
+    device: &cpal::Device,
+    name: &str,
+    rate: Option<u32>,
+    output: bool,
+) -> Result<cpal::SupportedStreamConfig> {
+    let default = if output {
+        device.default_output_config()
+    } else {
+        device.default_input_config()
+    }
+    .with_context(|| {
+        format!(
+            "Configuration {} indisponible pour '{name}'",
+            direction(output)
+        )
+    })?;
+    let Some(rate) = rate else {
+        return Ok(default);
+    };
+    if default.sample_rate().0 == rate {
+        return Ok(default);
+    }
+    let configs: Vec<cpal::SupportedStreamConfigRange> = if output {
+        device.supported_output_configs().map(Iterator::collect)
+    } else {
+        device.supported_input_configs().map(Iterator::collect)
+    }
+    .with_context(|| {
+        format!(
+            "Configurations {} indisponibles pour '{name}'",
+            direction(output)
+        )
+    })?;
+    configs
+        .iter()
+        .filter(|c| (c.min_sample_rate().0..=c.max_sample_rate().0).contains(&rate))
+        .max_by_key(|c| {
+            (
+                c.sample_format() == default.sample_format(),
+                c.channels() == default.channels(),
+            )
+        })
+        .map(|c| c.with_sample_rate(cpal::SampleRate(rate)))
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Fréquence {rate} Hz non supportée par '{name}' (plages : {})",
+                format_ranges(&sorted_ranges(&configs))
+            )
+        })
+}
+
+/// What feeds the ring buffers of an [`AudioCapture`].
+enum CaptureInput {
+    Stream(cpal::Stream),
+    /// Raw PCM reader thread; ends on EOF or once the capture is dropped.
+    Reader(thread::JoinHandle<()>),
+}
+
+/// Audio capture via cpal, or from a raw PCM stream.
+///
+/// Writes mono f32 samples into a lock-free ring buffer. With
+/// [`ChannelRouting::Split`], each channel also gets its own ring buffer.
 ///
 /// # Example
 /// ```no_run
@@ -12,15 +446,30 @@ use rtrb::{Consumer, RingBuffer};
 /// let capture = AudioCapture::start_default().unwrap();
 /// ```
 pub struct AudioCapture {
-    stream: cpal::Stream,
+    input: CaptureInput,
     consumer: Consumer<f32>,
+    channel_consumers: Vec<Consumer<f32>>,
     sample_rate: u32,
+    device_name: String,
 }
 
 impl AudioCapture {
     /// Reference to the underlying cpal stream (kept alive for capture).
-    pub fn stream(&self) -> &cpal::Stream {
-        &self.stream
+    /// `None` for a raw PCM input.
+    pub fn stream(&self) -> Option<&cpal::Stream> {
+        match self.input {
+            CaptureInput::Stream(ref stream) => Some(stream),
+            CaptureInput::Reader(_) => None,
+        }
+    }
+
+    /// The raw PCM stream reached EOF or failed. Always `false` for a device.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        match self.input {
+            CaptureInput::Stream(_) => false,
+            CaptureInput::Reader(ref handle) => handle.is_finished(),
+        }
     }
 
     /// Start capturing from the default input device.
@@ -28,49 +477,84 @@ impl AudioCapture {
     /// # Errors
     /// Returns an error if the audio device is unavailable.
     pub fn start_default() -> Result<Self> {
-        let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .ok_or_else(|| anyhow::anyhow!("Pas de périphérique audio trouvé"))?;
+        Self::start(&CaptureOptions::default())
+    }
 
-        let config = device.default_input_config()?;
+    /// Start capturing with explicit device, routing and sample rate.
+    ///
+    /// # Errors
+    /// Returns an error if the device is not found (the message lists the
+    /// available inputs), the sample rate or channel is not supported, or the
+    /// stream cannot be built.
+    pub fn start(options: &CaptureOptions) -> Result<Self> {
+        let host = cpal::default_host();
+        let (device, device_name, from_output) = if options.loopback {
+            select_loopback_device(&host, options.device.as_deref())?
+        } else {
+            let (device, name) = select_device(&host, options.device.as_deref())?;
+            (device, name, false)
+        };
+        let config = negotiate_config(&device, &device_name, options.sample_rate, from_output)?;
         let sample_rate = config.sample_rate().0;
-        let channels = (config.channels() as usize).max(1);
+        let channels = usize::from(config.channels()).max(1);
+        let (router, consumer, channel_consumers) =
+            Router::new(options.routing, channels, sample_rate, &device_name)?;
 
-        // Ring buffer: 100ms of audio @ sample_rate (sufficient for 60 FPS frame reads)
-        let buf_size = (sample_rate as usize / 10).max(4096);
-        let (mut producer, consumer) = RingBuffer::new(buf_size);
-
-        let mut drop_count: usize = 0;
-
-        let stream = device.build_input_stream(
-            &config.into(),
-            move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                // Downmix to mono and push into ring buffer
-                for chunk in data.chunks(channels) {
-                    let mono: f32 = chunk.iter().sum::<f32>() / channels as f32;
-                    if producer.push(mono).is_err() {
-                        drop_count += 1;
-                        if drop_count % 10000 == 1 {
-                            log::warn!(
-                                "Audio ring buffer full: {drop_count} samples dropped so far"
-                            );
-                        }
-                    }
-                }
-            },
-            |err| {
-                log::error!("Audio stream error: {err}");
-            },
-            None,
-        )?;
+        let stream_config: cpal::StreamConfig = config.config();
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::F32 => build_capture_stream::<f32>(&device, &stream_config, router),
+            cpal::SampleFormat::I16 => build_capture_stream::<i16>(&device, &stream_config, router),
+            cpal::SampleFormat::U16 => build_capture_stream::<u16>(&device, &stream_config, router),
+            cpal::SampleFormat::I32 => build_capture_stream::<i32>(&device, &stream_config, router),
+            fmt => anyhow::bail!("Format d'échantillon non supporté par '{device_name}' : {fmt:?}"),
+        }
+        .with_context(|| format!("Ouverture du flux audio '{device_name}' @ {sample_rate} Hz"))?;
 
         stream.play()?;
+        if device_name == JACK_LOOPBACK {
+            route_jack_monitors()?;
+        }
+        log::info!(
+            "Audio capture: {device_name} @ {sample_rate} Hz, {channels} ch, {:?}",
+            options.routing
+        );
 
         Ok(Self {
-            stream,
+            input: CaptureInput::Stream(stream),
             consumer,
+            channel_consumers,
             sample_rate,
+            device_name,
+        })
+    }
+
+    /// Read raw interleaved PCM from `source` instead of a device. The
+    /// layout comes from `options.pcm_format`; `options.routing` applies as
+    /// for a device, the other fields are ignored.
+    ///
+    /// # Errors
+    /// Returns an error if the routed channel does not exist in the declared
+    /// format or the reader thread fails to spawn. I/O errors (missing FIFO,
+    /// broken pipe) are logged by the reader thread.
+    pub fn start_pcm(source: PcmSource, options: &CaptureOptions) -> Result<Self> {
+        let format = options.pcm_format;
+        let device_name = source.label();
+        let (router, consumer, channel_consumers) = Router::new(
+            options.routing,
+            usize::from(format.channels),
+            format.sample_rate,
+            &device_name,
+        )?;
+        let handle = thread::Builder::new()
+            .name("af-audio-pcm".to_string())
+            .spawn(move || pcm::run_reader(source, format, router))?;
+
+        Ok(Self {
+            input: CaptureInput::Reader(handle),
+            consumer,
+            channel_consumers,
+            sample_rate: format.sample_rate,
+            device_name,
         })
     }
 
@@ -81,14 +565,21 @@ impl AudioCapture {
     ///
     /// Returns how many samples were appended.
     pub fn read_samples(&mut self, out: &mut Vec<f32>) -> usize {
-        let available = self.consumer.slots();
-        out.reserve(available);
-        let mut count = 0;
-        while let Ok(sample) = self.consumer.pop() {
-            out.push(sample);
-            count += 1;
-        }
-        count
+        drain_into(&mut self.consumer, out)
+    }
+
+    /// Number of separately captured channels (0 unless routing is `Split`).
+    #[must_use]
+    pub fn split_channels(&self) -> usize {
+        self.channel_consumers.len()
+    }
+
+    /// Like [`Self::read_samples`], for split channel `idx` (0-based).
+    /// Returns 0 if `idx` is not captured.
+    pub fn read_channel_samples(&mut self, idx: usize, out: &mut Vec<f32>) -> usize {
+        self.channel_consumers
+            .get_mut(idx)
+            .map_or(0, |c| drain_into(c, out))
     }
 
     /// The sample rate of the capture stream.
@@ -96,4 +587,191 @@ impl AudioCapture {
     pub fn sample_rate(&self) -> u32 {
         self.sample_rate
     }
+
+    /// Name of the capture device.
+    #[must_use]
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+}
+
+fn drain_into(consumer: &mut Consumer<f32>, out: &mut Vec<f32>) -> usize {
+    let available = consumer.slots();
+    out.reserve(available);
+    let mut count = 0;
+    while let Ok(sample) = consumer.pop() {
+        out.push(sample);
+        count += 1;
+    }
+    count
+}
+
+/// Routes interleaved input frames to the main and per-channel ring buffers.
+pub(crate) struct Router {
+    routing: ChannelRouting,
+    channels: usize,
+    main: Producer<f32>,
+    split: Vec<Producer<f32>>,
+    drop_count: usize,
+}
+
+impl Router {
+    /// Router for `channels` interleaved channels, with the matching main
+    /// and per-channel consumers. Fails if `routing` names a missing channel.
+    fn new(
+        routing: ChannelRouting,
+        channels: usize,
+        sample_rate: u32,
+        device_name: &str,
+    ) -> Result<(Self, Consumer<f32>, Vec<Consumer<f32>>)> {
+        let channels = channels.max(1);
+        if let ChannelRouting::Channel(ch) = routing
+            && usize::from(ch) >= channels
+        {
+            anyhow::bail!(
+                "Canal {} absent : '{device_name}' n'a que {channels} canal(aux)",
+                ch + 1
+            );
+        }
+
+        // Ring buffer: 100ms of audio @ sample_rate (sufficient for 60 FPS frame reads)
+        let buf_size = (sample_rate as usize / 10).max(4096);
+        let (main, consumer) = RingBuffer::new(buf_size);
+        let split_count = if routing == ChannelRouting::Split {
+            channels.min(MAX_INPUT_CHANNELS)
+        } else {
+            0
+        };
+        let (split, channel_consumers): (Vec<_>, Vec<_>) =
+            (0..split_count).map(|_| RingBuffer::new(buf_size)).unzip();
+
+        let router = Self {
+            routing,
+            channels,
+            main,
+            split,
+            drop_count: 0,
+        };
+        Ok((router, consumer, channel_consumers))
+    }
+
+    /// Every ring buffer can take one more frame.
+    pub(crate) fn has_room(&self) -> bool {
+        self.main.slots() > 0 && self.split.iter().all(|p| p.slots() > 0)
+    }
+
+    /// The capture was dropped: nobody reads the ring buffers anymore.
+    pub(crate) fn is_abandoned(&self) -> bool {
+        self.main.is_abandoned()
+    }
+
+    pub(crate) fn push_frame(&mut self, frame: &[f32]) {
+        let main = match self.routing {
+            ChannelRouting::Channel(ch) => frame.get(usize::from(ch)).copied().unwrap_or(0.0),
+            ChannelRouting::Mix | ChannelRouting::Split => {
+                frame.iter().sum::<f32>() / self.channels as f32
+            }
+        };
+        let mut dropped = usize::from(self.main.push(main).is_err());
+        for (producer, &sample) in self.split.iter_mut().zip(frame) {
+            dropped += usize::from(producer.push(sample).is_err());
+        }
+        if dropped > 0 {
+            self.drop_count += dropped;
+            if self.drop_count % 10000 < dropped {
+                log::warn!(
+                    "Audio ring buffer full: {} samples dropped so far",
+                    self.drop_count
+                );
+            }
+        }
+    }
+}
+
+/// Generic input stream builder (F32, I16, U16, I32 dynamic dispatch for cpal).
+fn build_capture_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut router: Router,
+) -> Result<cpal::Stream>
+where
+    T: cpal::SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    let channels = router.channels;
+    let mut frame = vec![0.0f32; channels];
+    let stream = device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            for chunk in data.chunks(channels) {
+                for (slot, &s) in frame.iter_mut().zip(chunk) {
+                    *slot = cpal::Sample::to_sample::<f32>(s);
+                }
+                router.push_frame(&frame[..chunk.len()]);
+            }
+        },
+        |err| {
+            log::error!("Audio stream error: {err}");
+        },
+        None,
+    )?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_channel_routing() {
+        assert_eq!(
+            "mix".parse::<ChannelRouting>().unwrap(),
+            ChannelRouting::Mix
+        );
+        assert_eq!(
+            "Split".parse::<ChannelRouting>().unwrap(),
+            ChannelRouting::Split
+        );
+        assert_eq!(
+            "2".parse::<ChannelRouting>().unwrap(),
+            ChannelRouting::Channel(1)
+        );
+        assert!("0".parse::<ChannelRouting>().is_err());
+        assert!("left".parse::<ChannelRouting>().is_err());
+    }
+
+    #[test]
+    fn loopback_patterns_match_monitor_names() {
+        assert!(looks_like_loopback(
+            "Monitor of Built-in Audio Analog Stereo"
+        ));
+        assert!(looks_like_loopback("Stereo Mix (Realtek Audio)"));
+        assert!(looks_like_loopback("BlackHole 2ch"));
+        assert!(looks_like_loopback("What U Hear (Sound Blaster)"));
+        assert!(!looks_like_loopback("USB Microphone"));
+        assert!(!looks_like_loopback("pulse"));
+    }
+
+    #[test]
+    fn router_selects_and_splits_channels() {
+        let (main, mut main_rx) = RingBuffer::new(16);
+        let (left, mut left_rx) = RingBuffer::new(16);
+        let (right, mut right_rx) = RingBuffer::new(16);
+        let mut router = Router {
+            routing: ChannelRouting::Split,
+            channels: 2,
+            main,
+            split: vec![left, right],
+            drop_count: 0,
+        };
+        router.push_frame(&[1.0, 0.0]);
+        assert!((main_rx.pop().unwrap() - 0.5).abs() < f32::EPSILON);
+        assert!((left_rx.pop().unwrap() - 1.0).abs() < f32::EPSILON);
+        assert!(right_rx.pop().unwrap().abs() < f32::EPSILON);
+
+        router.routing = ChannelRouting::Channel(1);
+        router.push_frame(&[1.0, 0.25]);
+        assert!((main_rx.pop().unwrap() - 0.25).abs() < f32::EPSILON);
+    }
 }
@@ -1,3 +1,4 @@
+use af_core::config::{AnalysisConfig, BUILTIN_BANDS};
 use af_core::frame::AudioFeatures;
 
 /// Gain applied to average FFT magnitude before sqrt compression.
@@ -6,7 +7,47 @@ use af_core::frame::AudioFeatures;
 /// Mapping: 0.005→0.32, 0.02→0.63, 0.05→1.0.
 const BAND_ENERGY_GAIN: f32 = 20.0;
 
-/// Extract audio features from a spectrum and raw samples.
+/// Frequency edges of the band energies (`[audio.analysis] bands`): the 7
+/// built-in bands, possibly moved, plus up to [`af_core::frame::MAX_CUSTOM_BANDS`] user bands.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BandLayout {
+    builtin: [(f32, f32); 7],
+    custom: Vec<(f32, f32)>,
+}
+
+impl BandLayout {
+    /// Layout described by an `[audio.analysis]` section.
+    ///
+    /// # Example
+    /// ```
+    /// use af_audio::features::BandLayout;
+    /// use af_core::config::AnalysisConfig;
+    /// assert_eq!(BandLayout::from_config(&AnalysisConfig::default()), BandLayout::default());
+    /// ```
+    #[must_use]
+    pub fn from_config(analysis: &AnalysisConfig) -> Self {
+        Self {
+            builtin: analysis.builtin_edges(),
+            custom: analysis
+                .custom_bands()
+                .map(|b| (b.low_hz, b.high_hz))
+                .collect(),
+        }
+    }
+}
+
+impl Default for BandLayout {
+    /// Built-in edges only (sub_bass 20–60 Hz … brilliance 6–20 kHz).
+    fn default() -> Self {
+        Self {
+            builtin: BUILTIN_BANDS.map(|(_, lo, hi)| (lo, hi)),
+            custom: Vec::new(),
+        }
+    }
+}
+
+/// Extract audio features from a spectrum and raw samples, with the default
+/// [`BandLayout`].
 ///
 /// # Example
 /// ```
@@ -18,7 +59,19 @@ const BAND_ENERGY_GAIN: f32 = 20.0;
 /// let features = extract_features(&samples, &spectrum, 44100);
 /// assert!(features.rms.abs() < f32::EPSILON);
 /// ```
+#[must_use]
 pub fn extract_features(samples: &[f32], spectrum: &[f32], sample_rate: u32) -> AudioFeatures {
+    extract_features_with(samples, spectrum, sample_rate, &BandLayout::default())
+}
+
+/// Extract audio features with the band edges of `layout`.
+#[must_use]
+pub fn extract_features_with(
+    samples: &[f32],
+    spectrum: &[f32],
+    sample_rate: u32,
+    layout: &BandLayout,
+) -> AudioFeatures {
     let mut features = AudioFeatures::default();
 
     // RMS
@@ -38,13 +91,19 @@ pub fn extract_features(samples: &[f32], spectrum: &[f32], sample_rate: u32) ->
     if spectrum.len() > 1 {
         let bin_hz = sample_rate as f32 / ((spectrum.len() - 1) * 2) as f32;
 
-        features.sub_bass = band_energy(spectrum, 20.0, 60.0, bin_hz);
-        features.bass = band_energy(spectrum, 60.0, 250.0, bin_hz);
-        features.low_mid = band_energy(spectrum, 250.0, 500.0, bin_hz);
-        features.mid = band_energy(spectrum, 500.0, 2000.0, bin_hz);
-        features.high_mid = band_energy(spectrum, 2000.0, 4000.0, bin_hz);
-        features.presence = band_energy(spectrum, 4000.0, 6000.0, bin_hz);
-        features.brilliance = band_energy(spectrum, 6000.0, 20000.0, bin_hz);
+        let [sub_bass, bass, low_mid, mid, high_mid, presence, brilliance] = layout
+            .builtin
+            .map(|(lo, hi)| band_energy(spectrum, lo, hi, bin_hz));
+        features.sub_bass = sub_bass;
+        features.bass = bass;
+        features.low_mid = low_mid;
+        features.mid = mid;
+        features.high_mid = high_mid;
+        features.presence = presence;
+        features.brilliance = brilliance;
+        for (slot, &(lo, hi)) in features.custom_bands.iter_mut().zip(&layout.custom) {
+            *slot = band_energy(spectrum, lo, hi, bin_hz);
+        }
 
         // Spectral centroid
         let total_energy: f32 = spectrum.iter().sum();
@@ -1,3 +1,4 @@
+use af_core::config::WindowFunction;
 use realfft::RealFftPlanner;
 
 /// FFT pipeline: windowed real FFT using realfft.
@@ -17,17 +18,37 @@ pub struct FftPipeline {
     plan: std::sync::Arc<dyn realfft::RealToComplex<f32>>,
     /// Pre-allocated magnitude buffer for zero allocation process.
     magnitude_buf: Vec<f32>,
-    /// Hann window coefficients.
+    /// Window coefficients.
     window: Vec<f32>,
+    /// Magnitude divisor: `fft_size`, corrected by the window's coherent gain
+    /// relative to Hann so band levels do not depend on the window.
+    scale: f32,
 }
 
 impl FftPipeline {
-    /// Create a new FFT pipeline with the given window size.
+    /// Create a new FFT pipeline with the given window size and a Hann window.
     ///
     /// # Panics
     /// Panics if `size` is 0.
     #[must_use]
     pub fn new(size: usize) -> Self {
+        Self::with_window(size, WindowFunction::Hann)
+    }
+
+    /// Create a new FFT pipeline with the given window size and function.
+    ///
+    /// # Panics
+    /// Panics if `size` is 0.
+    ///
+    /// # Example
+    /// ```
+    /// use af_audio::fft::FftPipeline;
+    /// use af_core::config::WindowFunction;
+    /// let mut fft = FftPipeline::with_window(1024, WindowFunction::Blackman);
+    /// assert_eq!(fft.process(&[0.0; 1024]).len(), 513);
+    /// ```
+    #[must_use]
+    pub fn with_window(size: usize, window_fn: WindowFunction) -> Self {
         assert!(size > 0, "FFT size must be > 0");
 
         let mut planner = RealFftPlanner::<f32>::new();
@@ -37,17 +58,10 @@ impl FftPipeline {
         let spectrum_buf = plan.make_output_vec();
         let scratch = plan.make_scratch_vec();
 
-        // Hann window (guard size==1 to avoid 0/0 NaN)
-        let window: Vec<f32> = (0..size)
-            .map(|i| {
-                if size <= 1 {
-                    1.0
-                } else {
-                    0.5 * (1.0
-                        - (2.0 * std::f32::consts::PI * i as f32 / (size as f32 - 1.0)).cos())
-                }
-            })
-            .collect();
+        let window = window_coefficients(size, window_fn);
+        let hann_sum: f32 = window_coefficients(size, WindowFunction::Hann).iter().sum();
+        let window_sum: f32 = window.iter().sum();
+        let scale = size as f32 * window_sum / hann_sum.max(f32::EPSILON);
 
         let magnitude_buf = vec![0.0; spectrum_buf.len()];
 
@@ -59,6 +73,7 @@ impl FftPipeline {
             plan,
             magnitude_buf,
             window,
+            scale,
         }
     }
 
@@ -101,7 +116,7 @@ impl FftPipeline {
         }
 
         // Magnitude (Zero-Allocation)
-        let scale = self.fft_size as f32;
+        let scale = self.scale;
         let it = self.spectrum_buf.iter().zip(self.magnitude_buf.iter_mut());
         for (c, mag) in it {
             *mag = (c.re * c.re + c.im * c.im).sqrt() / scale;
@@ -116,3 +131,21 @@ impl FftPipeline {
         self.fft_size
     }
 }
+
+/// Symmetric window of `size` coefficients (guard size==1 to avoid 0/0 NaN).
+fn window_coefficients(size: usize, window_fn: WindowFunction) -> Vec<f32> {
+    (0..size)
+        .map(|i| {
+            if size <= 1 {
+                return 1.0;
+            }
+            let x = 2.0 * std::f32::consts::PI * i as f32 / (size as f32 - 1.0);
+            match window_fn {
+                WindowFunction::Hann => 0.5 * (1.0 - x.cos()),
+                WindowFunction::Hamming => 0.54 - 0.46 * x.cos(),
+                WindowFunction::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                WindowFunction::Rectangular => 1.0,
+            }
+        })
+        .collect()
+}
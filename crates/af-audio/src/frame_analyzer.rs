@@ -0,0 +1,104 @@
+use af_core::config::AnalysisConfig;
+use af_core::frame::AudioFeatures;
+
+use crate::beat::BeatDetector;
+use crate::drums::DrumOnsets;
+use crate::features::{BandLayout, extract_features_with};
+use crate::fft::FftPipeline;
+use crate::harmony::HarmonyAnalyzer;
+use crate::mfcc::MelFilterbank;
+
+/// MFCC coefficient 1 normalization divisor.
+/// Maps typical MFCC[1] range [-25, 25] to centered [0, 1] via `(mfcc[1] / SCALE + 0.5)`.
+const MFCC_BRIGHTNESS_SCALE: f32 = 50.0;
+
+/// MFCC coefficient 2 normalization divisor.
+/// Maps typical |MFCC[2]| range [0, 15] to [0, 1] via `(mfcc[2].abs() / SCALE)`.
+const MFCC_ROUGHNESS_SCALE: f32 = 30.0;
+
+/// Décroissance par fenêtre de `onset_envelope` (strobe).
+const ONSET_ENVELOPE_DECAY: f32 = 0.85;
+
+/// Extraction de features d'une suite de fenêtres FFT, commune à l'analyse
+/// live (capture, fichier), aux stems et au [`crate::batch_analyzer::BatchAnalyzer`].
+///
+/// Par fenêtre : FFT, bandes et descripteurs spectraux, flux / onsets / grille
+/// de tempo ([`BeatDetector`]), onsets de batterie, chroma / pitch / tonalité,
+/// MFCC et `onset_envelope`. Ni lissage, ni sonie, ni normalisation : ils
+/// dépendent du chemin (temps réel ou morceau entier).
+///
+/// # Example
+/// ```
+/// use af_audio::frame_analyzer::FrameAnalyzer;
+/// use af_core::config::AnalysisConfig;
+/// let mut analyzer = FrameAnalyzer::new(44100, &AnalysisConfig::default());
+/// let features = analyzer.analyze(&vec![0.0; 2048], 30.0);
+/// assert!(!features.onset);
+/// ```
+pub struct FrameAnalyzer {
+    fft: FftPipeline,
+    layout: BandLayout,
+    beat: BeatDetector,
+    drums: DrumOnsets,
+    harmony: HarmonyAnalyzer,
+    filterbank: MelFilterbank,
+    sample_rate: u32,
+    onset_env: f32,
+}
+
+impl FrameAnalyzer {
+    /// Analyseur avec la taille de FFT, la fenêtre et les bandes de `analysis`.
+    #[must_use]
+    pub fn new(sample_rate: u32, analysis: &AnalysisConfig) -> Self {
+        let fft_size = analysis.fft_size;
+        Self {
+            fft: FftPipeline::with_window(fft_size, analysis.window),
+            layout: BandLayout::from_config(analysis),
+            beat: BeatDetector::new(),
+            drums: DrumOnsets::new(),
+            harmony: HarmonyAnalyzer::new(fft_size, sample_rate),
+            filterbank: MelFilterbank::new(fft_size, sample_rate),
+            sample_rate,
+            onset_env: 0.0,
+        }
+    }
+
+    /// Taille de la fenêtre FFT.
+    #[must_use]
+    pub fn fft_size(&self) -> usize {
+        self.fft.fft_size()
+    }
+
+    /// Analyse la fenêtre suivante, `fps` fenêtres par seconde. Une fenêtre
+    /// plus courte que la FFT est complétée par des zéros.
+    pub fn analyze(&mut self, window: &[f32], fps: f32) -> AudioFeatures {
+        let spectrum = self.fft.process(window);
+        let mut feats = extract_features_with(window, spectrum, self.sample_rate, &self.layout);
+
+        let (onset, intensity, _, _, flux) = self.beat.process(spectrum, fps);
+        feats.onset = onset;
+        feats.beat_intensity = intensity;
+        feats.spectral_flux = flux;
+        self.beat.tempo().write_to(&mut feats);
+        self.drums
+            .process(spectrum, self.sample_rate, fps)
+            .write_to(&mut feats);
+        self.harmony.process(spectrum, fps).write_to(&mut feats);
+
+        // onset_envelope: strobe-style decay
+        if onset {
+            self.onset_env = 1.0;
+        } else {
+            self.onset_env *= ONSET_ENVELOPE_DECAY;
+        }
+        feats.onset_envelope = self.onset_env;
+
+        // MFCC timbral features
+        let mfcc = self.filterbank.compute(spectrum);
+        feats.mfcc = mfcc;
+        feats.timbral_brightness = (mfcc[1] / MFCC_BRIGHTNESS_SCALE + 0.5).clamp(0.0, 1.0);
+        feats.timbral_roughness = (mfcc[2].abs() / MFCC_ROUGHNESS_SCALE).clamp(0.0, 1.0);
+
+        feats
+    }
+}
@@ -0,0 +1,285 @@
+//! Harmonic features: chromagram, fundamental frequency, running key.
+//!
+//! Computed from the magnitudes of the existing [`crate::fft::FftPipeline`]:
+//! no extra transform. The chromagram folds 55 Hz – 5 kHz onto 12 pitch
+//! classes; the fundamental is the candidate maximising a weighted harmonic
+//! sum; the key correlates a slow average of the chroma with the
+//! Krumhansl–Kessler major and minor profiles.
+
+use af_core::frame::AudioFeatures;
+
+/// Chroma range, in Hz.
+const CHROMA_MIN_HZ: f32 = 55.0;
+const CHROMA_MAX_HZ: f32 = 5000.0;
+/// Fundamental search range, in Hz (see `af_core::frame::PITCH_MIN_HZ`).
+const PITCH_MIN_HZ: f32 = af_core::frame::PITCH_MIN_HZ;
+const PITCH_MAX_HZ: f32 = af_core::frame::PITCH_MAX_HZ;
+/// Harmonics summed per fundamental candidate, and their weight decay.
+const HARMONICS: usize = 5;
+const HARMONIC_DECAY: f32 = 0.8;
+/// Time constant of the chroma average used for the key, in seconds.
+const KEY_WINDOW_SECS: f32 = 10.0;
+
+/// Krumhansl–Kessler key profiles, tonic first.
+const MAJOR_PROFILE: [f32; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f32; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Harmonic features of one frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Harmony {
+    /// Energy per pitch class (C = 0), normalized to a maximum of 1.0.
+    pub chroma: [f32; 12],
+    /// Fundamental frequency in Hz (0.0 when silent).
+    pub pitch: f32,
+    /// Share of the energy explained by the harmonics of `pitch` [0.0, 1.0].
+    pub pitch_confidence: f32,
+    /// Tonic pitch class of the running key (C = 0).
+    pub key: u8,
+    /// Running key is minor.
+    pub key_minor: bool,
+    /// Correlation of the average chroma with the key profile [0.0, 1.0].
+    pub key_confidence: f32,
+}
+
+impl Harmony {
+    /// Copy the harmonic features into `features`.
+    pub fn write_to(&self, features: &mut AudioFeatures) {
+        features.chroma = self.chroma;
+        features.pitch = self.pitch;
+        features.pitch_confidence = self.pitch_confidence;
+        features.key = self.key;
+        features.key_minor = self.key_minor;
+        features.key_confidence = self.key_confidence;
+    }
+}
+
+/// Chroma, pitch and key analysis over FFT magnitude spectra.
+///
+/// # Example
+/// ```
+/// use af_audio::harmony::HarmonyAnalyzer;
+/// let mut harmony = HarmonyAnalyzer::new(2048, 44100);
+/// let h = harmony.process(&vec![0.0; 1025], 60.0);
+/// assert!(h.pitch.abs() < f32::EPSILON);
+/// ```
+pub struct HarmonyAnalyzer {
+    bin_hz: f32,
+    /// Pitch class of each bin inside the chroma range.
+    bin_class: Vec<Option<u8>>,
+    /// Slow chroma average for the key estimate.
+    key_chroma: [f32; 12],
+    state: Harmony,
+}
+
+impl HarmonyAnalyzer {
+    /// Analyzer for spectra of `fft_size / 2 + 1` bins at `sample_rate`.
+    #[must_use]
+    pub fn new(fft_size: usize, sample_rate: u32) -> Self {
+        let bins = fft_size / 2 + 1;
+        let bin_hz = sample_rate as f32 / fft_size.max(1) as f32;
+        let bin_class = (0..bins)
+            .map(|i| {
+                let hz = i as f32 * bin_hz;
+                (CHROMA_MIN_HZ..=CHROMA_MAX_HZ).contains(&hz).then(|| {
+                    let midi = 69.0 + 12.0 * (hz / 440.0).log2();
+                    (midi.round() as i32).rem_euclid(12) as u8
+                })
+            })
+            .collect();
+        Self {
+            bin_hz,
+            bin_class,
+            key_chroma: [0.0; 12],
+            state: Harmony::default(),
+        }
+    }
+
+    /// Analyse one magnitude spectrum; `fps` sets the key averaging rate.
+    pub fn process(&mut self, spectrum: &[f32], fps: f32) -> Harmony {
+        let mut chroma = [0.0f32; 12];
+        for (mag, class) in spectrum.iter().zip(&self.bin_class) {
+            if let Some(c) = class {
+                chroma[usize::from(*c)] += mag * mag;
+            }
+        }
+        let max = chroma.iter().copied().fold(0.0f32, f32::max);
+        if max > 1e-12 {
+            for c in &mut chroma {
+                *c /= max;
+            }
+        } else {
+            chroma = [0.0; 12];
+        }
+
+        let (pitch, pitch_confidence) = self.fundamental(spectrum);
+
+        // Key: slow chroma average against the 24 rotated profiles
+        let alpha = 1.0 - (-1.0 / (KEY_WINDOW_SECS * fps.max(1.0))).exp();
+        for (avg, c) in self.key_chroma.iter_mut().zip(chroma) {
+            *avg += alpha * (c - *avg);
+        }
+        if self.key_chroma.iter().sum::<f32>() > 1e-6 {
+            let mut best = (0.0f32, 0u8, false);
+            for tonic in 0..12u8 {
+                for (minor, profile) in [(false, &MAJOR_PROFILE), (true, &MINOR_PROFILE)] {
+                    let r = correlation(&self.key_chroma, profile, usize::from(tonic));
+                    if r > best.0 {
+                        best = (r, tonic, minor);
+                    }
+                }
+            }
+            self.state.key_confidence = best.0.clamp(0.0, 1.0);
+            self.state.key = best.1;
+            self.state.key_minor = best.2;
+        }
+
+        self.state.chroma = chroma;
+        self.state.pitch = pitch;
+        self.state.pitch_confidence = pitch_confidence;
+        self.state
+    }
+
+    /// Weighted harmonic sum over the candidates in `PITCH_MIN_HZ..PITCH_MAX_HZ`,
+    /// refined by parabolic interpolation. Returns `(hz, confidence)`.
+    fn fundamental(&self, spectrum: &[f32]) -> (f32, f32) {
+        let n = spectrum.len();
+        let lo = ((PITCH_MIN_HZ / self.bin_hz).ceil() as usize).max(1);
+        let hi = ((PITCH_MAX_HZ / self.bin_hz).floor() as usize).min(n.saturating_sub(2));
+        if lo >= hi {
+            return (0.0, 0.0);
+        }
+        // Harmonic k of bin b, tolerant of the k·δ drift of an off-bin fundamental
+        let partial = |b: usize, k: usize| -> f32 {
+            let c = b * k;
+            if k == 1 {
+                return spectrum[c];
+            }
+            spectrum[c.saturating_sub(1)..(c + 2).min(n)]
+                .iter()
+                .copied()
+                .fold(0.0, f32::max)
+        };
+        let salience = |b: usize| -> f32 {
+            (1..=HARMONICS)
+                .take_while(|&k| b * k < n)
+                .map(|k| HARMONIC_DECAY.powf((k - 1) as f32) * partial(b, k))
+                .sum()
+        };
+        let Some(best) = (lo..=hi).max_by(|&a, &b| salience(a).total_cmp(&salience(b))) else {
+            return (0.0, 0.0);
+        };
+
+        let top = ((CHROMA_MAX_HZ / self.bin_hz) as usize).min(n);
+        let total: f32 = spectrum[lo.min(top)..top].iter().map(|m| m * m).sum();
+        if total <= 1e-12 {
+            return (0.0, 0.0);
+        }
+        let harmonic: f32 = (1..=HARMONICS)
+            .take_while(|&k| best * k < n)
+            .map(|k| {
+                let p = partial(best, k);
+                p * p
+            })
+            .sum();
+
+        let (l, c, r) = (spectrum[best - 1], spectrum[best], spectrum[best + 1]);
+        let denom = l - 2.0 * c + r;
+        let offset = if denom.abs() > 1e-12 {
+            (0.5 * (l - r) / denom).clamp(-0.5, 0.5)
+        } else {
+            0.0
+        };
+        (
+            (best as f32 + offset) * self.bin_hz,
+            (harmonic / total).clamp(0.0, 1.0),
+        )
+    }
+}
+
+/// Pearson correlation of `chroma` with `profile` rotated to `tonic`.
+fn correlation(chroma: &[f32; 12], profile: &[f32; 12], tonic: usize) -> f32 {
+    let mean_c = chroma.iter().sum::<f32>() / 12.0;
+    let mean_p = profile.iter().sum::<f32>() / 12.0;
+    let (mut num, mut var_c, mut var_p) = (0.0f32, 0.0f32, 0.0f32);
+    for (i, &c) in chroma.iter().enumerate() {
+        let p = profile[(i + 12 - tonic) % 12];
+        num += (c - mean_c) * (p - mean_p);
+        var_c += (c - mean_c) * (c - mean_c);
+        var_p += (p - mean_p) * (p - mean_p);
+    }
+    let denom = (var_c * var_p).sqrt();
+    if denom > 1e-12 { num / denom } else { 0.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fft::FftPipeline;
+
+    const SR: u32 = 44100;
+
+    /// Sum of harmonic tones, each with 4 partials of decreasing amplitude.
+    fn tones(freqs: &[f32]) -> Vec<f32> {
+        (0..2048)
+            .map(|i| {
+                let t = i as f32 / SR as f32;
+                freqs
+                    .iter()
+                    .flat_map(|&f| {
+                        (1..=4).map(move |k| {
+                            (std::f32::consts::TAU * f * k as f32 * t).sin() * 0.3 / k as f32
+                        })
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_fundamental_and_chroma() {
+        let mut fft = FftPipeline::new(2048);
+        let mut harmony = HarmonyAnalyzer::new(2048, SR);
+        let h = harmony.process(fft.process(&tones(&[220.0])), 60.0);
+        assert!((h.pitch - 220.0).abs() < 8.0, "pitch = {}", h.pitch);
+        assert!(
+            h.pitch_confidence > 0.5,
+            "confidence = {}",
+            h.pitch_confidence
+        );
+        // A = pitch class 9
+        let top = h
+            .chroma
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(i, _)| i);
+        assert_eq!(top, Some(9));
+    }
+
+    #[test]
+    fn estimates_key_from_triads() {
+        let mut fft = FftPipeline::new(2048);
+        let mut harmony = HarmonyAnalyzer::new(2048, SR);
+        // A minor: A C E, then E G# B (dominant), alternating
+        let am = tones(&[220.0, 261.63, 329.63]);
+        let e = tones(&[164.81, 207.65, 246.94]);
+        let mut h = Harmony::default();
+        for i in 0..600 {
+            let frame = if (i / 30) % 3 == 2 { &e } else { &am };
+            h = harmony.process(fft.process(frame), 60.0);
+        }
+        assert_eq!((h.key, h.key_minor), (9, true), "key = {h:?}");
+        assert!(h.key_confidence > 0.5);
+    }
+
+    #[test]
+    fn silence_has_no_pitch() {
+        let mut harmony = HarmonyAnalyzer::new(2048, SR);
+        let h = harmony.process(&[0.0; 1025], 60.0);
+        assert_eq!(h, Harmony::default());
+    }
+}
@@ -4,8 +4,15 @@ pub mod batch_analyzer;
 pub mod beat;
 pub mod capture;
 pub mod decode;
+pub mod drums;
 pub mod features;
 pub mod fft;
+pub mod frame_analyzer;
+pub mod harmony;
+pub mod loudness;
 pub mod mfcc;
+pub mod network;
+pub mod pcm;
+pub mod segmentation;
 pub mod smoothing;
 pub mod state;
@@ -0,0 +1,450 @@
+//! Loudness metering (EBU R128 / ITU-R BS.1770) and automatic gain control.
+//!
+//! [`LoudnessMeter`] K-weights the analysed mono signal and integrates it
+//! over the momentary (400 ms) and short-term (3 s) windows, in 100 ms
+//! blocks; the true peak comes from 4× oversampling. [`AutoGain`] follows
+//! the short-term loudness towards a target so quiet and loud rooms give
+//! comparable band levels, without pushing the true peak above −1 dBTP.
+
+use std::collections::VecDeque;
+
+use af_core::frame::AudioFeatures;
+
+/// Floor of every level reported by the meter (see `af_core::frame::LOUDNESS_FLOOR_LUFS`).
+pub const LOUDNESS_FLOOR_LUFS: f32 = af_core::frame::LOUDNESS_FLOOR_LUFS;
+/// Peak-to-loudness ratio mapped to `dynamic_range` = 1.0, in dB.
+const DYNAMIC_RANGE_MAX_DB: f32 = 24.0;
+/// Block length of the meter, in seconds.
+const BLOCK_SECS: f32 = 0.1;
+/// Blocks per momentary / short-term window.
+const MOMENTARY_BLOCKS: usize = 4;
+const SHORT_TERM_BLOCKS: usize = 30;
+/// Taps per phase of the true-peak interpolator.
+const TP_TAPS: usize = 12;
+const TP_PHASES: usize = 4;
+
+/// Gain range of the AGC, in dB.
+const AGC_MIN_DB: f32 = -12.0;
+const AGC_MAX_DB: f32 = 24.0;
+/// Below this short-term loudness the AGC holds its gain (no boost of room noise).
+const AGC_GATE_LUFS: f32 = -50.0;
+/// Ceiling of the true peak after AGC gain, in dBTP.
+const AGC_CEILING_DBTP: f32 = -1.0;
+/// Time constants of the gain: fast down, slow up.
+const AGC_ATTACK_SECS: f32 = 0.5;
+const AGC_RELEASE_SECS: f32 = 3.0;
+
+/// Levels of the signal at the end of the last processed block.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Loudness {
+    /// Momentary loudness (400 ms), in LUFS.
+    pub momentary_lufs: f32,
+    /// Short-term loudness (3 s), in LUFS.
+    pub short_term_lufs: f32,
+    /// Highest true peak over the short-term window, in dBTP.
+    pub true_peak_dbtp: f32,
+}
+
+impl Default for Loudness {
+    fn default() -> Self {
+        Self {
+            momentary_lufs: LOUDNESS_FLOOR_LUFS,
+            short_term_lufs: LOUDNESS_FLOOR_LUFS,
+            true_peak_dbtp: LOUDNESS_FLOOR_LUFS,
+        }
+    }
+}
+
+impl Loudness {
+    /// Peak-to-loudness ratio (true peak − short-term loudness) over
+    /// 0–24 dB, normalized to [0.0, 1.0]: low for dense masters, high for
+    /// dynamic material. 0.0 when silent.
+    #[must_use]
+    pub fn dynamic_range(&self) -> f32 {
+        if self.short_term_lufs <= LOUDNESS_FLOOR_LUFS {
+            return 0.0;
+        }
+        ((self.true_peak_dbtp - self.short_term_lufs) / DYNAMIC_RANGE_MAX_DB).clamp(0.0, 1.0)
+    }
+
+    /// Copy the levels into `features`.
+    pub fn write_to(&self, features: &mut AudioFeatures) {
+        features.loudness_lufs = self.momentary_lufs;
+        features.true_peak = self.true_peak_dbtp;
+        features.dynamic_range = self.dynamic_range();
+    }
+}
+
+/// Transposed direct form II biquad.
+#[derive(Clone, Copy, Debug)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// BS.1770 K-weighting (high shelf + RLB high-pass) for `sample_rate`.
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let fs = f64::from(sample_rate.max(1));
+
+    // Stage 1: high shelf, +4 dB above ~1.7 kHz
+    let k = (std::f64::consts::PI * 1_681.974_450_955_533 / fs).tan();
+    let q = 0.707_175_236_955_419_6;
+    let vh = 10f64.powf(3.999_843_853_973_347 / 20.0);
+    let vb = vh.powf(0.499_666_774_154_541_6);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    // Stage 2: RLB high-pass at ~38 Hz
+    let k = (std::f64::consts::PI * 38.135_470_876_024_44 / fs).tan();
+    let q = 0.500_327_037_323_877_3;
+    let a0 = 1.0 + k / q + k * k;
+    let highpass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    [shelf, highpass]
+}
+
+/// Windowed-sinc coefficients of the 4× true-peak interpolator: phase `p`
+/// estimates the signal `p / 4` sample after the centre of the history.
+fn true_peak_kernel() -> [[f32; TP_TAPS]; TP_PHASES] {
+    let half = TP_TAPS as f32 / 2.0;
+    let mut kernel = [[0.0; TP_TAPS]; TP_PHASES];
+    for (p, phase) in kernel.iter_mut().enumerate() {
+        for (k, tap) in phase.iter_mut().enumerate() {
+            let u = half - k as f32 - p as f32 / TP_PHASES as f32;
+            let sinc = if u.abs() < 1e-6 {
+                1.0
+            } else {
+                (std::f32::consts::PI * u).sin() / (std::f32::consts::PI * u)
+            };
+            let window = 0.5 * (1.0 + (std::f32::consts::PI * u / (half + 0.5)).cos());
+            *tap = sinc * window;
+        }
+    }
+    kernel
+}
+
+fn to_lufs(mean_square: f64) -> f32 {
+    if mean_square <= 0.0 {
+        return LOUDNESS_FLOOR_LUFS;
+    }
+    ((-0.691 + 10.0 * mean_square.log10()) as f32).max(LOUDNESS_FLOOR_LUFS)
+}
+
+fn to_db(peak: f32) -> f32 {
+    if peak <= 0.0 {
+        return LOUDNESS_FLOOR_LUFS;
+    }
+    (20.0 * peak.log10()).max(LOUDNESS_FLOOR_LUFS)
+}
+
+/// EBU R128 momentary / short-term loudness and true peak of a mono signal.
+///
+/// # Example
+/// ```
+/// use af_audio::loudness::LoudnessMeter;
+/// let mut meter = LoudnessMeter::new(48000);
+/// // Full-scale 1 kHz sine: −3.0 dB RMS, −0.7 dB K-weighting offset
+/// let sine: Vec<f32> = (0..48000)
+///     .map(|i| (std::f32::consts::TAU * 1000.0 * i as f32 / 48000.0).sin())
+///     .collect();
+/// let l = meter.process(&sine);
+/// assert!((l.short_term_lufs + 3.0).abs() < 0.5, "{l:?}");
+/// ```
+pub struct LoudnessMeter {
+    filters: [Biquad; 2],
+    block_len: usize,
+    /// K-weighted energy and true peak of the block being filled.
+    block_energy: f64,
+    block_peak: f32,
+    block_fill: usize,
+    /// Completed blocks, most recent last: (energy, samples, peak).
+    blocks: VecDeque<(f64, usize, f32)>,
+    kernel: [[f32; TP_TAPS]; TP_PHASES],
+    history: [f32; TP_TAPS],
+    /// Playback position reached by [`Self::process_to`].
+    read_pos: Option<usize>,
+    level: Loudness,
+}
+
+impl LoudnessMeter {
+    /// Meter for a signal at `sample_rate`.
+    #[must_use]
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            filters: k_weighting(sample_rate),
+            block_len: ((sample_rate as f32 * BLOCK_SECS) as usize).max(1),
+            block_energy: 0.0,
+            block_peak: 0.0,
+            block_fill: 0,
+            blocks: VecDeque::with_capacity(SHORT_TERM_BLOCKS + 1),
+            kernel: true_peak_kernel(),
+            history: [0.0; TP_TAPS],
+            read_pos: None,
+            level: Loudness::default(),
+        }
+    }
+
+    /// Forget the signal (after a seek).
+    pub fn reset(&mut self) {
+        for f in &mut self.filters {
+            f.z = [0.0; 2];
+        }
+        self.block_energy = 0.0;
+        self.block_peak = 0.0;
+        self.block_fill = 0;
+        self.blocks.clear();
+        self.history = [0.0; TP_TAPS];
+        self.level = Loudness::default();
+    }
+
+    /// Levels after the last processed sample.
+    #[must_use]
+    pub fn level(&self) -> Loudness {
+        self.level
+    }
+
+    /// Feed consecutive samples and return the updated levels.
+    pub fn process(&mut self, samples: &[f32]) -> Loudness {
+        self.process_scaled(samples, 1.0)
+    }
+
+    /// Feed the samples of a decoded buffer played up to `pos`, scaled by
+    /// `gain`: only the samples since the previous call, or the last 3 s
+    /// after a seek or a loop.
+    pub fn process_to(&mut self, samples: &[f32], pos: usize, gain: f32) -> Loudness {
+        let pos = pos.min(samples.len());
+        let window = self.block_len * SHORT_TERM_BLOCKS;
+        let start = match self.read_pos {
+            Some(prev) if prev <= pos && pos - prev <= window => prev,
+            _ => {
+                self.reset();
+                pos.saturating_sub(window)
+            }
+        };
+        self.read_pos = Some(pos);
+        self.process_scaled(&samples[start..pos], gain)
+    }
+
+    fn process_scaled(&mut self, samples: &[f32], gain: f32) -> Loudness {
+        for &s in samples {
+            let x = s * gain;
+
+            let mut y = f64::from(x);
+            for f in &mut self.filters {
+                y = f.process(y);
+            }
+            self.block_energy += y * y;
+
+            self.history.rotate_left(1);
+            self.history[TP_TAPS - 1] = x;
+            let mut peak = self.history[TP_TAPS / 2].abs();
+            for phase in &self.kernel[1..] {
+                let v: f32 = phase.iter().zip(&self.history).map(|(c, h)| c * h).sum();
+                peak = peak.max(v.abs());
+            }
+            self.block_peak = self.block_peak.max(peak);
+
+            self.block_fill += 1;
+            if self.block_fill == self.block_len {
+                self.blocks
+                    .push_back((self.block_energy, self.block_fill, self.block_peak));
+                if self.blocks.len() > SHORT_TERM_BLOCKS {
+                    self.blocks.pop_front();
+                }
+                self.block_energy = 0.0;
+                self.block_peak = 0.0;
+                self.block_fill = 0;
+            }
+        }
+        if !samples.is_empty() {
+            self.update_level();
+        }
+        self.level
+    }
+
+    /// Recompute the levels from the completed blocks plus the partial one,
+    /// so they move every call rather than every 100 ms.
+    fn update_level(&mut self) {
+        let window = |count: usize| {
+            let (energy, len, peak) = self
+                .blocks
+                .iter()
+                .rev()
+                .take(count)
+                .fold((0.0f64, 0usize, 0.0f32), |(e, n, p), &(be, bn, bp)| {
+                    (e + be, n + bn, p.max(bp))
+                });
+            let energy = energy + self.block_energy;
+            let len = len + self.block_fill;
+            (energy / len.max(1) as f64, peak.max(self.block_peak))
+        };
+        let (momentary, _) = window(MOMENTARY_BLOCKS);
+        let (short_term, peak) = window(SHORT_TERM_BLOCKS);
+        self.level = Loudness {
+            momentary_lufs: to_lufs(momentary),
+            short_term_lufs: to_lufs(short_term),
+            true_peak_dbtp: to_db(peak),
+        };
+    }
+}
+
+/// Adaptive input gain following the short-term loudness towards a target.
+///
+/// # Example
+/// ```
+/// use af_audio::loudness::{AutoGain, Loudness};
+/// let mut agc = AutoGain::new(44100, -16.0);
+/// let quiet = Loudness { momentary_lufs: -40.0, short_term_lufs: -40.0, true_peak_dbtp: -30.0 };
+/// let mut gain = 1.0;
+/// for _ in 0..600 {
+///     gain = agc.update(&quiet, 735);
+/// }
+/// assert!(gain > 10.0); // ≈ +24 dB
+/// ```
+pub struct AutoGain {
+    target_lufs: f32,
+    sample_rate: f32,
+    gain_db: f32,
+}
+
+impl AutoGain {
+    /// AGC towards `target_lufs` for a signal at `sample_rate`.
+    #[must_use]
+    pub fn new(sample_rate: u32, target_lufs: f32) -> Self {
+        Self {
+            target_lufs,
+            sample_rate: sample_rate.max(1) as f32,
+            gain_db: 0.0,
+        }
+    }
+
+    /// Current gain, linear.
+    #[must_use]
+    pub fn gain(&self) -> f32 {
+        10f32.powf(self.gain_db / 20.0)
+    }
+
+    /// Move the gain after `samples` new samples measured at `level` (before
+    /// AGC) and return it, linear. Holds below the −50 LUFS gate.
+    pub fn update(&mut self, level: &Loudness, samples: usize) -> f32 {
+        if level.short_term_lufs > AGC_GATE_LUFS {
+            let desired = (self.target_lufs - level.short_term_lufs)
+                .clamp(AGC_MIN_DB, AGC_MAX_DB)
+                .min(AGC_CEILING_DBTP - level.true_peak_dbtp);
+            let tau = if desired < self.gain_db {
+                AGC_ATTACK_SECS
+            } else {
+                AGC_RELEASE_SECS
+            };
+            let alpha = 1.0 - (-(samples as f32) / (tau * self.sample_rate)).exp();
+            self.gain_db += alpha * (desired - self.gain_db);
+        }
+        self.gain()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(freq: f32, amplitude: f32, sample_rate: u32, secs: f32) -> Vec<f32> {
+        (0..(sample_rate as f32 * secs) as usize)
+            .map(|i| {
+                amplitude * (std::f32::consts::TAU * freq * i as f32 / sample_rate as f32).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn level_follows_amplitude() {
+        let mut meter = LoudnessMeter::new(48000);
+        let loud = meter.process(&sine(1000.0, 0.5, 48000, 3.0));
+        meter.reset();
+        let quiet = meter.process(&sine(1000.0, 0.05, 48000, 3.0));
+        // −20 dB amplitude → −20 LU
+        let diff = loud.short_term_lufs - quiet.short_term_lufs;
+        assert!((diff - 20.0).abs() < 0.5, "diff = {diff}");
+        assert!(quiet.dynamic_range() > 0.0 && quiet.dynamic_range() < 0.3);
+
+        meter.reset();
+        let silence = meter.process(&vec![0.0; 48000]);
+        assert_eq!(silence, Loudness::default());
+        assert!(silence.dynamic_range().abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn k_weighting_cuts_low_end() {
+        let mut meter = LoudnessMeter::new(48000);
+        let mid = meter.process(&sine(1000.0, 0.5, 48000, 3.0));
+        meter.reset();
+        let low = meter.process(&sine(20.0, 0.5, 48000, 3.0));
+        assert!(mid.short_term_lufs - low.short_term_lufs > 10.0);
+    }
+
+    #[test]
+    fn true_peak_catches_inter_sample_peaks() {
+        // Sine at fs/4 sampled 45° off its crest: sample peak is −3 dB
+        let samples: Vec<f32> = (0..48000)
+            .map(|i| (std::f32::consts::FRAC_PI_2 * i as f32 + std::f32::consts::FRAC_PI_4).sin())
+            .collect();
+        let sample_peak = samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        let mut meter = LoudnessMeter::new(48000);
+        let level = meter.process(&samples);
+        assert!(to_db(sample_peak) < -2.5);
+        assert!(level.true_peak_dbtp > -1.0, "{level:?}");
+    }
+
+    #[test]
+    fn process_to_restarts_after_seek() {
+        let signal = sine(1000.0, 0.5, 48000, 10.0);
+        let mut meter = LoudnessMeter::new(48000);
+        let a = meter.process_to(&signal, 48000, 1.0);
+        let b = meter.process_to(&signal, 96000, 1.0);
+        assert!((a.short_term_lufs - b.short_term_lufs).abs() < 0.5);
+        // Jump back: the meter restarts from the last 3 s before the position
+        let c = meter.process_to(&signal, 240_000, 0.1);
+        assert!((b.short_term_lufs - c.short_term_lufs - 20.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn agc_brings_rooms_together() {
+        let sr = 44100;
+        let mut levels = Vec::new();
+        for amplitude in [0.02, 0.8] {
+            let mut meter = LoudnessMeter::new(sr);
+            let mut agc = AutoGain::new(sr, -16.0);
+            let signal = sine(200.0, amplitude, sr, 20.0);
+            let mut out = LoudnessMeter::new(sr);
+            let mut level = Loudness::default();
+            for chunk in signal.chunks(735) {
+                let gain = agc.update(&meter.process(chunk), chunk.len());
+                let scaled: Vec<f32> = chunk.iter().map(|s| s * gain).collect();
+                level = out.process(&scaled);
+            }
+            levels.push(level.short_term_lufs);
+        }
+        assert!((levels[0] - levels[1]).abs() < 2.0, "levels = {levels:?}");
+    }
+}
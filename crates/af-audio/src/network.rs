@@ -0,0 +1,378 @@
+//! Audio features over UDP: one instance analyses, others follow.
+//!
+//! [`FeatureSender`] publishes each [`AudioFeatures`] frame as one fixed-size
+//! binary packet (`--audio-send`). [`spawn_network_thread`] is the matching
+//! audio source (`--audio net:<PORT>`): it feeds the same triple buffer as a
+//! local capture, so a secondary instance needs no microphone.
+//!
+//! Packet layout, little-endian, [`PACKET_LEN`] bytes:
+//!
+//! | Offset | Type       | Content                                        |
+//! |--------|------------|------------------------------------------------|
+//! | 0      | `[u8; 4]`  | magic `AFNF`                                   |
+//! | 4      | `u8`       | version ([`PROTOCOL_VERSION`])                 |
+//! | 5      | `u8`       | flags, see below                               |
+//! | 6      | `u32`      | sequence number (wrapping)                     |
+//! | 10     | `u8`       | key: bits 0–3 tonic, bit 4 minor               |
+//! | 11     | `[f32; 34]`| scalar features, in `AudioFeatures` field order|
+//! | 147    | `[f32; 5]` | `mfcc`                                         |
+//! | 167    | `[f32; 12]`| `chroma`                                       |
+//! | 215    | `[f32; 8]` | `custom_bands` (`[audio.analysis]` user bands) |
+//! | 247    | `[f32; 32]`| `spectrum_bands`                               |
+//!
+//! Flags: bit 0 `onset`, bits 1–2 `beat_in_bar`, bits 3–5 `kick_onset`,
+//! `snare_onset`, `hat_onset`.
+
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::Ordering;
+use std::time::{Duration, Instant};
+
+use af_core::frame::{AudioFeatures, MAX_CUSTOM_BANDS};
+use anyhow::Context;
+use triple_buffer::TripleBuffer;
+
+use crate::state::AudioThread;
+
+/// Packet magic: "Audio Features, Network Frame".
+const MAGIC: [u8; 4] = *b"AFNF";
+/// Bumped on any layout change; packets of another version are ignored.
+pub const PROTOCOL_VERSION: u8 = 6;
+const HEADER_LEN: usize = 11;
+const SCALAR_COUNT: usize = 34;
+const FLOAT_COUNT: usize = SCALAR_COUNT + 5 + 12 + MAX_CUSTOM_BANDS + 32;
+/// Size of every feature packet, in bytes.
+pub const PACKET_LEN: usize = HEADER_LEN + FLOAT_COUNT * 4;
+
+/// Without packets for this long, the receiver falls back to silence
+/// and accepts any sequence number (sender restarted).
+const STALE_AFTER: Duration = Duration::from_secs(1);
+
+/// Scalar fields in wire order. Single source of truth for encode and decode.
+fn scalars_mut(f: &mut AudioFeatures) -> [&mut f32; SCALAR_COUNT] {
+    [
+        &mut f.rms,
+        &mut f.peak,
+        &mut f.sub_bass,
+        &mut f.bass,
+        &mut f.low_mid,
+        &mut f.mid,
+        &mut f.high_mid,
+        &mut f.presence,
+        &mut f.brilliance,
+        &mut f.spectral_centroid,
+        &mut f.spectral_flux,
+        &mut f.spectral_flatness,
+        &mut f.beat_intensity,
+        &mut f.bpm,
+        &mut f.beat_phase,
+        &mut f.bar_phase,
+        &mut f.tempo_confidence,
+        &mut f.timbral_brightness,
+        &mut f.timbral_roughness,
+        &mut f.spectral_rolloff,
+        &mut f.zero_crossing_rate,
+        &mut f.onset_envelope,
+        &mut f.kick_intensity,
+        &mut f.kick_envelope,
+        &mut f.snare_intensity,
+        &mut f.snare_envelope,
+        &mut f.hat_intensity,
+        &mut f.hat_envelope,
+        &mut f.pitch,
+        &mut f.pitch_confidence,
+        &mut f.key_confidence,
+        &mut f.loudness_lufs,
+        &mut f.true_peak,
+        &mut f.dynamic_range,
+    ]
+}
+
+/// Encode one frame with sequence number `seq`.
+#[must_use]
+pub fn encode_packet(features: &AudioFeatures, seq: u32) -> [u8; PACKET_LEN] {
+    let mut f = *features;
+    let mut out = [0u8; PACKET_LEN];
+    out[..4].copy_from_slice(&MAGIC);
+    out[4] = PROTOCOL_VERSION;
+    out[5] = u8::from(f.onset)
+        | (f.beat_in_bar & 0b11) << 1
+        | u8::from(f.kick_onset) << 3
+        | u8::from(f.snare_onset) << 4
+        | u8::from(f.hat_onset) << 5;
+    out[6..10].copy_from_slice(&seq.to_le_bytes());
+    out[10] = (f.key & 0x0f) | u8::from(f.key_minor) << 4;
+
+    let floats = scalars_mut(&mut f)
+        .into_iter()
+        .map(|v| *v)
+        .chain(features.mfcc)
+        .chain(features.chroma)
+        .chain(features.custom_bands)
+        .chain(features.spectrum_bands);
+    for (chunk, v) in out[HEADER_LEN..].chunks_exact_mut(4).zip(floats) {
+        chunk.copy_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+/// Decode a packet into `(seq, features)`. Returns `None` for foreign or
+/// truncated packets and other protocol versions. Non-finite values become 0.0.
+#[must_use]
+pub fn decode_packet(bytes: &[u8]) -> Option<(u32, AudioFeatures)> {
+    if bytes.len() != PACKET_LEN || bytes[..4] != MAGIC || bytes[4] != PROTOCOL_VERSION {
+        return None;
+    }
+    let seq = u32::from_le_bytes(bytes[6..10].try_into().ok()?);
+    let mut floats = bytes[HEADER_LEN..].chunks_exact(4).map(|c| {
+        let v = f32::from_le_bytes([c[0], c[1], c[2], c[3]]);
+        if v.is_finite() { v } else { 0.0 }
+    });
+
+    let mut f = AudioFeatures {
+        onset: bytes[5] & 1 != 0,
+        beat_in_bar: (bytes[5] >> 1) & 0b11,
+        kick_onset: bytes[5] & 1 << 3 != 0,
+        snare_onset: bytes[5] & 1 << 4 != 0,
+        hat_onset: bytes[5] & 1 << 5 != 0,
+        key: (bytes[10] & 0x0f) % 12,
+        key_minor: bytes[10] & 1 << 4 != 0,
+        ..AudioFeatures::default()
+    };
+    for slot in scalars_mut(&mut f) {
+        *slot = floats.next()?;
+    }
+    for slot in f
+        .mfcc
+        .iter_mut()
+        .chain(f.chroma.iter_mut())
+        .chain(f.custom_bands.iter_mut())
+        .chain(f.spectrum_bands.iter_mut())
+    {
+        *slot = floats.next()?;
+    }
+    Some((seq, f))
+}
+
+/// `seq` follows `prev` (wrapping, within half the sequence space).
+fn is_newer(seq: u32, prev: u32) -> bool {
+    let d = seq.wrapping_sub(prev);
+    d != 0 && d < u32::MAX / 2
+}
+
+/// `"9100"` is shorthand for `0.0.0.0:9100`.
+fn resolve_addr(spec: &str) -> anyhow::Result<SocketAddr> {
+    if let Ok(port) = spec.parse::<u16>() {
+        return Ok(([0, 0, 0, 0], port).into());
+    }
+    spec.to_socket_addrs()
+        .with_context(|| format!("Adresse réseau invalide : {spec}"))?
+        .next()
+        .with_context(|| format!("Adresse réseau introuvable : {spec}"))
+}
+
+/// Publishes feature frames to one UDP target (unicast or broadcast).
+pub struct FeatureSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    seq: u32,
+}
+
+impl FeatureSender {
+    /// Open a non-blocking socket towards `target` (`HOST:PORT`).
+    /// Broadcast addresses are allowed, to drive several displays at once.
+    ///
+    /// # Errors
+    /// Returns an error if the target does not resolve or the socket cannot be bound.
+    pub fn new(target: &str) -> anyhow::Result<Self> {
+        let target = resolve_addr(target)?;
+        let bind: SocketAddr = if target.is_ipv4() {
+            ([0, 0, 0, 0], 0).into()
+        } else {
+            ([0u16; 8], 0).into()
+        };
+        let socket = UdpSocket::bind(bind).context("Audio réseau : socket d'envoi")?;
+        socket.set_nonblocking(true)?;
+        if target.is_ipv4() {
+            socket.set_broadcast(true)?;
+        }
+        log::info!("Audio réseau : envoi des features vers {target}");
+        Ok(Self {
+            socket,
+            target,
+            seq: 0,
+        })
+    }
+
+    /// Destination of the packets.
+    #[must_use]
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Send one frame. Never blocks: a full buffer or absent receiver drops it.
+    pub fn send(&mut self, features: &AudioFeatures) {
+        let packet = encode_packet(features, self.seq);
+        self.seq = self.seq.wrapping_add(1);
+        let _ = self.socket.send_to(&packet, self.target);
+    }
+}
+
+/// Spawn the network audio source: receives [`FeatureSender`] packets on
+/// `listen` (`PORT` or `ADDR:PORT`) and writes them to a triple buffer.
+///
+/// Late or duplicated packets are dropped by sequence number. After
+/// [`STALE_AFTER`] without packets, silence (`AudioFeatures::default()`) is
+/// written once so visuals do not freeze on the last frame. Dropping the
+/// returned [`AudioThread`] stops the receiver and releases the port.
+///
+/// # Errors
+/// Returns an error if the address does not resolve, the socket cannot be
+/// bound, or the thread fails to spawn.
+pub fn spawn_network_thread(
+    listen: &str,
+) -> anyhow::Result<(
+    triple_buffer::Output<AudioFeatures>,
+    SocketAddr,
+    AudioThread,
+)> {
+    let listen_addr = resolve_addr(listen)?;
+    let socket = UdpSocket::bind(listen_addr)
+        .with_context(|| format!("Audio réseau : impossible d'écouter sur {listen_addr}"))?;
+    // Le timeout borne aussi le délai de réaction au drapeau d'arrêt.
+    socket.set_read_timeout(Some(Duration::from_millis(100)))?;
+    let local_addr = socket.local_addr()?;
+
+    let (mut buf_input, buf_output) = TripleBuffer::new(&AudioFeatures::default()).split();
+
+    let thread = AudioThread::spawn("af-audio-net", move |stop| {
+        let mut buf = [0u8; 2048];
+        let mut last_seq: Option<u32> = None;
+        let mut last_packet = Instant::now();
+        let mut live = false;
+        while !stop.load(Ordering::Relaxed) {
+            match socket.recv(&mut buf) {
+                Ok(n) => {
+                    let Some((seq, features)) = decode_packet(&buf[..n]) else {
+                        continue;
+                    };
+                    let fresh = last_seq.is_none_or(|prev| {
+                        is_newer(seq, prev) || last_packet.elapsed() > STALE_AFTER
+                    });
+                    if fresh {
+                        if !live {
+                            log::info!("Audio réseau : flux reçu sur {local_addr}");
+                        }
+                        last_seq = Some(seq);
+                        last_packet = Instant::now();
+                        live = true;
+                        buf_input.write(features);
+                    }
+                }
+                Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    if live && last_packet.elapsed() > STALE_AFTER {
+                        log::warn!("Audio réseau : flux interrompu, silence");
+                        buf_input.write(AudioFeatures::default());
+                        live = false;
+                    }
+                }
+                Err(e) => {
+                    log::error!("Audio réseau : erreur de réception : {e}");
+                    return;
+                }
+            }
+        }
+    })?;
+
+    log::info!("Audio réseau : écoute sur {local_addr}");
+    Ok((buf_output, local_addr, thread))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packet_roundtrip() {
+        let mut f = AudioFeatures {
+            rms: 0.5,
+            bass: 0.75,
+            bpm: 128.0,
+            onset: true,
+            beat_in_bar: 3,
+            bar_phase: 0.875,
+            snare_onset: true,
+            hat_envelope: 0.5,
+            onset_envelope: 0.25,
+            mfcc: [1.0, -2.0, 3.0, -4.0, 5.0],
+            pitch: 220.0,
+            key: 11,
+            loudness_lufs: -14.5,
+            key_minor: true,
+            ..AudioFeatures::default()
+        };
+        f.spectrum_bands[31] = 0.125;
+        f.chroma[9] = 1.0;
+        f.custom_bands[7] = 0.625;
+
+        let packet = encode_packet(&f, 42);
+        assert_eq!(packet.len(), PACKET_LEN);
+        let (seq, back) = decode_packet(&packet).unwrap();
+        assert_eq!(seq, 42);
+        assert!(back.onset);
+        assert_eq!(back.beat_in_bar, 3);
+        assert_eq!(back.bar_phase, 0.875);
+        assert!(back.snare_onset && !back.kick_onset && !back.hat_onset);
+        assert_eq!(back.hat_envelope, 0.5);
+        assert_eq!(back.rms, 0.5);
+        assert_eq!(back.bass, 0.75);
+        assert_eq!(back.bpm, 128.0);
+        assert_eq!(back.onset_envelope, 0.25);
+        assert_eq!(back.mfcc, f.mfcc);
+        assert_eq!(back.chroma, f.chroma);
+        assert_eq!(back.custom_bands, f.custom_bands);
+        assert_eq!(back.pitch, 220.0);
+        assert_eq!(back.loudness_lufs, -14.5);
+        assert_eq!((back.key, back.key_minor), (11, true));
+        assert_eq!(back.spectrum_bands, f.spectrum_bands);
+
+        assert!(decode_packet(&packet[..PACKET_LEN - 1]).is_none());
+        let mut foreign = packet;
+        foreign[4] = PROTOCOL_VERSION + 1;
+        assert!(decode_packet(&foreign).is_none());
+    }
+
+    #[test]
+    fn sequence_wraps() {
+        assert!(is_newer(1, 0));
+        assert!(is_newer(0, u32::MAX));
+        assert!(!is_newer(5, 5));
+        assert!(!is_newer(4, 5));
+    }
+
+    #[test]
+    fn receiver_follows_sender() {
+        let (mut output, addr, _thread) = spawn_network_thread("127.0.0.1:0").unwrap();
+        let mut sender = FeatureSender::new(&addr.to_string()).unwrap();
+        let f = AudioFeatures {
+            bass: 0.5,
+            ..AudioFeatures::default()
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while output.read().bass != 0.5 {
+            assert!(Instant::now() < deadline, "no frame received");
+            sender.send(&f);
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn dropping_the_receiver_releases_the_port() {
+        let (_output, addr, thread) = spawn_network_thread("127.0.0.1:0").unwrap();
+        drop(thread);
+        let (_output, rebound, thread) = spawn_network_thread(&addr.to_string()).unwrap();
+        assert_eq!(rebound, addr);
+        assert!(!thread.is_finished());
+    }
+}
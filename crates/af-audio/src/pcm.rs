@@ -0,0 +1,233 @@
+//! Raw PCM input from stdin or a named pipe (`--audio -`, `--audio fifo:PATH`).
+//!
+//! Interleaved samples in a declared [`PcmFormat`] are decoded on a reader
+//! thread and routed like a cpal capture (see
+//! [`crate::capture::AudioCapture::start_pcm`]), so the live analysis loop
+//! is the same. Also the headless way to drive it with synthetic signals.
+
+use std::io::{ErrorKind, Read};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::capture::Router;
+
+/// Sample encoding, little-endian.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PcmEncoding {
+    /// 32-bit float, [-1.0, 1.0].
+    F32,
+    /// Signed 16-bit integer.
+    #[default]
+    S16,
+}
+
+impl PcmEncoding {
+    /// Size of one sample in bytes.
+    #[must_use]
+    pub fn sample_bytes(self) -> usize {
+        match self {
+            Self::F32 => 4,
+            Self::S16 => 2,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> f32 {
+        match self {
+            Self::F32 => {
+                let v = f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                if v.is_finite() { v } else { 0.0 }
+            }
+            Self::S16 => f32::from(i16::from_le_bytes([bytes[0], bytes[1]])) / 32768.0,
+        }
+    }
+}
+
+/// Declared layout of a raw PCM stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PcmFormat {
+    pub encoding: PcmEncoding,
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Interleaved channel count.
+    pub channels: u16,
+}
+
+impl Default for PcmFormat {
+    /// `s16:44100:2`, what `ffmpeg -f s16le -` and most tools emit.
+    fn default() -> Self {
+        Self {
+            encoding: PcmEncoding::S16,
+            sample_rate: 44100,
+            channels: 2,
+        }
+    }
+}
+
+impl FromStr for PcmFormat {
+    type Err = anyhow::Error;
+
+    /// `ENCODING:RATE:CHANNELS`, e.g. `f32:48000:1` or `s16le:44100:2`.
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.trim().split(':').collect();
+        let [encoding, rate, channels] = parts.as_slice() else {
+            anyhow::bail!("Format PCM invalide : '{s}' (attendu : ENCODAGE:FRÉQUENCE:CANAUX)");
+        };
+        let encoding = match encoding.to_ascii_lowercase().as_str() {
+            "f32" | "f32le" => PcmEncoding::F32,
+            "s16" | "s16le" => PcmEncoding::S16,
+            other => anyhow::bail!("Encodage PCM inconnu : '{other}' (f32 ou s16)"),
+        };
+        let sample_rate: u32 = rate
+            .parse()
+            .ok()
+            .filter(|r| (8000..=384_000).contains(r))
+            .ok_or_else(|| anyhow::anyhow!("Fréquence PCM invalide : '{rate}' (8000–384000)"))?;
+        let channels: u16 = channels
+            .parse()
+            .ok()
+            .filter(|c| (1..=32).contains(c))
+            .ok_or_else(|| {
+                anyhow::anyhow!("Nombre de canaux PCM invalide : '{channels}' (1–32)")
+            })?;
+        Ok(Self {
+            encoding,
+            sample_rate,
+            channels,
+        })
+    }
+}
+
+/// Where raw PCM bytes come from.
+pub enum PcmSource {
+    /// Standard input (`--audio -`).
+    Stdin,
+    /// Named pipe or file, opened on the reader thread: opening a FIFO
+    /// blocks until a writer connects.
+    Fifo(PathBuf),
+    /// Any byte stream (tests, embedding).
+    Reader(Box<dyn Read + Send>),
+}
+
+impl PcmSource {
+    /// Parse an `--audio` argument: `-` or `fifo:PATH`. `None` otherwise.
+    #[must_use]
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        if arg == "-" {
+            return Some(Self::Stdin);
+        }
+        arg.strip_prefix("fifo:")
+            .map(|path| Self::Fifo(PathBuf::from(path)))
+    }
+
+    /// Label for logs and the UI.
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self {
+            Self::Stdin => "stdin".to_string(),
+            Self::Fifo(path) => format!("fifo:{}", path.display()),
+            Self::Reader(_) => "reader".to_string(),
+        }
+    }
+
+    fn open(self) -> std::io::Result<Box<dyn Read + Send>> {
+        Ok(match self {
+            Self::Stdin => Box::new(std::io::stdin()),
+            Self::Fifo(path) => Box::new(std::fs::File::open(path)?),
+            Self::Reader(reader) => reader,
+        })
+    }
+}
+
+/// Reader thread body: decode `source` until EOF and push frames into `router`.
+///
+/// Waits for room in the ring buffers instead of dropping samples, so a
+/// writer faster than real time is slowed down rather than truncated.
+pub(crate) fn run_reader(source: PcmSource, format: PcmFormat, mut router: Router) {
+    let label = source.label();
+    let mut reader = match source.open() {
+        Ok(r) => r,
+        Err(e) => {
+            log::error!("PCM : ouverture de {label} impossible : {e}");
+            return;
+        }
+    };
+    log::info!(
+        "PCM : lecture de {label} ({:?}, {} Hz, {} ch)",
+        format.encoding,
+        format.sample_rate,
+        format.channels
+    );
+
+    let sample_bytes = format.encoding.sample_bytes();
+    let frame_bytes = sample_bytes * usize::from(format.channels);
+    let mut buf = vec![0u8; frame_bytes * 1024];
+    let mut pending = 0usize;
+    let mut frame = vec![0.0f32; usize::from(format.channels)];
+
+    loop {
+        let n = match reader.read(&mut buf[pending..]) {
+            Ok(0) => {
+                log::info!("PCM : fin du flux {label}");
+                return;
+            }
+            Ok(n) => n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => {
+                log::error!("PCM : erreur de lecture {label} : {e}");
+                return;
+            }
+        };
+        let filled = pending + n;
+        let whole = filled - filled % frame_bytes;
+        for chunk in buf[..whole].chunks_exact(frame_bytes) {
+            for (slot, bytes) in frame.iter_mut().zip(chunk.chunks_exact(sample_bytes)) {
+                *slot = format.encoding.decode(bytes);
+            }
+            while !router.has_room() {
+                if router.is_abandoned() {
+                    return;
+                }
+                std::thread::sleep(Duration::from_millis(1));
+            }
+            router.push_frame(&frame);
+        }
+        // Partial frame kept for the next read
+        buf.copy_within(whole..filled, 0);
+        pending = filled - whole;
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_format() {
+        assert_eq!(
+            "f32le:48000:1".parse::<PcmFormat>().unwrap(),
+            PcmFormat {
+                encoding: PcmEncoding::F32,
+                sample_rate: 48000,
+                channels: 1
+            }
+        );
+        assert_eq!(
+            "s16:44100:2".parse::<PcmFormat>().unwrap(),
+            PcmFormat::default()
+        );
+        assert!("s24:44100:2".parse::<PcmFormat>().is_err());
+        assert!("f32:100:2".parse::<PcmFormat>().is_err());
+        assert!("f32:48000".parse::<PcmFormat>().is_err());
+    }
+
+    #[test]
+    fn decodes_s16_extremes() {
+        assert!((PcmEncoding::S16.decode(&i16::MIN.to_le_bytes()) + 1.0).abs() < f32::EPSILON);
+        assert!(PcmEncoding::S16.decode(&[0, 0]).abs() < f32::EPSILON);
+        assert!(PcmEncoding::F32.decode(&f32::NAN.to_le_bytes()).abs() < f32::EPSILON);
+    }
+}
@@ -0,0 +1,426 @@
+//! Structural segmentation: intro, verse, chorus, build-up, drop...
+//!
+//! Offline only, over a complete [`FeatureTimeline`]. Frames are pooled into
+//! half-second blocks described by chroma, timbre (MFCC) and band energies;
+//! a checkerboard kernel slid along the diagonal of their self-similarity
+//! matrix gives a novelty curve (Foote, 2000) whose peaks are the section
+//! boundaries. Sections with similar content share a label, and each one is
+//! classified from its energy relative to the track and its trend.
+
+use af_core::feature_timeline::{FeatureTimeline, Section, SectionKind};
+use af_core::frame::AudioFeatures;
+
+/// Block length for the self-similarity matrix, in seconds.
+const BLOCK_SECS: f32 = 0.5;
+/// Half-width of the checkerboard kernel, in blocks (8 s).
+const KERNEL_HALF: usize = 16;
+/// Minimum section length, in seconds.
+const MIN_SECTION_SECS: f32 = 8.0;
+/// Boundaries move to the nearest bar start within this distance, in seconds.
+const BAR_SNAP_SECS: f32 = 1.0;
+/// Beat grid confidence required to snap on bar starts.
+const BAR_SNAP_CONFIDENCE: f32 = 0.3;
+/// Cosine similarity above which two sections share a label.
+const SAME_LABEL_SIMILARITY: f32 = 0.8;
+/// Energy rise over a section, relative to the track's section energy range,
+/// that makes it a build-up.
+const BUILD_UP_RISE: f32 = 0.3;
+/// 12 chroma + 5 MFCC + 7 bands + RMS.
+const DIMS: usize = 25;
+
+type Descriptor = [f32; DIMS];
+
+/// Split `timeline` into contiguous sections covering all its frames.
+///
+/// Expects the onset / beat grid post-processing of
+/// [`crate::batch_analyzer::BatchAnalyzer`] (bar starts come from
+/// `bar_phase`). A track shorter than two minimum sections, or without
+/// contrast, is a single `Verse`.
+///
+/// # Example
+/// ```
+/// use af_audio::segmentation::segment;
+/// use af_core::feature_timeline::FeatureTimeline;
+/// use af_core::frame::AudioFeatures;
+/// let timeline = FeatureTimeline {
+///     frames: vec![AudioFeatures::default(); 600],
+///     frame_duration: 1.0 / 60.0,
+///     sample_rate: 44100,
+///     energy_levels: vec![],
+///     sections: vec![],
+/// };
+/// let sections = segment(&timeline);
+/// assert_eq!(sections.len(), 1);
+/// assert_eq!(sections[0].end, 600);
+/// ```
+#[must_use]
+pub fn segment(timeline: &FeatureTimeline) -> Vec<Section> {
+    let frames = &timeline.frames;
+    if frames.is_empty() {
+        return Vec::new();
+    }
+    let frame_secs = timeline.frame_duration.max(1e-4);
+    let block = ((BLOCK_SECS / frame_secs).round() as usize).max(1);
+
+    let mut blocks: Vec<Descriptor> = frames.chunks(block).map(mean_descriptor).collect();
+    standardize(&mut blocks);
+
+    let min_blocks = ((MIN_SECTION_SECS / (block as f32 * frame_secs)).round() as usize).max(1);
+    let novelty = novelty(&blocks, KERNEL_HALF);
+    let mut bounds: Vec<usize> = vec![0];
+    let snap = (BAR_SNAP_SECS / frame_secs) as usize;
+    for peak in pick_peaks(&novelty, min_blocks) {
+        let frame = snap_to_bar(frames, peak * block, snap);
+        if frame > bounds[bounds.len() - 1] && frame < frames.len() {
+            bounds.push(frame);
+        }
+    }
+    bounds.push(frames.len());
+
+    // Per section: normalized mean descriptor (labels), energy, energy trend
+    let spans: Vec<(usize, usize)> = bounds.windows(2).map(|w| (w[0], w[1])).collect();
+    let profiles: Vec<Descriptor> = spans
+        .iter()
+        .map(|&(start, end)| {
+            let mut mean = [0.0; DIMS];
+            let blocks = &blocks[start / block..end.div_ceil(block).min(blocks.len())];
+            for b in blocks {
+                for (m, v) in mean.iter_mut().zip(b) {
+                    *m += v;
+                }
+            }
+            normalize(&mut mean);
+            mean
+        })
+        .collect();
+    let energies: Vec<f32> = spans
+        .iter()
+        .map(|&(start, end)| mean_rms(&frames[start..end]))
+        .collect();
+    let rises: Vec<f32> = spans
+        .iter()
+        .map(|&(start, end)| {
+            let third = ((end - start) / 3).max(1);
+            mean_rms(&frames[end - third..end]) - mean_rms(&frames[start..start + third])
+        })
+        .collect();
+
+    let labels = assign_labels(&profiles);
+    let kinds = classify(&energies, &rises);
+
+    spans
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| Section {
+            start,
+            end,
+            kind: kinds[i],
+            label: labels[i],
+            energy: energies[i],
+        })
+        .collect()
+}
+
+fn mean_descriptor(frames: &[AudioFeatures]) -> Descriptor {
+    let mut d = [0.0; DIMS];
+    for f in frames {
+        let levels = [
+            f.sub_bass,
+            f.bass,
+            f.low_mid,
+            f.mid,
+            f.high_mid,
+            f.presence,
+            f.brilliance,
+            f.rms,
+        ];
+        let values = f.chroma.iter().chain(&f.mfcc).chain(&levels);
+        for (slot, v) in d.iter_mut().zip(values) {
+            *slot += v;
+        }
+    }
+    let n = frames.len().max(1) as f32;
+    for slot in &mut d {
+        *slot /= n;
+    }
+    d
+}
+
+/// Z-score every dimension over the track (constant dimensions drop out),
+/// then scale each block to unit length: dot products are cosine similarities.
+fn standardize(blocks: &mut [Descriptor]) {
+    let n = blocks.len().max(1) as f32;
+    for dim in 0..DIMS {
+        let mean = blocks.iter().map(|b| b[dim]).sum::<f32>() / n;
+        let var = blocks.iter().map(|b| (b[dim] - mean).powi(2)).sum::<f32>() / n;
+        let std = var.sqrt();
+        for b in blocks.iter_mut() {
+            b[dim] = if std > 1e-6 {
+                (b[dim] - mean) / std
+            } else {
+                0.0
+            };
+        }
+    }
+    for b in blocks.iter_mut() {
+        normalize(b);
+    }
+}
+
+fn normalize(d: &mut Descriptor) {
+    let norm = d.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 1e-9 {
+        for v in d.iter_mut() {
+            *v /= norm;
+        }
+    }
+}
+
+fn dot(a: &Descriptor, b: &Descriptor) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Gaussian-tapered checkerboard kernel along the self-similarity diagonal.
+/// `novelty[i]` is high when blocks before `i` resemble each other, blocks
+/// from `i` on resemble each other, and the two groups differ.
+fn novelty(blocks: &[Descriptor], half: usize) -> Vec<f32> {
+    let m = blocks.len();
+    let h = half.cast_signed();
+    let sigma = half as f32 / 2.0;
+    (0..m)
+        .map(|i| {
+            let (mut sum, mut weight) = (0.0f32, 0.0f32);
+            for a in -h..h {
+                for b in -h..h {
+                    let (Some(x), Some(y)) = (i.checked_add_signed(a), i.checked_add_signed(b))
+                    else {
+                        continue;
+                    };
+                    if x >= m || y >= m {
+                        continue;
+                    }
+                    let (fa, fb) = (a as f32 + 0.5, b as f32 + 0.5);
+                    let w = (-(fa * fa + fb * fb) / (2.0 * sigma * sigma)).exp();
+                    let sign = if (a < 0) == (b < 0) { 1.0 } else { -1.0 };
+                    sum += sign * w * dot(&blocks[x], &blocks[y]);
+                    weight += w;
+                }
+            }
+            if weight > 0.0 {
+                (sum / weight).max(0.0)
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// Novelty peaks above an adaptive threshold, strongest first, at least
+/// `min_gap` blocks apart and from both ends. Returned in time order.
+fn pick_peaks(novelty: &[f32], min_gap: usize) -> Vec<usize> {
+    let m = novelty.len();
+    if m < 2 * min_gap {
+        return Vec::new();
+    }
+    let mean = novelty.iter().sum::<f32>() / m as f32;
+    let std = (novelty.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / m as f32).sqrt();
+    let threshold = (mean + 0.5 * std).max(0.05);
+
+    let mut candidates: Vec<usize> = (min_gap..=m - min_gap)
+        .filter(|&i| i < m && novelty[i] > threshold)
+        .filter(|&i| {
+            let lo = i.saturating_sub(min_gap / 2);
+            let hi = (i + min_gap / 2 + 1).min(m);
+            novelty[lo..hi].iter().all(|&v| v <= novelty[i])
+        })
+        .collect();
+    candidates.sort_by(|&a, &b| novelty[b].total_cmp(&novelty[a]));
+
+    let mut peaks: Vec<usize> = Vec::new();
+    for c in candidates {
+        if peaks.iter().all(|&p| p.abs_diff(c) >= min_gap) {
+            peaks.push(c);
+        }
+    }
+    peaks.sort_unstable();
+    peaks
+}
+
+/// Nearest bar start (`bar_phase` wrapping) within `radius` frames of
+/// `frame`, when the beat grid is confident enough.
+fn snap_to_bar(frames: &[AudioFeatures], frame: usize, radius: usize) -> usize {
+    let lo = frame.saturating_sub(radius).max(1);
+    let hi = (frame + radius).min(frames.len().saturating_sub(1));
+    (lo..=hi)
+        .filter(|&i| {
+            frames[i].tempo_confidence >= BAR_SNAP_CONFIDENCE
+                && frames[i].bar_phase + 0.5 < frames[i - 1].bar_phase
+        })
+        .min_by_key(|&i| i.abs_diff(frame))
+        .unwrap_or(frame)
+}
+
+fn mean_rms(frames: &[AudioFeatures]) -> f32 {
+    frames.iter().map(|f| f.rms).sum::<f32>() / frames.len().max(1) as f32
+}
+
+/// Greedy labelling: a section takes the label of the first earlier section
+/// it resembles, or a new one.
+fn assign_labels(profiles: &[Descriptor]) -> Vec<u8> {
+    let mut representatives: Vec<&Descriptor> = Vec::new();
+    profiles
+        .iter()
+        .map(|p| {
+            let found = representatives
+                .iter()
+                .position(|r| dot(r, p) > SAME_LABEL_SIMILARITY);
+            let label = found.unwrap_or_else(|| {
+                representatives.push(p);
+                representatives.len() - 1
+            });
+            u8::try_from(label).unwrap_or(u8::MAX)
+        })
+        .collect()
+}
+
+/// Section kinds from energies relative to the track range and energy trends.
+fn classify(energies: &[f32], rises: &[f32]) -> Vec<SectionKind> {
+    let n = energies.len();
+    let min = energies.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = energies.iter().copied().fold(0.0f32, f32::max);
+    let range = max - min;
+    if n < 2 || range < 1e-3 {
+        return vec![SectionKind::Verse; n];
+    }
+    let level = |e: f32| (e - min) / range;
+
+    let mut kinds: Vec<SectionKind> = Vec::with_capacity(n);
+    for i in 0..n {
+        let r = level(energies[i]);
+        let high = r >= 0.66;
+        let low = r < 0.33;
+        let builds = rises[i] / range > BUILD_UP_RISE && i + 1 < n && energies[i + 1] > energies[i];
+        let kind = if i == 0 && !high {
+            if builds {
+                SectionKind::BuildUp
+            } else {
+                SectionKind::Intro
+            }
+        } else if i == n - 1 && !high {
+            SectionKind::Outro
+        } else if high {
+            let after_lift =
+                i > 0 && (kinds[i - 1] == SectionKind::BuildUp || level(energies[i - 1]) < 0.33);
+            if after_lift {
+                SectionKind::Drop
+            } else {
+                SectionKind::Chorus
+            }
+        } else if builds {
+            SectionKind::BuildUp
+        } else if low {
+            SectionKind::Breakdown
+        } else {
+            SectionKind::Verse
+        };
+        kinds.push(kind);
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FPS: f32 = 60.0;
+
+    /// `secs` seconds of one steady texture: `tone` pitch class, `rms` level
+    /// (ramping from `rms.0` to `rms.1`), timbre shifted with the level.
+    fn part(secs: f32, tone: usize, rms: (f32, f32)) -> Vec<AudioFeatures> {
+        let n = (secs * FPS) as usize;
+        (0..n)
+            .map(|i| {
+                let level = rms.0 + (rms.1 - rms.0) * i as f32 / n as f32;
+                let mut f = AudioFeatures {
+                    rms: level,
+                    bass: level,
+                    brilliance: level * 0.5,
+                    mfcc: [level * 10.0, tone as f32, 0.0, 0.0, 0.0],
+                    ..AudioFeatures::default()
+                };
+                f.chroma[tone] = 1.0;
+                f
+            })
+            .collect()
+    }
+
+    fn timeline(parts: &[Vec<AudioFeatures>]) -> FeatureTimeline {
+        FeatureTimeline {
+            frames: parts.concat(),
+            frame_duration: 1.0 / FPS,
+            sample_rate: 44100,
+            energy_levels: vec![],
+            sections: vec![],
+        }
+    }
+
+    #[test]
+    fn finds_boundaries_and_repeats() {
+        let quiet = part(20.0, 0, (0.2, 0.2));
+        let loud = part(20.0, 9, (0.9, 0.9));
+        let t = timeline(&[quiet.clone(), loud.clone(), quiet, loud]);
+        let sections = segment(&t);
+
+        let starts: Vec<usize> = sections.iter().map(|s| s.start).collect();
+        assert_eq!(starts.len(), 4, "sections: {sections:?}");
+        for (start, expected) in starts.iter().zip([0.0, 20.0, 40.0, 60.0]) {
+            assert!(
+                (*start as f32 / FPS - expected).abs() <= 1.0,
+                "boundary {start} expected near {expected}s"
+            );
+        }
+        assert_eq!(sections.last().map(|s| s.end), Some(t.frames.len()));
+
+        let labels: Vec<u8> = sections.iter().map(|s| s.label).collect();
+        assert_eq!(labels, [0, 1, 0, 1]);
+        let kinds: Vec<SectionKind> = sections.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SectionKind::Intro,
+                SectionKind::Drop,
+                SectionKind::Breakdown,
+                SectionKind::Drop
+            ]
+        );
+    }
+
+    #[test]
+    fn detects_build_up_before_drop() {
+        let t = timeline(&[
+            part(16.0, 0, (0.3, 0.3)),
+            part(16.0, 4, (0.3, 0.8)),
+            part(16.0, 7, (0.9, 0.9)),
+            part(16.0, 0, (0.3, 0.3)),
+        ]);
+        let kinds: Vec<SectionKind> = segment(&t).iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SectionKind::Intro,
+                SectionKind::BuildUp,
+                SectionKind::Drop,
+                SectionKind::Outro
+            ]
+        );
+    }
+
+    #[test]
+    fn short_track_is_one_section() {
+        let t = timeline(&[part(6.0, 0, (0.2, 0.2)), part(6.0, 9, (0.9, 0.9))]);
+        let sections = segment(&t);
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].kind, SectionKind::Verse);
+        assert!(segment(&timeline(&[])).is_empty());
+    }
+}
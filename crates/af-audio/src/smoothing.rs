@@ -73,9 +73,30 @@ impl FeatureSmoother {
         // beat_intensity, onset_envelope need full amplitude on first frame for punch.
         smoothed.onset = current.onset;
         smoothed.beat_phase = current.beat_phase;
+        smoothed.bar_phase = current.bar_phase;
+        smoothed.beat_in_bar = current.beat_in_bar;
         smoothed.beat_intensity = current.beat_intensity;
         smoothed.onset_envelope = current.onset_envelope;
 
+        // Chroma: smoothed like the bands; pitch and key pass through (a glide
+        // between two notes would read as a third one)
+        for (s, (&c, &p)) in smoothed
+            .chroma
+            .iter_mut()
+            .zip(current.chroma.iter().zip(&self.prev.chroma))
+        {
+            *s = self.ar(c, p);
+        }
+
+        // User bands ([audio.analysis])
+        for (s, (&c, &p)) in smoothed
+            .custom_bands
+            .iter_mut()
+            .zip(current.custom_bands.iter().zip(&self.prev.custom_bands))
+        {
+            *s = self.ar(c, p);
+        }
+
         // Spectrum bands
         for i in 0..32 {
             smoothed.spectrum_bands[i] =
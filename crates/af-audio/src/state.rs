@@ -1,20 +1,20 @@
-/// MFCC coefficient 1 normalization divisor.
-/// Maps typical MFCC[1] range [-25, 25] to centered [0, 1] via `(mfcc[1] / SCALE + 0.5)`.
-const MFCC_BRIGHTNESS_SCALE: f32 = 50.0;
-
-/// MFCC coefficient 2 normalization divisor.
-/// Maps typical |MFCC[2]| range [0, 15] to [0, 1] via `(mfcc[2].abs() / SCALE)`.
-const MFCC_ROUGHNESS_SCALE: f32 = 30.0;
-
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 
 use af_core::clock::MediaClock;
-use af_core::frame::AudioFeatures;
+use af_core::config::AnalysisConfig;
+use af_core::frame::{AudioFeatures, ChannelFeatures};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use triple_buffer::TripleBuffer;
 
+use crate::capture::{AudioCapture, CaptureOptions};
+use crate::decode;
+use crate::frame_analyzer::FrameAnalyzer;
+use crate::loudness::{AutoGain, LoudnessMeter};
+use crate::pcm::PcmSource;
+use crate::smoothing::FeatureSmoother;
+
 /// Commandes interactives pour le thread audio .
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum AudioCommand {
@@ -24,44 +24,148 @@ pub enum AudioCommand {
     Quit,
 }
 
-use crate::beat::BeatDetector;
-use crate::capture::AudioCapture;
-use crate::decode;
-use crate::features;
-use crate::fft::FftPipeline;
-use crate::mfcc::MelFilterbank;
-use crate::smoothing::FeatureSmoother;
+/// Thread d'une source audio live (réseau, capture, PCM). Le drop lève le
+/// drapeau d'arrêt et joint le thread : la source libère son port ou son
+/// périphérique avant qu'une autre ne démarre.
+pub struct AudioThread {
+    stop: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl AudioThread {
+    /// Spawn `name` running `body`, which must return soon after the stop
+    /// flag it receives is raised.
+    pub(crate) fn spawn(
+        name: &str,
+        body: impl FnOnce(&AtomicBool) + Send + 'static,
+    ) -> std::io::Result<Self> {
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let handle = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || body(&flag))?;
+        Ok(Self {
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// The thread has returned (end of stream or error).
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.handle
+            .as_ref()
+            .is_none_or(thread::JoinHandle::is_finished)
+    }
+}
 
-/// Spawn the audio analysis thread from microphone capture.
+impl Drop for AudioThread {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Outputs of a live capture: main features, per-channel features when the
+/// routing is [`crate::capture::ChannelRouting::Split`], and the analysis
+/// thread (dropping it stops the capture).
+pub type CaptureOutputs = (
+    triple_buffer::Output<AudioFeatures>,
+    Option<triple_buffer::Output<ChannelFeatures>>,
+    AudioThread,
+);
+
+/// Spawn the audio analysis thread from microphone capture, with the FFT,
+/// window, overlap and bands of `analysis`.
 ///
 /// # Errors
-/// Returns an error if audio capture fails to initialize.
+/// Returns an error if audio capture fails to initialize (device not found,
+/// sample rate or channel not supported).
 pub fn spawn_audio_thread(
     target_fps: u32,
     audio_smoothing: f32,
     input_gain: f32,
-) -> anyhow::Result<triple_buffer::Output<AudioFeatures>> {
-    let mut capture = AudioCapture::start_default()?;
+    options: &CaptureOptions,
+    analysis: &AnalysisConfig,
+) -> anyhow::Result<CaptureOutputs> {
+    let capture = AudioCapture::start(options)?;
+    spawn_capture_analysis(capture, target_fps, audio_smoothing, input_gain, analysis)
+}
+
+/// Spawn the audio analysis thread from raw PCM on stdin or a FIFO
+/// (`options.pcm_format`, `options.routing`). Same analysis as a device capture.
+///
+/// # Errors
+/// Returns an error if the routed channel does not exist in the declared
+/// format or a thread fails to spawn.
+pub fn spawn_pcm_thread(
+    source: PcmSource,
+    target_fps: u32,
+    audio_smoothing: f32,
+    input_gain: f32,
+    options: &CaptureOptions,
+    analysis: &AnalysisConfig,
+) -> anyhow::Result<CaptureOutputs> {
+    let capture = AudioCapture::start_pcm(source, options)?;
+    spawn_capture_analysis(capture, target_fps, audio_smoothing, input_gain, analysis)
+}
+
+/// Analysis thread over any [`AudioCapture`]: [`run_analysis_loop`] fed by
+/// the capture's ring buffer, plus the per-channel split if any.
+fn spawn_capture_analysis(
+    mut capture: AudioCapture,
+    target_fps: u32,
+    audio_smoothing: f32,
+    input_gain: f32,
+    analysis: &AnalysisConfig,
+) -> anyhow::Result<CaptureOutputs> {
     let sample_rate = capture.sample_rate();
+    let analysis = analysis.clone();
 
     let (mut buf_input, buf_output) = TripleBuffer::new(&AudioFeatures::default()).split();
+    let (mut split, channel_output) = if capture.split_channels() > 0 {
+        let (input, output) = TripleBuffer::new(&ChannelFeatures::default()).split();
+        let split = ChannelSplit::new(
+            input,
+            capture.split_channels(),
+            sample_rate,
+            audio_smoothing,
+            &analysis,
+        );
+        (Some(split), Some(output))
+    } else {
+        (None, None)
+    };
+
+    let thread = AudioThread::spawn("af-audio", move |stop| {
+        run_analysis_loop(
+            &mut buf_input,
+            target_fps,
+            sample_rate,
+            audio_smoothing,
+            input_gain,
+            &analysis,
+            stop,
+            &mut |out| {
+                // État lu avant la lecture : tout ce que le lecteur a poussé
+                // avant sa fin est consommé
+                let ended = capture.is_finished();
+                capture.read_samples(out);
+                if let Some(ref mut split) = split {
+                    split.process(&mut capture, input_gain);
+                }
+                !ended
+            },
+        );
+        if let Some(ref mut split) = split {
+            split.silence();
+        }
+        log::info!("Audio : fin de la capture {}", capture.device_name());
+    })?;
 
-    thread::Builder::new()
-        .name("af-audio".to_string())
-        .spawn(move || {
-            run_analysis_loop(
-                &mut buf_input,
-                target_fps,
-                sample_rate,
-                audio_smoothing,
-                input_gain,
-                &mut |out| {
-                    capture.read_samples(out);
-                },
-            );
-        })?;
-
-    Ok(buf_output)
+    Ok((buf_output, channel_output, thread))
 }
 
 /// Spawn both audio playback and analysis from a decoded audio file.
@@ -74,6 +178,7 @@ pub fn spawn_audio_thread(
 /// # Errors
 /// Retourne une erreur uniquement si le spawn du thread OS échoue (très rare).
 /// Les erreurs de décodage ou de config audio sont loguées dans le thread.
+#[allow(clippy::too_many_lines)]
 pub fn spawn_audio_file_thread(
     path: &std::path::Path,
     target_fps: u32,
@@ -81,9 +186,11 @@ pub fn spawn_audio_file_thread(
     input_gain: f32,
     cmd_rx: flume::Receiver<AudioCommand>,
     clock: Arc<MediaClock>,
+    analysis: &AnalysisConfig,
 ) -> anyhow::Result<triple_buffer::Output<AudioFeatures>> {
     // Clone du chemin pour le thread (le décodage se fait en arrière-plan).
     let path = path.to_path_buf();
+    let analysis = analysis.clone();
     let (mut buf_input, buf_output) = TripleBuffer::new(&AudioFeatures::default()).split();
 
     thread::Builder::new()
@@ -191,6 +298,7 @@ pub fn spawn_audio_file_thread(
                 &is_paused,
                 &cmd_rx,
                 &clock,
+                &analysis,
             );
         })?;
 
@@ -210,14 +318,11 @@ fn run_file_analysis_loop(
     is_paused: &AtomicBool,
     cmd_rx: &flume::Receiver<AudioCommand>,
     clock: &MediaClock,
+    analysis: &AnalysisConfig,
 ) {
-    let fft_size = 2048;
-    let mut fft = FftPipeline::new(fft_size);
-    let mut beat = BeatDetector::new();
-    let mut smoother = FeatureSmoother::new(audio_smoothing);
-    let mut filterbank = MelFilterbank::new(fft_size, sample_rate);
+    let mut analyzer = LiveAnalyzer::new(sample_rate, audio_smoothing, analysis);
+    let fft_size = analysis.fft_size;
     let mut window_buf: Vec<f32> = vec![0.0; fft_size];
-    let mut onset_env: f32 = 0.0;
 
     let frame_period = std::time::Duration::from_secs_f64(1.0 / f64::from(target_fps.max(1)));
 
@@ -268,114 +373,258 @@ fn run_file_analysis_loop(
             *slot = samples[idx];
         }
 
-        // Apply input gain before FFT
-        if (input_gain - 1.0).abs() > f32::EPSILON {
+        // Apply input gain (and AGC) before FFT
+        let gain = input_gain * analyzer.level_playback(samples, current_pos, input_gain);
+        if (gain - 1.0).abs() > f32::EPSILON {
             for s in &mut window_buf {
-                *s *= input_gain;
+                *s *= gain;
             }
         }
 
-        let spectrum = fft.process(&window_buf);
-        let mut feats = features::extract_features(&window_buf, spectrum, sample_rate);
-
-        let fps = target_fps as f32;
-        let (onset, intensity, bpm, phase, flux) = beat.process(spectrum, fps);
-        feats.onset = onset;
-        feats.beat_intensity = intensity;
-        feats.bpm = bpm;
-        feats.beat_phase = phase;
-        feats.spectral_flux = flux;
-
-        // onset_envelope: strobe-style decay (parity with batch_analyzer)
-        if onset {
-            onset_env = 1.0;
-        } else {
-            onset_env *= 0.85;
-        }
-        feats.onset_envelope = onset_env;
-
-        // MFCC timbral features
-        let mfcc = filterbank.compute(spectrum);
-        feats.mfcc = mfcc;
-        feats.timbral_brightness = (mfcc[1] / MFCC_BRIGHTNESS_SCALE + 0.5).clamp(0.0, 1.0);
-        feats.timbral_roughness = (mfcc[2].abs() / MFCC_ROUGHNESS_SCALE).clamp(0.0, 1.0);
-
-        let smoothed = smoother.smooth(&feats);
-        buf_input.write(smoothed);
+        buf_input.write(analyzer.analyze(&window_buf, target_fps as f32));
 
         thread::sleep(frame_period);
     }
 }
 
-/// Core analysis loop for capture mode.
+/// Core analysis loop for capture mode. `read_fn` returns `false` once the
+/// source has ended (PCM EOF): the loop then publishes silence and returns,
+/// as it does when `stop` is raised.
+#[allow(clippy::too_many_arguments)]
 fn run_analysis_loop(
     buf_input: &mut triple_buffer::Input<AudioFeatures>,
     target_fps: u32,
     sample_rate: u32,
     audio_smoothing: f32,
     input_gain: f32,
-    read_fn: &mut dyn FnMut(&mut Vec<f32>),
+    analysis: &AnalysisConfig,
+    stop: &AtomicBool,
+    read_fn: &mut dyn FnMut(&mut Vec<f32>) -> bool,
 ) {
-    let fft_size = 2048;
-    let mut fft = FftPipeline::new(fft_size);
-    let mut beat = BeatDetector::new();
-    let mut smoother = FeatureSmoother::new(audio_smoothing);
-    let mut filterbank = MelFilterbank::new(fft_size, sample_rate);
-    let mut sample_buf: Vec<f32> = Vec::with_capacity(fft_size * 2);
-    let mut onset_env: f32 = 0.0;
+    let mut analyzer = LiveAnalyzer::new(sample_rate, audio_smoothing, analysis);
+    let mut sample_buf: Vec<f32> = Vec::with_capacity(analysis.fft_size * 2);
 
     let frame_period = std::time::Duration::from_secs_f64(1.0 / f64::from(target_fps.max(1)));
 
-    loop {
-        let prev_len = sample_buf.len();
-        read_fn(&mut sample_buf);
+    let mut live = true;
+    while live && !stop.load(Ordering::Relaxed) {
+        let mut read = |out: &mut Vec<f32>| live = read_fn(out);
+        if let Some(features) = analyzer.capture(&mut sample_buf, input_gain, &mut read) {
+            buf_input.write(features);
+        }
+
+        thread::sleep(frame_period);
+    }
+    // Les mappings retombent au repos au lieu de figer la dernière frame
+    buf_input.write(AudioFeatures::default());
+}
+
+/// Rejoue `samples` à travers la boucle de capture live, sans attente
+/// temps réel : `chunk` nouveaux échantillons par tick, puis le même
+/// traitement que [`spawn_audio_thread`] (gain, sonie / AGC, fenêtres
+/// recouvrantes, lissage). Retourne les features de chaque fenêtre analysée.
+///
+/// Sert aux tests de parité live / batch et à reproduire hors-ligne ce
+/// qu'une capture aurait produit.
+///
+/// # Example
+/// ```
+/// use af_audio::state::analyze_capture;
+/// use af_core::config::AnalysisConfig;
+/// let analysis = AnalysisConfig { overlap: 0.5, ..AnalysisConfig::default() };
+/// // 2048-sample FFT, 1024-sample hop: one window per 1024 samples after the first
+/// let frames = analyze_capture(&vec![0.0; 8192], 44100, 512, 0.3, 1.0, &analysis);
+/// assert_eq!(frames.len(), 7);
+/// ```
+#[must_use]
+pub fn analyze_capture(
+    samples: &[f32],
+    sample_rate: u32,
+    chunk: usize,
+    audio_smoothing: f32,
+    input_gain: f32,
+    analysis: &AnalysisConfig,
+) -> Vec<AudioFeatures> {
+    let mut analyzer = LiveAnalyzer::new(sample_rate, audio_smoothing, analysis);
+    let mut sample_buf: Vec<f32> = Vec::with_capacity(analysis.fft_size * 2);
+    samples
+        .chunks(chunk.max(1))
+        .filter_map(|new| {
+            analyzer.capture(&mut sample_buf, input_gain, &mut |out| {
+                out.extend_from_slice(new);
+            })
+        })
+        .collect()
+}
+
+/// Per-signal analysis state of the live loops: loudness and AGC, the
+/// shared [`FrameAnalyzer`] and smoothing.
+struct LiveAnalyzer {
+    meter: LoudnessMeter,
+    agc: Option<AutoGain>,
+    /// Playback position of the last file tick.
+    play_pos: usize,
+    frames: FrameAnalyzer,
+    /// Samples between two capture windows (`AnalysisConfig::hop_size`).
+    hop: usize,
+    smoother: FeatureSmoother,
+    sample_rate: u32,
+    /// Smoothed analysis rate of a capture (windows per second).
+    hop_rate: f32,
+}
+
+impl LiveAnalyzer {
+    fn new(sample_rate: u32, audio_smoothing: f32, analysis: &AnalysisConfig) -> Self {
+        Self {
+            meter: LoudnessMeter::new(sample_rate),
+            agc: analysis
+                .agc
+                .then(|| AutoGain::new(sample_rate, analysis.agc_target_lufs)),
+            play_pos: 0,
+            frames: FrameAnalyzer::new(sample_rate, analysis),
+            hop: analysis.hop_size().min(analysis.fft_size),
+            smoother: FeatureSmoother::new(audio_smoothing),
+            sample_rate,
+            hop_rate: 0.0,
+        }
+    }
 
-        // Apply input gain ONLY to newly appended samples (avoid cumulative gain^N)
+    /// One tick of a capture loop: append new samples with `read_fn`, apply
+    /// `input_gain` to them only (avoid cumulative gain^N), meter them, and
+    /// analyse the buffer if it holds a full window.
+    fn capture(
+        &mut self,
+        buf: &mut Vec<f32>,
+        input_gain: f32,
+        read_fn: &mut dyn FnMut(&mut Vec<f32>),
+    ) -> Option<AudioFeatures> {
+        let prev_len = buf.len();
+        read_fn(buf);
         if (input_gain - 1.0).abs() > f32::EPSILON {
-            for s in &mut sample_buf[prev_len..] {
+            for s in &mut buf[prev_len..] {
                 *s *= input_gain;
             }
         }
+        self.level(&mut buf[prev_len..]);
+        self.analyze_buffer(buf)
+    }
 
-        if sample_buf.len() >= fft_size {
-            let window = if sample_buf.len() > fft_size {
-                &sample_buf[sample_buf.len() - fft_size..]
-            } else {
-                &sample_buf
-            };
-
-            let spectrum = fft.process(window);
-            let mut feats = features::extract_features(window, spectrum, sample_rate);
-
-            let fps = target_fps as f32;
-            let (onset, intensity, bpm, phase, flux) = beat.process(spectrum, fps);
-            feats.onset = onset;
-            feats.beat_intensity = intensity;
-            feats.bpm = bpm;
-            feats.beat_phase = phase;
-            feats.spectral_flux = flux;
-
-            // onset_envelope: strobe-style decay (parity with batch_analyzer)
-            if onset {
-                onset_env = 1.0;
-            } else {
-                onset_env *= 0.85;
+    /// Meter the samples a capture just appended (after `input_gain`) and
+    /// apply the AGC gain to them.
+    fn level(&mut self, new: &mut [f32]) {
+        let level = self.meter.process(new);
+        if let Some(agc) = &mut self.agc {
+            let gain = agc.update(&level, new.len());
+            for s in new {
+                *s *= gain;
             }
-            feats.onset_envelope = onset_env;
+        }
+    }
+
+    /// Meter a decoded file up to the playback position and return the AGC
+    /// gain to apply on top of `input_gain` (1.0 without AGC).
+    fn level_playback(&mut self, samples: &[f32], pos: usize, input_gain: f32) -> f32 {
+        let level = self.meter.process_to(samples, pos, input_gain);
+        // Samples played since the last tick (0 after a backward seek)
+        let elapsed = pos.saturating_sub(self.play_pos);
+        self.play_pos = pos;
+        self.agc
+            .as_mut()
+            .map_or(1.0, |agc| agc.update(&level, elapsed))
+    }
+
+    /// Analyse the latest window of a capture buffer once it holds a full
+    /// FFT window, then keep only its last `fft_size - hop` samples: the
+    /// overlap with the next window.
+    fn analyze_buffer(&mut self, buf: &mut Vec<f32>) -> Option<AudioFeatures> {
+        let fft_size = self.frames.fft_size();
+        if buf.len() < fft_size {
+            return None;
+        }
+        let keep = fft_size - self.hop;
+        let features = self.analyze_hop(&buf[buf.len() - fft_size..], buf.len() - keep);
+        buf.drain(..buf.len() - keep);
+        Some(features)
+    }
+
+    /// Analyse the latest window of a capture, `hop` new samples after the
+    /// previous one. The beat tracker runs at the real analysis rate
+    /// (`sample_rate / hop`), which is below the render fps when a tick
+    /// brings fewer than `hop` samples.
+    fn analyze_hop(&mut self, window: &[f32], hop: usize) -> AudioFeatures {
+        let rate = self.sample_rate as f32 / hop.max(1) as f32;
+        self.hop_rate = if self.hop_rate > 0.0 {
+            self.hop_rate * 0.9 + rate * 0.1
+        } else {
+            rate
+        };
+        self.analyze(window, self.hop_rate)
+    }
 
-            // MFCC timbral features
-            let mfcc = filterbank.compute(spectrum);
-            feats.mfcc = mfcc;
-            feats.timbral_brightness = (mfcc[1] / MFCC_BRIGHTNESS_SCALE + 0.5).clamp(0.0, 1.0);
-            feats.timbral_roughness = (mfcc[2].abs() / MFCC_ROUGHNESS_SCALE).clamp(0.0, 1.0);
+    /// Analyse one FFT window at `fps` windows per second and return the
+    /// smoothed features.
+    fn analyze(&mut self, window: &[f32], fps: f32) -> AudioFeatures {
+        let feats = self.frames.analyze(window, fps);
 
-            let smoothed = smoother.smooth(&feats);
-            buf_input.write(smoothed);
+        // Loudness: already integrated over 400 ms / 3 s, not smoothed again
+        let mut smoothed = self.smoother.smooth(&feats);
+        self.meter.level().write_to(&mut smoothed);
+        smoothed
+    }
+}
+
+/// Per-channel analysis of a split capture, driven from the capture read
+/// closure so the main loop stays single-signal.
+struct ChannelSplit {
+    analyzers: Vec<LiveAnalyzer>,
+    bufs: Vec<Vec<f32>>,
+    current: ChannelFeatures,
+    output: triple_buffer::Input<ChannelFeatures>,
+}
 
-            sample_buf.clear();
+impl ChannelSplit {
+    fn new(
+        output: triple_buffer::Input<ChannelFeatures>,
+        count: usize,
+        sample_rate: u32,
+        audio_smoothing: f32,
+        analysis: &AnalysisConfig,
+    ) -> Self {
+        Self {
+            analyzers: (0..count)
+                .map(|_| LiveAnalyzer::new(sample_rate, audio_smoothing, analysis))
+                .collect(),
+            bufs: vec![Vec::with_capacity(analysis.fft_size * 2); count],
+            current: ChannelFeatures {
+                count,
+                ..ChannelFeatures::default()
+            },
+            output,
         }
+    }
 
-        thread::sleep(frame_period);
+    fn process(&mut self, capture: &mut AudioCapture, input_gain: f32) {
+        let mut updated = false;
+        for (i, (analyzer, buf)) in self.analyzers.iter_mut().zip(&mut self.bufs).enumerate() {
+            let mut read = |out: &mut Vec<f32>| {
+                capture.read_channel_samples(i, out);
+            };
+            if let Some(features) = analyzer.capture(buf, input_gain, &mut read) {
+                self.current.features[i] = features;
+                updated = true;
+            }
+        }
+        if updated {
+            self.output.write(self.current);
+        }
+    }
+
+    /// Publish silent features for every channel (end of capture).
+    fn silence(&mut self) {
+        self.output.write(ChannelFeatures {
+            count: self.current.count,
+            ..ChannelFeatures::default()
+        });
     }
 }
 
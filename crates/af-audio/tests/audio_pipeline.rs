@@ -7,9 +7,10 @@
     clippy::field_reassign_with_default
 )]
 
-use af_audio::features::extract_features;
+use af_audio::features::{BandLayout, extract_features, extract_features_with};
 use af_audio::fft::FftPipeline;
 use af_audio::smoothing::FeatureSmoother;
+use af_core::config::{AnalysisBand, AnalysisConfig, WindowFunction};
 
 /// Generate a sine wave at `freq_hz` with given `amplitude` and `sample_rate`.
 fn sine_wave(freq_hz: f32, amplitude: f32, sample_rate: u32, num_samples: usize) -> Vec<f32> {
@@ -122,3 +123,114 @@ fn high_frequency_sine_produces_brilliance() {
         features.bass
     );
 }
+
+#[test]
+fn batch_kick_pattern_yields_tempo_grid() {
+    use af_audio::batch_analyzer::BatchAnalyzer;
+
+    // 15 s of decaying 60 Hz kicks at 128 BPM
+    let sample_rate = 44100u32;
+    let beat_len = (sample_rate as f32 * 60.0 / 128.0) as usize;
+    let samples: Vec<f32> = (0..sample_rate as usize * 15)
+        .map(|i| {
+            let t = (i % beat_len) as f32 / sample_rate as f32;
+            (2.0 * std::f32::consts::PI * 60.0 * t).sin() * (-t * 30.0).exp() * 0.8
+        })
+        .collect();
+
+    let timeline = BatchAnalyzer::new(60, sample_rate, 2048).analyze_all(&samples);
+    let last = timeline.frames.last().expect("frames");
+    assert!(
+        (last.bpm - 128.0).abs() < 3.0,
+        "kicks at 128 BPM should be tracked, got {}",
+        last.bpm
+    );
+    assert!(last.tempo_confidence > 0.0);
+    assert!(last.beat_in_bar < af_core::frame::BEATS_PER_BAR);
+    assert!((0.0..1.0).contains(&last.bar_phase));
+}
+
+#[test]
+fn batch_sections_split_on_texture_change() {
+    use af_audio::batch_analyzer::BatchAnalyzer;
+
+    // 12 s of a soft 220 Hz tone, then 12 s of a loud 880 Hz tone over kicks
+    let sample_rate = 44100u32;
+    let part = sample_rate as usize * 12;
+    let beat_len = sample_rate as usize / 2;
+    let samples: Vec<f32> = (0..part * 2)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            if i < part {
+                (2.0 * std::f32::consts::PI * 220.0 * t).sin() * 0.1
+            } else {
+                let k = (i % beat_len) as f32 / sample_rate as f32;
+                (2.0 * std::f32::consts::PI * 880.0 * t).sin() * 0.4
+                    + (2.0 * std::f32::consts::PI * 60.0 * k).sin() * (-k * 30.0).exp() * 0.5
+            }
+        })
+        .collect();
+
+    let timeline = BatchAnalyzer::new(30, sample_rate, 2048).analyze_all(&samples);
+    let starts: Vec<f32> = timeline
+        .sections
+        .iter()
+        .map(|s| s.start as f32 * timeline.frame_duration)
+        .collect();
+    assert_eq!(starts.len(), 2, "section starts: {starts:?}");
+    assert!((starts[1] - 12.0).abs() < 1.5, "boundary at {}s", starts[1]);
+    assert!(timeline.sections[1].energy > timeline.sections[0].energy);
+    assert_eq!(
+        timeline.sections.last().map(|s| s.end),
+        Some(timeline.frames.len())
+    );
+}
+
+#[test]
+fn custom_bands_follow_analysis_config() {
+    let sample_rate = 44100;
+    let analysis = AnalysisConfig {
+        fft_size: 4096,
+        bands: vec![
+            AnalysisBand {
+                name: "kick_body".into(),
+                low_hz: 40.0,
+                high_hz: 90.0,
+            },
+            AnalysisBand {
+                name: "air".into(),
+                low_hz: 12000.0,
+                high_hz: 18000.0,
+            },
+        ],
+        ..AnalysisConfig::default()
+    };
+    let layout = BandLayout::from_config(&analysis);
+    let samples = sine_wave(60.0, 0.5, sample_rate, 4096);
+
+    let mut levels = Vec::new();
+    for window in [
+        WindowFunction::Hann,
+        WindowFunction::Hamming,
+        WindowFunction::Blackman,
+        WindowFunction::Rectangular,
+    ] {
+        let mut fft = FftPipeline::with_window(4096, window);
+        let spectrum = fft.process(&samples);
+        let features = extract_features_with(&samples, spectrum, sample_rate, &layout);
+        assert!(
+            features.custom_bands[0] > 0.1,
+            "60 Hz sine should fill kick_body ({window:?}), got {}",
+            features.custom_bands[0]
+        );
+        assert!(features.custom_bands[1] < 0.05, "air should stay quiet");
+        assert_eq!(features.custom_bands[2], 0.0, "unused slot");
+        levels.push(features.custom_bands[0]);
+    }
+
+    // Window gain is compensated: levels stay comparable across windows
+    let (min, max) = levels
+        .iter()
+        .fold((f32::MAX, 0.0f32), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    assert!(max / min < 1.6, "window levels diverge: {levels:?}");
+}
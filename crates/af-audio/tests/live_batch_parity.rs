@@ -0,0 +1,205 @@
+//! Integration test: live / offline analysis parity.
+//! Verifies: the same signal through the capture loop (`analyze_capture`) and
+//! `BatchAnalyzer::analyze_frames` yields the same features, frame by frame.
+#![allow(clippy::expect_used)]
+
+use af_audio::batch_analyzer::BatchAnalyzer;
+use af_audio::smoothing::FeatureSmoother;
+use af_audio::state::analyze_capture;
+use af_core::config::AnalysisConfig;
+use af_core::frame::AudioFeatures;
+
+/// 51.2 kHz at 50 fps: one batch frame every 1024 samples, the live hop of a
+/// 2048-sample FFT with 50% overlap, so both paths analyse the same windows.
+const SR: u32 = 51_200;
+const FPS: u32 = 50;
+const SMOOTHING: f32 = 0.3;
+
+/// Feature, value, and the largest accepted difference.
+type Check = (&'static str, fn(&AudioFeatures) -> f32, f32);
+
+fn flag(b: bool) -> f32 {
+    if b { 1.0 } else { 0.0 }
+}
+
+const CHECKS: [Check; 36] = [
+    ("rms", |f| f.rms, 1e-4),
+    ("peak", |f| f.peak, 1e-4),
+    ("sub_bass", |f| f.sub_bass, 1e-4),
+    ("bass", |f| f.bass, 1e-4),
+    ("low_mid", |f| f.low_mid, 1e-4),
+    ("mid", |f| f.mid, 1e-4),
+    ("high_mid", |f| f.high_mid, 1e-4),
+    ("presence", |f| f.presence, 1e-4),
+    ("brilliance", |f| f.brilliance, 1e-4),
+    ("spectral_centroid", |f| f.spectral_centroid, 1e-4),
+    ("spectral_flux", |f| f.spectral_flux, 1e-4),
+    ("spectral_flatness", |f| f.spectral_flatness, 1e-4),
+    ("spectral_rolloff", |f| f.spectral_rolloff, 1e-4),
+    ("zero_crossing_rate", |f| f.zero_crossing_rate, 1e-4),
+    ("onset", |f| flag(f.onset), 0.0),
+    ("beat_intensity", |f| f.beat_intensity, 1e-4),
+    ("onset_envelope", |f| f.onset_envelope, 1e-4),
+    ("bpm", |f| f.bpm, 0.1),
+    ("beat_phase", |f| f.beat_phase, 1e-3),
+    ("beat_in_bar", |f| f32::from(f.beat_in_bar), 0.0),
+    ("tempo_confidence", |f| f.tempo_confidence, 1e-3),
+    ("timbral_brightness", |f| f.timbral_brightness, 1e-4),
+    ("timbral_roughness", |f| f.timbral_roughness, 1e-4),
+    ("kick_onset", |f| flag(f.kick_onset), 0.0),
+    ("kick_envelope", |f| f.kick_envelope, 1e-4),
+    ("snare_onset", |f| flag(f.snare_onset), 0.0),
+    ("snare_envelope", |f| f.snare_envelope, 1e-4),
+    ("hat_onset", |f| flag(f.hat_onset), 0.0),
+    ("hat_envelope", |f| f.hat_envelope, 1e-4),
+    ("pitch", |f| f.pitch, 0.1),
+    ("pitch_confidence", |f| f.pitch_confidence, 1e-3),
+    ("key", |f| f32::from(f.key), 0.0),
+    ("key_confidence", |f| f.key_confidence, 1e-3),
+    ("loudness_lufs", |f| f.loudness_lufs, 0.05),
+    ("true_peak", |f| f.true_peak, 0.05),
+    ("dynamic_range", |f| f.dynamic_range, 1e-3),
+];
+
+fn analysis() -> AnalysisConfig {
+    AnalysisConfig {
+        overlap: 0.5,
+        ..AnalysisConfig::default()
+    }
+}
+
+/// Deterministic white noise in [-1, 1].
+fn noise(len: usize) -> Vec<f32> {
+    let mut state = 0x2545_f491_u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as f32 / u32::MAX as f32 * 2.0 - 1.0
+        })
+        .collect()
+}
+
+/// Exponential sine sweep 50 Hz → 8 kHz.
+fn sweep(secs: f32) -> Vec<f32> {
+    let len = (SR as f32 * secs) as usize;
+    let k = (8000.0f32 / 50.0).ln();
+    (0..len)
+        .map(|i| {
+            let t = i as f32 / SR as f32;
+            let phase = 50.0 * secs / k * ((k * t / secs).exp() - 1.0);
+            0.5 * (std::f32::consts::TAU * phase).sin()
+        })
+        .collect()
+}
+
+/// Decaying 60 Hz thump every half second (120 BPM), accented every 4th beat.
+fn click_track(secs: f32) -> Vec<f32> {
+    let beat = SR as usize / 2;
+    (0..(SR as f32 * secs) as usize)
+        .map(|i| {
+            let n = i % beat;
+            let accent = if (i / beat).is_multiple_of(4) {
+                0.9
+            } else {
+                0.6
+            };
+            let t = n as f32 / SR as f32;
+            accent * (-t * 40.0).exp() * (std::f32::consts::TAU * 60.0 * t).sin()
+        })
+        .collect()
+}
+
+/// 200 ms white-noise bursts every 500 ms.
+fn noise_bursts(secs: f32) -> Vec<f32> {
+    let period = SR as usize / 2;
+    let on = SR as usize / 5;
+    noise((SR as f32 * secs) as usize)
+        .into_iter()
+        .enumerate()
+        .map(|(i, s)| if i % period < on { 0.4 * s } else { 0.0 })
+        .collect()
+}
+
+/// Batch frames as the live loop publishes them: smoothed with the same
+/// smoother, loudness passing through.
+fn batch_as_live(samples: &[f32], analysis: &AnalysisConfig) -> Vec<AudioFeatures> {
+    let mut smoother = FeatureSmoother::new(SMOOTHING);
+    BatchAnalyzer::with_analysis(FPS, SR, analysis)
+        .analyze_frames(samples)
+        .iter()
+        .map(|f| smoother.smooth(f))
+        .collect()
+}
+
+fn assert_parity(signal: &str, samples: &[f32]) -> Vec<AudioFeatures> {
+    let analysis = analysis();
+    assert_eq!(analysis.hop_size() as u32, SR / FPS);
+    let expected = batch_as_live(samples, &analysis);
+
+    for chunk in [256, 1024] {
+        let live = analyze_capture(samples, SR, chunk, SMOOTHING, 1.0, &analysis);
+        // The capture stops at the last full window; the batch pads the tail.
+        assert_eq!(
+            live.len(),
+            (samples.len() - analysis.fft_size) / analysis.hop_size() + 1,
+            "{signal}: live window count"
+        );
+        assert!(live.len() <= expected.len());
+
+        for (i, (l, b)) in live.iter().zip(&expected).enumerate() {
+            for (name, get, tol) in CHECKS {
+                let (lv, bv) = (get(l), get(b));
+                assert!(
+                    (lv - bv).abs() <= tol,
+                    "{signal} (chunk {chunk}) frame {i}: {name} live {lv} vs batch {bv}"
+                );
+            }
+            for (k, (lv, bv)) in l.mfcc.iter().zip(&b.mfcc).enumerate() {
+                assert!(
+                    (lv - bv).abs() <= 1e-3,
+                    "{signal} frame {i}: mfcc_{k} live {lv} vs batch {bv}"
+                );
+            }
+            for (k, (lv, bv)) in l.chroma.iter().zip(&b.chroma).enumerate() {
+                assert!(
+                    (lv - bv).abs() <= 1e-4,
+                    "{signal} frame {i}: chroma {k} live {lv} vs batch {bv}"
+                );
+            }
+            for (k, (lv, bv)) in l.spectrum_bands.iter().zip(&b.spectrum_bands).enumerate() {
+                assert!(
+                    (lv - bv).abs() <= 1e-4,
+                    "{signal} frame {i}: spectrum_{k} live {lv} vs batch {bv}"
+                );
+            }
+            assert_eq!(l.key_minor, b.key_minor, "{signal} frame {i}: key_minor");
+        }
+    }
+    expected
+}
+
+#[test]
+fn sine_sweep_parity() {
+    let frames = assert_parity("sweep", &sweep(6.0));
+    // The sweep moves the centroid upwards
+    let first = frames[10].spectral_centroid;
+    let last = frames[frames.len() - 60].spectral_centroid;
+    assert!(last > first, "centroid {first} → {last}");
+}
+
+#[test]
+fn click_track_parity() {
+    let frames = assert_parity("clicks", &click_track(10.0));
+    assert!(frames.iter().filter(|f| f.kick_onset).count() >= 10);
+    let bpm = frames.last().expect("frames").bpm;
+    assert!((bpm - 120.0).abs() < 3.0, "bpm {bpm}");
+}
+
+#[test]
+fn noise_burst_parity() {
+    let frames = assert_parity("bursts", &noise_bursts(6.0));
+    assert!(frames.iter().any(|f| f.hat_onset || f.snare_onset));
+    assert!(frames.iter().any(|f| f.loudness_lufs > -30.0));
+}
@@ -0,0 +1,156 @@
+//! Integration test: raw PCM input.
+//! Verifies: s16/f32 bytes → reader thread → live analysis → features.
+#![allow(clippy::expect_used)]
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use af_audio::capture::{CaptureOptions, ChannelRouting};
+use af_audio::pcm::{PcmEncoding, PcmFormat, PcmSource};
+use af_audio::state::spawn_pcm_thread;
+use af_core::config::AnalysisConfig;
+
+/// Interleaved sine at `freq_hz` on every channel, encoded as `format`.
+fn sine_bytes(freq_hz: f32, format: PcmFormat, seconds: f32) -> Vec<u8> {
+    sine_bytes_at(freq_hz, 0.5, format, seconds)
+}
+
+fn sine_bytes_at(freq_hz: f32, amplitude: f32, format: PcmFormat, seconds: f32) -> Vec<u8> {
+    let frames = (format.sample_rate as f32 * seconds) as usize;
+    let mut out = Vec::new();
+    for i in 0..frames {
+        let v = amplitude
+            * (2.0 * std::f32::consts::PI * freq_hz * i as f32 / format.sample_rate as f32).sin();
+        for _ in 0..format.channels {
+            match format.encoding {
+                PcmEncoding::F32 => out.extend_from_slice(&v.to_le_bytes()),
+                PcmEncoding::S16 => {
+                    out.extend_from_slice(&((v * 32767.0) as i16).to_le_bytes());
+                }
+            }
+        }
+    }
+    out
+}
+
+fn assert_bass_from(format: PcmFormat, routing: ChannelRouting) {
+    let options = CaptureOptions {
+        routing,
+        pcm_format: format,
+        ..CaptureOptions::default()
+    };
+    let source = PcmSource::Reader(Box::new(Cursor::new(sine_bytes(100.0, format, 2.0))));
+    let (mut output, _, _thread) =
+        spawn_pcm_thread(source, 60, 0.0, 1.0, &options, &AnalysisConfig::default())
+            .expect("PCM thread should start");
+
+    let deadline = Instant::now() + Duration::from_secs(3);
+    loop {
+        let features = *output.read();
+        if features.bass > 0.1 {
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "100 Hz PCM sine should produce bass > 0.1 ({format:?}), got {}",
+            features.bass
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn s16_stereo_pcm_produces_bass_energy() {
+    assert_bass_from(PcmFormat::default(), ChannelRouting::Mix);
+}
+
+#[test]
+fn f32_mono_pcm_produces_bass_energy() {
+    let format = "f32:48000:1".parse().expect("valid format");
+    assert_bass_from(format, ChannelRouting::Channel(0));
+}
+
+#[test]
+fn end_of_stream_resets_features_and_stops() {
+    let format = PcmFormat::default();
+    let source = PcmSource::Reader(Box::new(Cursor::new(sine_bytes(100.0, format, 0.5))));
+    let (mut output, _, thread) = spawn_pcm_thread(
+        source,
+        60,
+        0.0,
+        1.0,
+        &CaptureOptions::default(),
+        &AnalysisConfig::default(),
+    )
+    .expect("PCM thread should start");
+
+    let deadline = Instant::now() + Duration::from_secs(3);
+    while !thread.is_finished() {
+        assert!(
+            Instant::now() < deadline,
+            "analysis thread should exit on EOF"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let features = *output.read();
+    assert!(features.bass.abs() < f32::EPSILON && features.rms.abs() < f32::EPSILON);
+}
+
+#[test]
+fn pcm_rejects_missing_channel() {
+    let options = CaptureOptions {
+        routing: ChannelRouting::Channel(3),
+        ..CaptureOptions::default()
+    };
+    let source = PcmSource::Reader(Box::new(Cursor::new(Vec::new())));
+    assert!(spawn_pcm_thread(source, 60, 0.0, 1.0, &options, &AnalysisConfig::default()).is_err());
+}
+
+/// Bass level and loudness once a 100 Hz sine at `amplitude` has been
+/// analysed. The reader runs faster than real time: the AGC follows the
+/// signal time, the smoother (fastest setting here) the analysed windows.
+/// Returns the last frame before the end of the stream resets the features.
+fn settled_features(amplitude: f32, analysis: &AnalysisConfig) -> (f32, f32) {
+    let format = PcmFormat::default();
+    let source = PcmSource::Reader(Box::new(Cursor::new(sine_bytes_at(
+        100.0, amplitude, format, 8.0,
+    ))));
+    let (mut output, _, thread) =
+        spawn_pcm_thread(source, 60, 1.0, 1.0, &CaptureOptions::default(), analysis)
+            .expect("PCM thread should start");
+    let deadline = Instant::now() + Duration::from_secs(10);
+    let mut last = None;
+    while !thread.is_finished() {
+        let features = *output.read();
+        if features.bass > 0.0 {
+            last = Some((features.bass, features.loudness_lufs));
+        }
+        assert!(Instant::now() < deadline, "stream never ended");
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    last.expect("no analysed frame")
+}
+
+#[test]
+fn agc_evens_out_quiet_and_loud_input() {
+    let plain = AnalysisConfig::default();
+    let (quiet_bass, quiet_lufs) = settled_features(0.02, &plain);
+    let (loud_bass, loud_lufs) = settled_features(0.8, &plain);
+    // Metering is always on: −32 dB of amplitude reads ≈ 32 LU quieter
+    assert!(
+        (loud_lufs - quiet_lufs - 32.0).abs() < 2.0,
+        "{quiet_lufs} / {loud_lufs}"
+    );
+    assert!(loud_bass - quiet_bass > 0.3, "{quiet_bass} / {loud_bass}");
+
+    let agc = AnalysisConfig {
+        agc: true,
+        ..AnalysisConfig::default()
+    };
+    let (quiet_bass, _) = settled_features(0.02, &agc);
+    let (loud_bass, _) = settled_features(0.8, &agc);
+    assert!(
+        (loud_bass - quiet_bass).abs() < 0.1,
+        "AGC should even out bass: {quiet_bass} / {loud_bass}"
+    );
+}
@@ -0,0 +1,268 @@
+//! Keyframe automation: scripted parameter changes over time.
+//!
+//! An automation file lists lanes, each driving one [`AUDIO_TARGETS`] parameter
+//! through time-stamped keyframes:
+//!
+//! ```toml
+//! [[automation]]
+//! target = "camera_zoom_amplitude"
+//! keyframes = [
+//!     { time = 0.0, value = 1.0 },
+//!     { time = 8.0, value = 3.0, easing = "EaseInOut" },
+//! ]
+//! ```
+//!
+//! Lanes set *absolute* values on the per-frame config. They are evaluated
+//! before the audio mappings, which then add their deltas on top, so an
+//! automated zoom still pulses with the music.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{AUDIO_TARGETS, RenderConfig, default_true};
+
+/// Interpolation shape of the segment arriving at a keyframe.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Easing {
+    /// Hold the previous value, jump at the keyframe time.
+    Step,
+    #[default]
+    Linear,
+    /// Quadratic, slow start.
+    EaseIn,
+    /// Quadratic, slow end.
+    EaseOut,
+    /// Smoothstep, slow start and end.
+    EaseInOut,
+}
+
+impl Easing {
+    /// Map linear progress `t ∈ [0, 1]` to eased progress.
+    #[must_use]
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Step => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Self::Linear => t,
+            Self::EaseIn => t * t,
+            Self::EaseOut => t * (2.0 - t),
+            Self::EaseInOut => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// A single keyframe: `value` is reached at `time` (seconds).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Keyframe {
+    pub time: f64,
+    pub value: f32,
+    /// Shape of the segment from the previous keyframe to this one.
+    #[serde(default)]
+    pub easing: Easing,
+}
+
+/// Keyframes driving one config parameter.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AutomationLane {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Parameter name, one of [`AUDIO_TARGETS`].
+    pub target: String,
+    /// Sorted by time after [`Automation::validate`].
+    pub keyframes: Vec<Keyframe>,
+}
+
+impl AutomationLane {
+    /// Value of the lane at `t_secs`. Holds the first/last value outside the
+    /// keyframe range. `None` if the lane has no keyframes.
+    #[must_use]
+    pub fn value_at(&self, t_secs: f64) -> Option<f32> {
+        let first = self.keyframes.first()?;
+        if t_secs <= first.time {
+            return Some(first.value);
+        }
+        // Index of the first keyframe strictly after t
+        let next = self.keyframes.partition_point(|k| k.time <= t_secs);
+        let Some(to) = self.keyframes.get(next) else {
+            return self.keyframes.last().map(|k| k.value);
+        };
+        let from = &self.keyframes[next - 1];
+        let span = to.time - from.time;
+        let progress = if span > 0.0 {
+            ((t_secs - from.time) / span) as f32
+        } else {
+            1.0
+        };
+        let eased = to.easing.apply(progress.clamp(0.0, 1.0));
+        Some(from.value + (to.value - from.value) * eased)
+    }
+}
+
+/// Root of an automation file (`[[automation]]` lanes).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Automation {
+    #[serde(default)]
+    pub automation: Vec<AutomationLane>,
+}
+
+impl Automation {
+    /// Load an automation file. `.json` files are parsed as JSON, anything
+    /// else as TOML.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read, parsed, or fails
+    /// [`Automation::validate`].
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Read automation file {}", path.display()))?;
+        let is_json = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let mut automation: Self = if is_json {
+            serde_json::from_str(&content)
+                .with_context(|| format!("Parse automation JSON {}", path.display()))?
+        } else {
+            toml::from_str(&content)
+                .with_context(|| format!("Parse automation TOML {}", path.display()))?
+        };
+        automation.validate()?;
+        log::info!(
+            "Automation loaded: {} lanes from {}",
+            automation.automation.len(),
+            path.display()
+        );
+        Ok(automation)
+    }
+
+    /// Check targets and keyframes, then sort each lane by time.
+    ///
+    /// # Errors
+    /// Returns an error on an unknown target, an empty lane, or a non-finite
+    /// or negative keyframe time / non-finite value.
+    pub fn validate(&mut self) -> Result<()> {
+        for lane in &mut self.automation {
+            anyhow::ensure!(
+                AUDIO_TARGETS.contains(&lane.target.as_str()),
+                "Unknown automation target '{}'",
+                lane.target
+            );
+            anyhow::ensure!(
+                !lane.keyframes.is_empty(),
+                "Automation lane '{}' has no keyframes",
+                lane.target
+            );
+            for k in &lane.keyframes {
+                anyhow::ensure!(
+                    k.time.is_finite() && k.time >= 0.0 && k.value.is_finite(),
+                    "Invalid keyframe in lane '{}' (time {}, value {})",
+                    lane.target,
+                    k.time,
+                    k.value
+                );
+            }
+            lane.keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        }
+        Ok(())
+    }
+
+    /// Write every enabled lane's value at `t_secs` into `config`.
+    /// Later lanes win when two lanes drive the same target.
+    pub fn apply(&self, config: &mut RenderConfig, t_secs: f64) {
+        for lane in &self.automation {
+            if !lane.enabled {
+                continue;
+            }
+            if let Some(value) = lane.value_at(t_secs) {
+                config.set_target(&lane.target, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used, clippy::float_cmp)]
+mod tests {
+    use super::*;
+
+    fn lane(keyframes: Vec<Keyframe>) -> AutomationLane {
+        AutomationLane {
+            enabled: true,
+            target: "camera_zoom_amplitude".to_string(),
+            keyframes,
+        }
+    }
+
+    fn kf(time: f64, value: f32, easing: Easing) -> Keyframe {
+        Keyframe {
+            time,
+            value,
+            easing,
+        }
+    }
+
+    #[test]
+    fn lane_holds_outside_range_and_interpolates_inside() {
+        let l = lane(vec![
+            kf(1.0, 1.0, Easing::Linear),
+            kf(3.0, 3.0, Easing::Linear),
+        ]);
+        assert_eq!(l.value_at(0.0), Some(1.0));
+        assert_eq!(l.value_at(2.0), Some(2.0));
+        assert_eq!(l.value_at(10.0), Some(3.0));
+    }
+
+    #[test]
+    fn easing_shapes_segment() {
+        let step = lane(vec![
+            kf(0.0, 0.0, Easing::Linear),
+            kf(2.0, 4.0, Easing::Step),
+        ]);
+        assert_eq!(step.value_at(1.9), Some(0.0));
+        assert_eq!(step.value_at(2.0), Some(4.0));
+
+        let ease_in = lane(vec![
+            kf(0.0, 0.0, Easing::Linear),
+            kf(2.0, 4.0, Easing::EaseIn),
+        ]);
+        assert_eq!(ease_in.value_at(1.0), Some(1.0));
+    }
+
+    #[test]
+    fn parses_toml_and_validates() {
+        let src = r#"
+            [[automation]]
+            target = "brightness"
+            keyframes = [
+                { time = 4.0, value = 0.5, easing = "EaseOut" },
+                { time = 0.0, value = -0.5 },
+            ]
+        "#;
+        let mut a: Automation = toml::from_str(src).unwrap();
+        a.validate().unwrap();
+        assert_eq!(a.automation[0].keyframes[0].time, 0.0);
+
+        let mut config = RenderConfig::default();
+        a.apply(&mut config, 100.0);
+        assert_eq!(config.brightness, 0.5);
+    }
+
+    #[test]
+    fn unknown_target_rejected() {
+        let mut a = Automation {
+            automation: vec![AutomationLane {
+                enabled: true,
+                target: "nope".to_string(),
+                keyframes: vec![kf(0.0, 1.0, Easing::Linear)],
+            }],
+        };
+        assert!(a.validate().is_err());
+    }
+}
@@ -1,5 +1,78 @@
+use std::fmt;
 use std::sync::atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering};
 
+/// Cadence d'images rationnelle, telle que rapportée par ffprobe
+/// (`30000/1001` pour 29.97 fps) et transmise telle quelle à ffmpeg (`-r`).
+///
+/// Arrondir 29.97 à 30 décale la durée de 0,1 % et désynchronise l'audio sur
+/// une longue vidéo : les horodatages sont calculés sur la fraction exacte.
+///
+/// # Example
+/// ```
+/// use af_core::clock::FrameRate;
+/// let ntsc = FrameRate::parse("30000/1001").unwrap();
+/// assert_eq!(ntsc.rounded(), 30);
+/// assert_eq!(ntsc.to_string(), "30000/1001");
+/// assert!((ntsc.timestamp(30_000) - 1001.0).abs() < 1e-9);
+/// assert_eq!(FrameRate::integer(24).to_string(), "24");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameRate {
+    /// Numérateur (images).
+    pub num: u32,
+    /// Dénominateur (secondes), jamais nul.
+    pub den: u32,
+}
+
+impl FrameRate {
+    /// Cadence entière (`fps` images par seconde, au moins 1).
+    #[must_use]
+    pub const fn integer(fps: u32) -> Self {
+        Self {
+            num: if fps == 0 { 1 } else { fps },
+            den: 1,
+        }
+    }
+
+    /// Parse `num/den` (ou un entier). `None` si illisible ou nul.
+    #[must_use]
+    pub fn parse(s: &str) -> Option<Self> {
+        let (num, den) = s.trim().split_once('/').unwrap_or((s.trim(), "1"));
+        let num: u32 = num.trim().parse().ok()?;
+        let den: u32 = den.trim().parse().ok()?;
+        (num > 0 && den > 0).then_some(Self { num, den })
+    }
+
+    /// Images par seconde.
+    #[must_use]
+    pub fn as_f64(self) -> f64 {
+        f64::from(self.num) / f64::from(self.den)
+    }
+
+    /// Cadence entière la plus proche (au moins 1), pour les réglages qui
+    /// n'ont pas besoin de la fraction exacte (décroissances, analyse).
+    #[must_use]
+    pub fn rounded(self) -> u32 {
+        ((self.num + self.den / 2) / self.den).max(1)
+    }
+
+    /// Horodatage de l'image `frame` en secondes.
+    #[must_use]
+    pub fn timestamp(self, frame: u64) -> f64 {
+        (frame as f64) * f64::from(self.den) / f64::from(self.num)
+    }
+}
+
+impl fmt::Display for FrameRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
 /// Horloge partagée pour la synchronisation A/V.
 ///
 /// L'audio est le maître : le callback cpal écrit `sample_pos` à chaque buffer.
@@ -130,6 +203,25 @@ mod tests {
         assert!(clock.is_paused());
     }
 
+    #[test]
+    fn frame_rate_parse_and_round() {
+        assert_eq!(
+            FrameRate::parse("24000/1001"),
+            Some(FrameRate {
+                num: 24000,
+                den: 1001
+            })
+        );
+        assert_eq!(FrameRate::parse("25"), Some(FrameRate::integer(25)));
+        assert_eq!(FrameRate::parse("0/0"), None);
+        assert_eq!(FrameRate::parse("abc"), None);
+        assert_eq!(
+            FrameRate::parse("24000/1001").map(FrameRate::rounded),
+            Some(24)
+        );
+        assert_eq!(FrameRate::integer(0).rounded(), 1);
+    }
+
     #[test]
     fn clock_zero_sample_rate() {
         let clock = MediaClock::new(0);
@@ -87,6 +87,23 @@ pub fn apply_hsv_bright(r: u8, g: u8, b: u8, saturation_boost: f32) -> (u8, u8,
     hsv_to_rgb(h, s, 1.0)
 }
 
+/// Teinte imposée (`ColorMode::KeyHue`) : H = `hue`, V = 1.0, saturation
+/// relevée pour que les zones grises prennent aussi la teinte.
+///
+/// # Example
+/// ```
+/// use af_core::color::{apply_key_hue, rgb_to_hsv};
+/// let (r, g, b) = apply_key_hue(128, 128, 128, 1.0 / 3.0, 1.0);
+/// let (h, _, _) = rgb_to_hsv(r, g, b);
+/// assert!((h - 1.0 / 3.0).abs() < 0.01);
+/// ```
+#[must_use]
+pub fn apply_key_hue(r: u8, g: u8, b: u8, hue: f32, saturation_boost: f32) -> (u8, u8, u8) {
+    let (_h, s, _v) = rgb_to_hsv(r, g, b);
+    let s = ((0.35 + 0.65 * s) * saturation_boost).min(1.0);
+    hsv_to_rgb(hue.rem_euclid(1.0), s, 1.0)
+}
+
 /// Convert sRGB `[0,255]` to Oklab (L, a, b).
 /// L ∈ [0.0, 1.0], a ∈ ~[-0.23, 0.28], b ∈ ~[-0.31, 0.20].
 /// Björn Ottosson (2020). Perceptually uniform color space.
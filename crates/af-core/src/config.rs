@@ -3,6 +3,8 @@ use std::path::Path;
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
+use crate::frame::{MAX_CUSTOM_BANDS, MAX_INPUT_CHANNELS};
+
 /// Configuration complète du rendu, hot-rechargeable.
 ///
 /// Sérialisable en TOML. Chaque champ a une valeur par défaut saine.
@@ -53,6 +55,10 @@ pub struct RenderConfig {
     pub brightness: f32,
     /// Background rendering style.
     pub bg_style: BgStyle,
+    /// Teinte de la tonalité musicale [0.0, 1.0) pour `ColorMode::KeyHue`.
+    /// Écrite à chaque frame depuis l'analyse audio, jamais sérialisée.
+    #[serde(skip)]
+    pub key_hue: f32,
 
     // === Audio-réactivité ===
     /// Mapping des features audio vers les paramètres visuels.
@@ -65,6 +71,15 @@ pub struct RenderConfig {
     /// 1.0 = neutre. Augmenter pour micro faible, réduire pour source forte.
     #[serde(default = "default_input_gain")]
     pub input_gain: f32,
+    /// Analyse spectrale : FFT, fenêtre, recouvrement, bandes, AGC (section `[audio.analysis]`).
+    /// Lue au démarrage de l'audio : un changement demande de relancer la source.
+    #[serde(default)]
+    pub analysis: AnalysisConfig,
+
+    // === MIDI ===
+    /// Mapping des CC / notes MIDI vers les paramètres visuels (section `[midi]`).
+    #[serde(default)]
+    pub midi_mappings: Vec<MidiMapping>,
 
     // === Post-processing Effects ===
     /// Fade trails decay factor [0.0, 1.0]. 0.0 = disabled.
@@ -106,6 +121,17 @@ pub struct RenderConfig {
     #[serde(default)]
     pub camera_tilt_x: f32,
 
+    // === Procedural Generators ===
+    /// Vitesse d'animation des générateurs procéduraux [0.0, 5.0]. 1.0 = nominal.
+    #[serde(default = "default_generator_speed")]
+    pub generator_speed: f32,
+    /// Échelle spatiale des générateurs [0.1, 10.0]. >1.0 = motifs plus grands.
+    #[serde(default = "default_generator_scale")]
+    pub generator_scale: f32,
+    /// Complexité des générateurs [0.0, 1.0] (octaves, nombre de metaballs, densité…).
+    #[serde(default = "default_generator_complexity")]
+    pub generator_complexity: f32,
+
     // === Performance ===
     /// FPS cible. 30 ou 60.
     pub target_fps: u32,
@@ -115,6 +141,14 @@ pub struct RenderConfig {
     pub fullscreen: bool,
     /// Afficher le spectre audio sous le visualiseur (si pas en fullscreen).
     pub show_spectrum: bool,
+    /// Backend d'affichage du canvas : texte (cellules) ou protocole graphique.
+    #[serde(default)]
+    pub display_backend: DisplayBackend,
+
+    // === Export ===
+    /// Profil d'encodage de l'export batch (section `[export]`).
+    #[serde(default)]
+    pub export_profile: ExportProfile,
 }
 
 pub const AUDIO_SOURCES: &[&str] = &[
@@ -133,12 +167,40 @@ pub const AUDIO_SOURCES: &[&str] = &[
     "beat_intensity",
     "onset",
     "beat_phase",
+    "bar_phase",
+    "beat_in_bar",
+    "tempo_confidence",
     "bpm",
     "timbral_brightness",
     "timbral_roughness",
     "onset_envelope",
     "spectral_rolloff",
     "zero_crossing_rate",
+    "kick_onset",
+    "kick_intensity",
+    "kick_envelope",
+    "snare_onset",
+    "snare_intensity",
+    "snare_envelope",
+    "hat_onset",
+    "hat_intensity",
+    "hat_envelope",
+    "chroma_c",
+    "chroma_cs",
+    "chroma_d",
+    "chroma_ds",
+    "chroma_e",
+    "chroma_f",
+    "chroma_fs",
+    "chroma_g",
+    "chroma_gs",
+    "chroma_a",
+    "chroma_as",
+    "chroma_b",
+    "pitch",
+    "pitch_confidence",
+    "loudness_lufs",
+    "dynamic_range",
 ];
 
 pub const AUDIO_TARGETS: &[&str] = &[
@@ -161,6 +223,9 @@ pub const AUDIO_TARGETS: &[&str] = &[
     "camera_pan_x",
     "camera_pan_y",
     "camera_tilt_x",
+    "generator_speed",
+    "generator_scale",
+    "generator_complexity",
 ];
 
 #[must_use]
@@ -173,11 +238,31 @@ fn default_input_gain() -> f32 {
     1.0
 }
 
+#[must_use]
+fn default_midi_amount() -> f32 {
+    1.0
+}
+
 #[must_use]
 fn default_scanline_darken() -> f32 {
     0.3
 }
 
+#[must_use]
+fn default_generator_speed() -> f32 {
+    1.0
+}
+
+#[must_use]
+fn default_generator_scale() -> f32 {
+    1.0
+}
+
+#[must_use]
+fn default_generator_complexity() -> f32 {
+    0.5
+}
+
 /// Non-linear mapping curve for audio-to-visual shaping.
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub enum MappingCurve {
@@ -197,7 +282,7 @@ pub enum MappingCurve {
 /// # Example
 /// ```
 /// use af_core::config::AudioMapping;
-/// let m = AudioMapping { enabled: true, source: "bass".into(), target: "contrast".into(), amount: 0.5, offset: 0.0, curve: Default::default(), smoothing: None, stem_source: None };
+/// let m = AudioMapping { enabled: true, source: "bass".into(), target: "contrast".into(), amount: 0.5, offset: 0.0, curve: Default::default(), smoothing: None, stem_source: None, channel_source: None };
 /// assert_eq!(m.source, "bass");
 /// ```
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -225,6 +310,207 @@ pub struct AudioMapping {
     /// Backward-compatible: absent in TOML = None (existing configs work unchanged).
     #[serde(default)]
     pub stem_source: Option<String>,
+    /// Input channel source (1-based): reads the features of one channel of a
+    /// multi-channel capture (`--audio-channels split`). `stem_source` wins if both are set.
+    /// Falls back to the main features when the channel is not analysed.
+    #[serde(default)]
+    pub channel_source: Option<u8>,
+}
+
+/// A single MIDI-to-visual parameter mapping (`[[midi.mappings]]`).
+///
+/// The source is a Control Change (`cc`) or a note (`note`, velocity while held,
+/// 0 after note-off), normalized to [0.0, 1.0]. It then goes through `curve`,
+/// `amount` and `offset` like an [`AudioMapping`] and adds its delta to `target`.
+///
+/// # Example
+/// ```
+/// use af_core::config::MidiMapping;
+/// let m: MidiMapping = toml::from_str(r#"cc = 21
+/// target = "glow_intensity""#).unwrap();
+/// assert_eq!(m.cc, Some(21));
+/// assert!((m.amount - 1.0).abs() < f32::EPSILON);
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MidiMapping {
+    /// Mapping actif ou désactivé.
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Control Change number (0-127). Ignored when `note` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cc: Option<u8>,
+    /// Note number (0-127), velocity as value.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<u8>,
+    /// MIDI channel (1-16). None = any channel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+    /// Paramètre cible (mêmes noms que les audio mappings).
+    pub target: String,
+    /// Amplitude du mapping.
+    #[serde(default = "default_midi_amount")]
+    pub amount: f32,
+    /// Offset ajouté après multiplication.
+    #[serde(default)]
+    pub offset: f32,
+    /// Response curve applied before amount.
+    #[serde(default)]
+    pub curve: MappingCurve,
+}
+
+/// Bandes intégrées (sources `sub_bass` … `brilliance`) et leurs bornes par défaut, en Hz.
+pub const BUILTIN_BANDS: [(&str, f32, f32); 7] = [
+    ("sub_bass", 20.0, 60.0),
+    ("bass", 60.0, 250.0),
+    ("low_mid", 250.0, 500.0),
+    ("mid", 500.0, 2000.0),
+    ("high_mid", 2000.0, 4000.0),
+    ("presence", 4000.0, 6000.0),
+    ("brilliance", 6000.0, 20000.0),
+];
+
+/// Fenêtre d'analyse appliquée avant la FFT.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum WindowFunction {
+    /// Bon compromis résolution / fuite spectrale.
+    #[default]
+    Hann,
+    /// Lobe principal plus étroit que Hann, lobes secondaires plus hauts.
+    Hamming,
+    /// Fuite minimale, lobe principal large.
+    Blackman,
+    /// Pas de fenêtrage : résolution maximale, fuite maximale.
+    Rectangular,
+}
+
+/// Bande de fréquence nommée (`[[audio.analysis.bands]]`).
+///
+/// Le nom d'une bande intégrée ([`BUILTIN_BANDS`]) redéfinit ses bornes ;
+/// tout autre nom crée une source de mapping supplémentaire.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct AnalysisBand {
+    pub name: String,
+    /// Borne basse en Hz.
+    pub low_hz: f32,
+    /// Borne haute en Hz.
+    pub high_hz: f32,
+}
+
+/// Analyse spectrale (`[audio.analysis]`), identique pour l'analyse live,
+/// les stems et `BatchAnalyzer`.
+///
+/// # Example
+/// ```
+/// use af_core::config::AnalysisConfig;
+/// let analysis: AnalysisConfig = toml::from_str(r#"
+/// fft_size = 4096
+/// overlap = 0.75
+/// bands = [{ name = "kick_body", low_hz = 40.0, high_hz = 90.0 }]
+/// "#).unwrap();
+/// assert_eq!(analysis.hop_size(), 1024);
+/// assert_eq!(analysis.custom_band_index("kick_body"), Some(0));
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AnalysisConfig {
+    /// Taille de la FFT, puissance de 2 entre 256 et 16384.
+    pub fft_size: usize,
+    /// Fenêtre d'analyse.
+    pub window: WindowFunction,
+    /// Recouvrement des fenêtres successives de la capture live [0.0, 0.9].
+    /// Le pas d'analyse vaut `fft_size × (1 − overlap)`.
+    pub overlap: f32,
+    /// Bandes nommées, au plus [`MAX_CUSTOM_BANDS`] hors bandes intégrées.
+    pub bands: Vec<AnalysisBand>,
+    /// Contrôle automatique de gain de la capture live et des fichiers,
+    /// après `input_gain`.
+    pub agc: bool,
+    /// Sonie court terme visée par l'AGC, en LUFS [−40.0, −6.0].
+    pub agc_target_lufs: f32,
+}
+
+impl Default for AnalysisConfig {
+    fn default() -> Self {
+        Self {
+            fft_size: 2048,
+            window: WindowFunction::Hann,
+            overlap: 0.0,
+            bands: Vec::new(),
+            agc: false,
+            agc_target_lufs: -16.0,
+        }
+    }
+}
+
+impl AnalysisConfig {
+    /// Pas entre deux fenêtres d'analyse, en échantillons.
+    #[must_use]
+    pub fn hop_size(&self) -> usize {
+        ((self.fft_size as f32 * (1.0 - self.overlap)).round() as usize).max(1)
+    }
+
+    /// Bornes des 7 bandes intégrées, redéfinies par `bands` le cas échéant.
+    #[must_use]
+    pub fn builtin_edges(&self) -> [(f32, f32); 7] {
+        BUILTIN_BANDS.map(|(name, lo, hi)| {
+            self.bands
+                .iter()
+                .find(|b| b.name == name)
+                .map_or((lo, hi), |b| (b.low_hz, b.high_hz))
+        })
+    }
+
+    /// Bandes utilisateur (hors bandes intégrées), dans l'ordre de
+    /// `AudioFeatures::custom_bands`.
+    pub fn custom_bands(&self) -> impl Iterator<Item = &AnalysisBand> {
+        self.bands
+            .iter()
+            .filter(|b| !BUILTIN_BANDS.iter().any(|(name, _, _)| *name == b.name))
+            .take(MAX_CUSTOM_BANDS)
+    }
+
+    /// Index dans `AudioFeatures::custom_bands` de la bande `name`.
+    #[must_use]
+    pub fn custom_band_index(&self, name: &str) -> Option<usize> {
+        self.custom_bands().position(|b| b.name == name)
+    }
+
+    fn clamp(&mut self) {
+        let size = self.fft_size.clamp(256, 16384);
+        if !size.is_power_of_two() {
+            log::warn!(
+                "audio.analysis.fft_size {} n'est pas une puissance de 2, arrondi à {}",
+                self.fft_size,
+                size.next_power_of_two().min(16384)
+            );
+        }
+        self.fft_size = size.next_power_of_two().min(16384);
+        self.overlap = self.overlap.clamp(0.0, 0.9);
+        self.agc_target_lufs = self.agc_target_lufs.clamp(-40.0, -6.0);
+        for band in &mut self.bands {
+            band.low_hz = band.low_hz.max(0.0);
+            band.high_hz = band.high_hz.max(band.low_hz);
+        }
+        let custom = self.custom_bands().count();
+        let declared = self
+            .bands
+            .iter()
+            .filter(|b| !BUILTIN_BANDS.iter().any(|(name, _, _)| *name == b.name))
+            .count();
+        if declared > custom {
+            log::warn!(
+                "audio.analysis.bands : {declared} bandes déclarées, seules les {MAX_CUSTOM_BANDS} premières sont analysées"
+            );
+        }
+        for band in self.custom_bands() {
+            if AUDIO_SOURCES.contains(&band.name.as_str()) {
+                log::warn!(
+                    "audio.analysis.bands : '{}' masquée par la source intégrée du même nom",
+                    band.name
+                );
+            }
+        }
+    }
 }
 
 /// Render mode enumeration.
@@ -271,6 +557,9 @@ pub enum ColorMode {
     /// Oklab avec L forcé à 1.0 (perceptuellement uniforme).
     #[default]
     Oklab,
+    /// HSV avec V forcé à 1.0 et la teinte de la tonalité musicale
+    /// détectée (cercle des quintes).
+    KeyHue,
 }
 
 /// Dithering mode for luminance quantization.
@@ -305,7 +594,115 @@ pub enum BgStyle {
     Transparent,
 }
 
+/// Canvas display backend.
+///
+/// `Text` draws the grid as terminal cells. `Sixel` and `Kitty` rasterize the
+/// grid to pixels and emit graphics escape sequences instead.
+///
+/// # Example
+/// ```
+/// use af_core::config::DisplayBackend;
+/// assert_eq!(DisplayBackend::from_name("kitty"), Some(DisplayBackend::Kitty));
+/// assert_eq!(DisplayBackend::default(), DisplayBackend::Text);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum DisplayBackend {
+    /// Cellules texte (ratatui).
+    #[default]
+    Text,
+    /// Sixel (DEC) — xterm, foot, WezTerm, mlterm…
+    Sixel,
+    /// Kitty graphics protocol — kitty, WezTerm, Ghostty.
+    Kitty,
+}
+
+impl DisplayBackend {
+    /// Parse a CLI name (case-insensitive).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "text" | "ascii" => Some(Self::Text),
+            "sixel" => Some(Self::Sixel),
+            "kitty" => Some(Self::Kitty),
+            _ => None,
+        }
+    }
+
+    /// Whether the canvas is drawn as pixels rather than cells.
+    #[must_use]
+    pub fn is_graphics(self) -> bool {
+        !matches!(self, Self::Text)
+    }
+}
+
+/// Encoding profile for batch video export.
+///
+/// The ffmpeg arguments of each profile live in `af_export::muxer`.
+///
+/// # Example
+/// ```
+/// use af_core::config::ExportProfile;
+/// assert_eq!(ExportProfile::from_name("prores"), Some(ExportProfile::ProRes4444));
+/// assert_eq!(ExportProfile::default(), ExportProfile::LosslessRgb);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ExportProfile {
+    /// x264 RGB lossless (CRF 0, rgb24) — fichiers énormes, qualité maximale.
+    #[default]
+    LosslessRgb,
+    /// x264 yuv420p CRF 18 — partage, lisible partout.
+    H264,
+    /// x265 yuv420p CRF 20 — ~moitié de la taille du H264.
+    Hevc,
+    /// VP9 yuva420p en WebM (alpha supporté).
+    Vp9,
+    /// ProRes 4444 yuva444p10le en MOV (alpha supporté), pour le montage.
+    ProRes4444,
+    /// Séquence PNG RGBA numérotée, sans audio.
+    PngSequence,
+}
+
+impl ExportProfile {
+    /// All profiles, in CLI listing order.
+    pub const ALL: [Self; 6] = [
+        Self::LosslessRgb,
+        Self::H264,
+        Self::Hevc,
+        Self::Vp9,
+        Self::ProRes4444,
+        Self::PngSequence,
+    ];
+
+    /// Parse a CLI name (case-insensitive).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "lossless" | "lossless-rgb" | "losslessrgb" | "rgb" => Some(Self::LosslessRgb),
+            "h264" | "x264" => Some(Self::H264),
+            "hevc" | "h265" | "x265" => Some(Self::Hevc),
+            "vp9" | "webm" => Some(Self::Vp9),
+            "prores" | "prores4444" | "prores-4444" => Some(Self::ProRes4444),
+            "png" | "png-sequence" | "pngsequence" | "png-seq" => Some(Self::PngSequence),
+            _ => None,
+        }
+    }
+
+    /// Canonical CLI name.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LosslessRgb => "lossless",
+            Self::H264 => "h264",
+            Self::Hevc => "hevc",
+            Self::Vp9 => "vp9",
+            Self::ProRes4444 => "prores",
+            Self::PngSequence => "png-sequence",
+        }
+    }
+}
+
 impl Default for RenderConfig {
+    #[allow(clippy::too_many_lines)]
     fn default() -> Self {
         Self {
             render_mode: RenderMode::Octant,
@@ -324,6 +721,7 @@ impl Default for RenderConfig {
             contrast: 1.0,
             brightness: 0.0,
             bg_style: BgStyle::Black,
+            key_hue: 0.0,
             audio_mappings: vec![
                 AudioMapping {
                     enabled: true,
@@ -334,6 +732,7 @@ impl Default for RenderConfig {
                     curve: MappingCurve::Smooth,
                     smoothing: None,
                     stem_source: None,
+                    channel_source: None,
                 },
                 AudioMapping {
                     enabled: true,
@@ -344,6 +743,7 @@ impl Default for RenderConfig {
                     curve: MappingCurve::Linear,
                     smoothing: None,
                     stem_source: None,
+                    channel_source: None,
                 },
                 AudioMapping {
                     enabled: true,
@@ -354,6 +754,7 @@ impl Default for RenderConfig {
                     curve: MappingCurve::Linear,
                     smoothing: None,
                     stem_source: None,
+                    channel_source: None,
                 },
                 AudioMapping {
                     enabled: true,
@@ -364,6 +765,7 @@ impl Default for RenderConfig {
                     curve: MappingCurve::Smooth,
                     smoothing: None,
                     stem_source: None,
+                    channel_source: None,
                 },
                 AudioMapping {
                     enabled: true,
@@ -374,11 +776,14 @@ impl Default for RenderConfig {
                     curve: MappingCurve::Linear,
                     smoothing: None,
                     stem_source: None,
+                    channel_source: None,
                 },
             ],
             audio_smoothing: 0.3,
             audio_sensitivity: 2.0,
             input_gain: 1.0,
+            analysis: AnalysisConfig::default(),
+            midi_mappings: Vec::new(),
             fade_decay: 0.0,
             glow_intensity: 0.0,
             zalgo_intensity: 0.0,
@@ -396,9 +801,14 @@ impl Default for RenderConfig {
             camera_pan_x: 0.0,
             camera_pan_y: 0.0,
             camera_tilt_x: 0.0,
+            generator_speed: 1.0,
+            generator_scale: 1.0,
+            generator_complexity: 0.5,
             target_fps: 60,
             fullscreen: false,
             show_spectrum: false,
+            display_backend: DisplayBackend::Text,
+            export_profile: ExportProfile::LosslessRgb,
         }
     }
 }
@@ -429,6 +839,9 @@ impl RenderConfig {
         self.camera_pan_x = self.camera_pan_x.clamp(-2.0, 2.0);
         self.camera_pan_y = self.camera_pan_y.clamp(-2.0, 2.0);
         self.camera_tilt_x = self.camera_tilt_x.clamp(-1.0, 1.0);
+        self.generator_speed = self.generator_speed.clamp(0.0, 5.0);
+        self.generator_scale = self.generator_scale.clamp(0.1, 10.0);
+        self.generator_complexity = self.generator_complexity.clamp(0.0, 1.0);
         self.charset_index = self.charset_index.min(13);
         self.scanline_gap = self.scanline_gap.min(8);
         self.scanline_darken = self.scanline_darken.clamp(0.0, 1.0);
@@ -436,6 +849,7 @@ impl RenderConfig {
         self.audio_smoothing = self.audio_smoothing.clamp(0.0, 1.0);
         self.audio_sensitivity = self.audio_sensitivity.clamp(0.0, 5.0);
         self.input_gain = self.input_gain.clamp(0.1, 10.0);
+        self.analysis.clamp();
 
         for mapping in &mut self.audio_mappings {
             mapping.amount = mapping.amount.clamp(-10.0, 10.0);
@@ -443,7 +857,9 @@ impl RenderConfig {
             if let Some(s) = mapping.smoothing {
                 mapping.smoothing = Some(s.clamp(0.0, 1.0));
             }
-            if !AUDIO_SOURCES.contains(&mapping.source.as_str()) {
+            if !AUDIO_SOURCES.contains(&mapping.source.as_str())
+                && self.analysis.custom_band_index(&mapping.source).is_none()
+            {
                 log::warn!(
                     "Unknown audio source '{}' in mapping → target '{}' (ignored, will output 0.0)",
                     mapping.source,
@@ -464,7 +880,125 @@ impl RenderConfig {
                     "Unknown stem_source '{stem}' in mapping (ignored, will use combined mix)"
                 );
             }
+            if let Some(ch) = mapping.channel_source
+                && !(1..=MAX_INPUT_CHANNELS).contains(&usize::from(ch))
+            {
+                log::warn!(
+                    "channel_source {ch} out of range 1-{MAX_INPUT_CHANNELS} (ignored, will use main input)"
+                );
+            }
         }
+
+        for mapping in &mut self.midi_mappings {
+            mapping.amount = mapping.amount.clamp(-10.0, 10.0);
+            mapping.offset = mapping.offset.clamp(-5.0, 5.0);
+            mapping.cc = mapping.cc.map(|v| v.min(127));
+            mapping.note = mapping.note.map(|v| v.min(127));
+            mapping.channel = mapping.channel.map(|c| c.clamp(1, 16));
+            if mapping.cc.is_none() && mapping.note.is_none() {
+                log::warn!(
+                    "MIDI mapping → target '{}' has neither cc nor note (ignored)",
+                    mapping.target
+                );
+            }
+            if !AUDIO_TARGETS.contains(&mapping.target.as_str()) {
+                log::warn!("Unknown MIDI target '{}' (ignored)", mapping.target);
+            }
+        }
+    }
+
+    /// Current value of an [`AUDIO_TARGETS`] parameter (`invert` as 0 / 1).
+    /// `None` for an unknown target.
+    #[must_use]
+    pub fn target(&self, target: &str) -> Option<f32> {
+        Some(match target {
+            "edge_threshold" => self.edge_threshold,
+            "edge_mix" => self.edge_mix,
+            "contrast" => self.contrast,
+            "brightness" => self.brightness,
+            "saturation" => self.saturation,
+            "density_scale" => self.density_scale,
+            "invert" => f32::from(u8::from(self.invert)),
+            "zalgo_intensity" => self.zalgo_intensity,
+            "beat_flash_intensity" => self.beat_flash_intensity,
+            "chromatic_offset" => self.chromatic_offset,
+            "wave_amplitude" => self.wave_amplitude,
+            "color_pulse_speed" => self.color_pulse_speed,
+            "fade_decay" => self.fade_decay,
+            "glow_intensity" => self.glow_intensity,
+            "camera_zoom_amplitude" => self.camera_zoom_amplitude,
+            "camera_rotation" => self.camera_rotation,
+            "camera_pan_x" => self.camera_pan_x,
+            "camera_pan_y" => self.camera_pan_y,
+            "camera_tilt_x" => self.camera_tilt_x,
+            "generator_speed" => self.generator_speed,
+            "generator_scale" => self.generator_scale,
+            "generator_complexity" => self.generator_complexity,
+            _ => return None,
+        })
+    }
+
+    /// Add a mapping `delta` (audio, MIDI, batch mapper) to a target through
+    /// [`Self::set_target`], so every path shares its ranges. Camera and
+    /// generator scale deltas are scaled to their range; `invert` is a
+    /// threshold, not a toggle (`delta > 0.5` inverts). Returns `false` for an
+    /// unknown target.
+    ///
+    /// # Example
+    /// ```
+    /// use af_core::config::RenderConfig;
+    /// let mut config = RenderConfig::default();
+    /// config.contrast = 2.5;
+    /// assert!(config.apply_target_delta("contrast", 1.0));
+    /// assert_eq!(config.contrast, 3.0);
+    /// config.apply_target_delta("invert", 0.8);
+    /// assert!(config.invert);
+    /// ```
+    pub fn apply_target_delta(&mut self, target: &str, delta: f32) -> bool {
+        if target == "invert" {
+            return self.set_target(target, delta);
+        }
+        let gain = match target {
+            "camera_zoom_amplitude" | "generator_scale" => 2.0,
+            "camera_rotation" => 0.1,
+            "camera_pan_x" | "camera_pan_y" => 0.5,
+            "camera_tilt_x" => 0.3,
+            _ => 1.0,
+        };
+        self.target(target)
+            .is_some_and(|value| self.set_target(target, value + delta * gain))
+    }
+
+    /// Set an [`AUDIO_TARGETS`] parameter to an absolute `value`, clamped to
+    /// its range (the only range table: mappings, automation, OSC, MIDI and
+    /// HTTP all end here). Returns `false` for an unknown target.
+    pub fn set_target(&mut self, target: &str, value: f32) -> bool {
+        match target {
+            "edge_threshold" => self.edge_threshold = value.clamp(0.0, 1.0),
+            "edge_mix" => self.edge_mix = value.clamp(0.0, 1.0),
+            "contrast" => self.contrast = value.clamp(0.1, 3.0),
+            "brightness" => self.brightness = value.clamp(-1.0, 1.0),
+            "saturation" => self.saturation = value.clamp(0.0, 3.0),
+            "density_scale" => self.density_scale = value.clamp(0.25, 4.0),
+            "invert" => self.invert = value > 0.5,
+            "zalgo_intensity" => self.zalgo_intensity = value.clamp(0.0, 5.0),
+            "beat_flash_intensity" => self.beat_flash_intensity = value.clamp(0.0, 2.0),
+            "chromatic_offset" => self.chromatic_offset = value.clamp(0.0, 5.0),
+            "wave_amplitude" => self.wave_amplitude = value.clamp(0.0, 1.0),
+            "color_pulse_speed" => self.color_pulse_speed = value.clamp(0.0, 5.0),
+            "fade_decay" => self.fade_decay = value.clamp(0.0, 1.0),
+            "glow_intensity" => self.glow_intensity = value.clamp(0.0, 2.0),
+            "camera_zoom_amplitude" => self.camera_zoom_amplitude = value.clamp(0.1, 10.0),
+            "camera_rotation" => self.camera_rotation = value.rem_euclid(std::f32::consts::TAU),
+            "camera_pan_x" => self.camera_pan_x = value.clamp(-2.0, 2.0),
+            "camera_pan_y" => self.camera_pan_y = value.clamp(-2.0, 2.0),
+            "camera_tilt_x" => self.camera_tilt_x = value.clamp(-1.0, 1.0),
+            "generator_speed" => self.generator_speed = value.clamp(0.0, 5.0),
+            "generator_scale" => self.generator_scale = value.clamp(0.1, 10.0),
+            "generator_complexity" => self.generator_complexity = value.clamp(0.0, 1.0),
+            _ => return false,
+        }
+        true
     }
 }
 
@@ -489,6 +1023,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Smooth,
             smoothing: None,
             stem_source: Some("drums".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -499,6 +1034,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Smooth,
             smoothing: None,
             stem_source: Some("drums".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -509,6 +1045,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Threshold,
             smoothing: None,
             stem_source: Some("drums".into()),
+            channel_source: None,
         },
         // === Bass ===
         AudioMapping {
@@ -520,6 +1057,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Smooth,
             smoothing: None,
             stem_source: Some("bass".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -530,6 +1068,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Exponential,
             smoothing: Some(0.4),
             stem_source: Some("bass".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -540,6 +1079,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: Some("bass".into()),
+            channel_source: None,
         },
         // === Other (instruments, synths) ===
         AudioMapping {
@@ -551,6 +1091,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: Some("other".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -561,6 +1102,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: Some("other".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -571,6 +1113,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Smooth,
             smoothing: None,
             stem_source: Some("other".into()),
+            channel_source: None,
         },
         // === Vocals ===
         AudioMapping {
@@ -582,6 +1125,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: Some("vocals".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -592,6 +1136,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Smooth,
             smoothing: Some(0.5),
             stem_source: Some("vocals".into()),
+            channel_source: None,
         },
         AudioMapping {
             enabled: true,
@@ -602,6 +1147,7 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
             curve: MappingCurve::Linear,
             smoothing: None,
             stem_source: Some("vocals".into()),
+            channel_source: None,
         },
     ]
 }
@@ -611,6 +1157,8 @@ pub fn stem_default_mappings() -> Vec<AudioMapping> {
 struct ConfigFile {
     render: RenderSection,
     audio: Option<AudioSection>,
+    midi: Option<MidiSection>,
+    export: Option<ExportSection>,
 }
 
 /// Render section of the TOML config, all fields optional for partial override.
@@ -650,9 +1198,13 @@ struct RenderSection {
     camera_pan_x: Option<f32>,
     camera_pan_y: Option<f32>,
     camera_tilt_x: Option<f32>,
+    generator_speed: Option<f32>,
+    generator_scale: Option<f32>,
+    generator_complexity: Option<f32>,
     target_fps: Option<u32>,
     fullscreen: Option<bool>,
     show_spectrum: Option<bool>,
+    display_backend: Option<DisplayBackend>,
 }
 
 /// Audio section of the TOML config, all fields optional.
@@ -661,9 +1213,22 @@ struct AudioSection {
     smoothing: Option<f32>,
     sensitivity: Option<f32>,
     input_gain: Option<f32>,
+    analysis: Option<AnalysisConfig>,
     mappings: Option<Vec<AudioMapping>>,
 }
 
+/// MIDI section of the TOML config, all fields optional.
+#[derive(Deserialize)]
+struct MidiSection {
+    mappings: Option<Vec<MidiMapping>>,
+}
+
+/// Export section of the TOML config, all fields optional.
+#[derive(Deserialize)]
+struct ExportSection {
+    profile: Option<ExportProfile>,
+}
+
 /// Charge un fichier TOML et fusionne avec les valeurs par défaut.
 ///
 /// # Errors
@@ -802,6 +1367,15 @@ pub fn load_config_from_str(content: &str) -> Result<RenderConfig> {
     if let Some(v) = r.camera_tilt_x {
         config.camera_tilt_x = v;
     }
+    if let Some(v) = r.generator_speed {
+        config.generator_speed = v;
+    }
+    if let Some(v) = r.generator_scale {
+        config.generator_scale = v;
+    }
+    if let Some(v) = r.generator_complexity {
+        config.generator_complexity = v;
+    }
     if let Some(v) = r.target_fps {
         config.target_fps = v;
     }
@@ -811,6 +1385,9 @@ pub fn load_config_from_str(content: &str) -> Result<RenderConfig> {
     if let Some(v) = r.show_spectrum {
         config.show_spectrum = v;
     }
+    if let Some(v) = r.display_backend {
+        config.display_backend = v;
+    }
 
     if let Some(a) = file.audio {
         if let Some(v) = a.smoothing {
@@ -822,11 +1399,26 @@ pub fn load_config_from_str(content: &str) -> Result<RenderConfig> {
         if let Some(v) = a.input_gain {
             config.input_gain = v;
         }
+        if let Some(v) = a.analysis {
+            config.analysis = v;
+        }
         if let Some(v) = a.mappings {
             config.audio_mappings = v;
         }
     }
 
+    if let Some(m) = file.midi
+        && let Some(v) = m.mappings
+    {
+        config.midi_mappings = v;
+    }
+
+    if let Some(e) = file.export
+        && let Some(v) = e.profile
+    {
+        config.export_profile = v;
+    }
+
     config.clamp_all();
     Ok(config)
 }
@@ -1,10 +1,62 @@
-use crate::frame::AudioFeatures;
+use crate::frame::{AudioFeatures, LoudnessMix, MAX_CUSTOM_BANDS};
 
 /// Number of stems (duplicated here to avoid circular dep with af-stems).
 const STEM_TIMELINE_COUNT: usize = 4;
 
+/// Type d'une section structurelle du morceau.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum SectionKind {
+    Intro,
+    Verse,
+    Chorus,
+    /// Énergie croissante menant à une section plus forte.
+    BuildUp,
+    /// Section forte arrivant après une montée ou un passage calme.
+    Drop,
+    /// Passage calme au milieu du morceau.
+    Breakdown,
+    Outro,
+}
+
+impl SectionKind {
+    /// Classe d'énergie équivalente (0=low, 1=medium, 2=high), comme `energy_levels`.
+    ///
+    /// # Example
+    /// ```
+    /// use af_core::feature_timeline::SectionKind;
+    /// assert_eq!(SectionKind::Drop.energy_level(), 2);
+    /// ```
+    #[must_use]
+    pub fn energy_level(self) -> u8 {
+        match self {
+            Self::Intro | Self::Breakdown | Self::Outro => 0,
+            Self::Verse | Self::BuildUp => 1,
+            Self::Chorus | Self::Drop => 2,
+        }
+    }
+}
+
+/// Une section structurelle : frames `start..end` de la timeline.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Section {
+    /// Première frame de la section.
+    pub start: usize,
+    /// Frame suivant la dernière (exclusive).
+    pub end: usize,
+    pub kind: SectionKind,
+    /// Sections au contenu similaire (refrains répétés) partagent le même label.
+    pub label: u8,
+    /// RMS moyen de la section.
+    pub energy: f32,
+}
+
 /// Une timeline complète pré-calculée des features audio d'un morceau.
 /// Utilisée pour le rendu offline (batch export).
+///
+/// Sérialisée en bincode dans les workflows (`timeline.bin`) : ce format est
+/// positionnel et ignore `#[serde(default)]`, tout champ ajouté ici ou dans
+/// [`AudioFeatures`] impose d'incrémenter
+/// [`crate::workflow_io::TIMELINE_FORMAT_VERSION`].
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct FeatureTimeline {
     /// Les features extraites pour chaque frame.
@@ -15,6 +67,9 @@ pub struct FeatureTimeline {
     pub sample_rate: u32,
     /// Energy classification per frame (0=low, 1=medium, 2=high).
     pub energy_levels: Vec<u8>,
+    /// Sections structurelles contiguës couvrant toutes les frames (vide si non calculées).
+    #[serde(default)]
+    pub sections: Vec<Section>,
 }
 
 impl FeatureTimeline {
@@ -23,7 +78,7 @@ impl FeatureTimeline {
     /// # Example
     /// ```
     /// use af_core::feature_timeline::FeatureTimeline;
-    /// let timeline = FeatureTimeline { frames: vec![], frame_duration: 0.016, sample_rate: 44100, energy_levels: vec![] };
+    /// let timeline = FeatureTimeline { frames: vec![], frame_duration: 0.016, sample_rate: 44100, energy_levels: vec![], sections: vec![] };
     /// let features = timeline.get_at_time(1.0);
     /// ```
     #[must_use]
@@ -50,6 +105,21 @@ impl FeatureTimeline {
         self.energy_levels.get(frame_idx).copied().unwrap_or(1) // default medium
     }
 
+    /// Section contenant `frame_idx`, `None` sans sections ou hors timeline.
+    #[must_use]
+    pub fn section_at(&self, frame_idx: usize) -> Option<&Section> {
+        let i = self.sections.partition_point(|s| s.end <= frame_idx);
+        self.sections.get(i).filter(|s| s.start <= frame_idx)
+    }
+
+    /// Section commençant à `frame_idx` : une frontière structurelle.
+    /// Le début du morceau (frame 0) n'en est pas une.
+    #[must_use]
+    pub fn section_starting_at(&self, frame_idx: usize) -> Option<&Section> {
+        self.section_at(frame_idx)
+            .filter(|s| s.start == frame_idx && frame_idx > 0)
+    }
+
     /// Normalize continuous audio features to [0, 1] across the entire timeline.
     ///
     /// Ensures consistent dynamic range regardless of source volume.
@@ -88,6 +158,12 @@ impl FeatureTimeline {
         collect_minmax!(timbral_roughness, tr_min, tr_max);
         collect_minmax!(onset_envelope, oe_min, oe_max);
         collect_minmax!(beat_intensity, bi_min, bi_max);
+        collect_minmax!(kick_intensity, ki_min, ki_max);
+        collect_minmax!(kick_envelope, ke_min, ke_max);
+        collect_minmax!(snare_intensity, si_min, si_max);
+        collect_minmax!(snare_envelope, se_min, se_max);
+        collect_minmax!(hat_intensity, hi_min, hi_max);
+        collect_minmax!(hat_envelope, he_min, he_max);
 
         for f in &mut self.frames {
             f.rms = norm(f.rms, rms_min, rms_max);
@@ -108,6 +184,12 @@ impl FeatureTimeline {
             f.timbral_roughness = norm(f.timbral_roughness, tr_min, tr_max);
             f.onset_envelope = norm(f.onset_envelope, oe_min, oe_max);
             f.beat_intensity = norm(f.beat_intensity, bi_min, bi_max);
+            f.kick_intensity = norm(f.kick_intensity, ki_min, ki_max);
+            f.kick_envelope = norm(f.kick_envelope, ke_min, ke_max);
+            f.snare_intensity = norm(f.snare_intensity, si_min, si_max);
+            f.snare_envelope = norm(f.snare_envelope, se_min, se_max);
+            f.hat_intensity = norm(f.hat_intensity, hi_min, hi_max);
+            f.hat_envelope = norm(f.hat_envelope, he_min, he_max);
         }
 
         // Normalize mfcc[5] per-element
@@ -135,6 +217,19 @@ impl FeatureTimeline {
                 f.spectrum_bands[k] = norm(f.spectrum_bands[k], band_min, band_max);
             }
         }
+
+        // Normalize custom_bands[MAX_CUSTOM_BANDS] per-element
+        for k in 0..MAX_CUSTOM_BANDS {
+            let mut band_min = f32::MAX;
+            let mut band_max = f32::MIN;
+            for f in &self.frames {
+                band_min = band_min.min(f.custom_bands[k]);
+                band_max = band_max.max(f.custom_bands[k]);
+            }
+            for f in &mut self.frames {
+                f.custom_bands[k] = norm(f.custom_bands[k], band_min, band_max);
+            }
+        }
     }
 
     /// Compute per-frame energy levels from smoothed RMS.
@@ -196,9 +291,11 @@ impl StemFeatureTimeline {
     /// `gains[i]` = 0.0 for muted stems, volume otherwise.
     /// Uses the same weighted-average logic as `af_stems::analysis::combine_stem_features`.
     #[must_use]
+    #[allow(clippy::too_many_lines)]
     pub fn get_at_time(&self, time: f64, gains: &[f32; STEM_TIMELINE_COUNT]) -> AudioFeatures {
         let mut combined = AudioFeatures::default();
         let mut weight_sum = 0.0f32;
+        let mut loudness = LoudnessMix::default();
 
         for (i, gain) in gains.iter().enumerate() {
             if *gain <= 0.0 {
@@ -221,6 +318,13 @@ impl StemFeatureTimeline {
             combined.spectral_flatness += f.spectral_flatness * w;
             combined.beat_intensity = combined.beat_intensity.max(f.beat_intensity);
             combined.bpm = combined.bpm.max(f.bpm);
+            // Beat grid: from the stem with the clearest pulse
+            if f.tempo_confidence > combined.tempo_confidence {
+                combined.beat_phase = f.beat_phase;
+                combined.bar_phase = f.bar_phase;
+                combined.beat_in_bar = f.beat_in_bar;
+                combined.tempo_confidence = f.tempo_confidence;
+            }
             combined.timbral_brightness += f.timbral_brightness * w;
             combined.timbral_roughness += f.timbral_roughness * w;
             combined.spectral_rolloff += f.spectral_rolloff * w;
@@ -228,12 +332,39 @@ impl StemFeatureTimeline {
             if f.onset {
                 combined.onset = true;
             }
+            // Drum onsets: OR / max across stems
+            combined.kick_onset |= f.kick_onset;
+            combined.kick_intensity = combined.kick_intensity.max(f.kick_intensity);
+            combined.kick_envelope = combined.kick_envelope.max(f.kick_envelope);
+            combined.snare_onset |= f.snare_onset;
+            combined.snare_intensity = combined.snare_intensity.max(f.snare_intensity);
+            combined.snare_envelope = combined.snare_envelope.max(f.snare_envelope);
+            combined.hat_onset |= f.hat_onset;
+            combined.hat_intensity = combined.hat_intensity.max(f.hat_intensity);
+            combined.hat_envelope = combined.hat_envelope.max(f.hat_envelope);
+            // Harmony: chroma weighted, pitch and key from the most confident stem
+            for (c, v) in combined.chroma.iter_mut().zip(f.chroma) {
+                *c += v * w;
+            }
+            if f.pitch_confidence > combined.pitch_confidence {
+                combined.pitch = f.pitch;
+                combined.pitch_confidence = f.pitch_confidence;
+            }
+            if f.key_confidence > combined.key_confidence {
+                combined.key = f.key;
+                combined.key_minor = f.key_minor;
+                combined.key_confidence = f.key_confidence;
+            }
             for (j, band) in f.spectrum_bands.iter().enumerate() {
                 combined.spectrum_bands[j] += band * w;
             }
             for (j, coeff) in f.mfcc.iter().enumerate() {
                 combined.mfcc[j] += coeff * w;
             }
+            for (band, v) in combined.custom_bands.iter_mut().zip(f.custom_bands) {
+                *band += v * w;
+            }
+            loudness.add(&f, w);
             weight_sum += w;
         }
 
@@ -260,6 +391,13 @@ impl StemFeatureTimeline {
             for coeff in &mut combined.mfcc {
                 *coeff *= inv;
             }
+            for c in &mut combined.chroma {
+                *c *= inv;
+            }
+            for band in &mut combined.custom_bands {
+                *band *= inv;
+            }
+            loudness.write_to(&mut combined);
         }
 
         combined
@@ -311,6 +449,7 @@ mod tests {
             frame_duration: 1.0 / 60.0,
             sample_rate: 44100,
             energy_levels: vec![],
+            sections: vec![],
         };
         timeline.normalize();
         assert!(
@@ -340,6 +479,7 @@ mod tests {
             frame_duration: 1.0 / 60.0,
             sample_rate: 44100,
             energy_levels: vec![],
+            sections: vec![],
         };
         timeline.compute_energy_levels();
         assert_eq!(timeline.energy_levels.len(), 600);
@@ -357,4 +497,41 @@ mod tests {
             "should have high energy"
         );
     }
+
+    #[test]
+    fn section_lookup() {
+        let section = |start, end, kind| Section {
+            start,
+            end,
+            kind,
+            label: 0,
+            energy: 0.5,
+        };
+        let timeline = FeatureTimeline {
+            frames: vec![AudioFeatures::default(); 30],
+            frame_duration: 1.0 / 60.0,
+            sample_rate: 44100,
+            energy_levels: vec![],
+            sections: vec![
+                section(0, 10, SectionKind::Intro),
+                section(10, 25, SectionKind::Drop),
+                section(25, 30, SectionKind::Outro),
+            ],
+        };
+        assert_eq!(
+            timeline.section_at(0).map(|s| s.kind),
+            Some(SectionKind::Intro)
+        );
+        assert_eq!(
+            timeline.section_at(24).map(|s| s.kind),
+            Some(SectionKind::Drop)
+        );
+        assert_eq!(timeline.section_at(30), None);
+        assert!(timeline.section_starting_at(0).is_none());
+        assert!(timeline.section_starting_at(11).is_none());
+        assert_eq!(
+            timeline.section_starting_at(25).map(|s| s.kind),
+            Some(SectionKind::Outro)
+        );
+    }
 }
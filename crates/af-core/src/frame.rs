@@ -307,13 +307,18 @@ impl AsciiGrid {
 /// Écrit par le thread audio, lu par le thread de rendu.
 /// Taille fixe, Copy, jamais alloué dynamiquement.
 ///
+/// Le défaut est le silence : tout à zéro, sauf la sonie et le true peak au
+/// plancher [`LOUDNESS_FLOOR_LUFS`] (0.0 serait la pleine échelle).
+///
 /// # Example
 /// ```
 /// use af_core::frame::AudioFeatures;
 /// let f = AudioFeatures::default();
 /// assert!(f.rms.abs() < f32::EPSILON);
+/// assert!(f.loudness_normalized().abs() < f32::EPSILON);
 /// ```
-#[derive(Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct AudioFeatures {
     // === Amplitude ===
     /// RMS (Root Mean Square) normalisé [0.0, 1.0].
@@ -354,6 +359,16 @@ pub struct AudioFeatures {
     pub bpm: f32,
     /// Phase du beat [0.0, 1.0] (0.0 = sur le beat, 0.5 = entre deux beats).
     pub beat_phase: f32,
+    /// Phase dans la mesure [0.0, 1.0] (0.0 = premier temps), mesure de
+    /// [`BEATS_PER_BAR`] temps.
+    #[serde(default)]
+    pub bar_phase: f32,
+    /// Temps courant dans la mesure, 0 = premier temps (downbeat).
+    #[serde(default)]
+    pub beat_in_bar: u8,
+    /// Confiance de l'estimation de tempo [0.0, 1.0] (0.0 = pas de pulsation).
+    #[serde(default)]
+    pub tempo_confidence: f32,
 
     // === Timbral features (MFCC-derived) ===
     /// MFCC coefficients (first 5).
@@ -373,7 +388,289 @@ pub struct AudioFeatures {
     /// Onset envelope (continuous, decays via strobe_decay) [0.0, 1.0].
     pub onset_envelope: f32,
 
+    // === Onsets par bande (batterie) ===
+    /// Kick (40–120 Hz) : onset détecté dans cette frame.
+    #[serde(default)]
+    pub kick_onset: bool,
+    /// Kick : force de l'onset [0.0, 1.0].
+    #[serde(default)]
+    pub kick_intensity: f32,
+    /// Kick : enveloppe décroissante [0.0, 1.0].
+    #[serde(default)]
+    pub kick_envelope: f32,
+    /// Snare (180–400 Hz + 2–5 kHz) : onset détecté dans cette frame.
+    #[serde(default)]
+    pub snare_onset: bool,
+    /// Snare : force de l'onset [0.0, 1.0].
+    #[serde(default)]
+    pub snare_intensity: f32,
+    /// Snare : enveloppe décroissante [0.0, 1.0].
+    #[serde(default)]
+    pub snare_envelope: f32,
+    /// Hi-hat (7–16 kHz) : onset détecté dans cette frame.
+    #[serde(default)]
+    pub hat_onset: bool,
+    /// Hi-hat : force de l'onset [0.0, 1.0].
+    #[serde(default)]
+    pub hat_intensity: f32,
+    /// Hi-hat : enveloppe décroissante [0.0, 1.0].
+    #[serde(default)]
+    pub hat_envelope: f32,
+
+    // === Harmonie ===
+    /// Chromagramme : énergie par classe de hauteur (C = 0), max normalisé à 1.0.
+    #[serde(default)]
+    pub chroma: [f32; 12],
+    /// Fréquence fondamentale estimée en Hz (0.0 si silence).
+    #[serde(default)]
+    pub pitch: f32,
+    /// Confiance de la fondamentale [0.0, 1.0] (part d'énergie harmonique).
+    #[serde(default)]
+    pub pitch_confidence: f32,
+    /// Tonique de la tonalité courante (classe de hauteur, C = 0).
+    #[serde(default)]
+    pub key: u8,
+    /// Tonalité mineure.
+    #[serde(default)]
+    pub key_minor: bool,
+    /// Confiance de la tonalité [0.0, 1.0].
+    #[serde(default)]
+    pub key_confidence: f32,
+
+    // === Sonie (EBU R128) ===
+    /// Sonie momentanée (400 ms) en LUFS, avant AGC (−70.0 si silence).
+    #[serde(default = "loudness_floor")]
+    pub loudness_lufs: f32,
+    /// True peak sur les 3 dernières secondes, en dBTP, avant AGC.
+    #[serde(default = "loudness_floor")]
+    pub true_peak: f32,
+    /// Rapport crête / sonie court terme, 0–24 dB normalisé [0.0, 1.0].
+    #[serde(default)]
+    pub dynamic_range: f32,
+
+    // === Bandes utilisateur ===
+    /// Énergie des bandes de `[audio.analysis]`, dans l'ordre de
+    /// `AnalysisConfig::custom_bands` (0.0 au-delà des bandes déclarées).
+    #[serde(default)]
+    pub custom_bands: [f32; MAX_CUSTOM_BANDS],
+
     // === Spectre compressé pour visualisation ===
     /// 32 bandes log-fréquence, normalisées [0.0, 1.0].
     pub spectrum_bands: [f32; 32],
 }
+
+impl Default for AudioFeatures {
+    fn default() -> Self {
+        Self {
+            rms: 0.0,
+            peak: 0.0,
+            sub_bass: 0.0,
+            bass: 0.0,
+            low_mid: 0.0,
+            mid: 0.0,
+            high_mid: 0.0,
+            presence: 0.0,
+            brilliance: 0.0,
+            spectral_centroid: 0.0,
+            spectral_flux: 0.0,
+            spectral_flatness: 0.0,
+            onset: false,
+            beat_intensity: 0.0,
+            bpm: 0.0,
+            beat_phase: 0.0,
+            bar_phase: 0.0,
+            beat_in_bar: 0,
+            tempo_confidence: 0.0,
+            mfcc: [0.0; 5],
+            timbral_brightness: 0.0,
+            timbral_roughness: 0.0,
+            spectral_rolloff: 0.0,
+            zero_crossing_rate: 0.0,
+            onset_envelope: 0.0,
+            kick_onset: false,
+            kick_intensity: 0.0,
+            kick_envelope: 0.0,
+            snare_onset: false,
+            snare_intensity: 0.0,
+            snare_envelope: 0.0,
+            hat_onset: false,
+            hat_intensity: 0.0,
+            hat_envelope: 0.0,
+            chroma: [0.0; 12],
+            pitch: 0.0,
+            pitch_confidence: 0.0,
+            key: 0,
+            key_minor: false,
+            key_confidence: 0.0,
+            loudness_lufs: LOUDNESS_FLOOR_LUFS,
+            true_peak: LOUDNESS_FLOOR_LUFS,
+            dynamic_range: 0.0,
+            custom_bands: [0.0; MAX_CUSTOM_BANDS],
+            spectrum_bands: [0.0; 32],
+        }
+    }
+}
+
+/// Défaut serde de `loudness_lufs` / `true_peak` (JSON sans ces champs).
+fn loudness_floor() -> f32 {
+    LOUDNESS_FLOOR_LUFS
+}
+
+/// Plage de recherche de la fondamentale (`pitch`), en Hz.
+pub const PITCH_MIN_HZ: f32 = 50.0;
+pub const PITCH_MAX_HZ: f32 = 2000.0;
+
+/// Sonie correspondant à 0.0 pour la source `loudness_lufs`.
+pub const LOUDNESS_MIN_LUFS: f32 = -60.0;
+/// Plancher des niveaux mesurés (porte absolue EBU R128), en LUFS / dBTP.
+pub const LOUDNESS_FLOOR_LUFS: f32 = -70.0;
+
+/// Sonie d'un mix de stems : somme des énergies pondérées par le carré du
+/// gain, true peak le plus fort, `dynamic_range` moyenné par les gains.
+///
+/// # Example
+/// ```
+/// use af_core::frame::{AudioFeatures, LoudnessMix};
+/// let stem = AudioFeatures { loudness_lufs: -20.0, ..AudioFeatures::default() };
+/// let mut mix = LoudnessMix::default();
+/// mix.add(&stem, 1.0);
+/// mix.add(&stem, 1.0);
+/// let mut out = AudioFeatures::default();
+/// mix.write_to(&mut out);
+/// assert!((out.loudness_lufs + 17.0).abs() < 0.1); // +3 dB
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoudnessMix {
+    energy: f32,
+    true_peak: Option<f32>,
+    dynamic_range: f32,
+    weight: f32,
+}
+
+impl LoudnessMix {
+    /// Ajoute un signal de gain `gain` (> 0).
+    pub fn add(&mut self, f: &AudioFeatures, gain: f32) {
+        self.energy += gain * gain * 10f32.powf(f.loudness_lufs / 10.0);
+        let peak = f.true_peak + 20.0 * gain.log10();
+        self.true_peak = Some(self.true_peak.map_or(peak, |p| p.max(peak)));
+        self.dynamic_range += f.dynamic_range * gain;
+        self.weight += gain;
+    }
+
+    /// Écrit la sonie du mix dans `features`.
+    pub fn write_to(&self, features: &mut AudioFeatures) {
+        features.loudness_lufs = if self.energy > 0.0 {
+            (10.0 * self.energy.log10()).max(LOUDNESS_FLOOR_LUFS)
+        } else {
+            LOUDNESS_FLOOR_LUFS
+        };
+        features.true_peak = self
+            .true_peak
+            .unwrap_or(LOUDNESS_FLOOR_LUFS)
+            .max(LOUDNESS_FLOOR_LUFS);
+        features.dynamic_range = if self.weight > 0.0 {
+            self.dynamic_range / self.weight
+        } else {
+            0.0
+        };
+    }
+}
+
+/// Sources de mapping du chromagramme, dans l'ordre de `AudioFeatures::chroma`.
+pub const CHROMA_SOURCES: [&str; 12] = [
+    "chroma_c",
+    "chroma_cs",
+    "chroma_d",
+    "chroma_ds",
+    "chroma_e",
+    "chroma_f",
+    "chroma_fs",
+    "chroma_g",
+    "chroma_gs",
+    "chroma_a",
+    "chroma_as",
+    "chroma_b",
+];
+
+impl AudioFeatures {
+    /// Fondamentale normalisée [0.0, 1.0] sur une échelle log entre
+    /// [`PITCH_MIN_HZ`] et [`PITCH_MAX_HZ`] (source de mapping `pitch`).
+    ///
+    /// # Example
+    /// ```
+    /// use af_core::frame::AudioFeatures;
+    /// let f = AudioFeatures { pitch: 2000.0, ..AudioFeatures::default() };
+    /// assert!((f.pitch_normalized() - 1.0).abs() < 1e-6);
+    /// ```
+    #[must_use]
+    pub fn pitch_normalized(&self) -> f32 {
+        if self.pitch <= 0.0 {
+            return 0.0;
+        }
+        ((self.pitch / PITCH_MIN_HZ).log2() / (PITCH_MAX_HZ / PITCH_MIN_HZ).log2()).clamp(0.0, 1.0)
+    }
+
+    /// Sonie normalisée [0.0, 1.0] entre [`LOUDNESS_MIN_LUFS`] et 0 LUFS
+    /// (source de mapping `loudness_lufs`).
+    ///
+    /// # Example
+    /// ```
+    /// use af_core::frame::AudioFeatures;
+    /// let f = AudioFeatures { loudness_lufs: -30.0, ..AudioFeatures::default() };
+    /// assert!((f.loudness_normalized() - 0.5).abs() < 1e-6);
+    /// ```
+    #[must_use]
+    pub fn loudness_normalized(&self) -> f32 {
+        (1.0 - self.loudness_lufs / LOUDNESS_MIN_LUFS).clamp(0.0, 1.0)
+    }
+
+    /// Valeur d'une source `chroma_*`, `None` pour les autres sources.
+    #[must_use]
+    pub fn chroma_source(&self, source: &str) -> Option<f32> {
+        CHROMA_SOURCES
+            .iter()
+            .position(|&s| s == source)
+            .map(|i| self.chroma[i])
+    }
+
+    /// Teinte [0.0, 1.0) de la tonalité sur le cercle des quintes : les
+    /// tonalités voisines ont des teintes proches, une relative mineure
+    /// partage la teinte de sa majeure (`ColorMode::KeyHue`).
+    ///
+    /// # Example
+    /// ```
+    /// use af_core::frame::AudioFeatures;
+    /// let c_major = AudioFeatures::default();
+    /// let a_minor = AudioFeatures { key: 9, key_minor: true, ..AudioFeatures::default() };
+    /// assert!((c_major.key_hue() - a_minor.key_hue()).abs() < f32::EPSILON);
+    /// ```
+    #[must_use]
+    pub fn key_hue(&self) -> f32 {
+        let major = if self.key_minor {
+            (self.key + 3) % 12
+        } else {
+            self.key % 12
+        };
+        f32::from(major * 7 % 12) / 12.0
+    }
+}
+
+/// Nombre de temps par mesure de la grille rythmique (`bar_phase`, `beat_in_bar`).
+pub const BEATS_PER_BAR: u8 = 4;
+
+/// Nombre maximal de bandes utilisateur (`[audio.analysis] bands`).
+pub const MAX_CUSTOM_BANDS: usize = 8;
+
+/// Nombre maximal de canaux analysés séparément (`--audio-channels split`).
+pub const MAX_INPUT_CHANNELS: usize = 8;
+
+/// Per-channel audio features of a multi-channel input, published via triple buffer.
+///
+/// Only the first `count` entries are meaningful. Read by mappings with
+/// `channel_source` (1-based), like `StemFeatures` for `stem_source`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChannelFeatures {
+    /// Number of analysed channels (≤ [`MAX_INPUT_CHANNELS`]).
+    pub count: usize,
+    pub features: [AudioFeatures; MAX_INPUT_CHANNELS],
+}
@@ -2,6 +2,7 @@
 ///
 /// This crate contains all shared types, traits, and configuration logic
 /// used across the clasSCII workspace.
+pub mod automation;
 pub mod charset;
 pub mod clock;
 pub mod color;
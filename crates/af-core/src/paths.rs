@@ -44,6 +44,10 @@ pub struct AppPaths {
     pub presets_dir: PathBuf,
     /// `base_dir/workflows/`
     pub workflows_dir: PathBuf,
+    /// `base_dir/snapshots/` — text snapshots from the TUI (created on demand).
+    pub snapshots_dir: PathBuf,
+    /// `base_dir/recordings/` — asciicast recordings from the TUI (created on demand).
+    pub recordings_dir: PathBuf,
     /// `base_dir/bundle/` — present only if the directory exists.
     pub bundle_dir: Option<PathBuf>,
 }
@@ -61,6 +65,8 @@ impl AppPaths {
             default_config: base.join("config").join("default.toml"),
             presets_dir: base.join("config").join("presets"),
             workflows_dir: base.join("workflows"),
+            snapshots_dir: base.join("snapshots"),
+            recordings_dir: base.join("recordings"),
             bundle_dir,
             base_dir: base,
         }
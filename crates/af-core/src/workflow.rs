@@ -74,6 +74,8 @@ pub enum MediaType {
     Image,
     Video,
     Audio,
+    /// Procedural generator (`path` holds the generator name).
+    Procedural,
     None,
 }
 
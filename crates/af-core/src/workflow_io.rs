@@ -6,6 +6,8 @@
 //!   manifest.toml          — version, timestamp, flags
 //!   config.toml            — full RenderConfig snapshot
 //!   source.toml            — SourceInfo (original paths)
+//!   automation.toml        — (optional) keyframe automation lanes
+//!   timeline.bin           — (optional) versioned bincode FeatureTimeline
 //!   stems/                 — (optional, if has_stems)
 //!     states.toml          — StemStatesSnapshot
 //!     metadata.toml        — StemSeparationInfo
@@ -20,7 +22,9 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
+use serde::Serialize;
 
+use crate::automation::Automation;
 use crate::config::RenderConfig;
 use crate::feature_timeline::FeatureTimeline;
 use crate::workflow::{
@@ -37,6 +41,8 @@ pub struct LoadedWorkflow {
     pub stem_info: Option<StemSeparationInfo>,
     /// Pre-computed feature timeline (bincode), if saved.
     pub feature_timeline: Option<FeatureTimeline>,
+    /// Keyframe automation, if saved.
+    pub automation: Option<Automation>,
     /// Path to the workflow directory (for resolving stem WAVs).
     pub dir: PathBuf,
 }
@@ -174,13 +180,24 @@ fn write_wav_f32(path: &Path, samples: &[f32], sample_rate: u32) -> Result<()> {
     Ok(())
 }
 
+/// Magic of `timeline.bin`, followed by [`TIMELINE_FORMAT_VERSION`] (u32 LE)
+/// and the bincode payload.
+const TIMELINE_MAGIC: [u8; 4] = *b"AFTL";
+
+/// Layout version of `timeline.bin`. Bincode is positional: any field added
+/// to `FeatureTimeline` or `AudioFeatures` must bump it, `#[serde(default)]`
+/// does not apply.
+pub const TIMELINE_FORMAT_VERSION: u32 = 2;
+
 /// Save a pre-computed feature timeline as a binary (bincode) file within a workflow.
 /// Also updates the manifest to set `has_feature_timeline = true`.
 ///
 /// # Errors
 /// Returns an error if serialization or file writing fails.
 pub fn save_feature_timeline(workflow_dir: &Path, timeline: &FeatureTimeline) -> Result<()> {
-    let encoded = bincode::serialize(timeline).context("Serialize feature timeline")?;
+    let mut encoded = TIMELINE_MAGIC.to_vec();
+    encoded.extend_from_slice(&TIMELINE_FORMAT_VERSION.to_le_bytes());
+    bincode::serialize_into(&mut encoded, timeline).context("Serialize feature timeline")?;
     let path = workflow_dir.join("timeline.bin");
     fs::write(&path, &encoded).with_context(|| format!("Write {}", path.display()))?;
 
@@ -204,15 +221,38 @@ pub fn save_feature_timeline(workflow_dir: &Path, timeline: &FeatureTimeline) ->
     Ok(())
 }
 
+/// Save keyframe automation lanes into a workflow directory.
+///
+/// # Errors
+/// Returns an error if serialization or writing fails.
+pub fn save_automation(workflow_dir: &Path, automation: &Automation) -> Result<()> {
+    let automation_toml = toml::to_string_pretty(automation).context("Serialize automation")?;
+    fs::write(workflow_dir.join("automation.toml"), &automation_toml)
+        .context("Write automation.toml")?;
+    Ok(())
+}
+
 /// Load a pre-computed feature timeline from a workflow directory.
 ///
 /// # Errors
-/// Returns an error if the file is missing or deserialization fails.
+/// Returns an error if the file is missing, was written with another
+/// [`TIMELINE_FORMAT_VERSION`] (or before versioning), or deserialization fails.
 pub fn load_feature_timeline(workflow_dir: &Path) -> Result<FeatureTimeline> {
     let path = workflow_dir.join("timeline.bin");
     let data = fs::read(&path).with_context(|| format!("Read {}", path.display()))?;
-    let timeline: FeatureTimeline =
-        bincode::deserialize(&data).context("Deserialize feature timeline")?;
+    let version = data
+        .strip_prefix(&TIMELINE_MAGIC)
+        .and_then(<[u8]>::first_chunk::<4>)
+        .map_or(1, |v| u32::from_le_bytes(*v));
+    if version != TIMELINE_FORMAT_VERSION {
+        anyhow::bail!(
+            "{} has timeline format {version}, this build reads format {TIMELINE_FORMAT_VERSION}: \
+             re-analyse the audio and save the workflow again",
+            path.display()
+        );
+    }
+    let timeline: FeatureTimeline = bincode::deserialize(&data[TIMELINE_MAGIC.len() + 4..])
+        .context("Deserialize feature timeline")?;
     log::info!(
         "Feature timeline loaded: {} frames",
         timeline.total_frames()
@@ -271,7 +311,7 @@ pub fn load_workflow(dir: &Path) -> Result<LoadedWorkflow> {
         match load_feature_timeline(dir) {
             Ok(tl) => Some(tl),
             Err(e) => {
-                log::warn!("Could not load feature timeline: {e}");
+                log::warn!("Could not load feature timeline: {e:#}");
                 None
             }
         }
@@ -279,6 +319,14 @@ pub fn load_workflow(dir: &Path) -> Result<LoadedWorkflow> {
         None
     };
 
+    // Automation (optional)
+    let automation_path = dir.join("automation.toml");
+    let automation = if automation_path.exists() {
+        Some(Automation::load(&automation_path)?)
+    } else {
+        None
+    };
+
     log::info!("Workflow loaded from {}", dir.display());
     Ok(LoadedWorkflow {
         manifest,
@@ -287,6 +335,7 @@ pub fn load_workflow(dir: &Path) -> Result<LoadedWorkflow> {
         stem_states,
         stem_info,
         feature_timeline,
+        automation,
         dir: dir.to_path_buf(),
     })
 }
@@ -328,7 +377,7 @@ pub fn list_workflows(workflows_dir: &Path) -> Result<Vec<String>> {
 }
 
 /// Detailed workflow entry for TUI browsing.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct WorkflowEntry {
     pub name: String,
     pub created_at: String,
@@ -507,6 +556,77 @@ mod tests {
         let _ = fs::remove_dir_all(&tmp);
     }
 
+    #[test]
+    fn timeline_format_is_versioned() {
+        let dir = std::env::temp_dir().join("classcii_test_timeline_version");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let timeline = FeatureTimeline {
+            frames: vec![crate::frame::AudioFeatures::default(); 3],
+            frame_duration: 1.0 / 30.0,
+            sample_rate: 44100,
+            energy_levels: vec![1; 3],
+            sections: Vec::new(),
+        };
+        save_feature_timeline(&dir, &timeline).unwrap();
+        assert_eq!(load_feature_timeline(&dir).unwrap().total_frames(), 3);
+
+        // Timeline written before the header existed: plain bincode
+        fs::write(
+            dir.join("timeline.bin"),
+            bincode::serialize(&timeline).unwrap(),
+        )
+        .unwrap();
+        let err = load_feature_timeline(&dir).unwrap_err().to_string();
+        assert!(err.contains("re-analyse"), "{err}");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn automation_roundtrip() {
+        use crate::automation::{AutomationLane, Easing, Keyframe};
+
+        let tmp = std::env::temp_dir().join("classcii_test_workflow_automation");
+        let _ = fs::remove_dir_all(&tmp);
+
+        let automation = Automation {
+            automation: vec![AutomationLane {
+                enabled: true,
+                target: "camera_zoom_amplitude".into(),
+                keyframes: vec![
+                    Keyframe {
+                        time: 0.0,
+                        value: 1.0,
+                        easing: Easing::Linear,
+                    },
+                    Keyframe {
+                        time: 8.0,
+                        value: 3.0,
+                        easing: Easing::EaseInOut,
+                    },
+                ],
+            }],
+        };
+        let dir = save_workflow(
+            "auto_wf",
+            &test_config(),
+            &test_source(),
+            None,
+            None,
+            None,
+            &tmp,
+        )
+        .unwrap();
+        save_automation(&dir, &automation).unwrap();
+
+        let loaded = load_workflow(&dir).unwrap();
+        assert_eq!(loaded.automation, Some(automation));
+
+        let _ = fs::remove_dir_all(&tmp);
+    }
+
     #[test]
     fn save_and_load_with_stems() {
         let tmp = std::env::temp_dir().join("classcii_test_workflow_stems");
@@ -97,3 +97,50 @@ fn default_config_loads_successfully() {
         config.audio_smoothing
     );
 }
+
+#[test]
+fn export_section_sets_profile() {
+    use af_core::config::{ExportProfile, load_config_from_str};
+
+    let config = load_config_from_str("[render]\n").expect("minimal config");
+    assert_eq!(config.export_profile, ExportProfile::LosslessRgb);
+
+    let config =
+        load_config_from_str("[render]\n[export]\nprofile = \"Vp9\"\n").expect("export section");
+    assert_eq!(config.export_profile, ExportProfile::Vp9);
+
+    assert!(load_config_from_str("[render]\n[export]\nprofile = \"Divx\"\n").is_err());
+    for profile in ExportProfile::ALL {
+        assert_eq!(ExportProfile::from_name(profile.name()), Some(profile));
+    }
+}
+
+#[test]
+fn analysis_section_sets_fft_and_bands() {
+    use af_core::config::{WindowFunction, load_config_from_str};
+
+    let config = load_config_from_str("[render]\n").expect("minimal config");
+    assert_eq!(config.analysis.fft_size, 2048);
+    assert_eq!(config.analysis.hop_size(), 2048);
+
+    let toml = r#"
+[render]
+[audio.analysis]
+fft_size = 3000
+window = "Blackman"
+overlap = 0.5
+bands = [
+    { name = "bass", low_hz = 50.0, high_hz = 200.0 },
+    { name = "kick_body", low_hz = 40.0, high_hz = 90.0 },
+]
+"#;
+    let config = load_config_from_str(toml).expect("analysis section");
+    let analysis = &config.analysis;
+    assert_eq!(analysis.fft_size, 4096, "rounded up to a power of two");
+    assert_eq!(analysis.window, WindowFunction::Blackman);
+    assert_eq!(analysis.hop_size(), 2048);
+    // Built-in name moves its edges, other names become sources
+    assert_eq!(analysis.builtin_edges()[1], (50.0, 200.0));
+    assert_eq!(analysis.custom_band_index("kick_body"), Some(0));
+    assert_eq!(analysis.custom_band_index("bass"), None);
+}
@@ -0,0 +1,425 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use af_core::frame::FrameBuffer;
+use anyhow::{Context, Result};
+
+/// Durée maximale par défaut d'un export GIF/APNG (secondes).
+pub const DEFAULT_MAX_DURATION_SECS: f32 = 10.0;
+
+/// Nombre maximal de frames d'un export GIF/APNG, quelle que soit la durée demandée.
+pub const MAX_ANIM_FRAMES: u32 = 3000;
+
+/// Vitesse NeuQuant (1 = meilleure qualité, 30 = plus rapide).
+const GIF_QUANT_SPEED: i32 = 10;
+
+/// Cases de l'histogramme de couleurs (3 bits par canal) + une case transparente.
+const HISTOGRAM_BINS: usize = 513;
+
+/// Écart d'histogramme (distance L1 des proportions, 0–2) au-delà duquel la
+/// palette GIF est réentraînée.
+const PALETTE_REFRESH_DISTANCE: f32 = 0.2;
+
+/// Format d'animation pur Rust (sans ffmpeg).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnimFormat {
+    /// GIF 256 couleurs, palette NeuQuant conservée tant que les couleurs de
+    /// l'image ne changent pas sensiblement.
+    Gif,
+    /// APNG truecolor RGBA, sans perte.
+    Apng,
+}
+
+impl AnimFormat {
+    /// Détecte le format depuis l'extension : `.gif`, `.apng` ou `.png`.
+    ///
+    /// # Example
+    /// ```
+    /// use af_export::animation::AnimFormat;
+    /// use std::path::Path;
+    /// assert_eq!(AnimFormat::from_path(Path::new("loop.GIF")), Some(AnimFormat::Gif));
+    /// assert_eq!(AnimFormat::from_path(Path::new("loop.png")), Some(AnimFormat::Apng));
+    /// assert_eq!(AnimFormat::from_path(Path::new("out.mp4")), None);
+    /// ```
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "gif" => Some(Self::Gif),
+            "apng" | "png" => Some(Self::Apng),
+            _ => None,
+        }
+    }
+}
+
+enum Inner {
+    Gif {
+        encoder: gif::Encoder<BufWriter<File>>,
+        /// Copie RGBA normalisée (alpha 0 ou 255) de la frame courante.
+        scratch: Vec<u8>,
+        palette: GifPalette,
+        /// Indices de palette de la frame courante (réutilisés).
+        indices: Vec<u8>,
+    },
+    Apng(png::Writer<BufWriter<File>>),
+}
+
+/// Palette NeuQuant partagée entre frames.
+///
+/// Une palette entraînée à chaque frame varie légèrement d'une frame à l'autre
+/// et fait scintiller un contenu ASCII quasi statique : elle n'est réentraînée
+/// que lorsque l'histogramme de couleurs s'écarte de celui qui l'a produite
+/// (ou qu'apparaît de la transparence qu'elle ne connaît pas).
+struct GifPalette {
+    quant: Option<Box<color_quant::NeuQuant>>,
+    rgb: Vec<u8>,
+    trained_on: Vec<u32>,
+    trained_transparent: bool,
+    histogram: Vec<u32>,
+}
+
+impl GifPalette {
+    fn new() -> Self {
+        Self {
+            quant: None,
+            rgb: Vec::new(),
+            trained_on: vec![0; HISTOGRAM_BINS],
+            trained_transparent: false,
+            histogram: vec![0; HISTOGRAM_BINS],
+        }
+    }
+
+    /// Quantifie `pixels` (RGBA, alpha 0 ou 255) dans `indices` et retourne
+    /// l'indice transparent éventuel. Réentraîne la palette si besoin.
+    fn quantize(&mut self, pixels: &[u8], indices: &mut Vec<u8>) -> Option<u8> {
+        self.histogram.fill(0);
+        let mut transparent: Option<[u8; 4]> = None;
+        for px in pixels.chunks_exact(4) {
+            let bin = if px[3] == 0 {
+                transparent.get_or_insert([px[0], px[1], px[2], 0]);
+                HISTOGRAM_BINS - 1
+            } else {
+                (usize::from(px[0] >> 5) << 6)
+                    | (usize::from(px[1] >> 5) << 3)
+                    | usize::from(px[2] >> 5)
+            };
+            self.histogram[bin] += 1;
+        }
+
+        let stale = transparent.is_some() && !self.trained_transparent
+            || histogram_distance(&self.histogram, &self.trained_on) > PALETTE_REFRESH_DISTANCE;
+        let quant = match self.quant.take() {
+            Some(quant) if !stale => quant,
+            _ => {
+                let quant = Box::new(color_quant::NeuQuant::new(GIF_QUANT_SPEED, 256, pixels));
+                self.rgb = quant.color_map_rgb();
+                self.trained_on.copy_from_slice(&self.histogram);
+                self.trained_transparent = transparent.is_some();
+                quant
+            }
+        };
+
+        indices.clear();
+        indices.extend(pixels.chunks_exact(4).map(|px| quant.index_of(px) as u8));
+        let transparent = transparent.map(|t| quant.index_of(&t) as u8);
+        self.quant = Some(quant);
+        transparent
+    }
+}
+
+/// Distance L1 entre les proportions de deux histogrammes (0 = identiques, 2 = disjoints).
+fn histogram_distance(a: &[u32], b: &[u32]) -> f32 {
+    let (na, nb) = (a.iter().sum::<u32>(), b.iter().sum::<u32>());
+    if na == 0 || nb == 0 {
+        return 2.0;
+    }
+    a.iter()
+        .zip(b)
+        .map(|(&x, &y)| (x as f32 / na as f32 - y as f32 / nb as f32).abs())
+        .sum()
+}
+
+/// Encodeur GIF/APNG frame par frame, en streaming.
+///
+/// Le nombre de frames doit être connu à l'avance (exigé par l'en-tête APNG,
+/// vérifié pour les deux formats).
+pub struct AnimEncoder {
+    inner: Inner,
+    width: u32,
+    height: u32,
+    fps: u32,
+    frame_count: u32,
+    written: u32,
+}
+
+impl AnimEncoder {
+    /// Crée le fichier et écrit l'en-tête. L'animation boucle indéfiniment.
+    ///
+    /// # Errors
+    /// Retourne une erreur si le fichier ne peut être créé, si `frame_count`
+    /// vaut 0, ou si les dimensions dépassent 65535 px en GIF.
+    pub fn new(
+        path: &Path,
+        format: AnimFormat,
+        width: u32,
+        height: u32,
+        fps: u32,
+        frame_count: u32,
+    ) -> Result<Self> {
+        anyhow::ensure!(frame_count > 0, "Animation vide (0 frame)");
+        let fps = fps.clamp(1, u32::from(u16::MAX));
+        let file = File::create(path)
+            .with_context(|| format!("Impossible de créer {}", path.display()))?;
+        let writer = BufWriter::new(file);
+
+        let inner = match format {
+            AnimFormat::Gif => {
+                let w = u16::try_from(width).context("Largeur GIF > 65535 px")?;
+                let h = u16::try_from(height).context("Hauteur GIF > 65535 px")?;
+                let mut encoder = gif::Encoder::new(writer, w, h, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                Inner::Gif {
+                    encoder,
+                    scratch: Vec::with_capacity((width * height * 4) as usize),
+                    palette: GifPalette::new(),
+                    indices: Vec::with_capacity((width * height) as usize),
+                }
+            }
+            AnimFormat::Apng => {
+                let mut encoder = png::Encoder::new(writer, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_compression(png::Compression::Fast);
+                encoder.set_animated(frame_count, 0)?;
+                encoder.set_frame_delay(1, fps as u16)?;
+                Inner::Apng(encoder.write_header()?)
+            }
+        };
+
+        Ok(Self {
+            inner,
+            width,
+            height,
+            fps,
+            frame_count,
+            written: 0,
+        })
+    }
+
+    /// Ajoute une frame (dimensions identiques à celles de `new`).
+    ///
+    /// # Errors
+    /// Retourne une erreur si les dimensions diffèrent, si `frame_count` est
+    /// déjà atteint, ou en cas d'erreur d'encodage/écriture.
+    pub fn write_frame(&mut self, fb: &FrameBuffer) -> Result<()> {
+        anyhow::ensure!(
+            fb.width == self.width && fb.height == self.height,
+            "Frame {}x{} ≠ animation {}x{}",
+            fb.width,
+            fb.height,
+            self.width,
+            self.height
+        );
+        anyhow::ensure!(
+            self.written < self.frame_count,
+            "Animation déjà complète ({} frames)",
+            self.frame_count
+        );
+
+        match &mut self.inner {
+            Inner::Gif {
+                encoder,
+                scratch,
+                palette,
+                indices,
+            } => {
+                // GIF : transparence binaire, un seul RGBA pour tous les pixels transparents
+                scratch.clear();
+                scratch.extend(fb.data.chunks_exact(4).flat_map(|px| {
+                    if px[3] == 0 {
+                        [0; 4]
+                    } else {
+                        [px[0], px[1], px[2], 255]
+                    }
+                }));
+                let transparent = palette.quantize(scratch, indices);
+                let frame = gif::Frame {
+                    width: self.width as u16,
+                    height: self.height as u16,
+                    buffer: std::borrow::Cow::Borrowed(indices.as_slice()),
+                    palette: Some(palette.rgb.clone()),
+                    transparent,
+                    delay: gif_delay_cs(self.written, self.fps),
+                    ..gif::Frame::default()
+                };
+                encoder.write_frame(&frame)?;
+            }
+            Inner::Apng(writer) => writer.write_image_data(&fb.data)?,
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Nombre de frames écrites.
+    #[must_use]
+    pub fn frames_written(&self) -> u32 {
+        self.written
+    }
+
+    /// Finalise le fichier.
+    ///
+    /// # Errors
+    /// Retourne une erreur si moins de `frame_count` frames ont été écrites
+    /// (APNG) ou si le flush échoue.
+    pub fn finish(self) -> Result<()> {
+        match self.inner {
+            Inner::Gif { encoder, .. } => {
+                let mut writer = encoder.into_inner()?;
+                std::io::Write::flush(&mut writer)?;
+            }
+            Inner::Apng(writer) => {
+                anyhow::ensure!(
+                    self.written == self.frame_count,
+                    "APNG incomplet : {}/{} frames",
+                    self.written,
+                    self.frame_count
+                );
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Délai GIF (centièmes de seconde) de la frame `index` à `fps`.
+///
+/// Le GIF n'a qu'une résolution de 10 ms : les délais sont arrondis de façon
+/// cumulative pour que la durée totale reste exacte (30 fps → 3, 3, 4, 3…).
+fn gif_delay_cs(index: u32, fps: u32) -> u16 {
+    let at = |i: u32| (u64::from(i) * 100 + u64::from(fps) / 2) / u64::from(fps);
+    (at(index + 1) - at(index)).clamp(1, u64::from(u16::MAX)) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(w: u32, h: u32, rgb: [u8; 3]) -> FrameBuffer {
+        let mut fb = FrameBuffer::new(w, h);
+        for px in fb.data.chunks_exact_mut(4) {
+            px.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+        }
+        fb
+    }
+
+    fn tmp(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("classcii_{}_{name}", std::process::id()))
+    }
+
+    #[test]
+    fn gif_delays_sum_to_exact_duration() {
+        let total: u32 = (0..30).map(|i| u32::from(gif_delay_cs(i, 30))).sum();
+        assert_eq!(total, 100);
+        assert_eq!(gif_delay_cs(0, 25), 4);
+        assert_eq!(gif_delay_cs(0, 200), 1);
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn gif_roundtrip_frame_count_and_colors() {
+        let path = tmp("anim.gif");
+        let mut enc = AnimEncoder::new(&path, AnimFormat::Gif, 8, 4, 10, 3).expect("new");
+        for rgb in [[255, 0, 0], [0, 255, 0], [0, 0, 255]] {
+            enc.write_frame(&frame(8, 4, rgb)).expect("frame");
+        }
+        enc.finish().expect("finish");
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let file = File::open(&path).expect("open");
+        let mut decoder = options.read_info(file).expect("decode");
+        let mut firsts = Vec::new();
+        while let Some(f) = decoder.read_next_frame().expect("frame") {
+            assert_eq!(f.delay, 10);
+            firsts.push([f.buffer[0], f.buffer[1], f.buffer[2]]);
+        }
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(firsts.len(), 3);
+        // Couleurs disjointes : palette réentraînée, couleurs pleines reproduites
+        assert!(firsts[0][0] > 200 && firsts[0][1] < 50);
+        assert!(firsts[1][1] > 200 && firsts[1][0] < 50);
+        assert!(firsts[2][2] > 200 && firsts[2][1] < 50);
+    }
+
+    #[test]
+    fn palette_is_kept_for_similar_frames() {
+        let mut palette = GifPalette::new();
+        let mut indices = Vec::new();
+        let mut pixels: Vec<u8> = (0..64u8)
+            .flat_map(|i| [i * 4, 255 - i * 4, 128, 255])
+            .collect();
+        assert_eq!(palette.quantize(&pixels, &mut indices), None);
+        let first = palette.rgb.clone();
+
+        // Un glyphe change de couleur : même palette
+        pixels[..4].copy_from_slice(&[0, 0, 0, 255]);
+        palette.quantize(&pixels, &mut indices);
+        assert_eq!(palette.rgb, first);
+
+        // Image entièrement différente, puis transparence : réentraînée
+        let red: Vec<u8> = [255, 0, 0, 255].repeat(64);
+        palette.quantize(&red, &mut indices);
+        assert_ne!(palette.rgb, first);
+        let mut holes = red.clone();
+        holes[..8].fill(0);
+        let transparent = palette.quantize(&holes, &mut indices);
+        assert_eq!(transparent, Some(indices[0]));
+        assert_ne!(indices[0], indices[2]);
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn apng_roundtrip_is_lossless() {
+        let path = tmp("anim.apng");
+        let mut enc = AnimEncoder::new(&path, AnimFormat::Apng, 4, 2, 30, 2).expect("new");
+        enc.write_frame(&frame(4, 2, [10, 20, 30]))
+            .expect("frame 0");
+        enc.write_frame(&frame(4, 2, [200, 100, 50]))
+            .expect("frame 1");
+        enc.finish().expect("finish");
+
+        let decoder = png::Decoder::new(std::io::BufReader::new(File::open(&path).expect("open")));
+        let mut reader = decoder.read_info().expect("info");
+        let actl = reader.info().animation_control().expect("acTL");
+        assert_eq!(actl.num_frames, 2);
+        assert_eq!(actl.num_plays, 0);
+        let mut buf = vec![0; reader.output_buffer_size().expect("size")];
+        reader.next_frame(&mut buf).expect("frame 0");
+        assert_eq!(&buf[..4], &[10, 20, 30, 255]);
+        reader.next_frame(&mut buf).expect("frame 1");
+        assert_eq!(&buf[..4], &[200, 100, 50, 255]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn rejects_extra_frames_and_size_mismatch() {
+        let path = tmp("limits.gif");
+        let mut enc = AnimEncoder::new(&path, AnimFormat::Gif, 2, 2, 10, 1).expect("new");
+        assert!(enc.write_frame(&frame(3, 2, [0, 0, 0])).is_err());
+        enc.write_frame(&frame(2, 2, [0, 0, 0])).expect("frame");
+        assert!(enc.write_frame(&frame(2, 2, [0, 0, 0])).is_err());
+        enc.finish().expect("finish");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn apng_requires_all_declared_frames() {
+        let path = tmp("short.apng");
+        if let Ok(enc) = AnimEncoder::new(&path, AnimFormat::Apng, 2, 2, 10, 2) {
+            assert!(enc.finish().is_err());
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
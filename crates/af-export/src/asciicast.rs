@@ -0,0 +1,315 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use af_core::frame::{AsciiCell, AsciiGrid};
+use anyhow::{Context, Result};
+
+/// Encodeur différentiel `AsciiGrid` → flux ANSI.
+///
+/// La première frame (ou après un changement de taille) est un redraw complet ;
+/// les suivantes n'émettent que les cellules modifiées, avec positionnement
+/// curseur et SGR truecolor uniquement quand nécessaire. L'état du « stylo »
+/// (couleurs courantes, position curseur) persiste d'une frame à l'autre,
+/// comme dans un vrai terminal.
+pub struct DiffEncoder {
+    prev: AsciiGrid,
+    primed: bool,
+    pen_fg: Option<(u8, u8, u8)>,
+    pen_bg: Option<(u8, u8, u8)>,
+    cursor: Option<(u16, u16)>,
+    out: String,
+}
+
+impl Default for DiffEncoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiffEncoder {
+    /// Crée un encodeur vierge (le prochain `encode` sera un redraw complet).
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            prev: AsciiGrid::new(0, 0),
+            primed: false,
+            pen_fg: None,
+            pen_bg: None,
+            cursor: None,
+            out: String::new(),
+        }
+    }
+
+    /// Force un redraw complet au prochain `encode`.
+    pub fn reset(&mut self) {
+        self.primed = false;
+    }
+
+    /// Encode `grid` relativement à la frame précédente.
+    ///
+    /// Returns an empty string when nothing changed.
+    pub fn encode(&mut self, grid: &AsciiGrid) -> &str {
+        self.out.clear();
+
+        let full = !self.primed || self.prev.width != grid.width || self.prev.height != grid.height;
+        if full {
+            // Reset SGR, efface l'écran : l'état du stylo repart de zéro
+            self.out.push_str("\x1b[0m\x1b[H\x1b[2J");
+            self.pen_fg = None;
+            self.pen_bg = None;
+            self.cursor = Some((0, 0));
+            self.prev = AsciiGrid::new(grid.width, grid.height);
+        }
+
+        let w = usize::from(grid.width);
+        for (i, cell) in grid.cells.iter().enumerate() {
+            if !full && cells_equal(cell, &self.prev.cells[i]) {
+                continue;
+            }
+            let x = (i % w) as u16;
+            let y = (i / w) as u16;
+            if self.cursor != Some((x, y)) {
+                let _ = write!(self.out, "\x1b[{};{}H", y + 1, x + 1);
+            }
+            self.write_pen(cell);
+            self.out.push(cell.ch);
+            // Fin de ligne : position curseur indéterminée (auto-wrap)
+            self.cursor = if x + 1 < grid.width {
+                Some((x + 1, y))
+            } else {
+                None
+            };
+        }
+
+        self.prev.copy_from(grid);
+        self.primed = true;
+        &self.out
+    }
+
+    fn write_pen(&mut self, cell: &AsciiCell) {
+        if self.pen_fg != Some(cell.fg) {
+            let (r, g, b) = cell.fg;
+            let _ = write!(self.out, "\x1b[38;2;{r};{g};{b}m");
+            self.pen_fg = Some(cell.fg);
+        }
+        if self.pen_bg != Some(cell.bg) {
+            if cell.bg == (0, 0, 0) {
+                self.out.push_str("\x1b[49m");
+            } else {
+                let (r, g, b) = cell.bg;
+                let _ = write!(self.out, "\x1b[48;2;{r};{g};{b}m");
+            }
+            self.pen_bg = Some(cell.bg);
+        }
+    }
+}
+
+#[inline]
+fn cells_equal(a: &AsciiCell, b: &AsciiCell) -> bool {
+    a.ch == b.ch && a.fg == b.fg && a.bg == b.bg
+}
+
+/// Fichier audio joué pendant l'enregistrement, pour resynchroniser le replay.
+#[derive(Clone, Debug)]
+pub struct CastAudio {
+    /// Chemin du fichier audio.
+    pub file: String,
+    /// Position de lecture (secondes) au début de l'enregistrement.
+    pub offset_secs: f64,
+}
+
+/// Enregistreur asciicast v2 (`.cast`, rejouable avec `asciinema play`).
+///
+/// Header JSON sur la première ligne, puis un événement `[t, "o", data]` par
+/// frame modifiée (diff ANSI) et `[t, "r", "COLSxROWS"]` aux changements de
+/// taille. L'audio éventuel est décrit par un champ `audio` du header
+/// (`file`, `offset`), ignoré par les lecteurs standards.
+pub struct CastRecorder {
+    writer: BufWriter<File>,
+    encoder: DiffEncoder,
+    start: Instant,
+    size: (u16, u16),
+    path: PathBuf,
+}
+
+impl CastRecorder {
+    /// Crée le fichier et écrit le header pour une grille `width × height`.
+    ///
+    /// # Errors
+    /// Retourne une erreur si le fichier ne peut être créé.
+    pub fn create(path: &Path, width: u16, height: u16, audio: Option<&CastAudio>) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Impossible de créer {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let mut header = serde_json::json!({
+            "version": 2,
+            "width": width,
+            "height": height,
+            "timestamp": timestamp,
+            "title": "clasSCII",
+            "env": { "TERM": "xterm-256color" },
+        });
+        if let Some(a) = audio {
+            header["audio"] = serde_json::json!({ "file": a.file, "offset": a.offset_secs });
+        }
+        writeln!(writer, "{header}")?;
+
+        Ok(Self {
+            writer,
+            encoder: DiffEncoder::new(),
+            start: Instant::now(),
+            size: (width, height),
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Chemin du fichier `.cast`.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Enregistre `grid` à l'instant courant.
+    ///
+    /// # Errors
+    /// Retourne une erreur d'écriture disque.
+    pub fn record_frame(&mut self, grid: &AsciiGrid) -> Result<()> {
+        let t = self.start.elapsed().as_secs_f64();
+        self.record_frame_at(t, grid)
+    }
+
+    /// Enregistre `grid` au temps `t` (secondes depuis le début).
+    ///
+    /// # Errors
+    /// Retourne une erreur d'écriture disque.
+    pub fn record_frame_at(&mut self, t: f64, grid: &AsciiGrid) -> Result<()> {
+        if (grid.width, grid.height) != self.size {
+            self.size = (grid.width, grid.height);
+            writeln!(
+                self.writer,
+                "[{t:.6}, \"r\", \"{}x{}\"]",
+                grid.width, grid.height
+            )?;
+        }
+        let data = self.encoder.encode(grid);
+        if !data.is_empty() {
+            writeln!(
+                self.writer,
+                "[{t:.6}, \"o\", {}]",
+                serde_json::to_string(data)?
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Vide les buffers et ferme le fichier.
+    ///
+    /// # Errors
+    /// Retourne une erreur si le flush échoue.
+    pub fn finish(mut self) -> Result<PathBuf> {
+        self.writer.flush()?;
+        Ok(self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(ch: char, fg: (u8, u8, u8)) -> AsciiCell {
+        AsciiCell {
+            ch,
+            fg,
+            bg: (0, 0, 0),
+        }
+    }
+
+    #[test]
+    fn first_frame_is_full_redraw() {
+        let mut grid = AsciiGrid::new(2, 2);
+        grid.set(0, 0, cell('a', (255, 0, 0)));
+        grid.set(1, 0, cell('b', (255, 0, 0)));
+        let mut enc = DiffEncoder::new();
+        assert_eq!(
+            enc.encode(&grid),
+            "\x1b[0m\x1b[H\x1b[2J\x1b[38;2;255;0;0m\x1b[49mab\x1b[2;1H\x1b[38;2;0;0;0m  "
+        );
+    }
+
+    #[test]
+    fn unchanged_frame_emits_nothing() {
+        let grid = AsciiGrid::new(3, 2);
+        let mut enc = DiffEncoder::new();
+        let _ = enc.encode(&grid);
+        assert!(enc.encode(&grid).is_empty());
+    }
+
+    #[test]
+    fn diff_emits_only_changed_cells() {
+        let mut grid = AsciiGrid::new(4, 2);
+        let mut enc = DiffEncoder::new();
+        let _ = enc.encode(&grid);
+
+        grid.set(2, 1, cell('#', (0, 0, 0)));
+        grid.set(3, 1, cell('%', (0, 255, 0)));
+        assert_eq!(enc.encode(&grid), "\x1b[2;3H#\x1b[38;2;0;255;0m%");
+
+        // Le stylo vert persiste d'une frame à l'autre
+        grid.set(0, 0, cell('x', (0, 255, 0)));
+        assert_eq!(enc.encode(&grid), "\x1b[1;1Hx");
+    }
+
+    #[test]
+    fn resize_triggers_full_redraw() {
+        let mut enc = DiffEncoder::new();
+        let _ = enc.encode(&AsciiGrid::new(2, 1));
+        assert!(
+            enc.encode(&AsciiGrid::new(3, 1))
+                .starts_with("\x1b[0m\x1b[H\x1b[2J")
+        );
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn recorder_writes_asciicast_v2() {
+        let path = std::env::temp_dir().join(format!("classcii_cast_{}.cast", std::process::id()));
+        let audio = CastAudio {
+            file: "track.mp3".into(),
+            offset_secs: 12.5,
+        };
+        let mut rec = CastRecorder::create(&path, 2, 1, Some(&audio)).expect("create");
+        let mut grid = AsciiGrid::new(2, 1);
+        rec.record_frame_at(0.0, &grid).expect("frame 0");
+        rec.record_frame_at(0.016, &grid).expect("unchanged frame");
+        grid.set(1, 0, cell('@', (0, 0, 0)));
+        rec.record_frame_at(0.033, &grid).expect("frame 2");
+        rec.record_frame_at(0.05, &AsciiGrid::new(3, 1))
+            .expect("resize");
+        rec.finish().expect("finish");
+
+        let text = std::fs::read_to_string(&path).expect("read back");
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 5, "{text}");
+
+        let header: serde_json::Value = serde_json::from_str(lines[0]).expect("header json");
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 2);
+        assert_eq!(header["height"], 1);
+        assert_eq!(header["audio"]["file"], "track.mp3");
+        assert_eq!(header["audio"]["offset"], 12.5);
+
+        assert!(lines[1].starts_with("[0.000000, \"o\", \"\\u001b[0m"));
+        assert_eq!(lines[2], "[0.033000, \"o\", \"\\u001b[1;2H@\"]");
+        assert_eq!(lines[3], "[0.050000, \"r\", \"3x1\"]");
+        let event: serde_json::Value = serde_json::from_str(lines[4]).expect("event json");
+        assert_eq!(event[1], "o");
+    }
+}
@@ -0,0 +1,463 @@
+//! Affichage du canvas par protocole graphique terminal (Sixel, Kitty) :
+//! rasterisation de l'`AsciiGrid` puis encodage, découpé au budget de cellules
+//! du canvas pour ne jamais déborder sur la sidebar ni la dernière ligne.
+
+use af_core::config::DisplayBackend;
+use af_core::frame::{AsciiGrid, FrameBuffer};
+
+use crate::rasterizer::Rasterizer;
+
+/// Taille maximale d'un chunk base64 par séquence APC (spec Kitty).
+const KITTY_CHUNK: usize = 4096;
+
+/// Niveaux par canal du cube couleur Sixel (6×6×6 = 216 registres).
+const SIXEL_LEVELS: u32 = 6;
+
+/// Rendu du canvas via un protocole graphique terminal (Sixel ou Kitty).
+///
+/// Rasterise l'`AsciiGrid` avec le [`Rasterizer`] de l'export puis encode le
+/// framebuffer. Les buffers (pixels, octets de sortie, indices Sixel) sont
+/// réutilisés d'une frame à l'autre.
+pub struct GraphicsRenderer {
+    rasterizer: Rasterizer,
+    fb: FrameBuffer,
+    out: Vec<u8>,
+    sixel_indices: Vec<u8>,
+}
+
+impl GraphicsRenderer {
+    /// Crée un renderer avec la police embarquée, à la taille `scale_px`.
+    ///
+    /// # Errors
+    /// Retourne une erreur si la police embarquée ne peut être chargée.
+    pub fn new(scale_px: f32) -> anyhow::Result<Self> {
+        let font_data = include_bytes!("../assets/CascadiaMono.ttf");
+        Ok(Self {
+            rasterizer: Rasterizer::new(font_data, scale_px)?,
+            fb: FrameBuffer::new(0, 0),
+            out: Vec::new(),
+            sixel_indices: Vec::new(),
+        })
+    }
+
+    /// Pixel size of one rasterized cell `(width, height)`.
+    #[must_use]
+    pub fn cell_size(&self) -> (u32, u32) {
+        self.rasterizer.target_dimensions(1, 1)
+    }
+
+    /// Rasterise `grid` et encode le résultat pour `backend`.
+    ///
+    /// `max_cells` `(cols, rows)` is the canvas budget: the image is cropped to
+    /// `cols` columns (never over the sidebar) with both backends. Sixel is also
+    /// cropped to `rows` (keeps the image off the last terminal line, which
+    /// would scroll); Kitty does not scroll and keeps `grid.height` rows.
+    /// Returns an empty slice for [`DisplayBackend::Text`].
+    pub fn encode(
+        &mut self,
+        grid: &AsciiGrid,
+        backend: DisplayBackend,
+        zalgo_intensity: f32,
+        max_cells: (u16, u16),
+        image_id: u32,
+    ) -> &[u8] {
+        self.out.clear();
+        if !backend.is_graphics() || grid.width == 0 || grid.height == 0 {
+            return &self.out;
+        }
+
+        let (w, h) = self.rasterizer.target_dimensions(grid.width, grid.height);
+        if self.fb.width != w || self.fb.height != h {
+            self.fb = FrameBuffer::new(w, h);
+        }
+        self.rasterizer.render(grid, &mut self.fb, zalgo_intensity);
+
+        let (cell_w, cell_h) = self.cell_size();
+        let cols = grid.width.min(max_cells.0.max(1));
+        let width = (u32::from(cols) * cell_w).min(w);
+        match backend {
+            DisplayBackend::Sixel => {
+                let rows = u32::from(grid.height.min(max_cells.1.max(1)));
+                let height = (rows * cell_h).min(h);
+                write_sixel(
+                    &self.fb.data,
+                    w,
+                    width,
+                    height,
+                    &mut self.sixel_indices,
+                    &mut self.out,
+                );
+            }
+            DisplayBackend::Kitty => {
+                write_kitty(
+                    &self.fb.data,
+                    w,
+                    (width, h),
+                    (cols, grid.height),
+                    image_id,
+                    &mut self.out,
+                );
+            }
+            DisplayBackend::Text => {}
+        }
+        &self.out
+    }
+}
+
+/// Encode un framebuffer RGBA en séquence Sixel (DCS … ST).
+///
+/// Couleurs quantifiées sur un cube 6×6×6 ; seuls les registres utilisés sont
+/// déclarés. Alpha ignoré. Runs ≥ 4 compressés en `!n`.
+pub fn encode_sixel(fb: &FrameBuffer, out: &mut Vec<u8>) {
+    let mut indices = Vec::new();
+    write_sixel(&fb.data, fb.width, fb.width, fb.height, &mut indices, out);
+}
+
+/// Encode un framebuffer en séquence Kitty (`a=T`, RGB 24 bits, base64 chunké).
+///
+/// L'image est affichée à la position du curseur, mise à l'échelle sur
+/// `cols × rows` cellules, sans déplacer le curseur (`C=1`) et sans réponse
+/// du terminal (`q=2`). Réutiliser `image_id` remplace l'image précédente.
+pub fn encode_kitty(fb: &FrameBuffer, cols: u16, rows: u16, image_id: u32, out: &mut Vec<u8>) {
+    write_kitty(
+        &fb.data,
+        fb.width,
+        (fb.width, fb.height),
+        (cols, rows),
+        image_id,
+        out,
+    );
+}
+
+/// Cœur de l'encodeur Kitty sur le rectangle `size` en haut à gauche de
+/// `data` (lignes de `stride` pixels).
+fn write_kitty(
+    data: &[u8],
+    stride: u32,
+    size: (u32, u32),
+    (cols, rows): (u16, u16),
+    image_id: u32,
+    out: &mut Vec<u8>,
+) {
+    let (stride, w) = (stride.max(1) as usize, size.0.min(stride) as usize);
+    let h = (size.1 as usize).min(data.len() / 4 / stride);
+    let mut rgb = Vec::with_capacity(w * h * 3);
+    for row in data.chunks_exact(stride * 4).take(h) {
+        for px in row[..w * 4].chunks_exact(4) {
+            rgb.extend_from_slice(&px[..3]);
+        }
+    }
+    let mut b64 = Vec::with_capacity(rgb.len().div_ceil(3) * 4);
+    base64_encode_into(&rgb, &mut b64);
+
+    let mut chunks = b64.chunks(KITTY_CHUNK).peekable();
+    let mut first = true;
+    // Image vide : une seule séquence sans payload.
+    if chunks.peek().is_none() {
+        write_kitty_header(size, cols, rows, image_id, out);
+        out.extend_from_slice(b";\x1b\\");
+        return;
+    }
+    while let Some(chunk) = chunks.next() {
+        let more = chunks.peek().is_some();
+        if first {
+            write_kitty_header(size, cols, rows, image_id, out);
+            if more {
+                out.extend_from_slice(b",m=1");
+            }
+            first = false;
+        } else {
+            out.extend_from_slice(if more { b"\x1b_Gm=1" } else { b"\x1b_Gm=0" });
+        }
+        out.push(b';');
+        out.extend_from_slice(chunk);
+        out.extend_from_slice(b"\x1b\\");
+    }
+}
+
+/// Supprime l'image Kitty `image_id` et toutes ses placements.
+pub fn kitty_delete(image_id: u32, out: &mut Vec<u8>) {
+    out.extend_from_slice(format!("\x1b_Ga=d,d=I,i={image_id},q=2\x1b\\").as_bytes());
+}
+
+fn write_kitty_header(
+    (width, height): (u32, u32),
+    cols: u16,
+    rows: u16,
+    image_id: u32,
+    out: &mut Vec<u8>,
+) {
+    out.extend_from_slice(
+        format!("\x1b_Ga=T,f=24,s={width},v={height},c={cols},r={rows},i={image_id},q=2,C=1")
+            .as_bytes(),
+    );
+}
+
+/// Cœur de l'encodeur Sixel sur les `width × height` premiers pixels de
+/// `data` (lignes de `stride` pixels).
+fn write_sixel(
+    data: &[u8],
+    stride: u32,
+    width: u32,
+    height: u32,
+    indices: &mut Vec<u8>,
+    out: &mut Vec<u8>,
+) {
+    let (stride, w) = (stride.max(1) as usize, width.min(stride) as usize);
+    let h = (height as usize).min(data.len() / 4 / stride);
+
+    out.extend_from_slice(b"\x1bPq");
+    out.extend_from_slice(format!("\"1;1;{w};{h}").as_bytes());
+
+    // Quantification + registres utilisés
+    indices.clear();
+    for row in data.chunks_exact(stride * 4).take(h) {
+        indices.extend(
+            row[..w * 4]
+                .chunks_exact(4)
+                .map(|px| cube_index(px[0], px[1], px[2])),
+        );
+    }
+    let mut used = [false; 216];
+    for &i in indices.iter() {
+        used[i as usize] = true;
+    }
+    for (i, _) in used.iter().enumerate().filter(|(_, u)| **u) {
+        let (r, g, b) = cube_percent(i as u8);
+        out.extend_from_slice(format!("#{i};2;{r};{g};{b}").as_bytes());
+    }
+
+    // Bandes de 6 lignes
+    let mut line = Vec::with_capacity(w);
+    for y0 in (0..h).step_by(6) {
+        let band_h = (h - y0).min(6);
+        let mut band_used = [false; 216];
+        for row in 0..band_h {
+            for &i in &indices[(y0 + row) * w..(y0 + row + 1) * w] {
+                band_used[i as usize] = true;
+            }
+        }
+
+        let mut first_color = true;
+        for (color, _) in band_used.iter().enumerate().filter(|(_, u)| **u) {
+            line.clear();
+            for x in 0..w {
+                let mut bits = 0u8;
+                for row in 0..band_h {
+                    if indices[(y0 + row) * w + x] as usize == color {
+                        bits |= 1 << row;
+                    }
+                }
+                line.push(b'?' + bits);
+            }
+            // Les pixels vides en fin de ligne sont implicites
+            while line.last() == Some(&b'?') {
+                line.pop();
+            }
+
+            if !first_color {
+                out.push(b'$');
+            }
+            first_color = false;
+            out.extend_from_slice(format!("#{color}").as_bytes());
+            write_sixel_rle(&line, out);
+        }
+
+        if y0 + 6 < h {
+            out.push(b'-');
+        }
+    }
+
+    out.extend_from_slice(b"\x1b\\");
+}
+
+/// Écrit une ligne de caractères sixel avec compression `!count`.
+fn write_sixel_rle(line: &[u8], out: &mut Vec<u8>) {
+    let mut i = 0;
+    while i < line.len() {
+        let c = line[i];
+        let mut run = 1;
+        while i + run < line.len() && line[i + run] == c {
+            run += 1;
+        }
+        if run >= 4 {
+            out.extend_from_slice(format!("!{run}").as_bytes());
+            out.push(c);
+        } else {
+            out.extend(std::iter::repeat_n(c, run));
+        }
+        i += run;
+    }
+}
+
+#[inline]
+fn cube_level(v: u8) -> u32 {
+    (u32::from(v) * (SIXEL_LEVELS - 1) + 127) / 255
+}
+
+#[inline]
+fn cube_index(r: u8, g: u8, b: u8) -> u8 {
+    (cube_level(r) * SIXEL_LEVELS * SIXEL_LEVELS + cube_level(g) * SIXEL_LEVELS + cube_level(b))
+        as u8
+}
+
+/// Composantes du registre en pourcentage (0-100), comme l'attend `#i;2;r;g;b`.
+fn cube_percent(index: u8) -> (u32, u32, u32) {
+    let i = u32::from(index);
+    let step = 100 / (SIXEL_LEVELS - 1);
+    (
+        (i / (SIXEL_LEVELS * SIXEL_LEVELS)) * step,
+        (i / SIXEL_LEVELS % SIXEL_LEVELS) * step,
+        (i % SIXEL_LEVELS) * step,
+    )
+}
+
+/// Base64 standard (RFC 4648) avec padding.
+fn base64_encode_into(input: &[u8], out: &mut Vec<u8>) {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    for chunk in input.chunks(3) {
+        let b0 = u32::from(chunk[0]);
+        let b1 = chunk.get(1).copied().map_or(0, u32::from);
+        let b2 = chunk.get(2).copied().map_or(0, u32::from);
+        let n = (b0 << 16) | (b1 << 8) | b2;
+        out.push(TABLE[(n >> 18) as usize & 63]);
+        out.push(TABLE[(n >> 12) as usize & 63]);
+        out.push(if chunk.len() > 1 {
+            TABLE[(n >> 6) as usize & 63]
+        } else {
+            b'='
+        });
+        out.push(if chunk.len() > 2 {
+            TABLE[n as usize & 63]
+        } else {
+            b'='
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fb_from(width: u32, height: u32, pixels: &[[u8; 3]]) -> FrameBuffer {
+        let mut fb = FrameBuffer::new(width, height);
+        for (dst, src) in fb.data.chunks_exact_mut(4).zip(pixels) {
+            dst[..3].copy_from_slice(src);
+            dst[3] = 255;
+        }
+        fb
+    }
+
+    #[test]
+    fn base64_vectors() {
+        for (input, expected) in [
+            (&b""[..], ""),
+            (b"M", "TQ=="),
+            (b"Ma", "TWE="),
+            (b"Man", "TWFu"),
+            (b"\xff\x00\x00", "/wAA"),
+        ] {
+            let mut out = Vec::new();
+            base64_encode_into(input, &mut out);
+            assert_eq!(out, expected.as_bytes());
+        }
+    }
+
+    #[test]
+    fn sixel_golden_2x2() {
+        let fb = fb_from(
+            2,
+            2,
+            &[[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]],
+        );
+        let mut out = Vec::new();
+        encode_sixel(&fb, &mut out);
+        let expected: &[u8] = b"\x1bPq\"1;1;2;2\
+            #5;2;0;0;100#30;2;0;100;0#180;2;100;0;0#215;2;100;100;100\
+            #5A$#30?@$#180@$#215?A\
+            \x1b\\";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn sixel_golden_rle_and_bands() {
+        // 8×7 noir : une bande pleine (6 lignes) puis une bande d'une ligne.
+        let fb = fb_from(8, 7, &[[0, 0, 0]; 56]);
+        let mut out = Vec::new();
+        encode_sixel(&fb, &mut out);
+        let expected: &[u8] = b"\x1bPq\"1;1;8;7#0;2;0;0;0#0!8~-#0!8@\x1b\\";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn sixel_short_runs_not_compressed() {
+        let fb = fb_from(3, 1, &[[255, 255, 255]; 3]);
+        let mut out = Vec::new();
+        encode_sixel(&fb, &mut out);
+        let expected: &[u8] = b"\x1bPq\"1;1;3;1#215;2;100;100;100#215@@@\x1b\\";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn kitty_golden_single_chunk() {
+        let fb = fb_from(1, 1, &[[255, 0, 0]]);
+        let mut out = Vec::new();
+        encode_kitty(&fb, 1, 1, 7, &mut out);
+        let expected: &[u8] = b"\x1b_Ga=T,f=24,s=1,v=1,c=1,r=1,i=7,q=2,C=1;/wAA\x1b\\";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn kitty_chunks_large_payload() {
+        // 1366 px × 3 octets = 4098 octets → 5464 caractères base64 → 2 chunks.
+        let fb = fb_from(1366, 1, &[[0, 0, 0]; 1366]);
+        let mut out = Vec::new();
+        encode_kitty(&fb, 10, 1, 1, &mut out);
+
+        let header: &[u8] = b"\x1b_Ga=T,f=24,s=1366,v=1,c=10,r=1,i=1,q=2,C=1,m=1;";
+        assert!(out.starts_with(header));
+        let second = header.len() + KITTY_CHUNK;
+        assert_eq!(&out[second..second + 2], b"\x1b\\");
+        assert!(out[second + 2..].starts_with(b"\x1b_Gm=0;"));
+        assert!(out.ends_with(b"\x1b\\"));
+        assert_eq!(out.len(), header.len() + 5464 + 2 + 7 + 2);
+    }
+
+    #[test]
+    fn kitty_delete_sequence() {
+        let mut out = Vec::new();
+        kitty_delete(3, &mut out);
+        assert_eq!(out, b"\x1b_Ga=d,d=I,i=3,q=2\x1b\\");
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn renderer_text_backend_emits_nothing() {
+        let mut renderer = GraphicsRenderer::new(8.0).expect("font should load");
+        let grid = AsciiGrid::new(4, 2);
+        assert!(
+            renderer
+                .encode(&grid, DisplayBackend::Text, 0.0, (4, 2), 1)
+                .is_empty()
+        );
+        let sixel = renderer.encode(&grid, DisplayBackend::Sixel, 0.0, (4, 1), 1);
+        assert!(sixel.starts_with(b"\x1bPq"));
+        assert!(sixel.ends_with(b"\x1b\\"));
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn renderer_crops_to_column_budget() {
+        let mut renderer = GraphicsRenderer::new(8.0).expect("font should load");
+        let (cell_w, cell_h) = renderer.cell_size();
+        let grid = AsciiGrid::new(6, 2);
+
+        let sixel = renderer.encode(&grid, DisplayBackend::Sixel, 0.0, (4, 2), 1);
+        let raster = format!("\"1;1;{};{}", 4 * cell_w, 2 * cell_h);
+        assert!(sixel.starts_with(format!("\x1bPq{raster}").as_bytes()));
+
+        let kitty = renderer.encode(&grid, DisplayBackend::Kitty, 0.0, (4, 1), 1);
+        let header = format!("\x1b_Ga=T,f=24,s={},v={},c=4,r=2,", 4 * cell_w, 2 * cell_h);
+        assert!(kitty.starts_with(header.as_bytes()));
+    }
+}
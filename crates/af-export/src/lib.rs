@@ -1,2 +1,7 @@
+pub mod animation;
+pub mod asciicast;
+pub mod graphics;
 pub mod muxer;
 pub mod rasterizer;
+pub mod text;
+pub mod timeline;
@@ -1,10 +1,233 @@
+use af_core::clock::FrameRate;
+use af_core::config::ExportProfile;
 use af_core::frame::FrameBuffer;
 use anyhow::{Context, Result};
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
 
-/// Encode des raw frames RGBA dans un fichier MP4 avec ffmpeg (Lossless).
+/// Paramètres ffmpeg et contraintes de conteneur d'un [`ExportProfile`].
+#[derive(Clone, Copy, Debug)]
+pub struct ProfileSpec {
+    /// Arguments d'encodage vidéo (`-c:v …`, qualité, `-pix_fmt`).
+    pub video_args: &'static [&'static str],
+    /// Extensions de conteneur acceptées (la première est celle par défaut).
+    /// Vide pour une séquence d'images (la sortie est un dossier).
+    pub containers: &'static [&'static str],
+    /// Arguments audio du muxage final. `None` = pas de piste audio.
+    pub audio_args: Option<&'static [&'static str]>,
+    /// Le format pixel sous-échantillonne la chroma (largeur/hauteur paires).
+    pub needs_even_dims: bool,
+    /// Le format pixel conserve le canal alpha.
+    pub alpha: bool,
+}
+
+const AAC_320K: &[&str] = &["-c:a", "aac", "-b:a", "320k"];
+
+impl ProfileSpec {
+    /// Spécification d'un profil.
+    #[must_use]
+    pub fn of(profile: ExportProfile) -> Self {
+        match profile {
+            ExportProfile::LosslessRgb => Self {
+                video_args: &[
+                    "-c:v",
+                    "libx264rgb", // FORCE SOTA LOSSLESS RGB ENCODER
+                    "-crf",
+                    "0",
+                    "-preset",
+                    "veryslow",
+                    "-pix_fmt",
+                    "rgb24", // FORCE PURITY OF CHROMA (NO YUV SUBSAMPLING)
+                    "-color_range",
+                    "pc", // Use full PC range (0-255) for RGB instead of limited tv range
+                ],
+                containers: &["mp4", "mkv", "mov"],
+                audio_args: Some(AAC_320K),
+                needs_even_dims: false,
+                alpha: false,
+            },
+            ExportProfile::H264 => Self {
+                video_args: &[
+                    "-c:v",
+                    "libx264",
+                    "-crf",
+                    "18",
+                    "-preset",
+                    "slow",
+                    "-pix_fmt",
+                    "yuv420p",
+                    "-movflags",
+                    "+faststart",
+                ],
+                containers: &["mp4", "mkv", "mov"],
+                audio_args: Some(AAC_320K),
+                needs_even_dims: true,
+                alpha: false,
+            },
+            ExportProfile::Hevc => Self {
+                video_args: &[
+                    "-c:v",
+                    "libx265",
+                    "-crf",
+                    "20",
+                    "-preset",
+                    "medium",
+                    "-pix_fmt",
+                    "yuv420p",
+                    "-tag:v",
+                    "hvc1", // Lecture QuickTime / Safari
+                    "-movflags",
+                    "+faststart",
+                ],
+                containers: &["mp4", "mkv", "mov"],
+                audio_args: Some(AAC_320K),
+                needs_even_dims: true,
+                alpha: false,
+            },
+            ExportProfile::Vp9 => Self {
+                video_args: &[
+                    "-c:v",
+                    "libvpx-vp9",
+                    "-crf",
+                    "24",
+                    "-b:v",
+                    "0",
+                    "-row-mt",
+                    "1",
+                    "-pix_fmt",
+                    "yuva420p",
+                ],
+                containers: &["webm", "mkv"],
+                audio_args: Some(&["-c:a", "libopus", "-b:a", "256k"]),
+                needs_even_dims: true,
+                alpha: true,
+            },
+            ExportProfile::ProRes4444 => Self {
+                video_args: &[
+                    "-c:v",
+                    "prores_ks",
+                    "-profile:v",
+                    "4444",
+                    "-pix_fmt",
+                    "yuva444p10le",
+                    "-vendor",
+                    "apl0",
+                ],
+                containers: &["mov", "mkv"],
+                audio_args: Some(&["-c:a", "pcm_s16le"]),
+                needs_even_dims: false,
+                alpha: true,
+            },
+            ExportProfile::PngSequence => Self {
+                video_args: &["-c:v", "png", "-pix_fmt", "rgba", "-start_number", "0"],
+                containers: &[],
+                audio_args: None,
+                needs_even_dims: false,
+                alpha: true,
+            },
+        }
+    }
+
+    /// Extension par défaut (vide pour une séquence PNG).
+    #[must_use]
+    pub fn default_extension(&self) -> &'static str {
+        self.containers.first().copied().unwrap_or("")
+    }
+}
+
+/// Vérifie que `path` est une sortie valide pour `profile`.
+///
+/// Vidéo : l'extension doit appartenir aux conteneurs du profil (`.webm` pour
+/// VP9, `.mov` pour ProRes…). Séquence PNG : un dossier (sans extension) ou un
+/// motif `frame_%05d.png`.
+///
+/// # Errors
+/// Retourne une erreur décrivant les extensions attendues.
+pub fn validate_output(profile: ExportProfile, path: &Path) -> Result<()> {
+    let spec = ProfileSpec::of(profile);
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+
+    if profile == ExportProfile::PngSequence {
+        let is_pattern = path.to_string_lossy().contains('%');
+        return match ext.as_deref() {
+            None => Ok(()),
+            Some("png") if is_pattern => Ok(()),
+            _ => anyhow::bail!(
+                "Profil png-sequence : sortie attendue = dossier ou motif frame_%05d.png (reçu {})",
+                path.display()
+            ),
+        };
+    }
+
+    match ext {
+        Some(e) if spec.containers.contains(&e.as_str()) => Ok(()),
+        _ => anyhow::bail!(
+            "Profil {} incompatible avec {} (extensions acceptées : {})",
+            profile.name(),
+            path.display(),
+            spec.containers.join(", ")
+        ),
+    }
+}
+
+/// Motif ffmpeg des images d'une séquence PNG : `path` tel quel s'il contient
+/// `%`, sinon `path/frame_%05d.png`.
+#[must_use]
+pub fn png_sequence_pattern(path: &Path) -> PathBuf {
+    if path.to_string_lossy().contains('%') {
+        path.to_path_buf()
+    } else {
+        path.join("frame_%05d.png")
+    }
+}
+
+/// Arguments ffmpeg complets pour encoder du RGBA brut `width × height` depuis stdin.
+fn encoder_args(
+    profile: ExportProfile,
+    width: u32,
+    height: u32,
+    rate: FrameRate,
+    output: &str,
+) -> Vec<String> {
+    let spec = ProfileSpec::of(profile);
+    let mut args: Vec<String> = [
+        "-y",
+        "-f",
+        "rawvideo",
+        "-vcodec",
+        "rawvideo",
+        "-s",
+        &format!("{width}x{height}"),
+        "-pix_fmt",
+        "rgba",
+        "-r",
+        &rate.to_string(),
+        "-i",
+        "-",
+    ]
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+
+    // Chroma 4:2:0 : dimensions impaires refusées par l'encodeur → pad d'un pixel
+    if spec.needs_even_dims && (width % 2 == 1 || height % 2 == 1) {
+        args.push("-vf".into());
+        args.push("pad=ceil(iw/2)*2:ceil(ih/2)*2".into());
+    }
+    args.extend(spec.video_args.iter().map(ToString::to_string));
+    args.extend(
+        ["-hide_banner", "-loglevel", "error", output]
+            .iter()
+            .map(ToString::to_string),
+    );
+    args
+}
+
+/// Encode des raw frames RGBA avec ffmpeg selon un [`ExportProfile`].
 pub struct Mp4Muxer {
     ffmpeg_child: Option<Child>,
 }
@@ -16,38 +239,45 @@ impl Mp4Muxer {
     /// # Errors
     /// Retourne une erreur si ffmpeg n'est pas installé ou impossible à démarrer.
     pub fn new(output_path: &Path, width: u32, height: u32, target_fps: u32) -> Result<Self> {
-        let path_str = output_path.to_str().context("Chemin invalide")?;
+        Self::with_profile(
+            output_path,
+            width,
+            height,
+            FrameRate::integer(target_fps),
+            ExportProfile::LosslessRgb,
+        )
+    }
+
+    /// Crée un Muxer vidéo pour `profile`, à la cadence exacte `rate`.
+    ///
+    /// Pour [`ExportProfile::PngSequence`], `output_path` est un dossier (créé
+    /// si besoin) ou un motif `%05d`.
+    ///
+    /// # Errors
+    /// Retourne une erreur si la sortie est incompatible avec le profil, ou si
+    /// ffmpeg n'est pas installé ou impossible à démarrer.
+    pub fn with_profile(
+        output_path: &Path,
+        width: u32,
+        height: u32,
+        rate: FrameRate,
+        profile: ExportProfile,
+    ) -> Result<Self> {
+        validate_output(profile, output_path)?;
+        let output = if profile == ExportProfile::PngSequence {
+            let pattern = png_sequence_pattern(output_path);
+            if let Some(dir) = pattern.parent() {
+                std::fs::create_dir_all(dir)
+                    .with_context(|| format!("Impossible de créer {}", dir.display()))?;
+            }
+            pattern
+        } else {
+            output_path.to_path_buf()
+        };
+        let path_str = output.to_str().context("Chemin invalide")?;
 
         let child = Command::new(af_core::paths::ffmpeg_bin())
-            .args([
-                "-y",
-                "-f",
-                "rawvideo",
-                "-vcodec",
-                "rawvideo",
-                "-s",
-                &format!("{width}x{height}"),
-                "-pix_fmt",
-                "rgba",
-                "-r",
-                &target_fps.to_string(),
-                "-i",
-                "-",
-                "-c:v",
-                "libx264rgb", // FORCE SOTA LOSSLESS RGB ENCODER
-                "-crf",
-                "0",
-                "-preset",
-                "veryslow",
-                "-pix_fmt",
-                "rgb24", // FORCE PURITY OF CHROMA (NO YUV SUBSAMPLING)
-                "-color_range",
-                "pc", // Use full PC range (0-255) for RGB instead of limited tv range
-                "-hide_banner",
-                "-loglevel",
-                "error",
-                path_str,
-            ])
+            .args(encoder_args(profile, width, height, rate, path_str))
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
             .stderr(Stdio::piped())
@@ -107,34 +337,34 @@ impl Drop for Mp4Muxer {
     }
 }
 
-/// Fusionne un MP4 sans piste audio avec un fichier source Audio.
+/// Fusionne une vidéo sans piste audio avec un fichier source Audio.
+///
+/// Le codec audio suit le conteneur du profil (AAC en MP4, Opus en WebM,
+/// PCM en MOV ProRes). Sans piste audio (séquence PNG), ne fait rien.
+/// Les flux sont mappés explicitement (vidéo de `video_path`, première piste
+/// audio de `audio_path`) : `audio_path` peut être une vidéo ou un MP3 avec
+/// pochette sans que son flux image soit choisi.
 ///
 /// # Errors
 /// Retourne une erreur si le muxage ffmpeg échoue.
-pub fn mux_audio_video(video_path: &Path, audio_path: &Path, final_path: &Path) -> Result<()> {
+pub fn mux_audio_video(
+    video_path: &Path,
+    audio_path: &Path,
+    final_path: &Path,
+    profile: ExportProfile,
+) -> Result<()> {
+    let Some(audio_args) = ProfileSpec::of(profile).audio_args else {
+        return Ok(());
+    };
     let video_str = video_path.to_str().context("video path invalid")?;
     let audio_str = audio_path.to_str().context("audio path invalid")?;
     let final_str = final_path.to_str().context("final path invalid")?;
 
     let mut command = Command::new(af_core::paths::ffmpeg_bin());
-    command.args([
-        "-y",
-        "-i",
-        video_str,
-        "-i",
-        audio_str,
-        "-c:v",
-        "copy",
-        "-c:a",
-        "aac",
-        "-b:a",
-        "320k",
-        "-shortest",
-        "-hide_banner",
-        "-loglevel",
-        "error",
-        final_str,
-    ]);
+    command.args(["-y", "-i", video_str, "-i", audio_str]);
+    command.args(["-map", "0:v:0", "-map", "1:a:0", "-c:v", "copy"]);
+    command.args(audio_args);
+    command.args(["-shortest", "-hide_banner", "-loglevel", "error", final_str]);
 
     let output = command
         .stdout(Stdio::null())
@@ -163,4 +393,87 @@ mod tests {
             let _ = std::fs::remove_file("test_output.mp4");
         }
     }
+
+    #[test]
+    fn lossless_profile_args_unchanged() {
+        let args = encoder_args(
+            ExportProfile::LosslessRgb,
+            64,
+            48,
+            FrameRate::integer(30),
+            "out.mp4",
+        );
+        let joined = args.join(" ");
+        assert!(
+            joined.starts_with("-y -f rawvideo -vcodec rawvideo -s 64x48 -pix_fmt rgba -r 30 -i -")
+        );
+        assert!(joined.contains("-c:v libx264rgb -crf 0 -preset veryslow -pix_fmt rgb24"));
+        assert!(!joined.contains("-vf"));
+        assert_eq!(args.last().map(String::as_str), Some("out.mp4"));
+    }
+
+    #[test]
+    fn subsampled_profiles_pad_odd_dimensions() {
+        for profile in [ExportProfile::H264, ExportProfile::Hevc, ExportProfile::Vp9] {
+            let odd = encoder_args(profile, 65, 48, FrameRate::integer(30), "o");
+            assert!(odd.iter().any(|a| a.starts_with("pad=")), "{profile:?}");
+            let even = encoder_args(profile, 64, 48, FrameRate::integer(30), "o");
+            assert!(!even.iter().any(|a| a == "-vf"), "{profile:?}");
+        }
+        let prores = encoder_args(
+            ExportProfile::ProRes4444,
+            65,
+            49,
+            FrameRate::integer(30),
+            "o.mov",
+        );
+        assert!(!prores.iter().any(|a| a == "-vf"));
+    }
+
+    #[test]
+    fn every_profile_has_consistent_spec() {
+        for profile in ExportProfile::ALL {
+            let spec = ProfileSpec::of(profile);
+            let pix_fmt = spec
+                .video_args
+                .iter()
+                .position(|a| *a == "-pix_fmt")
+                .and_then(|i| spec.video_args.get(i + 1))
+                .copied();
+            assert!(pix_fmt.is_some(), "{profile:?} sans -pix_fmt");
+            let pix_fmt = pix_fmt.unwrap_or_default();
+            assert_eq!(spec.alpha, pix_fmt.contains('a'), "{profile:?} ≠ {pix_fmt}");
+            assert_eq!(spec.needs_even_dims, pix_fmt.contains("420"), "{profile:?}");
+            assert_eq!(spec.audio_args.is_none(), spec.containers.is_empty());
+            if !spec.containers.is_empty() {
+                let name = format!("x.{}", spec.default_extension());
+                assert!(validate_output(profile, Path::new(&name)).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn validate_output_checks_container() {
+        assert!(validate_output(ExportProfile::Vp9, Path::new("a.webm")).is_ok());
+        assert!(validate_output(ExportProfile::Vp9, Path::new("a.mp4")).is_err());
+        assert!(validate_output(ExportProfile::ProRes4444, Path::new("a.MOV")).is_ok());
+        assert!(validate_output(ExportProfile::ProRes4444, Path::new("a.mp4")).is_err());
+        assert!(validate_output(ExportProfile::H264, Path::new("a")).is_err());
+        assert!(validate_output(ExportProfile::PngSequence, Path::new("frames")).is_ok());
+        assert!(validate_output(ExportProfile::PngSequence, Path::new("f/%04d.png")).is_ok());
+        assert!(validate_output(ExportProfile::PngSequence, Path::new("f.png")).is_err());
+        assert!(validate_output(ExportProfile::PngSequence, Path::new("f.mp4")).is_err());
+    }
+
+    #[test]
+    fn png_sequence_pattern_in_directory() {
+        assert_eq!(
+            png_sequence_pattern(Path::new("out")),
+            Path::new("out").join("frame_%05d.png")
+        );
+        assert_eq!(
+            png_sequence_pattern(Path::new("out/f_%03d.png")),
+            Path::new("out/f_%03d.png")
+        );
+    }
 }
@@ -12,6 +12,9 @@ pub struct Rasterizer {
     glyph_cache: HashMap<char, Vec<u8>>,
     /// Pre-allocated fallback glyph (all zeros). Avoids per-frame allocation (R1).
     empty_glyph: Vec<u8>,
+    /// Fond transparent : alpha = couverture du glyphe, RGB = couleur du glyphe.
+    /// Seul un fond noir devient transparent ; un `bg` coloré reste opaque.
+    transparent_bg: bool,
 }
 
 impl Rasterizer {
@@ -40,6 +43,7 @@ impl Rasterizer {
             char_height,
             glyph_cache: HashMap::new(),
             empty_glyph: vec![0u8; (char_width * char_height) as usize],
+            transparent_bg: false,
         };
 
         rasterizer.cache_charset(&font, scale, 32..=126);
@@ -114,6 +118,16 @@ impl Rasterizer {
         skipped
     }
 
+    /// Active le fond transparent (`BgStyle::Transparent`).
+    ///
+    /// Les pixels sont alors écrits en alpha non prémultiplié : RGB = couleur
+    /// du glyphe, A = couverture du glyphe, fond à A = 0. Désactivé, le fond
+    /// `cell.bg` est composité et A = 255. À n'activer que si la sortie
+    /// conserve l'alpha (un encodeur qui l'ignore afficherait des blocs pleins).
+    pub fn set_transparent_bg(&mut self, enabled: bool) {
+        self.transparent_bg = enabled;
+    }
+
     /// Rendu de l'AsciiGrid sur le FrameBuffer.
     /// Zéro allocation dans le hot-loop (R1). Parallélisé.
     pub fn render(&self, grid: &AsciiGrid, fb: &mut FrameBuffer, zalgo_intensity: f32) {
@@ -132,6 +146,7 @@ impl Rasterizer {
         }
 
         let empty_glyph = &self.empty_glyph;
+        let transparent_bg = self.transparent_bg;
 
         let stride = (expected_w * 4) as usize;
         let band_size = stride * self.char_height as usize;
@@ -173,6 +188,9 @@ impl Rasterizer {
                     }
 
                     let cx_start = gx * self.char_width as usize;
+                    // Un bg non noir (ex. pixel haut du HalfBlock) fait partie de
+                    // l'image, comme dans le TUI : il reste opaque.
+                    let clear_bg = transparent_bg && cell.bg == (0, 0, 0);
 
                     for cy in 0..(self.char_height as usize) {
                         let fb_y_offset = cy * stride;
@@ -185,6 +203,16 @@ impl Rasterizer {
                                 alpha = alpha.max(d[local_idx]);
                             }
 
+                            let px_idx = fb_y_offset + (cx_start + cx) * 4;
+
+                            if clear_bg {
+                                band[px_idx] = cell.fg.0;
+                                band[px_idx + 1] = cell.fg.1;
+                                band[px_idx + 2] = cell.fg.2;
+                                band[px_idx + 3] = alpha;
+                                continue;
+                            }
+
                             let alpha_f = f32::from(alpha) / 255.0;
 
                             let r = (f32::from(cell.fg.0) * alpha_f
@@ -197,7 +225,6 @@ impl Rasterizer {
                                 + f32::from(cell.bg.2) * (1.0 - alpha_f))
                                 as u8;
 
-                            let px_idx = fb_y_offset + (cx_start + cx) * 4;
                             band[px_idx] = r;
                             band[px_idx + 1] = g;
                             band[px_idx + 2] = b;
@@ -295,4 +322,64 @@ mod tests {
         // Should not panic — just log and return
         rast.render(&grid, &mut fb, 0.0);
     }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn transparent_bg_writes_glyph_alpha() {
+        let mut rast = make_rasterizer();
+        let mut grid = AsciiGrid::new(2, 1);
+        grid.cells[0].ch = ' ';
+        grid.cells[1].ch = '#';
+        grid.cells[1].fg = (255, 128, 0);
+        let (w, h) = rast.target_dimensions(2, 1);
+        let mut fb = FrameBuffer::new(w, h);
+
+        rast.render(&grid, &mut fb, 0.0);
+        assert!(fb.data.chunks_exact(4).all(|px| px[3] == 255));
+
+        rast.set_transparent_bg(true);
+        rast.render(&grid, &mut fb, 0.0);
+        let cw = rast.char_width as usize;
+        let (space, glyph): (Vec<_>, Vec<_>) = fb
+            .data
+            .chunks_exact(4)
+            .enumerate()
+            .partition(|(i, _)| (i % w as usize) < cw);
+        assert!(
+            space.iter().all(|(_, px)| px[3] == 0),
+            "space cell must be clear"
+        );
+        assert!(glyph.iter().any(|(_, px)| px[3] == 255));
+        assert!(glyph.iter().any(|(_, px)| px[3] == 0));
+        assert!(
+            glyph.iter().all(|(_, px)| px[..3] == [255, 128, 0]),
+            "straight alpha keeps the glyph colour"
+        );
+    }
+
+    #[test]
+    #[allow(clippy::expect_used)]
+    fn transparent_bg_keeps_coloured_background() {
+        let mut rast = make_rasterizer();
+        rast.set_transparent_bg(true);
+        // HalfBlock : pixel haut dans bg, pixel bas dans fg.
+        let mut grid = AsciiGrid::new(1, 1);
+        grid.cells[0].ch = '▄';
+        grid.cells[0].fg = (0, 0, 255);
+        grid.cells[0].bg = (255, 0, 0);
+        let (w, h) = rast.target_dimensions(1, 1);
+        let mut fb = FrameBuffer::new(w, h);
+
+        rast.render(&grid, &mut fb, 0.0);
+        assert!(
+            fb.data.chunks_exact(4).all(|px| px[3] == 255),
+            "a coloured bg stays opaque"
+        );
+        let px = |y: u32| {
+            let i = ((y * w + w / 2) * 4) as usize;
+            &fb.data[i..i + 4]
+        };
+        assert_eq!(px(h / 4), [255, 0, 0, 255], "top half shows the bg");
+        assert_eq!(px(h * 3 / 4), [0, 0, 255, 255], "bottom half shows the fg");
+    }
 }
@@ -0,0 +1,286 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use af_core::frame::AsciiGrid;
+use anyhow::{Context, Result};
+
+/// Couleurs (fg, bg) d'un run de cellules HTML.
+type CellColors = ((u8, u8, u8), (u8, u8, u8));
+
+/// Format de snapshot texte d'une `AsciiGrid`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextFormat {
+    /// UTF-8 brut, sans couleur (`.txt`).
+    Plain,
+    /// Séquences ANSI truecolor (`.ans`), lisible avec `cat`.
+    Ansi,
+    /// Fichier HTML autonome, couleurs en `<span>` inline (`.html`).
+    Html,
+}
+
+impl TextFormat {
+    /// Tous les formats, dans l'ordre d'écriture du snapshot TUI.
+    pub const ALL: [Self; 3] = [Self::Plain, Self::Ansi, Self::Html];
+
+    /// Détecte le format depuis l'extension du fichier (insensible à la casse).
+    ///
+    /// # Example
+    /// ```
+    /// use af_export::text::TextFormat;
+    /// use std::path::Path;
+    /// assert_eq!(TextFormat::from_path(Path::new("out.ANS")), Some(TextFormat::Ansi));
+    /// assert_eq!(TextFormat::from_path(Path::new("out.mp4")), None);
+    /// ```
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "txt" | "text" => Some(Self::Plain),
+            "ans" | "ansi" => Some(Self::Ansi),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// Extension canonique (sans point).
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Plain => "txt",
+            Self::Ansi => "ans",
+            Self::Html => "html",
+        }
+    }
+
+    /// Sérialise la grille dans ce format.
+    #[must_use]
+    pub fn render(self, grid: &AsciiGrid) -> String {
+        match self {
+            Self::Plain => to_plain(grid),
+            Self::Ansi => to_ansi(grid),
+            Self::Html => to_html(grid),
+        }
+    }
+}
+
+/// Texte UTF-8 brut : une ligne par rangée, terminée par `\n`.
+#[must_use]
+pub fn to_plain(grid: &AsciiGrid) -> String {
+    let mut out = String::with_capacity(grid.cells.len() + usize::from(grid.height));
+    for row in rows(grid) {
+        out.extend(row.iter().map(|c| c.ch));
+        out.push('\n');
+    }
+    out
+}
+
+/// Texte ANSI truecolor (SGR 38;2 / 48;2).
+///
+/// Les séquences ne sont émises qu'aux changements de couleur. Un fond
+/// `(0, 0, 0)` est traité comme le fond par défaut du terminal (comme dans le
+/// canvas TUI). Chaque ligne se termine par un reset `ESC[0m`.
+#[must_use]
+pub fn to_ansi(grid: &AsciiGrid) -> String {
+    let mut out = String::with_capacity(grid.cells.len() * 4);
+    for row in rows(grid) {
+        let mut fg = None;
+        let mut bg = None;
+        for cell in row {
+            if fg != Some(cell.fg) {
+                let (r, g, b) = cell.fg;
+                let _ = write!(out, "\x1b[38;2;{r};{g};{b}m");
+                fg = Some(cell.fg);
+            }
+            if bg != Some(cell.bg) {
+                if cell.bg == (0, 0, 0) {
+                    // Évite un 49m superflu en début de ligne
+                    if bg.is_some() {
+                        out.push_str("\x1b[49m");
+                    }
+                } else {
+                    let (r, g, b) = cell.bg;
+                    let _ = write!(out, "\x1b[48;2;{r};{g};{b}m");
+                }
+                bg = Some(cell.bg);
+            }
+            out.push(cell.ch);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// Fichier HTML autonome : un `<pre>` sur fond noir, un `<span>` par run de
+/// cellules de même couleur.
+#[must_use]
+pub fn to_html(grid: &AsciiGrid) -> String {
+    let mut out = String::with_capacity(grid.cells.len() * 8 + 512);
+    out.push_str(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>clasSCII snapshot</title>\n\
+         <style>body{margin:0;background:#000}\
+         pre{margin:0;font-family:'Cascadia Mono',Menlo,Consolas,monospace;line-height:1}</style>\n\
+         </head>\n<body>\n<pre>",
+    );
+    for row in rows(grid) {
+        let mut current: Option<CellColors> = None;
+        for cell in row {
+            let colors = (cell.fg, cell.bg);
+            if current != Some(colors) {
+                if current.is_some() {
+                    out.push_str("</span>");
+                }
+                let (r, g, b) = cell.fg;
+                let _ = write!(out, "<span style=\"color:#{r:02x}{g:02x}{b:02x}");
+                if cell.bg != (0, 0, 0) {
+                    let (r, g, b) = cell.bg;
+                    let _ = write!(out, ";background:#{r:02x}{g:02x}{b:02x}");
+                }
+                out.push_str("\">");
+                current = Some(colors);
+            }
+            match cell.ch {
+                '&' => out.push_str("&amp;"),
+                '<' => out.push_str("&lt;"),
+                '>' => out.push_str("&gt;"),
+                '"' => out.push_str("&quot;"),
+                c => out.push(c),
+            }
+        }
+        if current.is_some() {
+            out.push_str("</span>");
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+/// Écrit la grille dans `path`, format déduit de l'extension.
+///
+/// # Errors
+/// Retourne une erreur si l'extension n'est pas reconnue ou si l'écriture échoue.
+pub fn write_snapshot(grid: &AsciiGrid, path: &Path) -> Result<TextFormat> {
+    let format = TextFormat::from_path(path).with_context(|| {
+        format!(
+            "Extension de snapshot inconnue : {} (attendu .txt, .ans ou .html)",
+            path.display()
+        )
+    })?;
+    std::fs::write(path, format.render(grid))
+        .with_context(|| format!("Impossible d'écrire {}", path.display()))?;
+    Ok(format)
+}
+
+fn rows(grid: &AsciiGrid) -> impl Iterator<Item = &[af_core::frame::AsciiCell]> {
+    grid.cells.chunks(usize::from(grid.width).max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use af_core::frame::AsciiCell;
+
+    fn sample() -> AsciiGrid {
+        let mut grid = AsciiGrid::new(3, 2);
+        let red = AsciiCell {
+            ch: '#',
+            fg: (255, 0, 0),
+            bg: (0, 0, 0),
+        };
+        grid.set(0, 0, red);
+        grid.set(1, 0, red);
+        grid.set(
+            2,
+            0,
+            AsciiCell {
+                ch: '<',
+                fg: (0, 128, 255),
+                bg: (16, 16, 16),
+            },
+        );
+        grid.set(
+            0,
+            1,
+            AsciiCell {
+                ch: '█',
+                fg: (255, 255, 255),
+                bg: (0, 0, 0),
+            },
+        );
+        grid
+    }
+
+    #[test]
+    fn plain_golden() {
+        assert_eq!(to_plain(&sample()), "##<\n█  \n");
+    }
+
+    #[test]
+    fn ansi_golden() {
+        let expected = "\x1b[38;2;255;0;0m##\x1b[38;2;0;128;255m\x1b[48;2;16;16;16m<\x1b[0m\n\
+                        \x1b[38;2;255;255;255m█\x1b[38;2;0;0;0m  \x1b[0m\n";
+        assert_eq!(to_ansi(&sample()), expected);
+    }
+
+    #[test]
+    fn ansi_resets_background_to_default() {
+        let mut grid = AsciiGrid::new(2, 1);
+        grid.set(
+            0,
+            0,
+            AsciiCell {
+                ch: 'a',
+                fg: (1, 2, 3),
+                bg: (9, 9, 9),
+            },
+        );
+        grid.set(
+            1,
+            0,
+            AsciiCell {
+                ch: 'b',
+                fg: (1, 2, 3),
+                bg: (0, 0, 0),
+            },
+        );
+        assert_eq!(
+            to_ansi(&grid),
+            "\x1b[38;2;1;2;3m\x1b[48;2;9;9;9ma\x1b[49mb\x1b[0m\n"
+        );
+    }
+
+    #[test]
+    fn html_spans_and_escaping() {
+        let html = to_html(&sample());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(
+            "<pre><span style=\"color:#ff0000\">##</span>\
+             <span style=\"color:#0080ff;background:#101010\">&lt;</span>\n\
+             <span style=\"color:#ffffff\">█</span><span style=\"color:#000000\">  </span>\n</pre>"
+        ));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(
+            TextFormat::from_path(Path::new("a/b.txt")),
+            Some(TextFormat::Plain)
+        );
+        assert_eq!(
+            TextFormat::from_path(Path::new("b.HTM")),
+            Some(TextFormat::Html)
+        );
+        assert_eq!(TextFormat::from_path(Path::new("noext")), None);
+        for f in TextFormat::ALL {
+            let name = format!("x.{}", f.extension());
+            assert_eq!(TextFormat::from_path(Path::new(&name)), Some(f));
+        }
+    }
+
+    #[test]
+    fn write_snapshot_rejects_unknown_extension() {
+        let grid = AsciiGrid::new(1, 1);
+        assert!(write_snapshot(&grid, Path::new("snapshot.png")).is_err());
+    }
+}
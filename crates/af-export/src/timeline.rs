@@ -0,0 +1,431 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use af_core::feature_timeline::FeatureTimeline;
+use af_core::frame::{AudioFeatures, CHROMA_SOURCES, MAX_CUSTOM_BANDS};
+use anyhow::{Context, Result};
+
+/// Colonne scalaire : nom et lecture depuis `AudioFeatures`.
+type ScalarColumn = (&'static str, fn(&AudioFeatures) -> f32);
+
+/// Colonnes scalaires, dans l'ordre des champs de `AudioFeatures`.
+/// Valeurs telles que stockées dans la timeline : brutes (Hz, BPM, LUFS…)
+/// pour les frames de `BatchAnalyzer::analyze_frames`, ramenées à [0, 1]
+/// pour les features continues d'une timeline normalisée. Booléens en 0/1.
+const SCALAR_COLUMNS: [ScalarColumn; 41] = [
+    ("rms", |f| f.rms),
+    ("peak", |f| f.peak),
+    ("sub_bass", |f| f.sub_bass),
+    ("bass", |f| f.bass),
+    ("low_mid", |f| f.low_mid),
+    ("mid", |f| f.mid),
+    ("high_mid", |f| f.high_mid),
+    ("presence", |f| f.presence),
+    ("brilliance", |f| f.brilliance),
+    ("spectral_centroid", |f| f.spectral_centroid),
+    ("spectral_flux", |f| f.spectral_flux),
+    ("spectral_flatness", |f| f.spectral_flatness),
+    ("onset", |f| flag(f.onset)),
+    ("beat_intensity", |f| f.beat_intensity),
+    ("bpm", |f| f.bpm),
+    ("beat_phase", |f| f.beat_phase),
+    ("bar_phase", |f| f.bar_phase),
+    ("beat_in_bar", |f| f32::from(f.beat_in_bar)),
+    ("tempo_confidence", |f| f.tempo_confidence),
+    ("timbral_brightness", |f| f.timbral_brightness),
+    ("timbral_roughness", |f| f.timbral_roughness),
+    ("spectral_rolloff", |f| f.spectral_rolloff),
+    ("zero_crossing_rate", |f| f.zero_crossing_rate),
+    ("onset_envelope", |f| f.onset_envelope),
+    ("kick_onset", |f| flag(f.kick_onset)),
+    ("kick_intensity", |f| f.kick_intensity),
+    ("kick_envelope", |f| f.kick_envelope),
+    ("snare_onset", |f| flag(f.snare_onset)),
+    ("snare_intensity", |f| f.snare_intensity),
+    ("snare_envelope", |f| f.snare_envelope),
+    ("hat_onset", |f| flag(f.hat_onset)),
+    ("hat_intensity", |f| f.hat_intensity),
+    ("hat_envelope", |f| f.hat_envelope),
+    ("pitch", |f| f.pitch),
+    ("pitch_confidence", |f| f.pitch_confidence),
+    ("key", |f| f32::from(f.key)),
+    ("key_minor", |f| flag(f.key_minor)),
+    ("key_confidence", |f| f.key_confidence),
+    ("loudness_lufs", |f| f.loudness_lufs),
+    ("true_peak", |f| f.true_peak),
+    ("dynamic_range", |f| f.dynamic_range),
+];
+
+fn flag(b: bool) -> f32 {
+    if b { 1.0 } else { 0.0 }
+}
+
+/// Format d'export d'une timeline de features.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimelineFormat {
+    /// Texte CSV, une ligne d'en-tête puis une ligne par frame (`.csv`).
+    Csv,
+    /// JSON `{"columns": [...], "data": [[...], ...]}` (`.json`),
+    /// lisible par `pandas.read_json(path, orient="split")`.
+    Json,
+    /// Tableau NumPy structuré, un champ `<f4` nommé par colonne (`.npy`).
+    Npy,
+}
+
+impl TimelineFormat {
+    /// Tous les formats.
+    pub const ALL: [Self; 3] = [Self::Csv, Self::Json, Self::Npy];
+
+    /// Détecte le format depuis l'extension du fichier (insensible à la casse).
+    ///
+    /// # Example
+    /// ```
+    /// use af_export::timeline::TimelineFormat;
+    /// use std::path::Path;
+    /// assert_eq!(TimelineFormat::from_path(Path::new("song.NPY")), Some(TimelineFormat::Npy));
+    /// assert_eq!(TimelineFormat::from_path(Path::new("song.bin")), None);
+    /// ```
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "npy" => Some(Self::Npy),
+            _ => None,
+        }
+    }
+
+    /// Extension canonique (sans point).
+    #[must_use]
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Npy => "npy",
+        }
+    }
+}
+
+/// Timeline de features à plat : une colonne nommée par feature, une ligne par frame.
+///
+/// Colonnes : `time` (s), `frame`, `energy_level`, `section` (index dans
+/// `FeatureTimeline::sections`, −1 sans segmentation), `section_label`, puis
+/// chaque feature sous son nom de source de mapping (`mfcc_0`…, `chroma_c`…,
+/// bandes utilisateur, `spectrum_0`…`spectrum_31`). Les stems ajoutés via
+/// [`FeatureTable::add_stem`] reprennent les colonnes de features préfixées
+/// (`drums.rms`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeatureTable {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<f32>>,
+}
+
+impl FeatureTable {
+    /// Aplatit `timeline`. `band_names` nomme les bandes utilisateur
+    /// (`AnalysisConfig::custom_bands`), dans l'ordre de `AudioFeatures::custom_bands`.
+    ///
+    /// # Example
+    /// ```
+    /// use af_core::feature_timeline::FeatureTimeline;
+    /// use af_core::frame::AudioFeatures;
+    /// use af_export::timeline::FeatureTable;
+    /// let timeline = FeatureTimeline {
+    ///     frames: vec![AudioFeatures { bass: 0.5, ..AudioFeatures::default() }; 3],
+    ///     frame_duration: 0.5,
+    ///     sample_rate: 44100,
+    ///     energy_levels: vec![],
+    ///     sections: vec![],
+    /// };
+    /// let table = FeatureTable::from_timeline(&timeline, &[]);
+    /// assert_eq!(table.rows.len(), 3);
+    /// assert_eq!(table.value(2, "time"), Some(1.0));
+    /// assert_eq!(table.value(0, "bass"), Some(0.5));
+    /// ```
+    #[must_use]
+    pub fn from_timeline(timeline: &FeatureTimeline, band_names: &[String]) -> Self {
+        let mut columns: Vec<String> =
+            ["time", "frame", "energy_level", "section", "section_label"]
+                .iter()
+                .map(ToString::to_string)
+                .collect();
+        let bands = feature_columns(band_names, "", &mut columns);
+
+        let rows = timeline
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(i, f)| {
+                let section = timeline.section_at(i);
+                let mut row = vec![
+                    i as f32 * timeline.frame_duration,
+                    i as f32,
+                    f32::from(timeline.energy_at(i)),
+                    section.map_or(-1.0, |_| {
+                        timeline.sections.partition_point(|s| s.end <= i) as f32
+                    }),
+                    section.map_or(-1.0, |s| f32::from(s.label)),
+                ];
+                push_features(f, &bands, &mut row);
+                row
+            })
+            .collect();
+
+        Self { columns, rows }
+    }
+
+    /// Ajoute les colonnes de features d'un stem, préfixées par `prefix.`
+    /// (frame manquante : features par défaut).
+    pub fn add_stem(&mut self, prefix: &str, timeline: &FeatureTimeline, band_names: &[String]) {
+        let bands = feature_columns(band_names, &format!("{prefix}."), &mut self.columns);
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            let f = timeline.frames.get(i).copied().unwrap_or_default();
+            push_features(&f, &bands, row);
+        }
+    }
+
+    /// Index de la colonne `name`.
+    #[must_use]
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    /// Valeur de la colonne `name` à la frame `row`.
+    #[must_use]
+    pub fn value(&self, row: usize, name: &str) -> Option<f32> {
+        self.rows.get(row)?.get(self.column(name)?).copied()
+    }
+
+    /// Sérialise la table dans `format`.
+    #[must_use]
+    pub fn render(&self, format: TimelineFormat) -> Vec<u8> {
+        match format {
+            TimelineFormat::Csv => self.to_csv().into_bytes(),
+            TimelineFormat::Json => self.to_json().into_bytes(),
+            TimelineFormat::Npy => self.to_npy(),
+        }
+    }
+
+    /// CSV : en-tête des noms de colonnes, puis une ligne par frame.
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        let mut out = String::new();
+        let header: Vec<String> = self.columns.iter().map(|c| csv_field(c)).collect();
+        out.push_str(&header.join(","));
+        out.push('\n');
+        for row in &self.rows {
+            for (i, v) in row.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "{v}");
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// JSON orienté « split » : `{"columns": [...], "data": [[...], ...]}`.
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        serde_json::json!({ "columns": self.columns, "data": self.rows }).to_string()
+    }
+
+    /// Fichier `.npy` (format 1.0, 2.0 si l'en-tête dépasse 64 Kio) : tableau
+    /// structuré à une dimension, un champ `<f4` par colonne, soit
+    /// `np.load(path)["bass"]`.
+    #[must_use]
+    pub fn to_npy(&self) -> Vec<u8> {
+        let mut header = String::from("{'descr': [");
+        for name in &self.columns {
+            let escaped = name.replace('\\', "\\\\").replace('\'', "\\'");
+            let _ = write!(header, "('{escaped}', '<f4'), ");
+        }
+        let _ = write!(
+            header,
+            "], 'fortran_order': False, 'shape': ({},), }}",
+            self.rows.len()
+        );
+
+        // Préambule + en-tête alignés sur 64 octets, en-tête terminé par '\n'.
+        let v1 = u16::try_from(header.len() + 11).is_ok();
+        let preamble = if v1 { 10 } else { 12 };
+        let total = (preamble + header.len() + 1).div_ceil(64) * 64;
+        header.push_str(&" ".repeat(total - preamble - header.len() - 1));
+        header.push('\n');
+
+        let mut out = Vec::with_capacity(total + self.rows.len() * self.columns.len() * 4);
+        out.extend_from_slice(b"\x93NUMPY");
+        if v1 {
+            out.extend_from_slice(&[1, 0]);
+            out.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        } else {
+            out.extend_from_slice(&[2, 0]);
+            out.extend_from_slice(&(header.len() as u32).to_le_bytes());
+        }
+        out.extend_from_slice(header.as_bytes());
+        for row in &self.rows {
+            for v in row {
+                out.extend_from_slice(&v.to_le_bytes());
+            }
+        }
+        out
+    }
+}
+
+/// Ajoute à `columns` les noms des colonnes de features préfixés par `prefix`.
+/// Retourne les index des bandes utilisateur retenues : celles masquées par
+/// une colonne existante (source intégrée du même nom) sont ignorées.
+fn feature_columns(band_names: &[String], prefix: &str, columns: &mut Vec<String>) -> Vec<usize> {
+    let mut push = |name: &str| columns.push(format!("{prefix}{name}"));
+    for (name, _) in &SCALAR_COLUMNS {
+        push(name);
+    }
+    for i in 0..5 {
+        push(&format!("mfcc_{i}"));
+    }
+    for name in CHROMA_SOURCES {
+        push(name);
+    }
+    let mut bands = Vec::new();
+    for (i, name) in band_names.iter().take(MAX_CUSTOM_BANDS).enumerate() {
+        let column = format!("{prefix}{name}");
+        if !columns.contains(&column) {
+            columns.push(column);
+            bands.push(i);
+        }
+    }
+    for i in 0..32 {
+        columns.push(format!("{prefix}spectrum_{i}"));
+    }
+    bands
+}
+
+fn push_features(f: &AudioFeatures, bands: &[usize], row: &mut Vec<f32>) {
+    row.extend(SCALAR_COLUMNS.iter().map(|(_, get)| get(f)));
+    row.extend_from_slice(&f.mfcc);
+    row.extend_from_slice(&f.chroma);
+    row.extend(bands.iter().map(|&i| f.custom_bands[i]));
+    row.extend_from_slice(&f.spectrum_bands);
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Écrit la table dans `path`, format déduit de l'extension.
+///
+/// # Errors
+/// Retourne une erreur si l'extension n'est pas reconnue ou si l'écriture échoue.
+pub fn write_timeline(table: &FeatureTable, path: &Path) -> Result<TimelineFormat> {
+    let format = TimelineFormat::from_path(path).with_context(|| {
+        format!(
+            "Extension de timeline inconnue : {} (attendu .csv, .json ou .npy)",
+            path.display()
+        )
+    })?;
+    std::fs::write(path, table.render(format))
+        .with_context(|| format!("Impossible d'écrire {}", path.display()))?;
+    Ok(format)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used, clippy::float_cmp)]
+mod tests {
+    use super::*;
+    use af_core::feature_timeline::{Section, SectionKind};
+
+    fn sample() -> FeatureTimeline {
+        let frames = (0..4)
+            .map(|i| AudioFeatures {
+                rms: i as f32 * 0.25,
+                onset: i == 2,
+                custom_bands: [0.5; MAX_CUSTOM_BANDS],
+                ..AudioFeatures::default()
+            })
+            .collect();
+        FeatureTimeline {
+            frames,
+            frame_duration: 0.5,
+            sample_rate: 44100,
+            energy_levels: vec![0, 1, 2, 2],
+            sections: vec![
+                Section {
+                    start: 0,
+                    end: 2,
+                    kind: SectionKind::Intro,
+                    label: 0,
+                    energy: 0.1,
+                },
+                Section {
+                    start: 2,
+                    end: 4,
+                    kind: SectionKind::Drop,
+                    label: 1,
+                    energy: 0.6,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn columns_match_rows() {
+        let names = vec!["kick_body".to_string(), "bass".to_string()];
+        let mut table = FeatureTable::from_timeline(&sample(), &names);
+        // "bass" est masquée par la source intégrée
+        assert!(table.column("kick_body").is_some());
+        assert_eq!(table.columns.iter().filter(|c| *c == "bass").count(), 1);
+        table.add_stem("drums", &sample(), &names);
+        assert!(table.column("drums.kick_body").is_some());
+        assert!(table.rows.iter().all(|r| r.len() == table.columns.len()));
+
+        assert_eq!(table.value(2, "onset"), Some(1.0));
+        assert_eq!(table.value(3, "rms"), Some(0.75));
+        assert_eq!(table.value(3, "section"), Some(1.0));
+        assert_eq!(table.value(3, "section_label"), Some(1.0));
+        assert_eq!(table.value(1, "energy_level"), Some(1.0));
+        assert_eq!(table.value(1, "drums.rms"), Some(0.25));
+    }
+
+    #[test]
+    fn csv_and_json_roundtrip_header() {
+        let table = FeatureTable::from_timeline(&sample(), &[]);
+        let csv = table.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().map(|h| h.split(',').count()),
+            Some(table.columns.len())
+        );
+        assert_eq!(lines.count(), 4);
+
+        let json: serde_json::Value = serde_json::from_str(&table.to_json()).expect("json");
+        assert_eq!(json["columns"][0], "time");
+        assert_eq!(json["data"].as_array().map(Vec::len), Some(4));
+        assert_eq!(json["data"][1][0], 0.5);
+    }
+
+    #[test]
+    fn npy_header_is_aligned() {
+        let table = FeatureTable::from_timeline(&sample(), &[]);
+        let npy = table.to_npy();
+        assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+        let header_len = usize::from(u16::from_le_bytes([npy[8], npy[9]]));
+        assert_eq!((10 + header_len) % 64, 0);
+        let header = std::str::from_utf8(&npy[10..10 + header_len]).expect("ascii");
+        assert!(header.starts_with("{'descr': [('time', '<f4'), "));
+        assert!(header.contains("'shape': (4,)"));
+        assert!(header.ends_with('\n'));
+        let data = &npy[10 + header_len..];
+        assert_eq!(data.len(), 4 * table.columns.len() * 4);
+        // Frame 1, colonne time
+        let row = table.columns.len() * 4;
+        assert_eq!(
+            f32::from_le_bytes(data[row..row + 4].try_into().expect("4 bytes")),
+            0.5
+        );
+    }
+}
@@ -55,6 +55,8 @@ pub enum RenderState {
     WorkflowSave,
     /// Workflow browse/load overlay (Ctrl+W).
     WorkflowBrowse,
+    /// MIDI learn overlay (Ctrl+L).
+    MidiLearn,
     /// Quitting (should not reach draw).
     Quitting,
 }
@@ -113,6 +115,17 @@ pub struct WorkflowBrowseData {
     pub selected_idx: usize,
 }
 
+/// Data for the MIDI learn overlay.
+pub struct MidiLearnData {
+    /// One row per learnable target: (target name, current binding label).
+    pub rows: Vec<(&'static str, Option<String>)>,
+    pub selected_idx: usize,
+    /// Connected port name, None without `--midi`.
+    pub port: Option<String>,
+    /// Last received message, for feedback while turning knobs.
+    pub last_event: Option<String>,
+}
+
 /// Single entry in the workflow browse list.
 pub struct WorkflowBrowseEntry {
     pub name: String,
@@ -150,6 +163,8 @@ pub struct DrawContext<'a> {
     pub workflow_save: Option<&'a WorkflowSaveData<'a>>,
     /// Workflow browse overlay data.
     pub workflow_browse: Option<&'a WorkflowBrowseData>,
+    /// MIDI learn overlay data.
+    pub midi_learn: Option<&'a MidiLearnData>,
     /// Flash message (workflow saved confirmation, etc.).
     pub flash_msg: Option<&'a str>,
 }
@@ -173,13 +188,19 @@ pub fn draw(frame: &mut Frame, ctx: &DrawContext<'_>) {
         return;
     }
 
+    // Backend graphique : cellules du canvas laissées vides, l'image Sixel/Kitty
+    // est émise par l'app après le draw (un buffer stable évite que ratatui l'écrase).
+    let draw_cells = !ctx.config.display_backend.is_graphics();
+
     if ctx.config.fullscreen {
-        canvas::render_grid(
-            frame.buffer_mut(),
-            area,
-            ctx.grid,
-            ctx.config.zalgo_intensity,
-        );
+        if draw_cells {
+            canvas::render_grid(
+                frame.buffer_mut(),
+                area,
+                ctx.grid,
+                ctx.config.zalgo_intensity,
+            );
+        }
     } else {
         let h_chunks = Layout::horizontal([Constraint::Min(40), Constraint::Length(SIDEBAR_WIDTH)])
             .split(area);
@@ -194,12 +215,14 @@ pub fn draw(frame: &mut Frame, ctx: &DrawContext<'_>) {
             h_chunks[0]
         };
 
-        canvas::render_grid(
-            frame.buffer_mut(),
-            canvas_area,
-            ctx.grid,
-            ctx.config.zalgo_intensity,
-        );
+        if draw_cells {
+            canvas::render_grid(
+                frame.buffer_mut(),
+                canvas_area,
+                ctx.grid,
+                ctx.config.zalgo_intensity,
+            );
+        }
 
         draw_sidebar(
             frame,
@@ -238,6 +261,9 @@ pub fn draw(frame: &mut Frame, ctx: &DrawContext<'_>) {
     } else if let Some(wf_browse) = ctx.workflow_browse {
         dim_overlay_background(frame, area);
         draw_workflow_browse_overlay(frame, area, wf_browse);
+    } else if let Some(midi) = ctx.midi_learn {
+        dim_overlay_background(frame, area);
+        draw_midi_learn_overlay(frame, area, midi);
     }
 
     // Flash message (workflow saved, etc.) — renders on top of everything
@@ -338,6 +364,7 @@ fn draw_sidebar(
         ColorMode::HsvBright => "HSV",
         ColorMode::Quantized => "Quant",
         ColorMode::Oklab => "Oklab",
+        ColorMode::KeyHue => "Key",
     };
 
     let bg_str = match config.bg_style {
@@ -356,6 +383,7 @@ fn draw_sidebar(
         RenderState::StemMode => "S STEMS",
         RenderState::WorkflowSave => "SAVE WF",
         RenderState::WorkflowBrowse => "LOAD WF",
+        RenderState::MidiLearn => "MIDI LRN",
         RenderState::Quitting => "\u{23f9} QUIT",
     };
 
@@ -508,6 +536,8 @@ fn draw_sidebar(
     if let Some(features) = audio {
         lines.push(kv_line("", "RMS", &fmt!("{:.2}", features.rms)));
         lines.push(kv_line("", "BPM", &fmt!("{:.0}", features.bpm)));
+        lines.push(kv_line("", "LUFS", &fmt!("{:.1}", features.loudness_lufs)));
+        lines.push(kv_line("", "TP", &fmt!("{:.1}", features.true_peak)));
         lines.push(Line::from(Span::styled(
             format!(" {onset_str}"),
             Style::default().fg(if features.onset {
@@ -695,7 +725,10 @@ fn draw_help_overlay(frame: &mut Frame, area: Rect, scroll: u16) {
         Line::from(" S        Stem separation mode"),
         Line::from(" Ctrl+S   Save workflow"),
         Line::from(" Ctrl+W   Load workflow"),
+        Line::from(" Ctrl+L   MIDI learn"),
+        Line::from(" Ctrl+R   Record .cast on/off"),
         Line::from(" x        Fullscreen"),
+        Line::from(" X        Snapshot (txt/ans/html)"),
         Line::from(""),
         Line::from(Span::styled(
             " Press ? or Esc to close ",
@@ -1273,3 +1306,67 @@ fn draw_workflow_browse_overlay(frame: &mut Frame, area: Rect, data: &WorkflowBr
 
     frame.render_widget(widget, overlay_area);
 }
+
+/// Draw the MIDI learn overlay (target list with current CC/note bindings).
+fn draw_midi_learn_overlay(frame: &mut Frame, area: Rect, data: &MidiLearnData) {
+    const VISIBLE_ROWS: usize = 14;
+    let mut lines: Vec<Line<'_>> = Vec::with_capacity(VISIBLE_ROWS + 8);
+
+    lines.push(Line::from(Span::styled(
+        "  MIDI Learn",
+        Style::default().fg(Color::Cyan),
+    )));
+    let status = match (&data.port, &data.last_event) {
+        (None, _) => "  No MIDI input (start with --midi <PORT>)".to_string(),
+        (Some(port), None) => format!("  {port} \u{2014} move a control to bind"),
+        (Some(port), Some(ev)) => format!("  {port} \u{2014} last: {ev}"),
+    };
+    lines.push(Line::from(Span::styled(
+        status,
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines.push(Line::from(""));
+
+    // Scroll window keeping the selection visible
+    let start = data
+        .selected_idx
+        .saturating_sub(VISIBLE_ROWS / 2)
+        .min(data.rows.len().saturating_sub(VISIBLE_ROWS));
+    for (i, (target, binding)) in data.rows.iter().enumerate().skip(start).take(VISIBLE_ROWS) {
+        let is_selected = i == data.selected_idx;
+        let prefix = if is_selected { "> " } else { "  " };
+        let style = if is_selected {
+            Style::default().fg(Color::White)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        let binding_span = match binding {
+            Some(label) => Span::styled(label.clone(), Style::default().fg(Color::Green)),
+            None => Span::styled("\u{2014}", Style::default().fg(Color::DarkGray)),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{prefix}{target:<24}"), style),
+            binding_span,
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "  Up/Down=nav  Del=unbind  Esc=close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let overlay_width = 55u16.min(area.width.saturating_sub(4));
+    let overlay_height = (lines.len() as u16 + 2).min(area.height.saturating_sub(4));
+    let x = area.x + area.width.saturating_sub(overlay_width) / 2;
+    let y = area.y + area.height.saturating_sub(overlay_height) / 2;
+    let overlay_area = Rect::new(x, y, overlay_width, overlay_height);
+
+    let widget = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(" MIDI LEARN ")
+            .style(Style::default().bg(Color::Black).fg(Color::Cyan)),
+    );
+
+    frame.render_widget(widget, overlay_area);
+}
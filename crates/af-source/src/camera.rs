@@ -0,0 +1,472 @@
+// Capture caméra live via ffmpeg subprocess (même machinerie que `video.rs`).
+//
+// Architecture :
+//   - `CameraSpec`          : parse `--camera` → format ffmpeg (`-f`) + device (`-i`)
+//   - `spawn_camera_thread` : thread dédié, lit les frames RGBA, gère les commandes
+//   - `CameraSource`        : wrapper `Source` (thread + canaux) pour usage direct
+//   - Commandes : `VideoCommand` (Resize/Pause/Play/Quit). Seek et UpdateClock
+//     sont ignorés — une caméra n'a pas de timeline.
+//   - Fin de flux (EOF, device débranché, ffmpeg introuvable) : le thread se
+//     termine, ce qui déconnecte le canal de frames (l'App l'affiche).
+
+use anyhow::{Context, Result};
+use flume::{Receiver, Sender};
+use std::process::{Child, ChildStdout};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use af_core::clock::FrameRate;
+use af_core::frame::FrameBuffer;
+use af_core::traits::Source;
+
+use crate::video::{VideoCommand, find_or_create_slot, read_exact_or_eof, spawn_rgba_pipe};
+
+/// Taille du pool de frames pré-allouées (> capacité du canal, cf. `video.rs`).
+const POOL_SIZE: usize = 6;
+
+/// Formats d'entrée ffmpeg acceptés en préfixe explicite (`lavfi:testsrc`).
+const KNOWN_FORMATS: &[&str] = &[
+    "v4l2",
+    "lavfi",
+    "avfoundation",
+    "dshow",
+    "x11grab",
+    "gdigrab",
+];
+
+/// Format d'entrée ffmpeg natif de la plateforme.
+#[must_use]
+pub fn default_format() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "avfoundation"
+    } else if cfg!(target_os = "windows") {
+        "dshow"
+    } else {
+        "v4l2"
+    }
+}
+
+/// Entrée ffmpeg d'une caméra : format (`-f`) + device (`-i`).
+///
+/// # Example
+/// ```
+/// use af_source::camera::CameraSpec;
+/// let spec = CameraSpec::parse("lavfi:testsrc=size=320x240:rate=30").unwrap();
+/// assert_eq!(spec.format, "lavfi");
+/// assert_eq!(spec.device, "testsrc=size=320x240:rate=30");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CameraSpec {
+    /// Format d'entrée ffmpeg (`v4l2`, `lavfi`, `avfoundation`, `dshow`, …).
+    pub format: String,
+    /// Device ou graphe de filtres passé à `-i`.
+    pub device: String,
+}
+
+impl CameraSpec {
+    /// Parse une spécification `--camera`.
+    ///
+    /// - `default` ou vide → device 0 de la plateforme
+    /// - `N` (entier)      → `/dev/videoN` (Linux), index `N` (macOS)
+    /// - `FORMAT:DEVICE`   → format explicite (`v4l2:/dev/video2`, `lavfi:testsrc`)
+    /// - autre             → device avec le format natif (`/dev/video1`)
+    ///
+    /// # Errors
+    /// Retourne une erreur si la plateforme n'a pas de device par défaut
+    /// (DirectShow exige un nom : `dshow:video=<nom>`).
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if let Some((format, device)) = spec.split_once(':')
+            && KNOWN_FORMATS.contains(&format)
+        {
+            return Ok(Self {
+                format: format.to_string(),
+                device: device.to_string(),
+            });
+        }
+
+        let format = default_format();
+        let index = match spec {
+            "" | "default" => Some(0),
+            s => s.parse::<u32>().ok(),
+        };
+        let device = match (index, format) {
+            (Some(n), "v4l2") => format!("/dev/video{n}"),
+            (Some(n), "avfoundation") => n.to_string(),
+            (Some(_), _) => anyhow::bail!(
+                "Caméra '{spec}' : index non supporté par {format}, utilisez {format}:video=<nom>"
+            ),
+            (None, _) => spec.to_string(),
+        };
+        Ok(Self {
+            format: format.to_string(),
+            device,
+        })
+    }
+
+    /// Arguments d'entrée ffmpeg (avant les arguments de sortie de `spawn_rgba_pipe`).
+    #[must_use]
+    pub fn input_args(&self, fps: u32) -> Vec<String> {
+        let mut args = vec!["-f".to_string(), self.format.clone()];
+        // lavfi/x11grab règlent leur cadence dans le graphe ou l'URL.
+        if matches!(self.format.as_str(), "v4l2" | "avfoundation" | "dshow") {
+            args.push("-framerate".to_string());
+            args.push(fps.to_string());
+        }
+        args.push("-i".to_string());
+        args.push(self.device.clone());
+        args
+    }
+}
+
+/// Processus ffmpeg courant, partagé pour pouvoir le tuer depuis un autre
+/// thread : une lecture bloquée sur son stdout se termine alors par EOF.
+type SharedChild = Arc<Mutex<Option<Child>>>;
+
+/// Lance ffmpeg sur l'entrée caméra, sortie RGBA `w × h`. Le processus est
+/// rangé dans `child`, son stdout est retourné.
+fn spawn_camera_pipe(
+    spec: &CameraSpec,
+    child: &SharedChild,
+    w: u32,
+    h: u32,
+    fps: u32,
+) -> Option<ChildStdout> {
+    let args = spec.input_args(fps);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let mut spawned = spawn_rgba_pipe(&args, w, h, FrameRate::integer(fps))?;
+    let stdout = spawned.stdout.take();
+    *child.lock().unwrap_or_else(PoisonError::into_inner) = Some(spawned);
+    log::debug!(
+        "ffmpeg caméra spawné: {} {} → {w}x{h} @ {fps}fps",
+        spec.format,
+        spec.device
+    );
+    stdout
+}
+
+fn kill_child(child: &SharedChild, stdout: &mut Option<ChildStdout>) {
+    *stdout = None;
+    if let Some(mut c) = child.lock().unwrap_or_else(PoisonError::into_inner).take() {
+        let _ = c.kill();
+        let _ = c.wait();
+    }
+}
+
+/// Vide les commandes en attente : `true` si un `Quit` est arrivé ou si
+/// l'émetteur a disparu.
+fn quit_requested(cmd_rx: &Receiver<VideoCommand>) -> bool {
+    loop {
+        match cmd_rx.try_recv() {
+            Ok(VideoCommand::Quit) | Err(flume::TryRecvError::Disconnected) => return true,
+            Ok(_) => {}
+            Err(flume::TryRecvError::Empty) => return false,
+        }
+    }
+}
+
+/// Spawne le thread de capture caméra.
+///
+/// Comme pour la vidéo, ffmpeg n'est lancé qu'au premier `VideoCommand::Resize`
+/// (envoyé par `check_resize()` du thread principal). Les frames sont envoyées
+/// avec `try_send` : si le consommateur est en retard, la frame est abandonnée
+/// (une source live privilégie la fraîcheur à l'exhaustivité).
+///
+/// Le thread se termine (et déconnecte `frame_tx`) à la fin du flux : EOF,
+/// device débranché, ffmpeg impossible à lancer.
+///
+/// # Errors
+/// Retourne une erreur si le thread ne peut être créé.
+pub fn spawn_camera_thread(
+    spec: CameraSpec,
+    frame_tx: Sender<Arc<FrameBuffer>>,
+    cmd_rx: Receiver<VideoCommand>,
+    fps: u32,
+) -> Result<thread::JoinHandle<()>> {
+    spawn_with_child(spec, frame_tx, cmd_rx, fps, SharedChild::default())
+}
+
+fn spawn_with_child(
+    spec: CameraSpec,
+    frame_tx: Sender<Arc<FrameBuffer>>,
+    cmd_rx: Receiver<VideoCommand>,
+    fps: u32,
+    child: SharedChild,
+) -> Result<thread::JoinHandle<()>> {
+    thread::Builder::new()
+        .name("af-camera".to_string())
+        .spawn(move || camera_loop(&spec, &frame_tx, &cmd_rx, fps.clamp(1, 60), &child))
+        .context("Impossible de spawner le thread caméra")
+}
+
+/// Boucle principale du thread caméra.
+fn camera_loop(
+    spec: &CameraSpec,
+    frame_tx: &Sender<Arc<FrameBuffer>>,
+    cmd_rx: &Receiver<VideoCommand>,
+    fps: u32,
+    child: &SharedChild,
+) {
+    let (mut w, mut h) = (0u32, 0u32);
+    let mut is_paused = false;
+    let mut pool: Vec<Arc<FrameBuffer>> = Vec::with_capacity(POOL_SIZE);
+    let mut stdout: Option<ChildStdout> = None;
+    let mut consecutive_pipe_errors: u32 = 0;
+
+    loop {
+        // === Commandes (non-bloquant) ===
+        loop {
+            match cmd_rx.try_recv() {
+                Ok(VideoCommand::Quit) | Err(flume::TryRecvError::Disconnected) => {
+                    kill_child(child, &mut stdout);
+                    log::info!("Thread caméra: arrêt propre.");
+                    return;
+                }
+                Ok(VideoCommand::Pause) => is_paused = true,
+                Ok(VideoCommand::Play) => is_paused = false,
+                Ok(VideoCommand::Resize(nw, nh)) => {
+                    if nw > 0 && nh > 0 && (nw != w || nh != h || stdout.is_none()) {
+                        w = nw;
+                        h = nh;
+                        pool.clear();
+                        kill_child(child, &mut stdout);
+                        stdout = spawn_camera_pipe(spec, child, w, h, fps);
+                        if stdout.is_none() {
+                            log::error!(
+                                "Thread caméra: impossible de lancer ffmpeg ({} {}).",
+                                spec.format,
+                                spec.device
+                            );
+                            return;
+                        }
+                        log::debug!("Thread caméra: Resize -> {w}x{h}");
+                    }
+                }
+                Ok(VideoCommand::Seek(_) | VideoCommand::UpdateClock(_)) => {}
+                Err(flume::TryRecvError::Empty) => break,
+            }
+        }
+
+        // === Guard : pas de ffmpeg encore → attendre le premier Resize ===
+        let Some(pipe) = stdout.as_mut() else {
+            thread::sleep(Duration::from_millis(10));
+            continue;
+        };
+
+        let frame_bytes = (w * h * 4) as usize;
+        let idx = find_or_create_slot(&mut pool, w, h);
+        let Some(fb) = Arc::get_mut(&mut pool[idx]) else {
+            continue;
+        };
+
+        // La caméra cadence elle-même : la lecture bloque jusqu'à la frame suivante.
+        // En pause, on continue de drainer le pipe pour ne pas accumuler de latence.
+        let read_result = read_exact_or_eof(pipe, &mut fb.data[..frame_bytes]);
+
+        match read_result {
+            Ok(true) => {
+                consecutive_pipe_errors = 0;
+                if is_paused {
+                    continue;
+                }
+                match frame_tx.try_send(Arc::clone(&pool[idx])) {
+                    Ok(()) | Err(flume::TrySendError::Full(_)) => {}
+                    Err(flume::TrySendError::Disconnected(_)) => {
+                        kill_child(child, &mut stdout);
+                        return;
+                    }
+                }
+            }
+            // EOF provoqué par `CameraSource::drop` (ffmpeg tué après Quit)
+            Ok(false) if quit_requested(cmd_rx) => {
+                log::info!("Thread caméra: arrêt propre.");
+                break;
+            }
+            Ok(false) => {
+                log::error!(
+                    "Thread caméra: flux terminé ({} {}), device indisponible ?",
+                    spec.format,
+                    spec.device
+                );
+                break;
+            }
+            Err(e) => {
+                consecutive_pipe_errors += 1;
+                log::warn!("Thread caméra: erreur lecture pipe ({consecutive_pipe_errors}/5): {e}");
+                kill_child(child, &mut stdout);
+                if consecutive_pipe_errors >= 5 {
+                    log::error!("Thread caméra: abandon après 5 erreurs pipe consécutives.");
+                    break;
+                }
+                thread::sleep(Duration::from_millis(100));
+                stdout = spawn_camera_pipe(spec, child, w, h, fps);
+            }
+        }
+    }
+
+    kill_child(child, &mut stdout);
+    log::info!("Thread caméra terminé.");
+}
+
+/// Source caméra autonome (thread de capture + canaux) implémentant [`Source`].
+///
+/// L'App utilise directement `spawn_camera_thread` (mêmes canaux que la vidéo) ;
+/// ce wrapper sert aux usages hors TUI et aux tests.
+///
+/// # Example
+/// ```no_run
+/// use af_core::traits::Source;
+/// use af_source::camera::{CameraSource, CameraSpec};
+/// let spec = CameraSpec::parse("lavfi:testsrc=size=320x240:rate=30").unwrap();
+/// let mut cam = CameraSource::open(spec, 160, 120, 30).unwrap();
+/// let frame = cam.next_frame();
+/// ```
+pub struct CameraSource {
+    frame_rx: Receiver<Arc<FrameBuffer>>,
+    cmd_tx: Sender<VideoCommand>,
+    last_frame: Option<Arc<FrameBuffer>>,
+    size: (u32, u32),
+    child: SharedChild,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl CameraSource {
+    /// Ouvre la caméra avec une sortie `width × height`.
+    ///
+    /// # Errors
+    /// Retourne une erreur si le thread de capture ne peut être créé.
+    pub fn open(spec: CameraSpec, width: u32, height: u32, fps: u32) -> Result<Self> {
+        let (frame_tx, frame_rx) = flume::bounded(3);
+        let (cmd_tx, cmd_rx) = flume::bounded(10);
+        let child = SharedChild::default();
+        let handle = spawn_with_child(spec, frame_tx, cmd_rx, fps, Arc::clone(&child))?;
+        let size = (width.max(1), height.max(1));
+        let _ = cmd_tx.send(VideoCommand::Resize(size.0, size.1));
+        Ok(Self {
+            frame_rx,
+            cmd_tx,
+            last_frame: None,
+            size,
+            child,
+            handle: Some(handle),
+        })
+    }
+
+    /// Change la résolution de sortie (redémarre ffmpeg).
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let size = (width.max(1), height.max(1));
+        if size != self.size {
+            self.size = size;
+            let _ = self.cmd_tx.send(VideoCommand::Resize(size.0, size.1));
+        }
+    }
+}
+
+impl Source for CameraSource {
+    fn next_frame(&mut self) -> Option<Arc<FrameBuffer>> {
+        // Ne bloque jamais : dernière frame reçue, ou la précédente.
+        while let Ok(frame) = self.frame_rx.try_recv() {
+            self.last_frame = Some(frame);
+        }
+        self.last_frame.clone()
+    }
+
+    fn native_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn is_live(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for CameraSource {
+    fn drop(&mut self) {
+        let _ = self.cmd_tx.send(VideoCommand::Quit);
+        // Le thread peut être bloqué dans une lecture du stdout de ffmpeg (device
+        // figé) : tuer ffmpeg la termine par EOF avant le join.
+        if let Some(mut c) = self
+            .child
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+        {
+            let _ = c.kill();
+            let _ = c.wait();
+        }
+        while self.frame_rx.try_recv().is_ok() {}
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_format_prefix() {
+        let spec = CameraSpec::parse("v4l2:/dev/video2").ok();
+        assert_eq!(
+            spec,
+            Some(CameraSpec {
+                format: "v4l2".into(),
+                device: "/dev/video2".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn lavfi_graph_keeps_colons() {
+        let spec = CameraSpec::parse("lavfi:testsrc=size=64x48:rate=10").ok();
+        assert_eq!(
+            spec.map(|s| s.device),
+            Some("testsrc=size=64x48:rate=10".into())
+        );
+    }
+
+    #[test]
+    fn quit_is_detected_among_pending_commands() {
+        let (tx, rx) = flume::unbounded();
+        let _ = tx.send(VideoCommand::Pause);
+        assert!(!quit_requested(&rx));
+        let _ = tx.send(VideoCommand::Resize(4, 4));
+        let _ = tx.send(VideoCommand::Quit);
+        assert!(quit_requested(&rx));
+        drop(tx);
+        assert!(quit_requested(&rx));
+    }
+
+    #[test]
+    fn lavfi_has_no_framerate_arg() {
+        let spec = CameraSpec {
+            format: "lavfi".into(),
+            device: "testsrc".into(),
+        };
+        assert_eq!(spec.input_args(30), ["-f", "lavfi", "-i", "testsrc"]);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn index_maps_to_v4l2_device() {
+        let spec = CameraSpec::parse("1").ok();
+        assert_eq!(
+            spec.as_ref().map(|s| s.device.as_str()),
+            Some("/dev/video1")
+        );
+        assert_eq!(
+            spec.map(|s| s.input_args(30)),
+            Some(
+                ["-f", "v4l2", "-framerate", "30", "-i", "/dev/video1"]
+                    .map(String::from)
+                    .to_vec()
+            )
+        );
+        assert_eq!(
+            CameraSpec::parse("default").ok().map(|s| s.device),
+            Some("/dev/video0".into())
+        );
+    }
+}
@@ -7,6 +7,9 @@ use std::sync::Arc;
 
 use crate::image::load_image;
 
+#[cfg(feature = "video")]
+use af_core::clock::FrameRate;
+
 #[cfg(feature = "video")]
 use crate::video::{VideoInfo, probe_video, read_exact_or_eof, spawn_ffmpeg_pipe};
 
@@ -209,9 +212,13 @@ impl FolderBatchSource {
             #[cfg(feature = "video")]
             {
                 if let Ok(info) = probe_video(path) {
-                    if let Some(child) =
-                        spawn_ffmpeg_pipe(path, info.width, info.height, 0.0, self.target_fps)
-                    {
+                    if let Some(child) = spawn_ffmpeg_pipe(
+                        path,
+                        info.width,
+                        info.height,
+                        0.0,
+                        FrameRate::integer(self.target_fps),
+                    ) {
                         self.video_child = Some(child);
                         self.video_info = Some(info);
                         self.video_frame =
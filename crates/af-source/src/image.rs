@@ -148,7 +148,7 @@ impl GifSource {
             }
 
             let (numer, denom) = raw.delay().numer_denom_ms();
-            let ms = if denom == 0 { 100 } else { numer / denom };
+            let ms = numer.checked_div(denom).unwrap_or(100);
             let delay = Duration::from_millis(u64::from(ms.max(10)));
 
             let (w, h) = (buf.width(), buf.height());
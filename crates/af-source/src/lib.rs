@@ -1,8 +1,11 @@
-//! Visual source modules for clasSCII (image, video).
+//! Visual source modules for clasSCII (image, video, camera, procedural).
 
 pub mod folder_batch;
 pub mod image;
+pub mod procedural;
 pub mod resize;
 
+#[cfg(feature = "video")]
+pub mod camera;
 #[cfg(feature = "video")]
 pub mod video;
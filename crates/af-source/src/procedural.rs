@@ -0,0 +1,784 @@
+//! Procedural generative sources (no media file required).
+//!
+//! Six generators render RGBA frames directly: plasma, Perlin fBm noise,
+//! Gray-Scott reaction-diffusion, Game of Life, metaballs and a tunnel.
+//! Their parameters (`speed`, `scale`, `complexity`) come from
+//! [`RenderConfig`] each frame, so they are regular audio-mapping targets.
+//!
+//! Time advances by a fixed step per frame (`speed / fps`), never by wall
+//! clock: identical parameters produce identical frames, live or in batch.
+
+use std::f32::consts::{PI, TAU};
+use std::sync::Arc;
+
+use af_core::config::RenderConfig;
+use af_core::frame::FrameBuffer;
+use af_core::traits::Source;
+
+/// Maximum simulation grid width for cellular generators (reaction-diffusion, life).
+const MAX_SIM_WIDTH: usize = 256;
+/// Number of reusable frame slots (same budget as the video decoder pool).
+const POOL_SIZE: usize = 4;
+/// Default seed: procedural output is reproducible unless overridden.
+const DEFAULT_SEED: u64 = 0x00C1_A55C_1100_5EED;
+
+/// Procedural generator family.
+///
+/// # Example
+/// ```
+/// use af_source::procedural::GeneratorKind;
+/// assert_eq!(GeneratorKind::from_name("gray-scott"), Some(GeneratorKind::ReactionDiffusion));
+/// assert_eq!(GeneratorKind::Tunnel.next(), GeneratorKind::Plasma);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeneratorKind {
+    /// Classic sum-of-sines plasma.
+    Plasma,
+    /// Domain-warped Perlin fBm.
+    Noise,
+    /// Gray-Scott reaction-diffusion.
+    ReactionDiffusion,
+    /// Conway's Game of Life with fading trails.
+    Life,
+    /// Orbiting metaballs (implicit field).
+    Metaballs,
+    /// Polar-mapped infinite tunnel.
+    Tunnel,
+}
+
+impl GeneratorKind {
+    /// All generators, in cycling order.
+    pub const ALL: [Self; 6] = [
+        Self::Plasma,
+        Self::Noise,
+        Self::ReactionDiffusion,
+        Self::Life,
+        Self::Metaballs,
+        Self::Tunnel,
+    ];
+
+    /// Canonical CLI name.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Plasma => "plasma",
+            Self::Noise => "noise",
+            Self::ReactionDiffusion => "reaction-diffusion",
+            Self::Life => "life",
+            Self::Metaballs => "metaballs",
+            Self::Tunnel => "tunnel",
+        }
+    }
+
+    /// Parse a generator name (case-insensitive, common aliases accepted).
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "plasma" => Some(Self::Plasma),
+            "noise" | "perlin" | "fbm" => Some(Self::Noise),
+            "reaction-diffusion" | "reaction_diffusion" | "rd" | "gray-scott" => {
+                Some(Self::ReactionDiffusion)
+            }
+            "life" | "gol" | "game-of-life" => Some(Self::Life),
+            "metaballs" | "metaball" => Some(Self::Metaballs),
+            "tunnel" => Some(Self::Tunnel),
+            _ => None,
+        }
+    }
+
+    /// Next generator in cycling order (wraps around).
+    #[must_use]
+    pub fn next(self) -> Self {
+        let idx = Self::ALL.iter().position(|&k| k == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+}
+
+/// Per-frame generator parameters (audio-mappable through [`RenderConfig`]).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeneratorParams {
+    /// Animation speed multiplier [0.0, 5.0].
+    pub speed: f32,
+    /// Spatial zoom [0.1, 10.0]. >1.0 = larger features.
+    pub scale: f32,
+    /// Detail level [0.0, 1.0].
+    pub complexity: f32,
+    /// Frame rate used to derive the fixed time step.
+    pub fps: u32,
+}
+
+impl Default for GeneratorParams {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            scale: 1.0,
+            complexity: 0.5,
+            fps: 30,
+        }
+    }
+}
+
+impl GeneratorParams {
+    /// Extract generator parameters from a (possibly audio-modulated) config.
+    #[must_use]
+    pub fn from_config(config: &RenderConfig) -> Self {
+        Self {
+            speed: config.generator_speed,
+            scale: config.generator_scale,
+            complexity: config.generator_complexity,
+            fps: config.target_fps,
+        }
+    }
+}
+
+/// Simulation state for the stateful generators.
+enum SimState {
+    /// Stateless generators (pure function of time).
+    None,
+    /// Gray-Scott chemical concentrations (toroidal grid).
+    ReactionDiffusion {
+        w: usize,
+        h: usize,
+        u: Vec<f32>,
+        v: Vec<f32>,
+        scratch_u: Vec<f32>,
+        scratch_v: Vec<f32>,
+    },
+    /// Life cells + per-cell trail intensity.
+    Life {
+        w: usize,
+        h: usize,
+        cells: Vec<bool>,
+        next: Vec<bool>,
+        trail: Vec<f32>,
+        step_accumulator: f32,
+        stagnant_steps: u32,
+    },
+}
+
+/// Procedural generative source implementing [`Source`].
+///
+/// # Example
+/// ```
+/// use af_core::traits::Source;
+/// use af_source::procedural::{GeneratorKind, ProceduralSource};
+///
+/// let mut source = ProceduralSource::new(GeneratorKind::Plasma, 64, 36);
+/// let frame = source.next_frame().unwrap();
+/// assert_eq!((frame.width, frame.height), (64, 36));
+/// assert!(source.is_live());
+/// ```
+pub struct ProceduralSource {
+    kind: GeneratorKind,
+    width: u32,
+    height: u32,
+    params: GeneratorParams,
+    time: f32,
+    seed: u64,
+    rng: u64,
+    perm: [u8; 512],
+    sim: SimState,
+    pool: Vec<Arc<FrameBuffer>>,
+}
+
+impl ProceduralSource {
+    /// Create a generator rendering frames of `width`×`height` pixels.
+    #[must_use]
+    pub fn new(kind: GeneratorKind, width: u32, height: u32) -> Self {
+        let mut source = Self {
+            kind,
+            width: width.max(1),
+            height: height.max(1),
+            params: GeneratorParams::default(),
+            time: 0.0,
+            seed: DEFAULT_SEED,
+            rng: DEFAULT_SEED,
+            perm: [0; 512],
+            sim: SimState::None,
+            pool: Vec::with_capacity(POOL_SIZE),
+        };
+        source.reseed();
+        source
+    }
+
+    /// Override the random seed (noise permutation, initial simulation state).
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.reseed();
+        self
+    }
+
+    /// Active generator.
+    #[must_use]
+    pub fn kind(&self) -> GeneratorKind {
+        self.kind
+    }
+
+    /// Switch generator. Simulation state is reset, time keeps running.
+    pub fn set_kind(&mut self, kind: GeneratorKind) {
+        if self.kind != kind {
+            self.kind = kind;
+            self.sim = SimState::None;
+        }
+    }
+
+    /// Update per-frame parameters (called after audio mappings).
+    pub fn set_params(&mut self, params: GeneratorParams) {
+        self.params = params;
+    }
+
+    /// Change output resolution. Frame pool and simulation grid are rebuilt.
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let (width, height) = (width.max(1), height.max(1));
+        if (width, height) != (self.width, self.height) {
+            self.width = width;
+            self.height = height;
+            self.pool.clear();
+            self.sim = SimState::None;
+        }
+    }
+
+    /// Reset the xorshift state and rebuild the noise permutation table.
+    fn reseed(&mut self) {
+        self.rng = self.seed.max(1);
+        let mut table: [u8; 256] = std::array::from_fn(|i| i as u8);
+        for i in (1..256).rev() {
+            let j = (self.next_random() % (i as u64 + 1)) as usize;
+            table.swap(i, j);
+        }
+        for i in 0..512 {
+            self.perm[i] = table[i & 255];
+        }
+    }
+
+    /// xorshift64 — small, deterministic, no external dependency.
+    fn next_random(&mut self) -> u64 {
+        let mut x = self.rng;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.rng = x;
+        x
+    }
+
+    /// Uniform random value in [0, 1).
+    fn next_unit(&mut self) -> f32 {
+        (self.next_random() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Simulation grid size for cellular generators (aspect-preserving, capped).
+    fn sim_size(&self) -> (usize, usize) {
+        let w = (self.width as usize).clamp(8, MAX_SIM_WIDTH);
+        let h = (w * self.height as usize / self.width.max(1) as usize).max(8);
+        (w, h)
+    }
+
+    /// Ensure the simulation state matches the active generator.
+    fn ensure_sim(&mut self) {
+        let (w, h) = self.sim_size();
+        match (self.kind, &self.sim) {
+            (GeneratorKind::ReactionDiffusion, SimState::ReactionDiffusion { .. })
+            | (GeneratorKind::Life, SimState::Life { .. }) => {}
+            (GeneratorKind::ReactionDiffusion, _) => {
+                let mut u = vec![1.0; w * h];
+                let mut v = vec![0.0; w * h];
+                self.seed_reaction(&mut u, &mut v, w, h);
+                self.sim = SimState::ReactionDiffusion {
+                    w,
+                    h,
+                    scratch_u: u.clone(),
+                    scratch_v: v.clone(),
+                    u,
+                    v,
+                };
+            }
+            (GeneratorKind::Life, _) => {
+                let mut cells = vec![false; w * h];
+                self.seed_life(&mut cells);
+                self.sim = SimState::Life {
+                    w,
+                    h,
+                    next: cells.clone(),
+                    trail: vec![0.0; w * h],
+                    cells,
+                    step_accumulator: 0.0,
+                    stagnant_steps: 0,
+                };
+            }
+            _ => self.sim = SimState::None,
+        }
+    }
+
+    /// Drop random squares of chemical V into a uniform U field.
+    fn seed_reaction(&mut self, u: &mut [f32], v: &mut [f32], w: usize, h: usize) {
+        let spots = 4 + (self.params.complexity * 20.0) as usize;
+        let half = (w.min(h) / 20).max(2);
+        for _ in 0..spots {
+            let cx = (self.next_unit() * w as f32) as usize;
+            let cy = (self.next_unit() * h as f32) as usize;
+            for dy in 0..half * 2 {
+                for dx in 0..half * 2 {
+                    let idx = ((cy + dy) % h) * w + (cx + dx) % w;
+                    u[idx] = 0.5;
+                    v[idx] = 0.25;
+                }
+            }
+        }
+    }
+
+    /// Random soup; density follows complexity.
+    fn seed_life(&mut self, cells: &mut [bool]) {
+        let density = 0.1 + self.params.complexity * 0.35;
+        for cell in cells.iter_mut() {
+            *cell = self.next_unit() < density;
+        }
+    }
+
+    /// Advance stateful simulations by one frame.
+    fn step_sim(&mut self, dt: f32) {
+        let speed = self.params.speed;
+        let complexity = self.params.complexity;
+        let mut reseed = false;
+        match &mut self.sim {
+            SimState::None => {}
+            SimState::ReactionDiffusion {
+                w,
+                h,
+                u,
+                v,
+                scratch_u,
+                scratch_v,
+            } => {
+                // complexity 0 → "mitosis" spots, 1 → "coral" labyrinths
+                let feed = 0.0367 + (0.0545 - 0.0367) * complexity;
+                let kill = 0.0649 + (0.062 - 0.0649) * complexity;
+                let steps = (speed * 8.0).round().clamp(0.0, 32.0) as usize;
+                for _ in 0..steps {
+                    gray_scott_step(*w, *h, u, v, scratch_u, scratch_v, feed, kill);
+                    std::mem::swap(u, scratch_u);
+                    std::mem::swap(v, scratch_v);
+                }
+                // Pattern died out → reseed
+                reseed = v.iter().all(|&c| c < 0.01);
+            }
+            SimState::Life {
+                w,
+                h,
+                cells,
+                next,
+                trail,
+                step_accumulator,
+                stagnant_steps,
+            } => {
+                // ~12 generations per second at speed 1.0
+                *step_accumulator += dt * 12.0;
+                while *step_accumulator >= 1.0 {
+                    *step_accumulator -= 1.0;
+                    let changed = life_step(*w, *h, cells, next);
+                    std::mem::swap(cells, next);
+                    *stagnant_steps = if changed == 0 { *stagnant_steps + 1 } else { 0 };
+                }
+                for (t, &alive) in trail.iter_mut().zip(cells.iter()) {
+                    *t = if alive { 1.0 } else { *t * 0.85 };
+                }
+                let population = cells.iter().filter(|&&c| c).count();
+                reseed = *stagnant_steps > 24 || population * 100 < cells.len();
+            }
+        }
+        if reseed {
+            self.sim = SimState::None;
+            self.ensure_sim();
+        }
+    }
+
+    /// Index of a pool slot that nobody else holds, allocating if needed.
+    fn acquire_slot(&mut self) -> usize {
+        if let Some(idx) = self.pool.iter().position(|fb| Arc::strong_count(fb) == 1) {
+            return idx;
+        }
+        if self.pool.len() < POOL_SIZE {
+            self.pool
+                .push(Arc::new(FrameBuffer::new(self.width, self.height)));
+            return self.pool.len() - 1;
+        }
+        // Every slot is still referenced downstream: replace the oldest one.
+        self.pool[0] = Arc::new(FrameBuffer::new(self.width, self.height));
+        0
+    }
+}
+
+impl Source for ProceduralSource {
+    fn next_frame(&mut self) -> Option<Arc<FrameBuffer>> {
+        let dt = self.params.speed / self.params.fps.max(1) as f32;
+        self.time += dt;
+        self.ensure_sim();
+        self.step_sim(dt);
+
+        let idx = self.acquire_slot();
+        let Self {
+            kind,
+            params,
+            time,
+            perm,
+            sim,
+            pool,
+            ..
+        } = self;
+        let fb = Arc::get_mut(&mut pool[idx])?;
+        let ctx = RenderCtx {
+            t: *time,
+            params: *params,
+            perm,
+        };
+        match kind {
+            GeneratorKind::Plasma => fill(fb, |x, y| render_plasma(&ctx, x, y)),
+            GeneratorKind::Noise => fill(fb, |x, y| render_noise(&ctx, x, y)),
+            GeneratorKind::Metaballs => fill(fb, |x, y| render_metaballs(&ctx, x, y)),
+            GeneratorKind::Tunnel => fill(fb, |x, y| render_tunnel(&ctx, x, y)),
+            GeneratorKind::ReactionDiffusion | GeneratorKind::Life => {
+                fill(fb, |x, y| render_sim(&ctx, sim, x, y));
+            }
+        }
+        Some(Arc::clone(&pool[idx]))
+    }
+
+    fn native_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn is_live(&self) -> bool {
+        true
+    }
+
+    fn seek(&mut self, timestamp: f64) {
+        self.time = timestamp as f32;
+    }
+}
+
+/// Immutable per-frame inputs shared by the pixel shaders.
+struct RenderCtx<'a> {
+    t: f32,
+    params: GeneratorParams,
+    perm: &'a [u8; 512],
+}
+
+/// Run a pixel shader over the whole frame.
+///
+/// The shader receives aspect-correct coordinates: `y` ∈ [-0.5, 0.5],
+/// `x` scaled by width/height, origin at the centre.
+fn fill(fb: &mut FrameBuffer, shader: impl Fn(f32, f32) -> (u8, u8, u8)) {
+    let w = fb.width as usize;
+    let h = fb.height.max(1) as f32;
+    let half_w = fb.width as f32 * 0.5;
+    for (py, row) in fb.data.chunks_exact_mut(w * 4).enumerate() {
+        let y = (py as f32 + 0.5) / h - 0.5;
+        for (px, pixel) in row.chunks_exact_mut(4).enumerate() {
+            let x = (px as f32 + 0.5 - half_w) / h;
+            let (r, g, b) = shader(x, y);
+            pixel[0] = r;
+            pixel[1] = g;
+            pixel[2] = b;
+            pixel[3] = 255;
+        }
+    }
+}
+
+/// Cosine palette (Inigo Quilez), `phase` rotates the hue.
+fn palette(t: f32, phase: f32, brightness: f32) -> (u8, u8, u8) {
+    let channel = |offset: f32| {
+        let c = 0.5 + 0.5 * (TAU * (t + phase + offset)).cos();
+        (c * brightness.clamp(0.0, 1.0) * 255.0) as u8
+    };
+    (channel(0.0), channel(0.33), channel(0.67))
+}
+
+fn render_plasma(ctx: &RenderCtx, x: f32, y: f32) -> (u8, u8, u8) {
+    let s = 6.0 / ctx.params.scale.max(0.1);
+    let (x, y, t) = (x * s, y * s, ctx.t);
+    let cx = x + 0.5 * (t / 5.0).sin() * s;
+    let cy = y + 0.5 * (t / 3.0).cos() * s;
+    let mut v = (x + t).sin()
+        + ((y + t) * 0.5).sin()
+        + ((x + y + t) * 0.5).sin()
+        + ((cx * cx + cy * cy + 1.0).sqrt() + t).sin();
+    // Complexity adds a high-frequency interference layer
+    v += ctx.params.complexity * (x * 3.0 - t * 1.3).sin() * (y * 3.0 + t).cos();
+    let norm = v / (4.0 + ctx.params.complexity);
+    palette(norm * 0.5 + 0.5, t * 0.05, 1.0)
+}
+
+fn render_noise(ctx: &RenderCtx, x: f32, y: f32) -> (u8, u8, u8) {
+    let s = 3.0 / ctx.params.scale.max(0.1);
+    let t = ctx.t;
+    let octaves = 1 + (ctx.params.complexity * 5.0).round() as u32;
+    let (px, py) = (x * s, y * s);
+    // Domain warping: noise displaced by noise
+    let wx = fbm(ctx.perm, px + t * 0.15, py - t * 0.1, octaves);
+    let wy = fbm(ctx.perm, px - t * 0.12 + 5.2, py + t * 0.08 + 1.3, octaves);
+    let v = fbm(ctx.perm, px + wx * 1.5, py + wy * 1.5, octaves);
+    let n = (v * 0.5 + 0.5).clamp(0.0, 1.0);
+    palette(n * 0.8, 0.55 + t * 0.02, n.sqrt())
+}
+
+fn render_metaballs(ctx: &RenderCtx, x: f32, y: f32) -> (u8, u8, u8) {
+    let count = 2 + (ctx.params.complexity * 10.0).round() as u32;
+    let radius = 0.08 * ctx.params.scale;
+    let r2 = radius * radius;
+    let t = ctx.t;
+    let mut field = 0.0;
+    for i in 0..count {
+        let fi = i as f32;
+        let bx = 0.6 * (t * (0.7 + 0.13 * fi) + fi * 1.7).sin();
+        let by = 0.35 * (t * (0.5 + 0.11 * fi) + fi * 2.3).cos();
+        let d2 = (x - bx) * (x - bx) + (y - by) * (y - by);
+        field += r2 / d2.max(1e-5);
+    }
+    if field >= 1.0 {
+        palette(0.1 + (field - 1.0).min(2.0) * 0.15, t * 0.03, 1.0)
+    } else {
+        // Soft halo outside the iso-surface
+        palette(0.6, t * 0.03, field * field * 0.6)
+    }
+}
+
+fn render_tunnel(ctx: &RenderCtx, x: f32, y: f32) -> (u8, u8, u8) {
+    let dist = (x * x + y * y).sqrt().max(1e-3);
+    let angle = y.atan2(x);
+    let t = ctx.t;
+    let depth = 0.3 * ctx.params.scale / dist + t;
+    let segments = 2.0 + (ctx.params.complexity * 6.0).round();
+    let around = angle / PI * segments + t * 0.2;
+    let checker = ((depth * 4.0).floor() + around.floor()).rem_euclid(2.0);
+    let stripe = 0.5 + 0.5 * (depth * TAU).sin();
+    // Fog: darker towards the vanishing point
+    let fog = (dist * 2.5).clamp(0.0, 1.0);
+    palette(
+        depth * 0.1 + checker * 0.25,
+        stripe * 0.1,
+        fog * (0.4 + 0.6 * checker),
+    )
+}
+
+/// Sample the cellular simulation (nearest neighbour, wrapped, zoomed by `scale`).
+fn render_sim(ctx: &RenderCtx, sim: &SimState, x: f32, y: f32) -> (u8, u8, u8) {
+    let (w, h) = match sim {
+        SimState::None => return (0, 0, 0),
+        SimState::ReactionDiffusion { w, h, .. } | SimState::Life { w, h, .. } => (*w, *h),
+    };
+    let zoom = ctx.params.scale.max(0.1);
+    let aspect = w as f32 / h as f32;
+    let u = (x / aspect / zoom + 0.5).rem_euclid(1.0);
+    let v = (y / zoom + 0.5).rem_euclid(1.0);
+    let idx = ((v * h as f32) as usize).min(h - 1) * w + ((u * w as f32) as usize).min(w - 1);
+    match sim {
+        SimState::ReactionDiffusion { v: chem, .. } => {
+            let c = (chem[idx] * 3.0).clamp(0.0, 1.0);
+            palette(0.55 - c * 0.4, ctx.t * 0.01, c.sqrt())
+        }
+        SimState::Life { cells, trail, .. } => {
+            if cells[idx] {
+                (255, 255, 255)
+            } else {
+                palette(0.6 + trail[idx] * 0.3, ctx.t * 0.02, trail[idx])
+            }
+        }
+        SimState::None => (0, 0, 0),
+    }
+}
+
+/// One Gray-Scott step on a toroidal grid (9-point Laplacian, Du=1.0, Dv=0.5).
+#[allow(clippy::too_many_arguments)]
+fn gray_scott_step(
+    w: usize,
+    h: usize,
+    u: &[f32],
+    v: &[f32],
+    out_u: &mut [f32],
+    out_v: &mut [f32],
+    feed: f32,
+    kill: f32,
+) {
+    for y in 0..h {
+        let ym = (y + h - 1) % h;
+        let yp = (y + 1) % h;
+        for x in 0..w {
+            let xm = (x + w - 1) % w;
+            let xp = (x + 1) % w;
+            let lap = |f: &[f32]| {
+                0.2 * (f[y * w + xm] + f[y * w + xp] + f[ym * w + x] + f[yp * w + x])
+                    + 0.05 * (f[ym * w + xm] + f[ym * w + xp] + f[yp * w + xm] + f[yp * w + xp])
+                    - f[y * w + x]
+            };
+            let i = y * w + x;
+            let (a, b) = (u[i], v[i]);
+            let reaction = a * b * b;
+            out_u[i] = (a + lap(u) - reaction + feed * (1.0 - a)).clamp(0.0, 1.0);
+            out_v[i] = (b + 0.5 * lap(v) + reaction - (kill + feed) * b).clamp(0.0, 1.0);
+        }
+    }
+}
+
+/// One Game of Life generation (toroidal). Returns the number of changed cells.
+fn life_step(w: usize, h: usize, cells: &[bool], next: &mut [bool]) -> usize {
+    let mut changed = 0;
+    for y in 0..h {
+        let ym = (y + h - 1) % h;
+        let yp = (y + 1) % h;
+        for x in 0..w {
+            let xm = (x + w - 1) % w;
+            let xp = (x + 1) % w;
+            let neighbours = [
+                cells[ym * w + xm],
+                cells[ym * w + x],
+                cells[ym * w + xp],
+                cells[y * w + xm],
+                cells[y * w + xp],
+                cells[yp * w + xm],
+                cells[yp * w + x],
+                cells[yp * w + xp],
+            ]
+            .iter()
+            .filter(|&&c| c)
+            .count();
+            let alive = cells[y * w + x];
+            let next_alive = matches!((alive, neighbours), (true, 2 | 3) | (false, 3));
+            if next_alive != alive {
+                changed += 1;
+            }
+            next[y * w + x] = next_alive;
+        }
+    }
+    changed
+}
+
+/// Fractal Brownian motion over 2D Perlin noise, output ≈ [-1, 1].
+fn fbm(perm: &[u8; 512], x: f32, y: f32, octaves: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut amp = 0.5;
+    let mut freq = 1.0;
+    let mut norm = 0.0;
+    for _ in 0..octaves {
+        sum += amp * perlin(perm, x * freq, y * freq);
+        norm += amp;
+        amp *= 0.5;
+        freq *= 2.0;
+    }
+    sum / norm.max(f32::EPSILON) * 1.4
+}
+
+/// Improved Perlin noise (2D).
+fn perlin(perm: &[u8; 512], x: f32, y: f32) -> f32 {
+    let xf = x.floor();
+    let yf = y.floor();
+    let xi = (xf as i32 & 255) as usize;
+    let yi = (yf as i32 & 255) as usize;
+    let (dx, dy) = (x - xf, y - yf);
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (u, v) = (fade(dx), fade(dy));
+    let grad = |hash: u8, gx: f32, gy: f32| match hash & 3 {
+        0 => gx + gy,
+        1 => -gx + gy,
+        2 => gx - gy,
+        _ => -gx - gy,
+    };
+    let aa = perm[perm[xi] as usize + yi];
+    let ab = perm[perm[xi] as usize + yi + 1];
+    let ba = perm[perm[xi + 1] as usize + yi];
+    let bb = perm[perm[xi + 1] as usize + yi + 1];
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let x1 = lerp(grad(aa, dx, dy), grad(ba, dx - 1.0, dy), u);
+    let x2 = lerp(grad(ab, dx, dy - 1.0), grad(bb, dx - 1.0, dy - 1.0), u);
+    lerp(x1, x2, v) * 0.7
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_generator_renders_full_frame() {
+        for kind in GeneratorKind::ALL {
+            let mut source = ProceduralSource::new(kind, 48, 27);
+            let frame = source.next_frame();
+            assert!(frame.is_some(), "{} produced no frame", kind.name());
+            let frame = frame.unwrap_or_else(|| Arc::new(FrameBuffer::new(0, 0)));
+            assert_eq!((frame.width, frame.height), (48, 27));
+            assert_eq!(frame.data.len(), 48 * 27 * 4);
+            assert!(
+                frame.data.chunks_exact(4).all(|p| p[3] == 255),
+                "{} must be opaque",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn output_is_deterministic() {
+        for kind in GeneratorKind::ALL {
+            let mut a = ProceduralSource::new(kind, 32, 18);
+            let mut b = ProceduralSource::new(kind, 32, 18);
+            for _ in 0..5 {
+                let fa = a.next_frame().map(|f| f.data.clone());
+                let fb = b.next_frame().map(|f| f.data.clone());
+                assert_eq!(fa, fb, "{} diverged", kind.name());
+            }
+        }
+    }
+
+    #[test]
+    fn animation_advances_over_time() {
+        let mut source = ProceduralSource::new(GeneratorKind::Plasma, 32, 18);
+        let first = source.next_frame().map(|f| f.data.clone());
+        let second = source.next_frame().map(|f| f.data.clone());
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn zero_speed_freezes_stateless_generators() {
+        let mut source = ProceduralSource::new(GeneratorKind::Tunnel, 32, 18);
+        source.set_params(GeneratorParams {
+            speed: 0.0,
+            ..GeneratorParams::default()
+        });
+        let first = source.next_frame().map(|f| f.data.clone());
+        let second = source.next_frame().map(|f| f.data.clone());
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn resize_changes_frame_dimensions() {
+        let mut source = ProceduralSource::new(GeneratorKind::Life, 32, 18);
+        let _ = source.next_frame();
+        source.resize(64, 20);
+        assert_eq!(source.native_size(), (64, 20));
+        let frame = source.next_frame();
+        assert_eq!(frame.map(|f| (f.width, f.height)), Some((64, 20)));
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for kind in GeneratorKind::ALL {
+            assert_eq!(GeneratorKind::from_name(kind.name()), Some(kind));
+        }
+        assert_eq!(
+            GeneratorKind::from_name("Perlin"),
+            Some(GeneratorKind::Noise)
+        );
+        assert_eq!(GeneratorKind::from_name("nope"), None);
+    }
+
+    #[test]
+    fn life_step_blinker_oscillates() {
+        let (w, h) = (5, 5);
+        let mut cells = vec![false; w * h];
+        for x in 1..4 {
+            cells[2 * w + x] = true;
+        }
+        let mut next = vec![false; w * h];
+        let changed = life_step(w, h, &cells, &mut next);
+        assert_eq!(changed, 4);
+        assert!(next[w + 2] && next[2 * w + 2] && next[3 * w + 2]);
+        assert!(!next[2 * w + 1] && !next[2 * w + 3]);
+    }
+}
@@ -5,6 +5,7 @@
 // Architecture :
 //   - `probe_video`       : interroge ffprobe pour obtenir width/height/fps
 //   - `spawn_ffmpeg_pipe` : lance ffmpeg → flux raw RGBA sur stdout
+//   - `spawn_rgba_pipe`   : idem pour une entrée ffmpeg arbitraire (caméra, lavfi)
 //   - `spawn_video_thread`: thread dédié, lit les frames, gère les commandes
 //   - `process_commands`  : dispatche les commandes dans la boucle principale
 //   - `find_or_create_slot`: gère le pool Arc<FrameBuffer> zero-alloc
@@ -19,7 +20,7 @@ use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use af_core::clock::MediaClock;
+use af_core::clock::{FrameRate, MediaClock};
 use af_core::frame::FrameBuffer;
 
 /// Taille du pool de frames pré-allouées.
@@ -60,6 +61,8 @@ pub struct VideoInfo {
     pub height: u32,
     /// Images par seconde (ex: 23.976, 24.0, 30.0, 60.0).
     pub fps: f64,
+    /// Cadence exacte (`24000/1001`), dont `fps` est la valeur décimale.
+    pub frame_rate: FrameRate,
 }
 
 /// État mutable centralisé du thread vidéo.
@@ -182,7 +185,7 @@ pub fn probe_video(path: &Path) -> Result<VideoInfo> {
 
     let mut width: u32 = 1920;
     let mut height: u32 = 1080;
-    let mut fps: f64 = 30.0;
+    let mut frame_rate = FrameRate::integer(30);
     let mut found_any = false;
 
     for line in text.lines() {
@@ -205,31 +208,13 @@ pub fn probe_video(path: &Path) -> Result<VideoInfo> {
         } else if let Some(val) = line.strip_prefix("r_frame_rate=") {
             found_any = true;
             // Format: "24/1" ou "30000/1001" ou "24000/1001"
-            let val = val.trim();
-            let mut parts = val.splitn(2, '/');
-            let num: f64 = parts
-                .next()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or_else(|| {
-                    log::warn!(
-                        "ffprobe: r_frame_rate numerator non-parseable '{val}', défaut 30.0"
-                    );
-                    30.0
-                });
-            let den: f64 = parts
-                .next()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or_else(|| {
-                    log::warn!(
-                        "ffprobe: r_frame_rate denominator non-parseable '{val}', défaut 1.0"
-                    );
-                    1.0
-                });
-            if den > 0.0 {
-                fps = num / den;
+            match FrameRate::parse(val) {
+                Some(rate) => frame_rate = rate,
+                None => log::warn!("ffprobe: r_frame_rate non-parseable '{val}', défaut 30"),
             }
         }
     }
+    let fps = frame_rate.as_f64();
 
     if !found_any {
         log::warn!(
@@ -253,7 +238,12 @@ pub fn probe_video(path: &Path) -> Result<VideoInfo> {
         path.display()
     );
 
-    Ok(VideoInfo { width, height, fps })
+    Ok(VideoInfo {
+        width,
+        height,
+        fps,
+        frame_rate,
+    })
 }
 
 /// Lance un processus `ffmpeg` qui écrit des frames RGBA brutes sur stdout.
@@ -269,7 +259,7 @@ pub fn spawn_ffmpeg_pipe(
     w: u32,
     h: u32,
     pos_secs: f64,
-    target_fps: u32,
+    rate: FrameRate,
 ) -> Option<Child> {
     let path_str = if path.to_str().is_none() {
         log::warn!(
@@ -281,16 +271,28 @@ pub fn spawn_ffmpeg_pipe(
         path.to_string_lossy().into_owned()
     };
 
-    let scale_filter = format!("scale={w}:{h}:flags=lanczos");
-    let fps_str = target_fps.to_string();
     let pos_str = format!("{pos_secs:.3}");
+    let input = [
+        "-ss", &pos_str, // seek rapide avant -i (keyframe-based)
+        "-i", &path_str, // fichier source
+    ];
+    let child = spawn_rgba_pipe(&input, w, h, rate)?;
+    log::debug!("ffmpeg spawné: {w}x{h} @ {rate}fps depuis {pos_secs:.1}s");
+    Some(child)
+}
+
+/// Lance `ffmpeg` avec des arguments d'entrée arbitraires (`-f v4l2 -i /dev/video0`,
+/// `-f lavfi -i testsrc`, …) et écrit des frames RGBA `w × h` brutes sur stdout.
+///
+/// Retourne `None` si le spawn échoue (log::warn émis).
+#[must_use]
+pub fn spawn_rgba_pipe(input_args: &[&str], w: u32, h: u32, rate: FrameRate) -> Option<Child> {
+    let scale_filter = format!("scale={w}:{h}:flags=lanczos");
+    let fps_str = rate.to_string();
 
     match Command::new(af_core::paths::ffmpeg_bin())
+        .args(input_args)
         .args([
-            "-ss",
-            &pos_str, // seek rapide avant -i (keyframe-based)
-            "-i",
-            &path_str, // fichier source
             "-vf",
             &scale_filter, // scale + filter
             "-f",
@@ -310,12 +312,9 @@ pub fn spawn_ffmpeg_pipe(
         .stderr(Stdio::null())
         .spawn()
     {
-        Ok(child) => {
-            log::debug!("ffmpeg spawné: {w}x{h} @ {target_fps}fps depuis {pos_secs:.1}s");
-            Some(child)
-        }
+        Ok(child) => Some(child),
         Err(e) => {
-            log::warn!("spawn_ffmpeg_pipe: impossible de lancer ffmpeg: {e}");
+            log::warn!("spawn_rgba_pipe: impossible de lancer ffmpeg: {e}");
             None
         }
     }
@@ -428,7 +427,7 @@ fn process_commands(
                 state.w,
                 state.h,
                 state.pipe_start_secs,
-                state.target_fps,
+                FrameRate::integer(state.target_fps),
             );
             need_restart = false;
         }
@@ -439,7 +438,7 @@ fn process_commands(
 ///
 /// Invariant : retourne un index `i` tel que `Arc::strong_count(&pool[i]) == 1`.
 /// Si tous les slots sont pris, alloue un nouveau slot (cas exceptionnel).
-fn find_or_create_slot(pool: &mut Vec<Arc<FrameBuffer>>, w: u32, h: u32) -> usize {
+pub(crate) fn find_or_create_slot(pool: &mut Vec<Arc<FrameBuffer>>, w: u32, h: u32) -> usize {
     let free_idx = pool.iter().position(|a| Arc::strong_count(a) == 1);
     if let Some(i) = free_idx {
         // Vérifier taille correcte (peut différer après Resize)
@@ -0,0 +1,54 @@
+//! Integration test: live camera source with an ffmpeg `lavfi testsrc` stand-in.
+//! Skipped (passes trivially) when ffmpeg is not available.
+#![cfg(feature = "video")]
+
+use std::time::{Duration, Instant};
+
+use af_core::traits::Source;
+use af_source::camera::{CameraSource, CameraSpec};
+
+fn ffmpeg_available() -> bool {
+    std::process::Command::new(af_core::paths::ffmpeg_bin())
+        .arg("-version")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+fn wait_for_frame(cam: &mut CameraSource, w: u32, h: u32) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if cam
+            .next_frame()
+            .is_some_and(|f| f.width == w && f.height == h)
+        {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    false
+}
+
+#[test]
+fn lavfi_testsrc_streams_and_resizes() {
+    if !ffmpeg_available() {
+        eprintln!("ffmpeg introuvable — test caméra ignoré");
+        return;
+    }
+    let Ok(spec) = CameraSpec::parse("lavfi:testsrc=size=320x240:rate=30") else {
+        panic!("spec lavfi invalide");
+    };
+    let Ok(mut cam) = CameraSource::open(spec, 64, 48, 30) else {
+        panic!("ouverture caméra lavfi échouée");
+    };
+    assert!(cam.is_live());
+    assert!(wait_for_frame(&mut cam, 64, 48), "aucune frame 64x48 reçue");
+
+    cam.resize(32, 24);
+    assert_eq!(cam.native_size(), (32, 24));
+    assert!(
+        wait_for_frame(&mut cam, 32, 24),
+        "aucune frame après resize"
+    );
+}
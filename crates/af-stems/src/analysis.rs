@@ -4,29 +4,26 @@ use std::thread;
 use std::time::Duration;
 
 use af_core::clock::MediaClock;
-use af_core::frame::AudioFeatures;
+use af_core::config::AnalysisConfig;
+use af_core::frame::{AudioFeatures, LoudnessMix};
 use anyhow::Result;
 use triple_buffer::TripleBuffer;
 
-use af_audio::beat::BeatDetector;
-use af_audio::fft::FftPipeline;
-use af_audio::mfcc::MelFilterbank;
+use af_audio::frame_analyzer::FrameAnalyzer;
+use af_audio::loudness::LoudnessMeter;
 use af_audio::smoothing::FeatureSmoother;
 
 use crate::stem::{STEM_COUNT, StemFeatures, StemSet};
 
-/// MFCC coefficient 1 normalization divisor (same as af-audio/state.rs).
-const MFCC_BRIGHTNESS_SCALE: f32 = 50.0;
-/// MFCC coefficient 2 normalization divisor.
-const MFCC_ROUGHNESS_SCALE: f32 = 30.0;
-
 /// Spawn a thread that performs per-stem FFT analysis synchronized to playback.
 ///
 /// Reads the current playback position from `MediaClock` and extracts features
-/// for each of the 4 stems independently. Publishes `StemFeatures` via triple buffer.
+/// for each of the 4 stems independently, with the FFT size, window and bands
+/// of `analysis`. Publishes `StemFeatures` via triple buffer.
 ///
 /// # Errors
 /// Returns an error if the analysis thread fails to spawn.
+#[allow(clippy::too_many_arguments)]
 pub fn spawn_stem_analysis_thread(
     stem_set: &StemSet,
     clock: Arc<MediaClock>,
@@ -35,6 +32,7 @@ pub fn spawn_stem_analysis_thread(
     target_fps: u32,
     smoothing: f32,
     input_gain: f32,
+    analysis: &AnalysisConfig,
 ) -> Result<(triple_buffer::Output<StemFeatures>, thread::JoinHandle<()>)> {
     let (mut buf_input, buf_output) = TripleBuffer::new(&StemFeatures::default()).split();
 
@@ -42,6 +40,7 @@ pub fn spawn_stem_analysis_thread(
     let stem_samples: [Arc<Vec<f32>>; STEM_COUNT] =
         std::array::from_fn(|i| Arc::clone(&stem_set.stems[i].samples));
     let sample_rate = stem_set.sample_rate;
+    let analysis = analysis.clone();
 
     let handle = thread::Builder::new()
         .name("af-stems-analysis".into())
@@ -56,6 +55,7 @@ pub fn spawn_stem_analysis_thread(
                 target_fps,
                 smoothing,
                 input_gain,
+                &analysis,
             );
         })?;
 
@@ -74,19 +74,19 @@ fn run_analysis_loop(
     target_fps: u32,
     smoothing: f32,
     input_gain: f32,
+    analysis: &AnalysisConfig,
 ) {
-    let fft_size = 2048;
+    let fft_size = analysis.fft_size;
     let frame_period = Duration::from_secs_f64(1.0 / f64::from(target_fps.max(1)));
 
     // Per-stem analysis state (4 independent pipelines)
-    let mut ffts: [FftPipeline; STEM_COUNT] = std::array::from_fn(|_| FftPipeline::new(fft_size));
-    let mut beats: [BeatDetector; STEM_COUNT] = std::array::from_fn(|_| BeatDetector::new());
+    let mut analyzers: [FrameAnalyzer; STEM_COUNT] =
+        std::array::from_fn(|_| FrameAnalyzer::new(sample_rate, analysis));
     let mut smoothers: [FeatureSmoother; STEM_COUNT] =
         std::array::from_fn(|_| FeatureSmoother::new(smoothing));
-    let mut filterbanks: [MelFilterbank; STEM_COUNT] =
-        std::array::from_fn(|_| MelFilterbank::new(fft_size, sample_rate));
+    let mut meters: [LoudnessMeter; STEM_COUNT] =
+        std::array::from_fn(|_| LoudnessMeter::new(sample_rate));
     let mut window_bufs: Vec<Vec<f32>> = (0..STEM_COUNT).map(|_| vec![0.0f32; fft_size]).collect();
-    let mut onset_envs: [f32; STEM_COUNT] = [0.0; STEM_COUNT];
 
     let fps = target_fps as f32;
 
@@ -128,37 +128,15 @@ fn run_analysis_loop(
                 }
             }
 
-            // FFT
-            let spectrum = ffts[stem_idx].process(&window_bufs[stem_idx]);
-
-            // Extract features
-            let mut feats =
-                af_audio::features::extract_features(&window_bufs[stem_idx], spectrum, sample_rate);
-
-            // Beat detection
-            let (onset, intensity, bpm, phase, flux) = beats[stem_idx].process(spectrum, fps);
-            feats.onset = onset;
-            feats.beat_intensity = intensity;
-            feats.bpm = bpm;
-            feats.beat_phase = phase;
-            feats.spectral_flux = flux;
-
-            // onset_envelope: strobe-style decay (parity with batch_analyzer)
-            if onset {
-                onset_envs[stem_idx] = 1.0;
-            } else {
-                onset_envs[stem_idx] *= 0.85;
-            }
-            feats.onset_envelope = onset_envs[stem_idx];
-
-            // MFCC timbral features
-            let mfcc = filterbanks[stem_idx].compute(spectrum);
-            feats.mfcc = mfcc;
-            feats.timbral_brightness = (mfcc[1] / MFCC_BRIGHTNESS_SCALE + 0.5).clamp(0.0, 1.0);
-            feats.timbral_roughness = (mfcc[2].abs() / MFCC_ROUGHNESS_SCALE).clamp(0.0, 1.0);
+            // FFT, bands, beat, drums, harmony, MFCC (same core as live and batch)
+            let feats = analyzers[stem_idx].analyze(&window_bufs[stem_idx], fps);
 
-            // Smooth
-            stem_features.features[stem_idx] = smoothers[stem_idx].smooth(&feats);
+            // Smooth; loudness (no AGC per stem, relative levels matter) is not smoothed again
+            let mut smoothed = smoothers[stem_idx].smooth(&feats);
+            meters[stem_idx]
+                .process_to(samples, current_pos, input_gain)
+                .write_to(&mut smoothed);
+            stem_features.features[stem_idx] = smoothed;
         }
 
         buf_input.write(stem_features);
@@ -177,6 +155,7 @@ pub fn combine_stem_features(
 ) -> AudioFeatures {
     let mut combined = AudioFeatures::default();
     let mut weight_sum = 0.0f32;
+    let mut loudness = LoudnessMix::default();
 
     for (i, gain) in gains.iter().enumerate() {
         if *gain <= 0.0 {
@@ -199,6 +178,13 @@ pub fn combine_stem_features(
         combined.spectral_flatness += f.spectral_flatness * w;
         combined.beat_intensity = combined.beat_intensity.max(f.beat_intensity);
         combined.bpm = combined.bpm.max(f.bpm);
+        // Beat grid: from the stem with the clearest pulse
+        if f.tempo_confidence > combined.tempo_confidence {
+            combined.beat_phase = f.beat_phase;
+            combined.bar_phase = f.bar_phase;
+            combined.beat_in_bar = f.beat_in_bar;
+            combined.tempo_confidence = f.tempo_confidence;
+        }
         combined.timbral_brightness += f.timbral_brightness * w;
         combined.timbral_roughness += f.timbral_roughness * w;
         combined.spectral_rolloff += f.spectral_rolloff * w;
@@ -209,10 +195,40 @@ pub fn combine_stem_features(
             combined.onset = true;
         }
 
-        // Spectrum bands: weighted sum
+        // Drum onsets: OR / max across stems
+        combined.kick_onset |= f.kick_onset;
+        combined.kick_intensity = combined.kick_intensity.max(f.kick_intensity);
+        combined.kick_envelope = combined.kick_envelope.max(f.kick_envelope);
+        combined.snare_onset |= f.snare_onset;
+        combined.snare_intensity = combined.snare_intensity.max(f.snare_intensity);
+        combined.snare_envelope = combined.snare_envelope.max(f.snare_envelope);
+        combined.hat_onset |= f.hat_onset;
+        combined.hat_intensity = combined.hat_intensity.max(f.hat_intensity);
+        combined.hat_envelope = combined.hat_envelope.max(f.hat_envelope);
+
+        // Harmony: chroma weighted, pitch and key from the most confident stem
+        for (c, v) in combined.chroma.iter_mut().zip(f.chroma) {
+            *c += v * w;
+        }
+        if f.pitch_confidence > combined.pitch_confidence {
+            combined.pitch = f.pitch;
+            combined.pitch_confidence = f.pitch_confidence;
+        }
+        if f.key_confidence > combined.key_confidence {
+            combined.key = f.key;
+            combined.key_minor = f.key_minor;
+            combined.key_confidence = f.key_confidence;
+        }
+
+        // Spectrum bands and user bands: weighted sum
         for (j, band) in f.spectrum_bands.iter().enumerate() {
             combined.spectrum_bands[j] += band * w;
         }
+        for (band, v) in combined.custom_bands.iter_mut().zip(f.custom_bands) {
+            *band += v * w;
+        }
+
+        loudness.add(f, w);
 
         weight_sum += w;
     }
@@ -238,6 +254,13 @@ pub fn combine_stem_features(
         for band in &mut combined.spectrum_bands {
             *band *= inv;
         }
+        for c in &mut combined.chroma {
+            *c *= inv;
+        }
+        for band in &mut combined.custom_bands {
+            *band *= inv;
+        }
+        loudness.write_to(&mut combined);
     }
 
     combined